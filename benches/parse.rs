@@ -0,0 +1,79 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Times parsing files made of many tiny elements,
+//! such as those with large tag and chapter sections
+//!
+//! Run with `cargo bench`.
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use matroska::{Chapter, ChapterDisplay, ChapterEdition, Matroska, SimpleTag, Tag, TagValue};
+
+/// Returns a file whose Tags and Chapters hold the given number of entries
+fn metadata_heavy_file(entries: usize) -> Vec<u8> {
+    let tags: Vec<Tag> = (0..entries)
+        .map(|i| {
+            Tag::default().with_simple(
+                SimpleTag::default()
+                    .with_name("TITLE")
+                    .with_value(TagValue::String(format!("Episode {}", i))),
+            )
+        })
+        .collect();
+    let edition = (0..entries).fold(ChapterEdition::default(), |edition, i| {
+        edition.with_chapter(
+            Chapter::default()
+                .with_uid(i as u64 + 1)
+                .with_time_start(Duration::from_secs(i as u64))
+                .with_display(ChapterDisplay::default().with_string(format!("Chapter {}", i))),
+        )
+    });
+
+    let mut segment = Vec::new();
+    // Info with a TimestampScale, MuxingApp and WritingApp
+    segment.extend_from_slice(&[
+        0x15, 0x49, 0xA9, 0x66, 0x8F, 0x2A, 0xD7, 0xB1, 0x83, 0x0F, 0x42, 0x40, 0x4D, 0x80, 0x81,
+        b'b', 0x57, 0x41, 0x81, b'b',
+    ]);
+    Tag::write_all(&tags, &mut segment).unwrap();
+    ChapterEdition::write_all(&[edition], &mut segment).unwrap();
+
+    // EBML header for a matroska DocType
+    let mut file = vec![
+        0x1A, 0x45, 0xDF, 0xA3, 0x8B, 0x42, 0x82, 0x88, b'm', b'a', b't', b'r', b'o', b's', b'k',
+        b'a',
+    ];
+    file.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0x01]);
+    file.extend_from_slice(&(segment.len() as u64).to_be_bytes()[1..]);
+    file.extend(segment);
+    file
+}
+
+fn main() {
+    let entries = 20_000;
+    let file = metadata_heavy_file(entries);
+    let iterations = 20;
+
+    // once to warm up, and to check the file parses
+    let parsed = Matroska::open(Cursor::new(&file)).unwrap();
+    assert_eq!(parsed.tags.len(), entries);
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        Matroska::open(Cursor::new(&file)).unwrap();
+    }
+    let elapsed = start.elapsed() / iterations;
+    println!(
+        "open, {} tags and chapters ({} KiB): {:?} per file",
+        entries,
+        file.len() / 1024,
+        elapsed
+    );
+}
@@ -0,0 +1,244 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::io;
+
+use crate::ebml::{MatroskaError, Result};
+
+/// How a block's frames are packed together
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Lacing {
+    /// a single frame
+    None,
+    /// frame sizes in Xiph-style 255-byte runs
+    Xiph,
+    /// frames of identical size
+    FixedSize,
+    /// an EBML-coded size followed by signed deltas
+    Ebml,
+}
+
+impl Lacing {
+    fn from_flags(flags: u8) -> Lacing {
+        match (flags >> 1) & 0b11 {
+            0b00 => Lacing::None,
+            0b01 => Lacing::Xiph,
+            0b10 => Lacing::FixedSize,
+            _ => Lacing::Ebml,
+        }
+    }
+}
+
+/// The header of a SimpleBlock or Block, parsed from its body
+///
+/// ## Example
+/// ```
+/// use matroska::{Block, Lacing};
+///
+/// // track 1, timestamp 5, a keyframe of two Xiph-laced frames
+/// let data = [0x81, 0x00, 0x05, 0x82, 0x01, 0x02, 1, 2, 3, 4, 5];
+/// let block = Block::parse_header(&data).unwrap();
+/// assert_eq!(block.track, 1);
+/// assert_eq!(block.lacing, Lacing::Xiph);
+/// assert_eq!(block.frame_sizes(), &[2, 3]);
+/// assert_eq!(block.frames(&data).collect::<Vec<_>>(), vec![&[1, 2][..], &[3, 4, 5][..]]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Block {
+    /// The track the block belongs to
+    pub track: u64,
+    /// The timestamp relative to the Cluster, in ticks
+    pub timestamp: i16,
+    /// Whether the block holds a keyframe, for SimpleBlocks
+    pub keyframe: bool,
+    /// Whether the block's frames shouldn't be displayed
+    pub invisible: bool,
+    /// Whether the block's frames may be dropped
+    /// during playback if needed, for SimpleBlocks
+    pub discardable: bool,
+    /// How the block's frames are packed together
+    pub lacing: Lacing,
+    /// The length of the header in bytes, up to the first frame
+    pub header_len: u64,
+    frame_sizes: Vec<u64>,
+}
+
+impl Block {
+    /// Parses the header of a SimpleBlock or Block
+    /// from its entire body, frame data included
+    ///
+    /// Lace sizes which don't add up to the length
+    /// of the frame data are [`MatroskaError::InvalidLacing`].
+    pub fn parse_header(data: &[u8]) -> Result<Block> {
+        let mut r = data;
+        let header = BlockHeader::read(&mut r, data.len() as u64)?;
+        Ok(Block {
+            track: header.track,
+            timestamp: header.timestamp,
+            keyframe: header.flags & 0x80 != 0,
+            invisible: header.flags & 0x08 != 0,
+            discardable: header.flags & 0x01 != 0,
+            lacing: Lacing::from_flags(header.flags),
+            header_len: header.len,
+            frame_sizes: header.frame_sizes,
+        })
+    }
+
+    /// Returns the size of each frame in the block, in bytes
+    ///
+    /// Unlaced blocks have a single frame.
+    #[inline]
+    pub fn frame_sizes(&self) -> &[u64] {
+        &self.frame_sizes
+    }
+
+    /// Splits the block's body into its frames,
+    /// given the same body the header was parsed from
+    ///
+    /// Frames missing from a shorter body are omitted.
+    pub fn frames<'d>(&'d self, data: &'d [u8]) -> impl Iterator<Item = &'d [u8]> + 'd {
+        let mut rest = data.get(self.header_len as usize..).unwrap_or_default();
+        self.frame_sizes.iter().map_while(move |size| {
+            let size: usize = std::convert::TryFrom::try_from(*size)
+                .ok()
+                .filter(|size| *size <= rest.len())?;
+            let (frame, remaining) = rest.split_at(size);
+            rest = remaining;
+            Some(frame)
+        })
+    }
+}
+
+/// The header of a SimpleBlock or Block, read without its frame data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BlockHeader {
+    /// the track the block belongs to
+    pub track: u64,
+    /// the timestamp relative to the Cluster, in ticks
+    pub timestamp: i16,
+    /// the block's raw flags
+    pub flags: u8,
+    /// the size of each frame in the block, in bytes
+    pub frame_sizes: Vec<u64>,
+    /// the number of header bytes read, up to the first frame
+    pub len: u64,
+}
+
+impl BlockHeader {
+    /// Reads the header of a block whose body is `size` bytes,
+    /// leaving the reader at the start of the first frame
+    ///
+    /// Only the header and lace sizes are read;
+    /// the frame data itself is left for the caller to read or skip.
+    pub fn read<R: io::Read>(r: &mut R, size: u64) -> Result<BlockHeader> {
+        let mut r = Counted { r, len: 0 };
+        let track = r.read_vint()?.0;
+        let timestamp = i16::from_be_bytes([r.read_u8()?, r.read_u8()?]);
+        let flags = r.read_u8()?;
+
+        let lacing = Lacing::from_flags(flags);
+        let frames = match lacing {
+            Lacing::None => 1,
+            _ => u64::from(r.read_u8()?) + 1,
+        };
+
+        let mut frame_sizes = Vec::with_capacity(frames as usize);
+        match lacing {
+            Lacing::None | Lacing::FixedSize => {}
+            Lacing::Xiph => {
+                for _ in 1..frames {
+                    let mut frame_size = 0;
+                    loop {
+                        let byte = r.read_u8()?;
+                        frame_size += u64::from(byte);
+                        if byte != 0xFF {
+                            break;
+                        }
+                    }
+                    frame_sizes.push(frame_size);
+                }
+            }
+            Lacing::Ebml => {
+                if frames > 1 {
+                    let mut frame_size = r.read_vint()?.0;
+                    frame_sizes.push(frame_size);
+                    for _ in 2..frames {
+                        let (raw, len) = r.read_vint()?;
+                        // signed values are stored with a bias
+                        // of half the vint's range
+                        let bias = (1i64 << (7 * len - 1)) - 1;
+                        let delta = raw as i64 - bias;
+                        frame_size = frame_size
+                            .checked_add_signed(delta)
+                            .ok_or(MatroskaError::InvalidLacing)?;
+                        frame_sizes.push(frame_size);
+                    }
+                }
+            }
+        }
+
+        let remaining = size
+            .checked_sub(r.len)
+            .ok_or(MatroskaError::InvalidLacing)?;
+        match lacing {
+            Lacing::FixedSize => {
+                if remaining % frames != 0 {
+                    return Err(MatroskaError::InvalidLacing);
+                }
+                frame_sizes.resize(frames as usize, remaining / frames);
+            }
+            _ => {
+                let laced = frame_sizes
+                    .iter()
+                    .try_fold(0u64, |total, size| total.checked_add(*size))
+                    .filter(|laced| *laced <= remaining)
+                    .ok_or(MatroskaError::InvalidLacing)?;
+                frame_sizes.push(remaining - laced);
+            }
+        }
+
+        Ok(BlockHeader {
+            track,
+            timestamp,
+            flags,
+            frame_sizes,
+            len: r.len,
+        })
+    }
+}
+
+/// A reader which counts the bytes read through it
+struct Counted<'r, R> {
+    r: &'r mut R,
+    len: u64,
+}
+
+impl<R: io::Read> Counted<'_, R> {
+    fn read_u8(&mut self) -> Result<u8> {
+        let mut byte = [0];
+        self.r.read_exact(&mut byte)?;
+        self.len += 1;
+        Ok(byte[0])
+    }
+
+    /// Reads a variable-length integer, returning its value and length
+    fn read_vint(&mut self) -> Result<(u64, u32)> {
+        let first = self.read_u8()?;
+        let len = first.leading_zeros() + 1;
+        if len > 8 {
+            return Err(MatroskaError::InvalidLacing);
+        }
+        let mut value = u64::from(first) & (0xFF >> len);
+        for _ in 1..len {
+            value = (value << 8) | u64::from(self.read_u8()?);
+        }
+        Ok((value, len))
+    }
+}
@@ -0,0 +1,398 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::io::{self, SeekFrom};
+use std::time::Duration;
+
+use crate::block::BlockHeader;
+use crate::ebml::{self, Limits, MatroskaError, Result};
+use crate::{find_segment, ids, read_unknown_size_child, skip_element, Info};
+
+/// A Cluster's timestamp and the blocks it holds,
+/// as returned by [`Clusters`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Cluster {
+    /// The file offset of the Cluster's header
+    pub offset: u64,
+    /// The Cluster's timestamp, scaled by the file's timestamp scale
+    pub timestamp: Duration,
+    /// The Cluster's blocks, in the order they're stored
+    pub blocks: Vec<ClusterBlock>,
+    /// The frame data of the blocks belonging to
+    /// the track being read, if any
+    pub(crate) track_blocks: Vec<TrackBlock>,
+    timestamp_scale: u64,
+}
+
+impl Cluster {
+    /// Returns the absolute timestamp of one of the Cluster's blocks,
+    /// or `None` if it would fall before the start of the file
+    pub fn block_timestamp(&self, block: &ClusterBlock) -> Option<Duration> {
+        self.relative_timestamp(block.timestamp)
+    }
+
+    /// Returns the absolute timestamp of a relative one, in ticks
+    pub(crate) fn relative_timestamp(&self, ticks: i16) -> Option<Duration> {
+        let offset = self
+            .timestamp_scale
+            .checked_mul(ticks.unsigned_abs().into())?;
+        let offset = Duration::from_nanos(offset);
+        if ticks < 0 {
+            self.timestamp.checked_sub(offset)
+        } else {
+            self.timestamp.checked_add(offset)
+        }
+    }
+
+    /// Converts a number of ticks, such as a block's BlockDuration, to a duration
+    #[inline]
+    pub fn ticks(&self, ticks: u64) -> Duration {
+        Duration::from_nanos(ticks.saturating_mul(self.timestamp_scale))
+    }
+}
+
+/// A SimpleBlock or BlockGroup's Block, read without its frame data
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ClusterBlock {
+    /// The track the block belongs to
+    pub track: u64,
+    /// The timestamp relative to the Cluster, in ticks
+    pub timestamp: i16,
+    /// Whether the block can be decoded on its own
+    ///
+    /// For a SimpleBlock this is its keyframe flag,
+    /// and for a BlockGroup it's whether it has no ReferenceBlock.
+    pub keyframe: bool,
+    /// The size of the block's frame data, in bytes
+    pub size: u64,
+    /// The number of frames laced into the block
+    pub frames: u64,
+    /// The BlockGroup's BlockDuration in ticks, if it has one
+    pub duration: Option<u64>,
+}
+
+/// A block of the track being read, along with its frame data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TrackBlock {
+    /// The timestamp relative to the Cluster, in ticks
+    pub timestamp: i16,
+    /// Whether the block can be decoded on its own
+    pub keyframe: bool,
+    /// The BlockGroup's BlockDuration, in ticks
+    pub duration: Option<u64>,
+    /// Each of the block's frames, still encoded
+    pub frames: Vec<Vec<u8>>,
+}
+
+/// An iterator over the Clusters of a Matroska file,
+/// as returned by [`clusters`]
+///
+/// Only element headers and block headers are read;
+/// frame data is skipped.
+/// The iteration stops at the end of the Segment or the file,
+/// and after any error.
+pub struct Clusters<'a, R> {
+    walker: ClusterWalker<&'a mut R>,
+}
+
+/// Walks the Clusters of a Segment, reading the frame data
+/// of one track's blocks along the way if asked to
+pub(crate) struct ClusterWalker<R> {
+    r: R,
+    timestamp_scale: u64,
+    remaining: u64,
+    done: bool,
+    // the track whose frame data is read
+    track: Option<u64>,
+}
+
+/// Returns an iterator over the Clusters of an open Matroska file,
+/// whose timestamps are scaled by the given Info's timestamp scale
+///
+/// ## Example
+/// ```no_run
+/// let mut file = std::fs::File::open("file.mkv").unwrap();
+/// let matroska = matroska::Matroska::open(&mut file).unwrap();
+/// for cluster in matroska::clusters(&mut file, &matroska.info).unwrap() {
+///     let cluster = cluster.unwrap();
+///     println!("{:?} : {} blocks", cluster.timestamp, cluster.blocks.len());
+/// }
+/// ```
+pub fn clusters<'a, R: io::Read + io::Seek>(r: &'a mut R, info: &Info) -> Result<Clusters<'a, R>> {
+    Ok(Clusters {
+        walker: ClusterWalker::new(r, info.timestamp_scale, None)?,
+    })
+}
+
+impl<R: io::Read + io::Seek> Iterator for Clusters<'_, R> {
+    type Item = Result<Cluster>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Result<Cluster>> {
+        self.walker.next()
+    }
+}
+
+impl<R: io::Read + io::Seek> Iterator for ClusterWalker<R> {
+    type Item = Result<Cluster>;
+
+    fn next(&mut self) -> Option<Result<Cluster>> {
+        if self.done {
+            return None;
+        }
+        match self.next_cluster() {
+            Ok(Some(cluster)) => Some(Ok(cluster)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<R: io::Read + io::Seek> ClusterWalker<R> {
+    /// Starts walking from the start of the Segment,
+    /// reading the frame data of the given track's blocks
+    pub(crate) fn new(mut r: R, timestamp_scale: u64, track: Option<u64>) -> Result<Self> {
+        r.seek(SeekFrom::Start(0))?;
+        let remaining = find_segment(&mut r)?;
+        Ok(ClusterWalker {
+            r,
+            timestamp_scale,
+            remaining,
+            done: false,
+            track,
+        })
+    }
+
+    /// Continues walking from the given file offset,
+    /// which must be within the Segment
+    pub(crate) fn seek_to(&mut self, offset: u64) -> Result<()> {
+        let position = self.r.stream_position()?;
+        let end = position.saturating_add(self.remaining);
+        self.remaining = end.saturating_sub(offset);
+        self.r.seek(SeekFrom::Start(offset))?;
+        self.done = false;
+        Ok(())
+    }
+
+    fn next_cluster(&mut self) -> Result<Option<Cluster>> {
+        let (offset, mut size, len) = match self.next_cluster_header()? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let mut cluster = Cluster {
+            offset,
+            timestamp: Duration::ZERO,
+            blocks: Vec::new(),
+            track_blocks: Vec::new(),
+            timestamp_scale: self.timestamp_scale,
+        };
+        match self.read_cluster(size, &mut cluster) {
+            Ok(()) => {}
+            // a truncated Cluster yields the blocks it does have
+            Err(MatroskaError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                self.remaining = 0;
+                return Ok(Some(cluster));
+            }
+            Err(err) => return Err(err.located(offset, Some(ids::CLUSTER))),
+        }
+        if size == ebml::UNKNOWN_SIZE {
+            size = self.r.stream_position()? - offset - len;
+        }
+        self.remaining = ebml::remaining_after(self.remaining, ids::CLUSTER, size, len)?;
+        Ok(Some(cluster))
+    }
+
+    /// Skips the next Cluster without reading its blocks,
+    /// returning whether there was one
+    pub(crate) fn skip_cluster(&mut self) -> Result<bool> {
+        if self.done {
+            return Ok(false);
+        }
+        let (offset, size, len) = match self.next_cluster_header() {
+            Ok(Some(header)) => header,
+            Ok(None) => {
+                self.done = true;
+                return Ok(false);
+            }
+            Err(err) => {
+                self.done = true;
+                return Err(err);
+            }
+        };
+        let size = skip_element(&mut self.r, size)
+            .map_err(|err| err.located(offset, Some(ids::CLUSTER)))?;
+        self.remaining = ebml::remaining_after(self.remaining, ids::CLUSTER, size, len)?;
+        Ok(true)
+    }
+
+    /// Reads up to the body of the next Cluster,
+    /// returning its offset, size and header length
+    fn next_cluster_header(&mut self) -> Result<Option<(u64, u64, u64)>> {
+        while self.remaining > 0 {
+            let offset = self.r.stream_position()?;
+            let (id, size, len) = match ebml::read_element_id_size(&mut self.r) {
+                Ok(header) => header,
+                // a truncated file simply has no more Clusters
+                Err(MatroskaError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Ok(None)
+                }
+                Err(err) => return Err(err.located(offset, Some(ids::SEGMENT))),
+            };
+            if id == ids::CLUSTER {
+                return Ok(Some((offset, size, len)));
+            }
+            let size = skip_element(&mut self.r, size)?;
+            self.remaining = ebml::remaining_after(self.remaining, id, size, len)?;
+        }
+        Ok(None)
+    }
+
+    fn read_cluster(&mut self, mut remaining: u64, cluster: &mut Cluster) -> Result<()> {
+        // an unknown-size Cluster ends where the next top-level element begins
+        let unknown = remaining == ebml::UNKNOWN_SIZE;
+        while remaining > 0 {
+            let (id, mut size, len) = if unknown {
+                match read_unknown_size_child(&mut self.r)? {
+                    Some(header) => header,
+                    None => break,
+                }
+            } else {
+                ebml::read_element_id_size(&mut self.r)?
+            };
+            match id {
+                ids::TIMECODE => {
+                    let ticks = ebml::read_uint(&mut self.r, size)?;
+                    cluster.timestamp =
+                        Duration::from_nanos(ticks.saturating_mul(self.timestamp_scale));
+                }
+                ids::SIMPLEBLOCK => {
+                    let (header, payload, track_block) = read_block(&mut self.r, size, self.track)?;
+                    cluster.track_blocks.extend(track_block);
+                    cluster.blocks.push(ClusterBlock {
+                        track: header.track,
+                        timestamp: header.timestamp,
+                        keyframe: header.flags & 0x80 != 0,
+                        size: payload,
+                        frames: header.frame_sizes.len() as u64,
+                        duration: None,
+                    });
+                }
+                ids::BLOCKGROUP => {
+                    if let Some((block, track_block)) =
+                        read_block_group(&mut self.r, size, self.track)?
+                    {
+                        cluster.blocks.push(block);
+                        cluster.track_blocks.extend(track_block);
+                    }
+                }
+                _ => {
+                    size = skip_element(&mut self.r, size)?;
+                }
+            }
+            remaining = ebml::remaining_after(remaining, id, size, len)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a block's header, then either the frame data
+/// if it belongs to the given track or skips past it,
+/// returning the header, the frame data's size and any frames read
+pub(crate) fn read_block<R: io::Read + io::Seek>(
+    r: &mut R,
+    size: u64,
+    track: Option<u64>,
+) -> Result<(BlockHeader, u64, Option<TrackBlock>)> {
+    if size == ebml::UNKNOWN_SIZE {
+        return Err(MatroskaError::UnknownSizeElement {
+            id: ids::SIMPLEBLOCK,
+        });
+    }
+    let header = BlockHeader::read(r, size)?;
+    let payload = size - header.len;
+    if track != Some(header.track) {
+        r.seek(SeekFrom::Current(payload as i64))?;
+        return Ok((header, payload, None));
+    }
+    let limit = Limits::MAX_ELEMENT_SIZE;
+    if payload > limit {
+        return Err(MatroskaError::ElementTooLarge {
+            id: ids::SIMPLEBLOCK,
+            size: payload,
+            limit,
+        });
+    }
+    let frames = header
+        .frame_sizes
+        .iter()
+        .map(|frame_size| ebml::read_bin(r, *frame_size))
+        .collect::<Result<Vec<_>>>()?;
+    let block = TrackBlock {
+        timestamp: header.timestamp,
+        keyframe: header.flags & 0x80 != 0,
+        duration: None,
+        frames,
+    };
+    Ok((header, payload, Some(block)))
+}
+
+/// Reads the Block from a BlockGroup, if it has one,
+/// along with its frame data if it belongs to the given track
+pub(crate) fn read_block_group<R: io::Read + io::Seek>(
+    r: &mut R,
+    mut remaining: u64,
+    track: Option<u64>,
+) -> Result<Option<(ClusterBlock, Option<TrackBlock>)>> {
+    if remaining == ebml::UNKNOWN_SIZE {
+        return Err(MatroskaError::UnknownSizeElement {
+            id: ids::BLOCKGROUP,
+        });
+    }
+    let mut block = None;
+    let mut referenced = false;
+    let mut duration = None;
+    while remaining > 0 {
+        let (id, mut size, len) = ebml::read_element_id_size(r)?;
+        match id {
+            ids::BLOCK => block = Some(read_block(r, size, track)?),
+            ids::REFERENCEBLOCK => {
+                referenced = true;
+                size = skip_element(r, size)?;
+            }
+            ids::BLOCKDURATION => duration = Some(ebml::read_uint(r, size)?),
+            _ => {
+                size = skip_element(r, size)?;
+            }
+        }
+        remaining = ebml::remaining_after(remaining, id, size, len)?;
+    }
+    Ok(block.map(|(header, payload, track_block)| {
+        let track_block = track_block.map(|block| TrackBlock {
+            keyframe: !referenced,
+            duration,
+            ..block
+        });
+        let block = ClusterBlock {
+            track: header.track,
+            timestamp: header.timestamp,
+            keyframe: !referenced,
+            size: payload,
+            frames: header.frame_sizes.len() as u64,
+            duration,
+        };
+        (block, track_block)
+    }))
+}
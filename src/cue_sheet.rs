@@ -0,0 +1,163 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Cue sheets for editions whose chapters are an album's tracks
+
+use std::fmt::Write;
+
+use crate::{ChapterEdition, Matroska, TagValue};
+
+/// The most tracks a cue sheet may have
+const MAX_TRACKS: usize = 99;
+
+/// The number of minutes an INDEX has room for, which is two digits
+const MAX_MINUTES: u64 = 99;
+
+impl ChapterEdition {
+    /// Returns a cue sheet splitting the given file into
+    /// a track for each of the edition's top-level chapters
+    ///
+    /// Each track's `INDEX 01` is its chapter's start,
+    /// in minutes, seconds and frames of 1/75 second,
+    /// and its `TITLE` is the chapter's first display string.
+    /// Tracks are ordered by their start.
+    /// Hidden chapters are left out, and so are those
+    /// which don't fit a valid cue sheet, which are those
+    /// starting after 99:59:74 and any after the 99th track.
+    /// Each left-out chapter which isn't hidden is noted by
+    /// an `REM OMITTED` line at the end giving its start and title.
+    ///
+    /// Double quotes in titles become single quotes,
+    /// since cue sheets have no way to escape them.
+    ///
+    /// ## Example
+    /// ```
+    /// use matroska::{Chapter, ChapterDisplay, ChapterEdition};
+    /// use std::time::Duration;
+    ///
+    /// let edition = ChapterEdition::default().with_chapter(
+    ///     Chapter::default()
+    ///         .with_time_start(Duration::from_millis(62_500))
+    ///         .with_display(ChapterDisplay::default().with_string("Song")),
+    /// );
+    /// assert_eq!(
+    ///     edition.to_cue_sheet("album.flac"),
+    ///     "FILE \"album.flac\" WAVE\n  TRACK 01 AUDIO\n    TITLE \"Song\"\n    INDEX 01 01:02:37\n"
+    /// );
+    /// ```
+    pub fn to_cue_sheet(&self, file_name: &str) -> String {
+        let mut sheet = String::new();
+        write_tracks(&mut sheet, self, file_name);
+        sheet
+    }
+}
+
+impl Matroska {
+    /// Returns a cue sheet for the default edition's chapters,
+    /// as by [`ChapterEdition::to_cue_sheet`],
+    /// or `None` if the file has no chapters
+    ///
+    /// The sheet's `PERFORMER` and `TITLE` come from the
+    /// segment's `ARTIST` and `TITLE` tags,
+    /// or the Info's title if there's no `TITLE` tag,
+    /// and `REM GENRE` and `REM DATE` lines come from its
+    /// `GENRE` and `DATE_RELEASED` or `DATE_RECORDED` tags.
+    /// Only tags which apply to the whole segment are used.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// let matroska = matroska::open("album.mka").unwrap();
+    /// if let Some(sheet) = matroska.to_cue_sheet("album.mka") {
+    ///     std::fs::write("album.cue", sheet).unwrap();
+    /// }
+    /// ```
+    pub fn to_cue_sheet(&self, file_name: &str) -> Option<String> {
+        let edition = self.default_edition()?;
+        let tag = |name: &str| {
+            self.tags_where(|_| false)
+                .filter(|tag| tag.name.eq_ignore_ascii_case(name))
+                .find_map(|tag| match &tag.value {
+                    Some(TagValue::String(value)) => Some(value.as_str()),
+                    _ => None,
+                })
+        };
+
+        let mut sheet = String::new();
+        if let Some(genre) = tag("GENRE") {
+            let _ = writeln!(sheet, "REM GENRE {}", quoted(genre));
+        }
+        if let Some(date) = tag("DATE_RELEASED").or_else(|| tag("DATE_RECORDED")) {
+            let _ = writeln!(sheet, "REM DATE {}", quoted(date));
+        }
+        if let Some(artist) = tag("ARTIST") {
+            let _ = writeln!(sheet, "PERFORMER {}", quoted(artist));
+        }
+        if let Some(title) = tag("TITLE").or(self.info.title.as_deref()) {
+            let _ = writeln!(sheet, "TITLE {}", quoted(title));
+        }
+        write_tracks(&mut sheet, edition, file_name);
+        Some(sheet)
+    }
+}
+
+fn write_tracks(sheet: &mut String, edition: &ChapterEdition, file_name: &str) {
+    let mut chapters: Vec<_> = edition.chapters.iter().filter(|c| !c.hidden).collect();
+    chapters.sort_by_key(|c| c.time_start);
+
+    // writing to a String can't fail
+    let _ = writeln!(sheet, "FILE {} WAVE", quoted(file_name));
+    let mut omitted = Vec::new();
+    let mut number = 0;
+    for chapter in chapters {
+        if number == MAX_TRACKS || chapter.time_start.as_secs() / 60 > MAX_MINUTES {
+            omitted.push(chapter);
+            continue;
+        }
+        number += 1;
+        let _ = writeln!(sheet, "  TRACK {:02} AUDIO", number);
+        if let Some(display) = chapter.display.first() {
+            let _ = writeln!(sheet, "    TITLE {}", quoted(&display.string));
+        }
+        let time = chapter.time_start;
+        let _ = writeln!(
+            sheet,
+            "    INDEX 01 {:02}:{:02}:{:02}",
+            time.as_secs() / 60,
+            time.as_secs() % 60,
+            u64::from(time.subsec_nanos()) * 75 / 1_000_000_000
+        );
+    }
+    for chapter in omitted {
+        let time = chapter.time_start;
+        let _ = write!(
+            sheet,
+            "REM OMITTED {}:{:02}:{:02}.{:03}",
+            time.as_secs() / 3600,
+            time.as_secs() / 60 % 60,
+            time.as_secs() % 60,
+            time.subsec_millis()
+        );
+        if let Some(display) = chapter.display.first() {
+            let _ = write!(sheet, " {}", quoted(&display.string));
+        }
+        sheet.push('\n');
+    }
+}
+
+/// Quotes a string, replacing what a cue sheet can't hold
+fn quoted(s: &str) -> String {
+    let s: String = s
+        .chars()
+        .map(|c| match c {
+            '"' => '\'',
+            '\r' | '\n' => ' ',
+            c => c,
+        })
+        .collect();
+    format!("\"{}\"", s)
+}
@@ -0,0 +1,373 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::{Attachment, Chapter, Matroska, SimpleTag, Target};
+
+/// The metadata differences between two parsed files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MetadataDiff {
+    /// The individual changes, Info first, then tags, chapters and attachments
+    pub changes: Vec<Change>,
+}
+
+impl MetadataDiff {
+    /// Returns `true` if no differences were found
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// An individual metadata change
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Change {
+    /// A field of the Info segment changed
+    Info {
+        /// The name of the changed field
+        field: &'static str,
+        /// The field's old value, debug-formatted
+        before: String,
+        /// The field's new value, debug-formatted
+        after: String,
+    },
+    /// A simple tag was added
+    TagAdded {
+        /// What the tag applies to
+        target: Option<Target>,
+        /// The new tag
+        tag: SimpleTag,
+    },
+    /// A simple tag was removed
+    TagRemoved {
+        /// What the tag applied to
+        target: Option<Target>,
+        /// The old tag
+        tag: SimpleTag,
+    },
+    /// A simple tag's value or flags changed
+    TagModified {
+        /// What the tag applies to
+        target: Option<Target>,
+        /// The tag before the change
+        before: SimpleTag,
+        /// The tag after the change
+        after: SimpleTag,
+    },
+    /// A chapter was added
+    ChapterAdded {
+        /// The UID of the chapter's edition
+        edition_uid: Option<u64>,
+        /// The new chapter
+        chapter: Chapter,
+    },
+    /// A chapter was removed
+    ChapterRemoved {
+        /// The UID of the chapter's edition
+        edition_uid: Option<u64>,
+        /// The old chapter
+        chapter: Chapter,
+    },
+    /// A chapter's start or end time changed
+    ChapterRetimed {
+        /// The UID of the chapter's edition
+        edition_uid: Option<u64>,
+        /// The chapter's UID
+        uid: u64,
+        /// The old start and end times
+        before: (Duration, Option<Duration>),
+        /// The new start and end times
+        after: (Duration, Option<Duration>),
+    },
+    /// Some other part of a chapter changed, such as its display strings
+    ChapterModified {
+        /// The UID of the chapter's edition
+        edition_uid: Option<u64>,
+        /// The chapter before the change
+        before: Chapter,
+        /// The chapter after the change
+        after: Chapter,
+    },
+    /// An attachment was added
+    AttachmentAdded {
+        /// The attachment's file name
+        name: String,
+        /// The attachment's size in bytes
+        size: usize,
+    },
+    /// An attachment was removed
+    AttachmentRemoved {
+        /// The attachment's file name
+        name: String,
+        /// The attachment's size in bytes
+        size: usize,
+    },
+    /// An attachment with the same UID has different contents
+    AttachmentReplaced {
+        /// The attachment's file name
+        name: String,
+        /// The new size minus the old size, in bytes
+        size_delta: i64,
+    },
+}
+
+/// Compares the metadata of two parsed files
+///
+/// Tags are matched by target, name and language,
+/// chapters by edition and chapter UID,
+/// and attachments by UID,
+/// so differences in the order of those elements are ignored.
+/// Attachments whose data was deferred are compared by size
+/// rather than by their data.
+/// Nested chapters are compared on their own, so the chapters
+/// in chapter changes are given without their nested ones.
+/// Tracks are not compared.
+pub fn diff(a: &Matroska, b: &Matroska) -> MetadataDiff {
+    let mut changes = Vec::new();
+    diff_info(a, b, &mut changes);
+    diff_tags(a, b, &mut changes);
+    diff_chapters(a, b, &mut changes);
+    diff_attachments(a, b, &mut changes);
+    MetadataDiff { changes }
+}
+
+fn diff_info(a: &Matroska, b: &Matroska, changes: &mut Vec<Change>) {
+    macro_rules! field {
+        ($name:ident) => {
+            if a.info.$name != b.info.$name {
+                changes.push(Change::Info {
+                    field: stringify!($name),
+                    before: format!("{:?}", a.info.$name),
+                    after: format!("{:?}", b.info.$name),
+                });
+            }
+        };
+    }
+
+    field!(uid);
+    field!(prev_uid);
+    field!(next_uid);
+    field!(filename);
+    field!(prev_filename);
+    field!(next_filename);
+    field!(family_uids);
+    field!(title);
+    field!(duration);
+    field!(date_utc);
+    field!(muxing_app);
+    field!(writing_app);
+    field!(timestamp_scale);
+}
+
+/// A normalized, comparable form of a tag's target
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct TargetKey {
+    level: &'static str,
+    target_type: Option<String>,
+    track_uids: Vec<u64>,
+    edition_uids: Vec<u64>,
+    chapter_uids: Vec<u64>,
+    attachment_uids: Vec<u64>,
+}
+
+impl TargetKey {
+    fn new(target: Option<&Target>) -> TargetKey {
+        fn sorted(uids: &[u64]) -> Vec<u64> {
+            let mut uids = uids.to_vec();
+            uids.sort_unstable();
+            uids.dedup();
+            uids
+        }
+
+        // an absent Targets element is the same as an empty one
+        let default = Target::new();
+        let target = target.unwrap_or(&default);
+        TargetKey {
+            level: target
+                .target_type_value
+                .map(|v| v.as_str())
+                .unwrap_or_default(),
+            target_type: target.target_type.clone(),
+            track_uids: sorted(&target.track_uids),
+            edition_uids: sorted(&target.edition_uids),
+            chapter_uids: sorted(&target.chapter_uids),
+            attachment_uids: sorted(&target.attachment_uids),
+        }
+    }
+}
+
+type TagKey = (TargetKey, String, Option<String>);
+
+fn collect_tags(m: &Matroska) -> BTreeMap<TagKey, Vec<(Option<&Target>, &SimpleTag)>> {
+    let mut tags: BTreeMap<TagKey, Vec<_>> = BTreeMap::new();
+    for tag in &m.tags {
+        let target = TargetKey::new(tag.targets.as_ref());
+        for simple in &tag.simple {
+            tags.entry((
+                target.clone(),
+                simple.name.clone(),
+                simple.language.as_ref().map(|l| l.to_string()),
+            ))
+            .or_default()
+            .push((tag.targets.as_ref(), simple));
+        }
+    }
+    tags
+}
+
+fn diff_tags(a: &Matroska, b: &Matroska, changes: &mut Vec<Change>) {
+    let before = collect_tags(a);
+    let mut after = collect_tags(b);
+
+    for (key, mut old) in before {
+        let mut new = after.remove(&key).unwrap_or_default();
+
+        // discard tags present on both sides, whatever their order
+        old.retain(|(_, o)| match new.iter().position(|(_, n)| n == o) {
+            Some(i) => {
+                new.remove(i);
+                false
+            }
+            None => true,
+        });
+
+        let mut new = new.into_iter();
+        for (target, old) in old {
+            match new.next() {
+                Some((_, new)) => changes.push(Change::TagModified {
+                    target: target.cloned(),
+                    before: old.clone(),
+                    after: new.clone(),
+                }),
+                None => changes.push(Change::TagRemoved {
+                    target: target.cloned(),
+                    tag: old.clone(),
+                }),
+            }
+        }
+        changes.extend(new.map(|(target, new)| Change::TagAdded {
+            target: target.cloned(),
+            tag: new.clone(),
+        }));
+    }
+
+    for (target, new) in after.into_values().flatten() {
+        changes.push(Change::TagAdded {
+            target: target.cloned(),
+            tag: new.clone(),
+        });
+    }
+}
+
+fn collect_chapters(m: &Matroska) -> BTreeMap<(Option<u64>, u64, usize), Chapter> {
+    let mut chapters = BTreeMap::new();
+    for edition in &m.chapters {
+        for (index, chapter) in edition.iter_all().enumerate() {
+            // chapters without a UID can only be matched by position
+            let index = if chapter.uid == 0 { index } else { 0 };
+            // nested chapters are compared separately
+            let mut chapter = chapter.clone();
+            chapter.chapters.clear();
+            chapters.insert((edition.uid, chapter.uid, index), chapter);
+        }
+    }
+    chapters
+}
+
+fn diff_chapters(a: &Matroska, b: &Matroska, changes: &mut Vec<Change>) {
+    let before = collect_chapters(a);
+    let mut after = collect_chapters(b);
+
+    for (key @ (edition_uid, uid, _), old) in before {
+        match after.remove(&key) {
+            Some(new) if new == old => {}
+            Some(new) if (new.time_start, new.time_end) != (old.time_start, old.time_end) => {
+                changes.push(Change::ChapterRetimed {
+                    edition_uid,
+                    uid,
+                    before: (old.time_start, old.time_end),
+                    after: (new.time_start, new.time_end),
+                })
+            }
+            Some(new) => changes.push(Change::ChapterModified {
+                edition_uid,
+                before: old,
+                after: new,
+            }),
+            None => changes.push(Change::ChapterRemoved {
+                edition_uid,
+                chapter: old,
+            }),
+        }
+    }
+
+    for ((edition_uid, _, _), new) in after {
+        changes.push(Change::ChapterAdded {
+            edition_uid,
+            chapter: new,
+        });
+    }
+}
+
+/// Returns attachments by UID, along with their position
+/// among those without one, which can only be matched by position
+fn collect_attachments(m: &Matroska) -> BTreeMap<(u64, usize), &Attachment> {
+    let mut unset = 0;
+    m.attachments
+        .iter()
+        .map(|a| match a.uid {
+            0 => {
+                unset += 1;
+                ((0, unset), a)
+            }
+            uid => ((uid, 0), a),
+        })
+        .collect()
+}
+
+/// Whether two attachments have the same contents,
+/// wherever they are in their files
+fn same_attachment(a: &Attachment, b: &Attachment) -> bool {
+    let unplaced = |a: &Attachment| {
+        let mut a = a.clone();
+        a.data_len = a.stored_len();
+        a.data_offset = 0;
+        a.data_range = None;
+        a
+    };
+    unplaced(a) == unplaced(b)
+}
+
+fn diff_attachments(a: &Matroska, b: &Matroska, changes: &mut Vec<Change>) {
+    let mut after = collect_attachments(b);
+
+    for (key, old) in collect_attachments(a) {
+        match after.remove(&key) {
+            Some(new) if same_attachment(new, old) => {}
+            Some(new) => changes.push(Change::AttachmentReplaced {
+                name: new.name.clone(),
+                size_delta: new.stored_len() as i64 - old.stored_len() as i64,
+            }),
+            None => changes.push(Change::AttachmentRemoved {
+                name: old.name.clone(),
+                size: old.stored_len() as usize,
+            }),
+        }
+    }
+
+    for new in after.into_values() {
+        changes.push(Change::AttachmentAdded {
+            name: new.name.clone(),
+            size: new.stored_len() as usize,
+        });
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::{fmt, io};
+
+use crate::ebml::{self, ElementType, Result};
+use crate::{ids, ElementReader};
+
+/// Binary bodies up to this long are read into a node's value
+const MAX_BINARY_VALUE: u64 = 16;
+
+/// An element in the tree returned by [`dump`]
+///
+/// Its `Display` implementation renders it and its children
+/// as an indented tree, much like `mkvinfo`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ElementNode {
+    /// The element's ID
+    pub id: u32,
+    /// The element's name, if it's one this crate knows about
+    pub name: Option<&'static str>,
+    /// The file offset of the element's header
+    pub offset: u64,
+    /// The length of the element's ID and size, in bytes
+    pub header_len: u64,
+    /// The length of the element's body in bytes,
+    /// or `None` if the element has an unknown size
+    pub size: Option<u64>,
+    /// The element's decoded value, for non-master elements
+    ///
+    /// Binary values longer than 16 bytes are left unread.
+    pub value: Option<ElementType>,
+    /// The element's children, for master elements
+    /// within the depth limit
+    pub children: Vec<ElementNode>,
+}
+
+impl ElementNode {
+    fn write_tree(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        for _ in 0..depth {
+            write!(f, "|")?;
+        }
+        match self.name {
+            Some(name) => write!(f, "+ {name}")?,
+            None => write!(f, "+ Unknown element")?,
+        }
+        write!(f, " ({:#X}) at {}", self.id, self.offset)?;
+        match self.size {
+            Some(size) => write!(f, " size {size}")?,
+            None => write!(f, " size unknown")?,
+        }
+        match &self.value {
+            Some(ElementType::Int(i)) => write!(f, ": {i}")?,
+            Some(ElementType::UInt(u)) => write!(f, ": {u}")?,
+            Some(ElementType::String(s)) | Some(ElementType::UTF8(s)) => write!(f, ": {s:?}")?,
+            Some(ElementType::Float(v)) => write!(f, ": {v}")?,
+            Some(ElementType::Date(d)) => write!(f, ": {}", i64::from(d.clone()))?,
+            Some(ElementType::Binary(b)) => {
+                write!(f, ":")?;
+                for byte in b {
+                    write!(f, " {byte:02x}")?;
+                }
+            }
+            Some(ElementType::Master(_)) | Some(ElementType::Skipped { .. }) | None => {}
+        }
+        writeln!(f)?;
+        self.children
+            .iter()
+            .try_for_each(|child| child.write_tree(f, depth + 1))
+    }
+}
+
+impl fmt::Display for ElementNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
+
+/// Returns the tree of elements in an open Matroska file,
+/// with their offsets, sizes and values, for debugging
+///
+/// Master elements are descended into up to `depth_limit` levels deep,
+/// except for Clusters, which are listed without their contents
+/// to keep large files fast, unless they're of unknown size.
+/// Use [`dump_all`] to include them.
+///
+/// ## Example
+/// ```no_run
+/// let file = std::io::BufReader::new(std::fs::File::open("file.mkv").unwrap());
+/// for node in matroska::dump(file, 4).unwrap() {
+///     print!("{}", node);
+/// }
+/// ```
+pub fn dump<R: io::Read + io::Seek>(r: R, depth_limit: usize) -> Result<Vec<ElementNode>> {
+    let mut reader = ElementReader::new(r)?.with_max_depth(depth_limit);
+    dump_level(&mut reader, None, depth_limit, false)
+}
+
+/// Returns the tree of elements in an open Matroska file,
+/// including the contents of its Clusters
+///
+/// See [`dump`] for details.
+pub fn dump_all<R: io::Read + io::Seek>(r: R, depth_limit: usize) -> Result<Vec<ElementNode>> {
+    let mut reader = ElementReader::new(r)?.with_max_depth(depth_limit);
+    dump_level(&mut reader, None, depth_limit, true)
+}
+
+fn dump_level<R: io::Read + io::Seek>(
+    reader: &mut ElementReader<R>,
+    parent_id: Option<u32>,
+    depth_limit: usize,
+    clusters: bool,
+) -> Result<Vec<ElementNode>> {
+    let mut nodes = Vec::new();
+    while let Some(header) = reader.next_element()? {
+        let mut node = ElementNode {
+            id: header.id,
+            name: ids::name(header.id),
+            offset: header.offset,
+            header_len: header.header_len,
+            size: header.size,
+            value: None,
+            children: Vec::new(),
+        };
+        if ebml::is_master(header.id, parent_id) {
+            // an element of unknown size can't be skipped, only read through
+            let listed = clusters || header.id != ids::CLUSTER || header.size.is_none();
+            if reader.depth() < depth_limit && listed {
+                reader.children()?;
+                node.children = dump_level(reader, Some(header.id), depth_limit, clusters)?;
+            }
+        } else if let Some(size) = header.size {
+            if !ebml::is_binary(header.id, parent_id) || size <= MAX_BINARY_VALUE {
+                node.value = Some(reader.read_body()?);
+            }
+        }
+        nodes.push(node);
+    }
+    Ok(nodes)
+}
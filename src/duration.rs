@@ -0,0 +1,149 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+use std::io::{self, SeekFrom};
+use std::time::Duration;
+
+use crate::cluster::{Cluster, ClusterWalker};
+use crate::ebml::{self, Result};
+use crate::{ids, stream_len, Matroska};
+
+/// How much of the file's end is searched for its last Cluster
+/// before walking every Cluster from the start instead
+const MAX_BACKWARD_SCAN: u64 = 32 * 1024 * 1024;
+
+/// How much is read at a time while searching backward
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+impl Matroska {
+    /// Finds the file's duration from the end of its last block,
+    /// for files whose Info has no Duration, such as unfinished recordings
+    ///
+    /// The last Cluster is found by searching backward from the end
+    /// of the file, or by walking every Cluster if that fails.
+    /// Each block ends after its BlockDuration, or its track's
+    /// default duration if it has none.
+    /// A truncated final Cluster contributes the blocks it does have.
+    /// Returns `None` if the file has no Clusters.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// let mut file = std::fs::File::open("file.mkv").unwrap();
+    /// let matroska = matroska::Matroska::open(&mut file).unwrap();
+    /// let duration = match matroska.info.duration {
+    ///     Some(duration) => Some(duration),
+    ///     None => matroska.scan_duration(&mut file).unwrap(),
+    /// };
+    /// ```
+    pub fn scan_duration<R: io::Read + io::Seek>(&self, mut r: R) -> Result<Option<Duration>> {
+        let default_durations: BTreeMap<u64, Duration> = self
+            .tracks
+            .iter()
+            .filter_map(|t| t.default_duration.map(|d| (t.number, d)))
+            .collect();
+        let end_of = |cluster: &Cluster| {
+            cluster
+                .blocks
+                .iter()
+                .filter_map(|block| {
+                    let duration = match block.duration {
+                        Some(ticks) => cluster.ticks(ticks),
+                        None => default_durations
+                            .get(&block.track)
+                            .copied()
+                            .unwrap_or_default(),
+                    };
+                    cluster.block_timestamp(block).map(|start| start + duration)
+                })
+                .max()
+                .unwrap_or(cluster.timestamp)
+        };
+
+        let scale = self.info.timestamp_scale;
+        let len = stream_len(&mut r)?;
+        let floor = self
+            .segment_start
+            .max(len.saturating_sub(MAX_BACKWARD_SCAN));
+        let mut before = len;
+        while let Some(offset) = find_cluster_before(&mut r, before, floor)? {
+            let mut walker = ClusterWalker::new(&mut r, scale, None)?;
+            walker.seek_to(offset)?;
+            // a false match is simply passed over
+            if let Ok(clusters) = walker.collect::<Result<Vec<_>>>() {
+                if let Some(end) = clusters.iter().map(end_of).max() {
+                    return Ok(Some(end));
+                }
+            }
+            before = offset;
+        }
+
+        let mut end = None;
+        for cluster in ClusterWalker::new(&mut r, scale, None)? {
+            end = end.max(Some(end_of(&cluster?)));
+        }
+        Ok(end)
+    }
+}
+
+/// Returns the offset of the last plausible Cluster header
+/// which starts before the given offset and at or after the floor
+fn find_cluster_before<R: io::Read + io::Seek>(
+    r: &mut R,
+    before: u64,
+    floor: u64,
+) -> Result<Option<u64>> {
+    let pattern = ids::CLUSTER.to_be_bytes();
+    // headers may straddle chunks, so each chunk overlaps the next
+    let mut end = before.saturating_add(pattern.len() as u64 - 1);
+    while end > floor {
+        let start = end.saturating_sub(CHUNK_SIZE).max(floor);
+        let mut chunk = vec![0; (end - start) as usize];
+        r.seek(SeekFrom::Start(start))?;
+        let read = read_up_to(r, &mut chunk)?;
+        chunk.truncate(read);
+        for (i, window) in chunk.windows(pattern.len()).enumerate().rev() {
+            let offset = start + i as u64;
+            if window == pattern && offset < before && is_cluster(r, offset)? {
+                return Ok(Some(offset));
+            }
+        }
+        if start == floor {
+            break;
+        }
+        end = start + pattern.len() as u64 - 1;
+    }
+    Ok(None)
+}
+
+/// Whether the element at the given offset looks like a Cluster,
+/// starting with its Timestamp
+fn is_cluster<R: io::Read + io::Seek>(r: &mut R, offset: u64) -> Result<bool> {
+    r.seek(SeekFrom::Start(offset))?;
+    if ebml::read_element_id_size(r).is_err() {
+        return Ok(false);
+    }
+    Ok(matches!(
+        ebml::read_element_id_size(r),
+        Ok((ids::TIMECODE, 1..=8, _))
+    ))
+}
+
+/// Reads as much of the buffer as the stream has left
+fn read_up_to<R: io::Read>(r: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match r.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(read)
+}
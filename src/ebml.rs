@@ -7,27 +7,89 @@
 // except according to those terms.
 
 use std::string::FromUtf8Error;
+use std::time::{Duration, SystemTime};
 use std::{error, fmt, io};
 
-use bitstream_io::BitRead;
+use crate::{ids, TrackProblem};
 use phf::{phf_map, phf_set, Map, Set};
 
 pub type Result<T> = std::result::Result<T, MatroskaError>;
 
-type BitReader<R> = bitstream_io::BitReader<R, bitstream_io::BigEndian>;
-
 /// An EBML tree element
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct Element {
+    /// The element's ID
     pub id: u32,
-    pub size: u64, /*total size of element, including header*/
+    /// The total size of the element, including its header
+    pub size: u64,
+    /// The element's parsed body
     pub val: ElementType,
 }
 
+/// Limits on the elements read, guarding against crafted files
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Limits {
+    /// The largest non-master element body to read, in bytes
+    pub max_element_size: u64,
+    /// The deepest master elements may nest within what's being parsed
+    pub max_depth: usize,
+    /// The largest unknown element body to keep, in bytes,
+    /// above which it's [`ElementType::Skipped`]
+    pub max_unknown_size: u64,
+}
+
+impl Limits {
+    /// The default largest element body, 64 MiB
+    pub const MAX_ELEMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+    /// The default nesting depth, which exceeds any legitimate file's
+    pub const MAX_DEPTH: usize = 32;
+
+    /// The default largest unknown element body kept, 64 KiB
+    pub const MAX_UNKNOWN_SIZE: u64 = 64 * 1024;
+
+    /// Sets the largest non-master element body to read, in bytes
+    pub fn with_max_element_size(mut self, max_element_size: u64) -> Self {
+        self.max_element_size = max_element_size;
+        self
+    }
+
+    /// Sets the deepest master elements may nest
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the largest unknown element body to keep, in bytes
+    pub fn with_max_unknown_size(mut self, max_unknown_size: u64) -> Self {
+        self.max_unknown_size = max_unknown_size;
+        self
+    }
+
+    /// The limits for parsing one level deeper
+    fn nested(&self) -> Option<Limits> {
+        self.max_depth
+            .checked_sub(1)
+            .map(|max_depth| Limits { max_depth, ..*self })
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_element_size: Limits::MAX_ELEMENT_SIZE,
+            max_depth: Limits::MAX_DEPTH,
+            max_unknown_size: Limits::MAX_UNKNOWN_SIZE,
+        }
+    }
+}
+
 static IDS_MASTER_DEFAULT: Set<u32> = phf_set! {
     0x80u32, 0x8Eu32, 0x8Fu32, 0xA0u32, 0xA6u32, 0xAEu32, 0xB6u32,
     0xB7u32, 0xBBu32, 0xC8u32, 0xDBu32, 0xE0u32, 0xE1u32, 0xE2u32,
-    0xE3u32, 0xE4u32, 0xE8u32, 0xE9u32, 0x45B9u32, 0x4DBBu32,
+    0xE3u32, 0xE4u32, 0xE8u32, 0xE9u32, 0x41E4u32, 0x45B9u32, 0x47E7u32, 0x4DBBu32,
     0x5034u32, 0x5035u32, 0x55B0u32, 0x55D0u32, 0x5854u32, 0x61A7u32,
     0x6240u32, 0x63C0u32, 0x6624u32, 0x67C8u32, 0x6911u32, 0x6924u32,
     0x6944u32, 0x6D80u32, 0x7373u32, 0x75A1u32, 0x7E5Bu32, 0x7E7Bu32,
@@ -76,10 +138,10 @@ static IDS_UINT: Set<u32> = phf_set! {
     0xC9u32, 0xCAu32, 0xCBu32, 0xCCu32, 0xCDu32, 0xCEu32,
     0xCFu32, 0xD7u32, 0xE5u32, 0xE6u32, 0xE7u32, 0xEAu32,
     0xEBu32, 0xEDu32, 0xEEu32, 0xF0u32, 0xF1u32, 0xF7u32,
-    0xFAu32, 0x4254u32, 0x4285u32, 0x4286u32, 0x4287u32,
+    0xFAu32, 0x41E7u32, 0x41F0u32, 0x4254u32, 0x4285u32, 0x4286u32, 0x4287u32,
     0x42F2u32, 0x42F3u32, 0x42F7u32, 0x4484u32, 0x4598u32,
     0x45BCu32, 0x45BDu32, 0x45DBu32, 0x45DDu32, 0x4661u32,
-    0x4662u32, 0x46AEu32, 0x47E1u32, 0x47E5u32, 0x47E6u32,
+    0x4662u32, 0x46AEu32, 0x47E1u32, 0x47E5u32, 0x47E6u32, 0x47E8u32,
     0x5031u32, 0x5032u32, 0x5033u32, 0x535Fu32, 0x5378u32,
     0x53ACu32, 0x53B8u32, 0x53B9u32, 0x53C0u32, 0x54AAu32,
     0x54B0u32, 0x54B2u32, 0x54B3u32, 0x54BAu32, 0x54BBu32,
@@ -95,7 +157,7 @@ static IDS_UINT: Set<u32> = phf_set! {
 };
 
 static IDS_STRING: Set<u32> = phf_set! {
-    0x86u32, 0x4282u32, 0x437Cu32, 0x437Du32, 0x437Eu32, 0x447Au32, 0x447Bu32,
+    0x86u32, 0x41A4u32, 0x4282u32, 0x437Cu32, 0x437Du32, 0x437Eu32, 0x447Au32, 0x447Bu32,
     0x4660u32, 0x63CAu32, 0x22_B59Cu32, 0x22_B59Du32, 0x26_B240u32,
     0x3B_4040u32
 };
@@ -108,7 +170,7 @@ static IDS_UTF8: Set<u32> = phf_set! {
 
 static IDS_BINARY: Set<u32> = phf_set! {
     0xA1u32, 0xA2u32, 0xA3u32, 0xA4u32, 0xA5u32, 0xAFu32,
-    0xBFu32, 0xC1u32, 0xC4u32, 0xECu32, 0x4255u32, 0x4444u32,
+    0xBFu32, 0xC1u32, 0xC4u32, 0xECu32, 0x41EDu32, 0x4255u32, 0x4444u32,
     0x4485u32, 0x450Du32, 0x465Cu32, 0x4675u32, 0x47E2u32,
     0x47E3u32, 0x47E4u32, 0x53ABu32, 0x63A2u32, 0x6532u32,
     0x66A5u32, 0x6933u32, 0x69A5u32, 0x6E67u32, 0x73A4u32,
@@ -123,31 +185,102 @@ static IDS_FLOAT: Set<u32> = phf_set! {
     0x2F_B523u32
 };
 
+/// Whether the element is a master, given its parent's ID
+pub(crate) fn is_master(id: u32, parent_id: Option<u32>) -> bool {
+    let ids_master = match parent_id {
+        Some(parent_id) => *IDS_MASTER.get(&parent_id).unwrap_or(&&IDS_MASTER_DEFAULT),
+        None => &IDS_MASTER_DEFAULT,
+    };
+    ids_master.contains(&id)
+}
+
+/// Whether the element's body is binary data,
+/// or of a type which isn't known
+pub(crate) fn is_binary(id: u32, parent_id: Option<u32>) -> bool {
+    !is_master(id, parent_id)
+        && ![&IDS_INT, &IDS_UINT, &IDS_STRING, &IDS_UTF8, &IDS_FLOAT]
+            .iter()
+            .any(|ids| ids.contains(&id))
+        && id != 0x4461
+}
+
+/// Whether the element's type isn't known at all
+fn is_unknown(id: u32, parent_id: Option<u32>) -> bool {
+    is_binary(id, parent_id) && !IDS_BINARY.contains(&id)
+}
+
 impl Element {
-    pub fn parse<R: io::Read>(r: &mut R, parent_id: Option<u32>) -> Result<Element> {
+    /// Parses an element which must fit within `remaining` bytes
+    /// of its parent, checking so before reading any of its body
+    fn parse_within<R: io::Read>(
+        r: &mut R,
+        parent_id: Option<u32>,
+        remaining: u64,
+        limits: &Limits,
+        offset: u64,
+    ) -> Result<Element> {
         let (id, size, header_len) = read_element_id_size(r)?;
-        let val = Element::parse_body(r, id, size, parent_id)?;
+        // unknown sizes are only supported for top-level elements
+        if size == UNKNOWN_SIZE {
+            return Err(MatroskaError::UnknownSizeElement { id });
+        }
+        remaining_after(remaining, id, size, header_len)?;
+        if is_skipped(id) {
+            // checksums are verified by the section, if at all,
+            // and Void payloads may be megabytes of reserved space
+            discard(r, size)?;
+            return Ok(Element {
+                id,
+                size: header_len + size,
+                val: ElementType::Binary(Vec::new()),
+            });
+        }
         Ok(Element {
             id,
             size: header_len + size,
-            val,
+            val: Element::parse_body_at(r, id, size, parent_id, limits, offset + header_len)?,
         })
     }
 
-    pub fn parse_body<R: io::Read>(
+    pub(crate) fn parse_body<R: io::Read>(
         r: &mut R,
         id: u32,
         size: u64,
         parent_id: Option<u32>,
+        limits: &Limits,
+    ) -> Result<ElementType> {
+        Element::parse_body_at(r, id, size, parent_id, limits, 0)
+    }
+
+    /// Parses the body of an element starting at the given offset,
+    /// which is where any [`ElementType::Skipped`] within it are placed from
+    pub(crate) fn parse_body_at<R: io::Read>(
+        r: &mut R,
+        id: u32,
+        size: u64,
+        parent_id: Option<u32>,
+        limits: &Limits,
+        offset: u64,
     ) -> Result<ElementType> {
-        let ids_master = match parent_id {
-            Some(parent_id) => *IDS_MASTER.get(&parent_id).unwrap_or(&&IDS_MASTER_DEFAULT),
-            None => &IDS_MASTER_DEFAULT,
-        };
         match id {
-            id if ids_master.contains(&id) => {
-                Element::parse_master(r, size, Some(id)).map(ElementType::Master)
+            id if is_master(id, parent_id) => match limits.nested() {
+                Some(nested) => Element::parse_master_at(r, size, Some(id), &nested, offset)
+                    .map(ElementType::Master),
+                None => Err(MatroskaError::NestingTooDeep { id }),
+            },
+            // only master elements may have an unknown size
+            _ if size == UNKNOWN_SIZE => Err(MatroskaError::UnknownSizeElement { id }),
+            _ if size > limits.max_unknown_size && is_unknown(id, parent_id) => {
+                // nothing reads unknown elements, so large ones
+                // are passed over rather than held in memory
+                discard(r, size)?;
+                Ok(ElementType::Skipped { offset, size })
             }
+            _ if size > limits.max_element_size => Err(MatroskaError::ElementTooLarge {
+                id,
+                size,
+                limit: limits.max_element_size,
+            }),
             id if IDS_INT.contains(&id) => read_int(r, size).map(ElementType::Int),
             id if IDS_UINT.contains(&id) => read_uint(r, size).map(ElementType::UInt),
             id if IDS_STRING.contains(&id) => read_string(r, size).map(ElementType::String),
@@ -159,58 +292,262 @@ impl Element {
         }
     }
 
-    pub fn parse_master<R: io::Read>(
+    pub(crate) fn parse_master<R: io::Read>(
+        r: &mut R,
+        size: u64,
+        parent_id: Option<u32>,
+    ) -> Result<Vec<Element>> {
+        Element::parse_master_with(r, size, parent_id, &Limits::default())
+    }
+
+    pub(crate) fn parse_master_with<R: io::Read>(
+        r: &mut R,
+        size: u64,
+        parent_id: Option<u32>,
+        limits: &Limits,
+    ) -> Result<Vec<Element>> {
+        Element::parse_master_at(r, size, parent_id, limits, 0)
+    }
+
+    fn parse_master_at<R: io::Read>(
         r: &mut R,
         mut size: u64,
         parent_id: Option<u32>,
+        limits: &Limits,
+        mut offset: u64,
     ) -> Result<Vec<Element>> {
         let mut elements = Vec::new();
         while size > 0 {
-            let e = Element::parse(r, parent_id)?;
-            if e.size > size {
-                return Err(MatroskaError::InvalidSize);
-            }
+            let e = Element::parse_within(r, parent_id, size, limits, offset)?;
             size -= e.size;
-            elements.push(e);
+            offset += e.size;
+            if !is_skipped(e.id) {
+                elements.push(e);
+            }
+        }
+        Ok(elements)
+    }
+
+    /// Parses only the children with the given IDs, at any depth,
+    /// passing over the bodies of all others with `skip`
+    /// so that none of them is ever read into memory
+    pub(crate) fn parse_master_only<R, S>(
+        r: &mut R,
+        mut size: u64,
+        parent_id: Option<u32>,
+        only: &[u32],
+        limits: &Limits,
+        skip: &mut S,
+    ) -> Result<Vec<Element>>
+    where
+        R: io::Read,
+        S: FnMut(&mut R, u64) -> Result<()>,
+    {
+        let mut elements = Vec::new();
+        while size > 0 {
+            let (id, body_size, header_len) = read_element_id_size(r)?;
+            if body_size == UNKNOWN_SIZE {
+                return Err(MatroskaError::UnknownSizeElement { id });
+            }
+            size = remaining_after(size, id, body_size, header_len)?;
+            let val = if !only.contains(&id) {
+                skip(r, body_size)?;
+                continue;
+            } else if is_master(id, parent_id) {
+                let nested = limits
+                    .nested()
+                    .ok_or(MatroskaError::NestingTooDeep { id })?;
+                Element::parse_master_only(r, body_size, Some(id), only, &nested, skip)
+                    .map(ElementType::Master)?
+            } else {
+                Element::parse_body(r, id, body_size, parent_id, limits)?
+            };
+            elements.push(Element {
+                id,
+                size: header_len + body_size,
+                val,
+            });
         }
         Ok(elements)
     }
 }
 
+/// The parsed body of an EBML element, by the element's type
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ElementType {
+    /// A master element's children, minus any CRC-32 or Void elements
     Master(Vec<Element>),
+    /// A signed integer
     Int(i64),
+    /// An unsigned integer
     UInt(u64),
+    /// An ASCII string
     String(String),
+    /// A UTF-8 string
     UTF8(String),
+    /// Binary data, or the body of an element whose type isn't known
     Binary(Vec<u8>),
+    /// A floating point number
     Float(f64),
+    /// A date
     Date(DateTime),
+    /// The body of an element whose type isn't known,
+    /// left unread since it was too large to be worth keeping
+    Skipped {
+        /// The body's offset, from the start of the body
+        /// whose parsing reached it, or in the file
+        /// when read by an [`crate::ElementReader`]
+        offset: u64,
+        /// The body's size in bytes
+        size: u64,
+    },
 }
 
 /// A possible error when parsing a Matroska file
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum MatroskaError {
     /// An I/O error
     Io(io::Error),
     /// An error decoding a UTF-8 string
     UTF8(FromUtf8Error),
-    /// An invalid element ID
+    /// An invalid element ID, whose length marker is missing
+    /// or longer than 4 bytes
     InvalidID,
-    /// An invalid element size
+    /// An invalid element size, whose length marker is missing
     InvalidSize,
-    /// An invalid unsigned integer
-    InvalidUint,
-    /// An invalid floating point value
-    InvalidFloat,
-    /// An invalid date value
+    /// An integer element wider than 8 bytes
+    InvalidIntegerWidth {
+        /// The element's size in bytes
+        size: u64,
+    },
+    /// A floating point element which isn't 0, 4 or 8 bytes wide
+    InvalidFloatWidth {
+        /// The element's size in bytes
+        size: u64,
+    },
+    /// A date element which isn't 8 bytes wide
     InvalidDate,
+    /// A non-master element with an unknown size,
+    /// which only master elements may have
+    UnknownSizeElement {
+        /// The element's ID
+        id: u32,
+    },
     /// Invalid seek head entry
     InvalidSeekHead {
         /// The invalid id
         id: u32,
     },
+    /// The file has no Segment contents, such as a zero-byte file
+    /// or one from a recording which never started
+    EmptySegment,
+    /// A block's lace sizes don't fit its payload
+    InvalidLacing,
+    /// A track entry is missing a mandatory element
+    InvalidTrack {
+        /// The track's number, or 0 if it has none
+        number: u64,
+        /// What's wrong with the track
+        problem: TrackProblem,
+    },
+    /// The Info's Duration is negative, NaN or infinite
+    InvalidDuration {
+        /// The Duration as stored, in timestamp-scale units
+        value: f64,
+    },
+    /// An edit can't be made without rewriting the whole file,
+    /// such as one which doesn't fit where the element was
+    /// and can't be appended to the end of the Segment
+    RequiresRemux,
+    /// An element is larger than the configured maximum
+    ElementTooLarge {
+        /// The element's ID
+        id: u32,
+        /// The element's declared size
+        size: u64,
+        /// The maximum size allowed, from [`ParseOptions::max_element_size`](crate::ParseOptions::max_element_size)
+        limit: u64,
+    },
+    /// A section's contents don't match its CRC-32
+    ChecksumMismatch {
+        /// The section's ID
+        element_id: u32,
+        /// The file offset of the section's CRC-32 element
+        offset: u64,
+    },
+    /// Master elements are nested deeper than the configured maximum
+    NestingTooDeep {
+        /// The ID of the master element beyond the maximum depth
+        id: u32,
+    },
+    /// A child element claims to be larger than what remains of its parent
+    OversizedChildElement {
+        /// The child's ID
+        id: u32,
+        /// The child's declared size, not including its header
+        size: u64,
+        /// The number of bytes remaining in the parent, including the child's header
+        parent_remaining: u64,
+    },
+    /// A different element was found where a SeekHead said one would be
+    UnexpectedElement {
+        /// The ID of the element the SeekHead pointed to
+        expected: u32,
+        /// The ID of the element actually found there
+        found: u32,
+        /// The file offset of the element found
+        offset: u64,
+    },
+    /// A section which may only occur once was repeated,
+    /// and the repetition was ignored
+    DuplicateElement {
+        /// The section's ID
+        id: u32,
+        /// The file offset of the ignored repetition
+        offset: u64,
+    },
+    /// Another error, along with where in the file it occurred
+    Located {
+        /// The file offset reading had reached when the error occurred
+        offset: u64,
+        /// The ID of the top-level element being read, if known
+        element: Option<u32>,
+        /// The error itself
+        error: Box<MatroskaError>,
+    },
+    /// No track has the requested track number
+    UnknownTrack {
+        /// The requested track number
+        number: u64,
+    },
+    /// A track's data uses a content encoding which can't be undone,
+    /// such as encryption
+    UnsupportedEncoding {
+        /// The track's number
+        track: u64,
+    },
+    /// A frame of a track's data couldn't be decompressed
+    InvalidCompressedData {
+        /// The track's number
+        track: u64,
+    },
+    /// Linked segments whose files couldn't be found
+    UnresolvedSegments {
+        /// The SegmentUIDs without a matching file, in the order encountered
+        uids: Vec<Vec<u8>>,
+    },
+    /// A line of simple chapters text couldn't be understood
+    InvalidSimpleChapters {
+        /// The line's number, starting from 1
+        line: usize,
+    },
+    /// Tags XML is malformed, or has an element or value which doesn't belong
+    InvalidXml {
+        /// The number of the line where the problem was found, starting from 1
+        line: usize,
+    },
 }
 
 impl From<std::io::Error> for MatroskaError {
@@ -227,120 +564,363 @@ impl fmt::Display for MatroskaError {
             MatroskaError::UTF8(error) => error.fmt(f),
             MatroskaError::InvalidID => write!(f, "invalid element ID"),
             MatroskaError::InvalidSize => write!(f, "invalid element size"),
-            MatroskaError::InvalidUint => write!(f, "invalid unsigned integer"),
-            MatroskaError::InvalidFloat => write!(f, "invalid float"),
+            MatroskaError::InvalidIntegerWidth { size } => {
+                write!(f, "invalid {size} byte integer")
+            }
+            MatroskaError::InvalidFloatWidth { size } => write!(f, "invalid {size} byte float"),
             MatroskaError::InvalidDate => write!(f, "invalid date"),
+            MatroskaError::UnknownSizeElement { id } => {
+                write!(f, "element {id:#X} has an unknown size")
+            }
             MatroskaError::InvalidSeekHead { id } => write!(f, "invalid seek head id={id}"),
+            MatroskaError::EmptySegment => write!(f, "empty segment"),
+            MatroskaError::InvalidLacing => write!(f, "invalid block lacing"),
+            MatroskaError::InvalidTrack { number, problem } => {
+                write!(f, "invalid track {number}: {problem}")
+            }
+            MatroskaError::InvalidDuration { value } => write!(f, "invalid duration {value}"),
+            MatroskaError::RequiresRemux => write!(f, "edit requires remuxing the file"),
+            MatroskaError::ElementTooLarge { id, size, limit } => write!(
+                f,
+                "element {id:#X} of size {size} exceeds the limit of {limit} bytes"
+            ),
+            MatroskaError::ChecksumMismatch { element_id, offset } => write!(
+                f,
+                "checksum mismatch in element {element_id:#X} with CRC-32 at offset {offset}"
+            ),
+            MatroskaError::NestingTooDeep { id } => {
+                write!(f, "element {id:#X} is nested too deeply")
+            }
+            MatroskaError::OversizedChildElement {
+                id,
+                size,
+                parent_remaining,
+            } => write!(
+                f,
+                "element {id:#X} of size {size} exceeds the {parent_remaining} bytes left in its parent"
+            ),
+            MatroskaError::UnexpectedElement {
+                expected,
+                found,
+                offset,
+            } => write!(
+                f,
+                "expected element {expected:#X} at offset {offset}, found {found:#X}"
+            ),
+            MatroskaError::DuplicateElement { id, offset } => {
+                write!(f, "ignored duplicate element {id:#X} at offset {offset}")
+            }
+            MatroskaError::Located {
+                offset,
+                element,
+                error,
+            } => {
+                write!(f, "{error} at offset {offset:#X}")?;
+                match element {
+                    Some(id) => write!(f, " inside element {id:#X}"),
+                    None => Ok(()),
+                }
+            }
+            MatroskaError::UnknownTrack { number } => write!(f, "no track numbered {number}"),
+            MatroskaError::UnsupportedEncoding { track } => {
+                write!(f, "track {track} uses an unsupported content encoding")
+            }
+            MatroskaError::InvalidCompressedData { track } => {
+                write!(f, "invalid compressed data in track {track}")
+            }
+            MatroskaError::UnresolvedSegments { uids } => {
+                write!(f, "unresolved linked segments:")?;
+                for uid in uids {
+                    write!(f, " ")?;
+                    for byte in uid {
+                        write!(f, "{byte:02x}")?;
+                    }
+                }
+                Ok(())
+            }
+            MatroskaError::InvalidSimpleChapters { line } => {
+                write!(f, "invalid simple chapters at line {line}")
+            }
+            MatroskaError::InvalidXml { line } => write!(f, "invalid tags XML at line {line}"),
         }
     }
 }
 
-impl error::Error for MatroskaError {}
+impl error::Error for MatroskaError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            MatroskaError::Io(error) => Some(error),
+            MatroskaError::UTF8(error) => Some(error),
+            MatroskaError::Located { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl MatroskaError {
+    /// Returns the underlying error, without any location
+    ///
+    /// Errors from parsing sections are wrapped in
+    /// [`MatroskaError::Located`] to say where they occurred,
+    /// and this unwraps them for matching on what went wrong.
+    pub fn root(&self) -> &MatroskaError {
+        match self {
+            MatroskaError::Located { error, .. } => error.root(),
+            error => error,
+        }
+    }
+
+    /// Returns the file offset at which the error occurred, if known
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            MatroskaError::Located { offset, .. }
+            | MatroskaError::UnexpectedElement { offset, .. }
+            | MatroskaError::DuplicateElement { offset, .. }
+            | MatroskaError::ChecksumMismatch { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Wraps the error with its location,
+    /// unless it's already located or is about the file as a whole
+    pub(crate) fn located(self, offset: u64, element: Option<u32>) -> Self {
+        match self {
+            MatroskaError::Located { .. }
+            | MatroskaError::UnexpectedElement { .. }
+            | MatroskaError::DuplicateElement { .. }
+            | MatroskaError::ChecksumMismatch { .. }
+            | MatroskaError::InvalidTrack { .. }
+            | MatroskaError::InvalidDuration { .. }
+            | MatroskaError::RequiresRemux
+            | MatroskaError::EmptySegment
+            | MatroskaError::UnknownTrack { .. }
+            | MatroskaError::UnsupportedEncoding { .. }
+            | MatroskaError::InvalidCompressedData { .. }
+            | MatroskaError::UnresolvedSegments { .. }
+            | MatroskaError::InvalidSimpleChapters { .. }
+            | MatroskaError::InvalidXml { .. } => self,
+            error => MatroskaError::Located {
+                offset,
+                element,
+                error: Box::new(error),
+            },
+        }
+    }
+}
+
+/// A reader which keeps track of its absolute position,
+/// so errors can say where they occurred
+pub struct Tracked<'r, R> {
+    inner: &'r mut R,
+    position: u64,
+}
+
+impl<'r, R: io::Seek> Tracked<'r, R> {
+    pub fn new(inner: &'r mut R) -> io::Result<Self> {
+        let position = inner.stream_position()?;
+        Ok(Tracked { inner, position })
+    }
+
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<R: io::Read> io::Read for Tracked<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: io::Seek> io::Seek for Tracked<'_, R> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+/// The CRC-32 lookup table for EBML's polynomial, 0xEDB88320 reflected
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// A reader which computes the CRC-32 of everything read through it
+pub struct Crc32Reader<R> {
+    inner: R,
+    crc: u32,
+}
+
+impl<R> Crc32Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Crc32Reader {
+            inner,
+            crc: 0xFFFF_FFFF,
+        }
+    }
+
+    /// The CRC-32 of everything read so far
+    pub fn checksum(&self) -> u32 {
+        !self.crc
+    }
+}
+
+impl<R: io::Read> io::Read for Crc32Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        for byte in &buf[..read] {
+            self.crc = CRC_TABLE[usize::from(self.crc as u8 ^ byte)] ^ (self.crc >> 8);
+        }
+        Ok(read)
+    }
+}
+
+/// The size of an element whose size is unknown,
+/// such as a Segment or Cluster being live-streamed
+///
+/// This is encoded as a size with all of its value bits set.
+pub const UNKNOWN_SIZE: u64 = u64::MAX;
+
+/// The longest possible element header,
+/// a 4 byte ID followed by an 8 byte size
+const MAX_HEADER_LEN: usize = 4 + 8;
 
 pub fn read_element_id_size<R: io::Read>(reader: &mut R) -> Result<(u32, u64, u64)> {
-    let mut r = BitReader::new(reader);
-    let (id, id_len) = read_element_id(&mut r)?;
-    let (size, size_len) = read_element_size(&mut r)?;
-    Ok((id, size, id_len + size_len))
-}
-
-fn read_element_id<R: BitRead>(r: &mut R) -> Result<(u32, u64)> {
-    match r.read_unary1() {
-        Ok(0) => r
-            .read_in::<7, u32>()
-            .map_err(MatroskaError::Io)
-            .map(|u| (0b1000_0000 | u, 1)),
-        Ok(1) => r
-            .read_in::<{ 6 + 8 }, u32>()
-            .map_err(MatroskaError::Io)
-            .map(|u| ((0b0100_0000 << 8) | u, 2)),
-        Ok(2) => r
-            .read_in::<{ 5 + 16 }, u32>()
-            .map_err(MatroskaError::Io)
-            .map(|u| ((0b0010_0000 << 16) | u, 3)),
-        Ok(3) => r
-            .read_in::<{ 4 + 24 }, u32>()
-            .map_err(MatroskaError::Io)
-            .map(|u| ((0b0001_0000 << 24) | u, 4)),
-        Ok(_) => Err(MatroskaError::InvalidID),
-        Err(err) => Err(MatroskaError::Io(err)),
-    }
-}
-
-fn read_element_size<R: BitRead>(r: &mut R) -> Result<(u64, u64)> {
-    match r.read_unary1() {
-        Ok(0) => r
-            .read_in::<7, _>()
-            .map(|s| (s, 1))
-            .map_err(MatroskaError::Io),
-        Ok(1) => r
-            .read_in::<{ 6 + 8 }, _>()
-            .map(|s| (s, 2))
-            .map_err(MatroskaError::Io),
-        Ok(2) => r
-            .read_in::<{ 5 + (2 * 8) }, _>()
-            .map(|s| (s, 3))
-            .map_err(MatroskaError::Io),
-        Ok(3) => r
-            .read_in::<{ 4 + (3 * 8) }, _>()
-            .map(|s| (s, 4))
-            .map_err(MatroskaError::Io),
-        Ok(4) => r
-            .read_in::<{ 3 + (4 * 8) }, _>()
-            .map(|s| (s, 5))
-            .map_err(MatroskaError::Io),
-        Ok(5) => r
-            .read_in::<{ 2 + (5 * 8) }, _>()
-            .map(|s| (s, 6))
-            .map_err(MatroskaError::Io),
-        Ok(6) => r
-            .read_in::<{ 1 + (6 * 8) }, _>()
-            .map(|s| (s, 7))
-            .map_err(MatroskaError::Io),
-        Ok(7) => r
-            .read_in::<{ 7 * 8 }, _>()
-            .map(|s| (s, 8))
-            .map_err(MatroskaError::Io),
-        Ok(_) => Err(MatroskaError::InvalidSize),
-        Err(err) => Err(MatroskaError::Io(err)),
+    let mut buf = [0; MAX_HEADER_LEN];
+    let mut filled = 0;
+    loop {
+        if let Some(header) = decode_element_id_size(&buf[..filled])? {
+            return Ok(header);
+        }
+        reader.read_exact(&mut buf[filled..filled + 1])?;
+        filled += 1;
+    }
+}
+
+/// Whether the element is skipped rather than parsed,
+/// never appearing among its parent's children
+pub(crate) fn is_skipped(id: u32) -> bool {
+    id == ids::CRC32 || id == ids::VOID
+}
+
+/// Reads and discards `size` bytes without buffering them all
+pub(crate) fn discard<R: io::Read>(r: &mut R, size: u64) -> Result<()> {
+    if io::copy(&mut io::Read::take(r, size), &mut io::sink())? < size {
+        return Err(MatroskaError::Io(io::ErrorKind::UnexpectedEof.into()));
     }
+    Ok(())
+}
+
+/// Decodes an element's ID, size and header length from the start of `buf`
+///
+/// Returns `Ok(None)` if `buf` is too short to hold the whole header,
+/// so the caller can fetch more bytes however it likes and try again.
+/// This keeps header decoding independent of any particular kind of reader.
+pub fn decode_element_id_size(buf: &[u8]) -> Result<Option<(u32, u64, u64)>> {
+    let id_len = match buf.first() {
+        Some(byte) => vint_len(*byte, 4).ok_or(MatroskaError::InvalidID)?,
+        None => return Ok(None),
+    };
+    let size_len = match buf.get(id_len) {
+        Some(byte) => vint_len(*byte, 8).ok_or(MatroskaError::InvalidSize)?,
+        None => return Ok(None),
+    };
+    let (id, size) = match buf.get(..id_len + size_len) {
+        Some(header) => header.split_at(id_len),
+        None => return Ok(None),
+    };
+
+    // IDs keep their length marker, sizes don't
+    let id = id.iter().fold(0, |acc, byte| (acc << 8) | u32::from(*byte));
+    let mask = (1 << (7 * size_len)) - 1;
+    let size = size
+        .iter()
+        .fold(0, |acc, byte| (acc << 8) | u64::from(*byte))
+        & mask;
+    let size = if size == mask { UNKNOWN_SIZE } else { size };
+    Ok(Some((id, size, (id_len + size_len) as u64)))
+}
+
+/// Returns the encoded length of a variable-length integer from its first byte,
+/// if no longer than `max`
+fn vint_len(first: u8, max: usize) -> Option<usize> {
+    Some(first.leading_zeros() as usize + 1).filter(|len| *len <= max)
+}
+
+/// Returns how many bytes remain in a parent element
+/// after the child with the given ID, size and header length
+pub fn remaining_after(remaining: u64, id: u32, size: u64, header_len: u64) -> Result<u64> {
+    header_len
+        .checked_add(size)
+        .and_then(|total| remaining.checked_sub(total))
+        .ok_or(MatroskaError::OversizedChildElement {
+            id,
+            size,
+            parent_remaining: remaining,
+        })
 }
 
 pub fn read_int<R: io::Read>(r: &mut R, size: u64) -> Result<i64> {
-    let mut r = BitReader::new(r);
     match size {
         0 => Ok(0),
-        s @ 1..=8 => r.read_signed(s as u32 * 8).map_err(MatroskaError::Io),
-        _ => Err(MatroskaError::InvalidUint),
+        s @ 1..=8 => {
+            // shifted up and back down to extend the sign
+            let unused = 64 - s as u32 * 8;
+            read_be(r, s as usize).map(|i| ((i << unused) as i64) >> unused)
+        }
+        size => Err(MatroskaError::InvalidIntegerWidth { size }),
     }
 }
 
 pub fn read_uint<R: io::Read>(r: &mut R, size: u64) -> Result<u64> {
-    let mut r = BitReader::new(r);
     match size {
         0 => Ok(0),
-        s @ 1..=8 => r.read(s as u32 * 8).map_err(MatroskaError::Io),
-        _ => Err(MatroskaError::InvalidUint),
+        s @ 1..=8 => read_be(r, s as usize),
+        size => Err(MatroskaError::InvalidIntegerWidth { size }),
     }
 }
 
 pub fn read_float<R: io::Read>(r: &mut R, size: u64) -> Result<f64> {
-    let mut r = BitReader::new(r);
     match size {
+        0 => Ok(0.0),
         4 => {
-            let i: u32 = r.read_to().map_err(MatroskaError::Io)?;
-            let f = f32::from_bits(i);
+            let f = f32::from_bits(read_be(r, 4)? as u32);
             Ok(f64::from(f))
         }
-        8 => {
-            let i: u64 = r.read_to().map_err(MatroskaError::Io)?;
-            let f = f64::from_bits(i);
-            Ok(f)
-        }
-        _ => Err(MatroskaError::InvalidFloat),
+        8 => Ok(f64::from_bits(read_be(r, 8)?)),
+        size => Err(MatroskaError::InvalidFloatWidth { size }),
     }
 }
 
+/// Reads a big-endian integer of 1 to 8 bytes
+fn read_be<R: io::Read>(r: &mut R, len: usize) -> Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf[8 - len..])?;
+    Ok(u64::from_be_bytes(buf))
+}
+
 pub fn read_string<R: io::Read>(r: &mut R, size: u64) -> Result<String> {
     /*FIXME - limit this to ASCII set*/
     read_bin(r, size).and_then(|bytes| String::from_utf8(bytes).map_err(MatroskaError::UTF8))
@@ -359,22 +939,166 @@ pub fn read_date<R: io::Read>(r: &mut R, size: u64) -> Result<DateTime> {
 }
 
 pub fn read_bin<R: io::Read>(r: &mut R, size: u64) -> Result<Vec<u8>> {
-    let mut buf = vec![0; size as usize];
-    r.read_exact(&mut buf)
-        .map(|()| buf)
-        .map_err(MatroskaError::Io)
+    // grow the buffer as data arrives, rather than trusting
+    // the declared size and allocating it all up front
+    let mut buf = Vec::new();
+    io::Read::read_to_end(&mut io::Read::take(r, size), &mut buf)?;
+    if buf.len() as u64 == size {
+        Ok(buf)
+    } else {
+        Err(MatroskaError::Io(io::ErrorKind::UnexpectedEof.into()))
+    }
+}
+
+/// Returns the encoded length of an element ID
+fn id_len(id: u32) -> usize {
+    match id {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x1_0000..=0xFF_FFFF => 3,
+        _ => 4,
+    }
+}
+
+/// Returns the shortest encoded length of an element size
+fn size_len(size: u64) -> usize {
+    // all 1 bits are reserved for unknown sizes
+    (1..8).find(|len| size < (1 << (7 * len)) - 1).unwrap_or(8)
 }
 
-/// An opaque DateTime value representing seconds since the MKV epoch
+/// Encodes an element's ID and size
+pub fn element_header(id: u32, size: u64) -> Vec<u8> {
+    element_header_sized(id, size, size_len(size)).expect("size fits its shortest length")
+}
+
+/// Encodes an element's ID and size, with the size taking `size_len` bytes,
+/// or returns `None` if it doesn't fit in that many
+pub(crate) fn element_header_sized(id: u32, size: u64, size_len: usize) -> Option<Vec<u8>> {
+    let id_len = id_len(id);
+    let mut header = Vec::with_capacity(id_len + size_len);
+    header.extend_from_slice(&id.to_be_bytes()[4 - id_len..]);
+    header.extend(encode_size(size, size_len)?);
+    Some(header)
+}
+
+/// Encodes an element size in `len` bytes,
+/// or returns `None` if it doesn't fit in that many
+pub(crate) fn encode_size(size: u64, len: usize) -> Option<Vec<u8>> {
+    // all 1 bits are reserved for unknown sizes
+    if !(1..=8).contains(&len) || size >= (1 << (7 * len)) - 1 {
+        return None;
+    }
+    let marked = size | (1 << (7 * len));
+    Some(marked.to_be_bytes()[8 - len..].to_vec())
+}
+
+/// Encodes the header of a Void element which is `len` bytes long in all,
+/// or returns `None` if no Void is that short
+///
+/// Whatever follows the header is left as the Void's contents.
+pub(crate) fn void_header(len: u64) -> Option<Vec<u8>> {
+    (1..=8).find_map(|size_len| {
+        let size = len.checked_sub(1 + size_len as u64)?;
+        element_header_sized(ids::VOID, size, size_len)
+    })
+}
+
+/// Encodes an element followed by the header of a Void element
+/// which pads it out to exactly `len` bytes,
+/// or returns `None` if it doesn't fit
+pub(crate) fn padded_element(id: u32, body: &[u8], len: u64) -> Option<Vec<u8>> {
+    let size = body.len() as u64;
+    let shortest = size_len(size);
+    let (size_len, void) = match len.checked_sub((id_len(id) + shortest) as u64 + size)? {
+        0 => (shortest, Vec::new()),
+        // no Void is a single byte, so the size is lengthened instead
+        1 => (shortest + 1, Vec::new()),
+        rest => (shortest, void_header(rest)?),
+    };
+    let mut element = element_header_sized(id, size, size_len)?;
+    element.extend_from_slice(body);
+    element.extend(void);
+    Some(element)
+}
+
+/// Encodes an element with the given body
+pub fn element(id: u32, body: &[u8]) -> Vec<u8> {
+    let mut element = element_header(id, body.len() as u64);
+    element.extend_from_slice(body);
+    element
+}
+
+/// Encodes a master element from its encoded children
+pub fn master_element(id: u32, children: Vec<Vec<u8>>) -> Vec<u8> {
+    element(id, &children.concat())
+}
+
+/// Encodes an unsigned integer element in as few bytes as possible
+pub fn uint_element(id: u32, value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = (value.leading_zeros() / 8).min(7) as usize;
+    element(id, &bytes[skip..])
+}
+
+/// Encodes a string or UTF-8 element
+pub fn string_element(id: u32, value: &str) -> Vec<u8> {
+    element(id, value.as_bytes())
+}
+
+/// An opaque DateTime value representing nanoseconds since the MKV epoch,
+/// 2001-01-01 00:00:00 UTC
+///
+/// Every `i64` value is accepted, since the epoch plus or minus
+/// roughly 292 years lies within the range of each of the optional
+/// `time`, `chrono` and `jiff` conversions, so none of them can overflow.
+/// Without any of those features, [`DateTime::to_system_time`]
+/// converts it using only the standard library.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DateTime(i64);
 
+/// Seconds from the Unix epoch to the MKV epoch
+const MKV_EPOCH: u64 = 978_307_200;
+
+impl DateTime {
+    /// Returns the nanoseconds since 2001-01-01 00:00:00 UTC
+    #[inline]
+    pub fn nanoseconds(&self) -> i64 {
+        self.0
+    }
+
+    /// Converts the date to a `SystemTime`
+    ///
+    /// ## Example
+    /// ```no_run
+    /// let matroska = matroska::open("file.mkv").unwrap();
+    /// if let Some(date) = matroska.info.date_utc {
+    ///     println!("{:?}", date.to_system_time());
+    /// }
+    /// ```
+    pub fn to_system_time(&self) -> SystemTime {
+        let epoch = SystemTime::UNIX_EPOCH + Duration::from_secs(MKV_EPOCH);
+        let offset = Duration::from_nanos(self.0.unsigned_abs());
+        if self.0 < 0 {
+            epoch - offset
+        } else {
+            epoch + offset
+        }
+    }
+}
+
 impl From<DateTime> for i64 {
     fn from(DateTime(n): DateTime) -> Self {
         n
     }
 }
 
+impl From<DateTime> for SystemTime {
+    #[inline]
+    fn from(date: DateTime) -> Self {
+        date.to_system_time()
+    }
+}
+
 #[cfg(feature = "time")]
 impl From<DateTime> for time::OffsetDateTime {
     fn from(DateTime(n): DateTime) -> Self {
@@ -396,6 +1120,7 @@ impl From<DateTime> for chrono::DateTime<chrono::Utc> {
 #[cfg(feature = "jiff")]
 impl From<DateTime> for jiff::civil::DateTime {
     fn from(DateTime(n): DateTime) -> Self {
-        jiff::civil::DateTime::new(2001, 1, 1, 0, 0, 0, 0).unwrap() + jiff::Span::new().nanoseconds(n)
+        jiff::civil::DateTime::new(2001, 1, 1, 0, 0, 0, 0).unwrap()
+            + jiff::Span::new().nanoseconds(n)
     }
 }
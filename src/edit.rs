@@ -0,0 +1,418 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Edits to the metadata of existing files, made without remuxing
+
+use std::io::{self, SeekFrom};
+use std::path::Path;
+
+use crate::ebml::{self, MatroskaError, Result};
+use crate::{
+    find_segment, ids, read_top_level_header, skip_element, stream_len, ChapterEdition, Parseable,
+    SeekEntry, SeekHead, Tag,
+};
+
+/// Replaces a file's tags with the given ones
+///
+/// The new Tags element is written over the first existing one
+/// if it fits there along with any Void elements which follow it,
+/// and is otherwise appended to the end of the Segment.
+/// Tags elements which aren't written over are turned into Void elements,
+/// as is every Tags element if there are no tags to write,
+/// and the SeekHead is updated to list where the new one is.
+///
+/// Nothing is written if the edit would need anything to be moved,
+/// such as a SeekHead with no room for another entry
+/// or a Segment followed by another, which is
+/// [`MatroskaError::RequiresRemux`].
+///
+/// ## Example
+/// ```no_run
+/// let mut tags = matroska::open("file.mkv").unwrap().tags;
+/// tags.retain(|tag| tag.targets.is_none());
+/// matroska::replace_tags("file.mkv", &tags).unwrap();
+/// ```
+pub fn replace_tags<P: AsRef<Path>>(path: P, tags: &[Tag]) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    replace_tags_in(&mut file, tags)
+}
+
+fn replace_tags_in<F: io::Read + io::Write + io::Seek>(f: &mut F, tags: &[Tag]) -> Result<()> {
+    let body = (!tags.is_empty()).then(|| {
+        let tags: Vec<_> = tags.iter().map(|t| t.encode()).collect();
+        tags.concat()
+    });
+    replace_section(f, ids::TAGS, body)
+}
+
+/// Replaces a file's chapters with the given editions
+///
+/// Chapters elements are replaced as tags are by [`replace_tags`],
+/// and any UIDs of 0 are generated as for [`ChapterEdition::write_all`].
+///
+/// ## Example
+/// ```no_run
+/// let mut editions = matroska::open("file.mkv").unwrap().chapters;
+/// editions.truncate(1);
+/// matroska::replace_chapters("file.mkv", &editions).unwrap();
+/// ```
+pub fn replace_chapters<P: AsRef<Path>>(path: P, editions: &[ChapterEdition]) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    replace_chapters_in(&mut file, editions)
+}
+
+fn replace_chapters_in<F: io::Read + io::Write + io::Seek>(
+    f: &mut F,
+    editions: &[ChapterEdition],
+) -> Result<()> {
+    let body = (!editions.is_empty()).then(|| ChapterEdition::encode_all(editions));
+    replace_section(f, ids::CHAPTERS, body)
+}
+
+/// Replaces every top-level element with the given ID
+/// by one with the given body, or removes them if there's none
+fn replace_section<F>(f: &mut F, id: u32, body: Option<Vec<u8>>) -> Result<()>
+where
+    F: io::Read + io::Write + io::Seek,
+{
+    let layout = Layout::read(f)?;
+    let existing: Vec<&Child> = layout.children_of(id).collect();
+    let mut edits = Vec::new();
+
+    let (position, voided) = match body {
+        None => (None, &existing[..]),
+        Some(body) => {
+            let in_place = existing.first().and_then(|first| {
+                ebml::padded_element(id, &body, layout.space(first)).map(|e| (first.offset, e))
+            });
+            match in_place {
+                Some((offset, element)) => {
+                    edits.push((offset, element));
+                    (Some(offset), &existing[1..])
+                }
+                None => {
+                    let element = ebml::master_element(id, vec![body]);
+                    let offset = layout.append(&mut edits, element)?;
+                    (Some(offset), &existing[..])
+                }
+            }
+        }
+    };
+    for child in voided {
+        edits.push((
+            child.offset,
+            ebml::void_header(child.len).expect("elements fit a Void"),
+        ));
+    }
+    layout.update_seek_head(f, &mut edits, id, position)?;
+
+    apply(f, edits)
+}
+
+/// Sets a file's title, or removes it if `None`
+///
+/// The Info element is rewritten in place, taking up
+/// any Void elements within or directly after it,
+/// so nothing else in the file moves.
+/// An Info with a CRC-32 has it recalculated.
+/// A title which doesn't fit, or a file with no Info element,
+/// is [`MatroskaError::RequiresRemux`] and nothing is written.
+///
+/// ## Example
+/// ```no_run
+/// matroska::set_title("file.mkv", Some("New Title")).unwrap();
+/// ```
+pub fn set_title<P: AsRef<Path>>(path: P, title: Option<&str>) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    set_title_in(&mut file, title)
+}
+
+fn set_title_in<F: io::Read + io::Write + io::Seek>(f: &mut F, title: Option<&str>) -> Result<()> {
+    let layout = Layout::read(f)?;
+    let info = layout
+        .children_of(ids::INFO)
+        .next()
+        .ok_or(MatroskaError::RequiresRemux)?;
+    f.seek(SeekFrom::Start(info.offset + info.header_len))?;
+    let body = ebml::read_bin(f, info.len - info.header_len)?;
+
+    let title = title.map(|title| ebml::string_element(ids::TITLE, title));
+    let body = rewrite_master(&body, vec![(ids::TITLE, title)])?;
+
+    let element = ebml::padded_element(ids::INFO, &body, layout.space(info))
+        .ok_or(MatroskaError::RequiresRemux)?;
+    apply(f, vec![(info.offset, element)])
+}
+
+/// Rewrites a master element's body so each child with one of the given IDs
+/// is replaced by the given element, or removed if there's none
+///
+/// A replacement goes where the first child with its ID was,
+/// or is appended if there was none.
+/// Void elements are dropped, and a CRC-32 is recalculated.
+pub(crate) fn rewrite_master(
+    body: &[u8],
+    replacements: Vec<(u32, Option<Vec<u8>>)>,
+) -> Result<Vec<u8>> {
+    let mut replacements = replacements;
+    let mut children = Vec::new();
+    let mut checksummed = false;
+    for (id, child) in split_children(body)? {
+        match replacements.iter_mut().find(|(r, _)| *r == id) {
+            Some((_, replacement)) => children.extend(replacement.take()),
+            None if id == ids::CRC32 => checksummed = true,
+            None if id == ids::VOID => {}
+            None => children.push(child.to_vec()),
+        }
+    }
+    children.extend(replacements.into_iter().filter_map(|(_, r)| r));
+    let mut body = children.concat();
+    if checksummed {
+        let mut crc = ebml::Crc32Reader::new(&body[..]);
+        io::copy(&mut crc, &mut io::sink())?;
+        let mut checksum = ebml::element(ids::CRC32, &crc.checksum().to_le_bytes());
+        checksum.extend(body);
+        body = checksum;
+    }
+    Ok(body)
+}
+
+/// Splits a master element's body into each child's ID and encoded bytes
+pub(crate) fn split_children(mut body: &[u8]) -> Result<Vec<(u32, &[u8])>> {
+    let mut children = Vec::new();
+    while !body.is_empty() {
+        let (id, size, header_len) = ebml::decode_element_id_size(body)?
+            .ok_or(MatroskaError::Io(io::ErrorKind::UnexpectedEof.into()))?;
+        let len = header_len
+            .checked_add(size)
+            .filter(|len| *len <= body.len() as u64)
+            .ok_or(MatroskaError::Io(io::ErrorKind::UnexpectedEof.into()))?;
+        let (child, rest) = body.split_at(len as usize);
+        children.push((id, child));
+        body = rest;
+    }
+    Ok(children)
+}
+
+/// Writes each edit at its file offset
+fn apply<F: io::Write + io::Seek>(f: &mut F, edits: Vec<(u64, Vec<u8>)>) -> Result<()> {
+    for (offset, bytes) in edits {
+        f.seek(SeekFrom::Start(offset))?;
+        f.write_all(&bytes)?;
+    }
+    f.flush().map_err(MatroskaError::Io)
+}
+
+/// A top-level element within the Segment
+pub(crate) struct Child {
+    pub id: u32,
+    /// The file offset of the element's header
+    pub offset: u64,
+    pub header_len: u64,
+    /// The element's total length, including its header
+    pub len: u64,
+}
+
+/// Where a Segment and its top-level elements are in a file
+pub(crate) struct Layout {
+    /// The file offset of the Segment's size
+    size_offset: u64,
+    /// The length of the Segment's size, in bytes
+    size_len: usize,
+    /// The Segment's size, which may be [`ebml::UNKNOWN_SIZE`]
+    size: u64,
+    /// The file offset of the Segment's contents
+    segment_start: u64,
+    file_len: u64,
+    children: Vec<Child>,
+}
+
+impl Layout {
+    pub(crate) fn read<R: io::Read + io::Seek>(r: &mut R) -> Result<Layout> {
+        r.seek(SeekFrom::Start(0))?;
+        let size = find_segment(r)?;
+        let segment_start = r.stream_position()?;
+        let file_len = stream_len(r)?;
+
+        // the Segment's ID is 4 bytes, followed by 1 to 8 of size
+        let mut header = None;
+        for size_len in 1..=8 {
+            let offset = match segment_start.checked_sub(4 + size_len as u64) {
+                Some(offset) => offset,
+                None => break,
+            };
+            let mut buf = vec![0; 4 + size_len];
+            r.seek(SeekFrom::Start(offset))?;
+            r.read_exact(&mut buf)?;
+            if let Ok(Some((ids::SEGMENT, _, len))) = ebml::decode_element_id_size(&buf) {
+                if len == buf.len() as u64 {
+                    header = Some((offset + 4, size_len));
+                    break;
+                }
+            }
+        }
+        let (size_offset, size_len) = header.ok_or(MatroskaError::RequiresRemux)?;
+
+        let end = match segment_start.checked_add(size) {
+            Some(end) if size != ebml::UNKNOWN_SIZE => end.min(file_len),
+            _ => file_len,
+        };
+        let mut children = Vec::new();
+        let mut offset = segment_start;
+        r.seek(SeekFrom::Start(offset))?;
+        while offset < end {
+            let (id, size, header_len) = match read_top_level_header(r)? {
+                Some(header) => header,
+                None => break,
+            };
+            let len = header_len + skip_element(r, size)?;
+            children.push(Child {
+                id,
+                offset,
+                header_len,
+                len,
+            });
+            offset += len;
+        }
+
+        Ok(Layout {
+            size_offset,
+            size_len,
+            size,
+            segment_start,
+            file_len,
+            children,
+        })
+    }
+
+    /// Returns the top-level elements with the given ID
+    pub(crate) fn children_of(&self, id: u32) -> impl Iterator<Item = &Child> {
+        self.children.iter().filter(move |c| c.id == id)
+    }
+
+    /// Returns the room an element has to be rewritten in,
+    /// which is its own length along with that of
+    /// any Void elements which directly follow it
+    fn space(&self, child: &Child) -> u64 {
+        let following = self
+            .children
+            .iter()
+            .skip_while(|c| c.offset <= child.offset)
+            .take_while(|c| c.id == ids::VOID)
+            .map(|c| c.len);
+        child.len + following.sum::<u64>()
+    }
+
+    /// Adds an edit appending the element to the end of the Segment,
+    /// along with one updating the Segment's size,
+    /// returning the element's file offset
+    ///
+    /// Only a Segment which ends the file can be appended to.
+    fn append(&self, edits: &mut Vec<(u64, Vec<u8>)>, element: Vec<u8>) -> Result<u64> {
+        if self.size == ebml::UNKNOWN_SIZE {
+            edits.push((self.file_len, element));
+            return Ok(self.file_len);
+        }
+        if self.segment_start.checked_add(self.size) != Some(self.file_len) {
+            return Err(MatroskaError::RequiresRemux);
+        }
+        let size = ebml::encode_size(self.size + element.len() as u64, self.size_len)
+            .ok_or(MatroskaError::RequiresRemux)?;
+        edits.push((self.size_offset, size));
+        edits.push((self.file_len, element));
+        Ok(self.file_len)
+    }
+
+    /// Adds edits rewriting the SeekHeads so that they list
+    /// the element with the given ID at the given file offset,
+    /// or don't list it at all if there's no offset
+    ///
+    /// The first SeekHead and every one chained from it are updated.
+    /// The element stays listed by whichever SeekHead listed it first,
+    /// or is added to the first if none did.
+    /// Files without a SeekHead are left without one.
+    fn update_seek_head<R: io::Read + io::Seek>(
+        &self,
+        r: &mut R,
+        edits: &mut Vec<(u64, Vec<u8>)>,
+        id: u32,
+        offset: Option<u64>,
+    ) -> Result<()> {
+        let first = match self.children_of(ids::SEEKHEAD).next() {
+            Some(child) => child,
+            None => return Ok(()),
+        };
+
+        // each SeekHead along with its entries, in the order they're followed
+        let mut seek_heads: Vec<(&Child, Vec<SeekEntry>)> = Vec::new();
+        let mut next = std::collections::VecDeque::from(vec![first]);
+        while let Some(child) = next.pop_front() {
+            if seek_heads.iter().any(|(c, _)| c.offset == child.offset) {
+                continue;
+            }
+            r.seek(SeekFrom::Start(child.offset + child.header_len))?;
+            let entries = SeekHead::parse(r, child.len - child.header_len)?.entries;
+            for entry in entries.iter().filter(|e| e.id == ids::SEEKHEAD) {
+                let chained = self.segment_start.checked_add(entry.position);
+                // a chained SeekHead which isn't there can't be rewritten
+                if let Some(chained) = self
+                    .children_of(ids::SEEKHEAD)
+                    .find(|c| Some(c.offset) == chained)
+                {
+                    next.push_back(chained);
+                }
+            }
+            seek_heads.push((child, entries));
+        }
+
+        let new_entry = offset.map(|offset| SeekEntry {
+            id,
+            position: offset - self.segment_start,
+        });
+        let mut listed = false;
+        let mut rewritten = Vec::with_capacity(seek_heads.len());
+        for (_, old) in &seek_heads {
+            let mut entries = Vec::with_capacity(old.len() + 1);
+            for entry in old {
+                if entry.id != id {
+                    entries.push(entry.clone());
+                } else if let (Some(new_entry), false) = (&new_entry, listed) {
+                    entries.push(new_entry.clone());
+                    listed = true;
+                }
+            }
+            rewritten.push(entries);
+        }
+        if let (Some(new_entry), false) = (new_entry, listed) {
+            rewritten[0].push(new_entry);
+        }
+
+        for ((child, old), entries) in seek_heads.iter().zip(rewritten) {
+            if entries != *old {
+                let body = entries
+                    .iter()
+                    .map(|e| e.encode())
+                    .collect::<Vec<_>>()
+                    .concat();
+                let element = ebml::padded_element(ids::SEEKHEAD, &body, self.space(child))
+                    .ok_or(MatroskaError::RequiresRemux)?;
+                edits.push((child.offset, element));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,472 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::{
+    Audio, Chapter, Colour, Language, Matroska, Settings, SimpleTag, Tag, TagValue, Track,
+    TrackOperation, Video,
+};
+
+/// How binary values are rendered by [`Matroska::to_flat_map_with`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum BinaryFormat {
+    /// the value's length in bytes
+    #[default]
+    Length,
+    /// the value itself, in padded standard Base64
+    Base64,
+}
+
+impl Matroska {
+    /// Flattens the file's metadata into dotted keys and string values,
+    /// rendering binary values as their length
+    ///
+    /// See [`Matroska::to_flat_map_with`] for the key grammar.
+    pub fn to_flat_map(&self) -> BTreeMap<String, String> {
+        self.to_flat_map_with(BinaryFormat::Length)
+    }
+
+    /// Flattens the file's metadata into dotted keys and string values
+    ///
+    /// Keys are built from these components,
+    /// with absent optional values omitted entirely:
+    ///
+    /// | key | value |
+    /// |-----|-------|
+    /// | `ebml.{field}` | an EBML header field, such as `ebml.doc_type` |
+    /// | `info.{field}` | an Info field, such as `info.title` |
+    /// | `info.family_uid.{n}` | the `n`th segment family UID, from 0 |
+    /// | `track.{number}.{field}` | a track field, such as `track.1.codec_id` |
+    /// | `track.{number}.video.{field}` | a video setting, such as `track.1.video.pixel_width` |
+    /// | `track.{number}.video.colour.{field}` | a video colour setting |
+    /// | `track.{number}.audio.{field}` | an audio setting, such as `track.2.audio.channels` |
+    /// | `track.{number}.plane.{uid}` | the plane type of a track combined into this one |
+    /// | `track.{number}.join.{n}` | the `n`th track UID joined into this one, from 0 |
+    /// | `track.{number}.translate.{n}.{field}` | the `n`th chapter codec mapping, from 0 |
+    /// | `track.{number}.encoding.{n}.{field}` | the `n`th content encoding, from 0 |
+    /// | `track.{number}.block_addition.{n}.{field}` | the `n`th BlockAddition mapping, from 0 |
+    /// | `edition.{uid}.{field}` | an edition's flags |
+    /// | `chapter.{uid}.{field}` | a chapter field, such as `chapter.2.start` |
+    /// | `chapter.{uid}.title.{language}` | a chapter's display string |
+    /// | `chapter.{uid}.title.{language}.country.{n}` | the `n`th country of a chapter's display string, from 0 |
+    /// | `tag.{level}.{NAME}.{language}` | a global tag, such as `tag.episode.TITLE.eng` |
+    /// | `tag.{level}.{kind}.{uid}.{NAME}.{language}` | a tag targeting a track, edition, chapter or attachment UID, such as `tag.episode.track.1234.BPS.eng` |
+    /// | `tag.{...}.{NAME}.{language}.{CHILD}.{language}` | a tag nested within another, such as `tag.episode.ARTIST.SORT_WITH` |
+    /// | `attachment.{n}.{field}` | the `n`th attachment's fields, from 0 |
+    ///
+    /// Tag levels are the lowercase target type names, such as
+    /// `collection` or `episode`, defaulting to `episode` as the spec does.
+    /// Tags without a language omit that suffix,
+    /// and a tag targeting several UIDs appears once per UID.
+    /// Nested chapters are keyed by their own UID like any other.
+    ///
+    /// A key that would repeat one already present, such as a tag name
+    /// given twice, two display strings in the same language or
+    /// two chapters without a UID, gets a `.{n}` suffix counting from 1
+    /// in the order they're stored, such as `tag.episode.ARTIST.1`.
+    ///
+    /// Booleans are `true` or `false`, durations are fractional seconds
+    /// with nanosecond precision such as `1.015000000`,
+    /// dates are the raw number of nanoseconds since 2001,
+    /// enumerated values are their `as_str` names such as `top_field_first`,
+    /// and binary values are rendered according to `binary`.
+    /// Cues are not included.
+    pub fn to_flat_map_with(&self, binary: BinaryFormat) -> BTreeMap<String, String> {
+        let mut map = Flat {
+            map: BTreeMap::new(),
+            binary,
+        };
+
+        if let Some(header) = &self.ebml_header {
+            map.insert("ebml.version", header.ebml_version);
+            map.insert("ebml.read_version", header.read_version);
+            map.insert("ebml.max_id_length", header.max_id_length);
+            map.insert("ebml.max_size_length", header.max_size_length);
+            map.insert("ebml.doc_type", &header.doc_type);
+            map.insert("ebml.doc_type_version", header.doc_type_version);
+            map.insert("ebml.doc_type_read_version", header.doc_type_read_version);
+        }
+
+        let info = &self.info;
+        map.binary("info.uid", info.uid.as_deref());
+        map.binary("info.prev_uid", info.prev_uid.as_deref());
+        map.binary("info.next_uid", info.next_uid.as_deref());
+        map.opt("info.filename", info.filename.as_ref());
+        map.opt("info.prev_filename", info.prev_filename.as_ref());
+        map.opt("info.next_filename", info.next_filename.as_ref());
+        for (n, uid) in info.family_uids.iter().enumerate() {
+            map.binary(format!("info.family_uid.{}", n), Some(uid));
+        }
+        map.opt("info.title", info.title.as_ref());
+        map.opt("info.duration", info.duration.map(seconds));
+        map.opt("info.date_utc", info.date_utc.clone().map(i64::from));
+        map.insert("info.muxing_app", &info.muxing_app);
+        map.insert("info.writing_app", &info.writing_app);
+        map.insert("info.timestamp_scale", info.timestamp_scale);
+
+        for track in &self.tracks {
+            map.track(track);
+        }
+
+        for edition in &self.chapters {
+            if let Some(uid) = edition.uid {
+                let key = |field: &str| format!("edition.{}.{}", uid, field);
+                map.insert(key("hidden"), edition.hidden);
+                map.insert(key("default"), edition.default);
+                map.insert(key("ordered"), edition.ordered);
+            }
+            for chapter in edition.iter_all() {
+                map.chapter(chapter);
+            }
+        }
+
+        for tag in &self.tags {
+            map.tag(tag);
+        }
+
+        for (n, attachment) in self.attachments.iter().enumerate() {
+            let key = |field: &str| format!("attachment.{}.{}", n, field);
+            map.insert(key("uid"), attachment.uid);
+            map.insert(key("name"), &attachment.name);
+            map.insert(key("mime_type"), &attachment.mime_type);
+            map.opt(key("description"), attachment.description.as_ref());
+            map.binary(key("data"), Some(&attachment.data));
+            map.binary(key("referral"), attachment.referral.as_deref());
+            map.opt(
+                key("used_start_time"),
+                attachment.used_start_time.map(seconds),
+            );
+            map.opt(key("used_end_time"), attachment.used_end_time.map(seconds));
+        }
+
+        map.map
+    }
+}
+
+struct Flat {
+    map: BTreeMap<String, String>,
+    binary: BinaryFormat,
+}
+
+impl Flat {
+    fn insert(&mut self, key: impl Into<String>, value: impl Display) {
+        let key = self.unique(key.into());
+        self.map.insert(key, value.to_string());
+    }
+
+    /// Returns the key, suffixed with `.{n}` if it's already present
+    fn unique(&self, key: String) -> String {
+        if !self.map.contains_key(&key) {
+            return key;
+        }
+        (1..)
+            .map(|n| format!("{}.{}", key, n))
+            .find(|suffixed| !self.map.contains_key(suffixed))
+            .unwrap()
+    }
+
+    fn opt(&mut self, key: impl Into<String>, value: Option<impl Display>) {
+        if let Some(value) = value {
+            self.insert(key, value);
+        }
+    }
+
+    fn binary(&mut self, key: impl Into<String>, value: Option<&[u8]>) {
+        if let Some(value) = value {
+            let value = match self.binary {
+                BinaryFormat::Length => value.len().to_string(),
+                BinaryFormat::Base64 => base64(value),
+            };
+            self.insert(key, value);
+        }
+    }
+
+    fn track(&mut self, track: &Track) {
+        let number = track.number;
+        let key = |field: &str| format!("track.{}.{}", number, field);
+        self.insert(key("uid"), track.uid);
+        self.insert(key("type"), track.tracktype.as_str());
+        self.insert(key("enabled"), track.enabled);
+        self.insert(key("default"), track.default);
+        self.insert(key("forced"), track.forced);
+        self.opt(key("hearing_impaired"), track.hearing_impaired);
+        self.opt(key("visual_impaired"), track.visual_impaired);
+        self.opt(key("text_descriptions"), track.text_descriptions);
+        self.opt(key("original"), track.original);
+        self.opt(key("commentary"), track.commentary);
+        self.insert(key("interlaced"), track.interlaced);
+        self.opt(key("min_cache"), track.min_cache);
+        self.opt(key("max_cache"), track.max_cache);
+        self.opt(key("max_block_addition_id"), track.max_block_addition_id);
+        self.opt(key("default_duration"), track.default_duration.map(seconds));
+        self.opt(key("codec_delay"), track.codec_delay.map(seconds));
+        self.opt(key("seek_pre_roll"), track.seek_pre_roll.map(seconds));
+        self.opt(key("name"), track.name.as_ref());
+        self.opt(key("language"), track.language.as_ref());
+        self.insert(key("codec_id"), &track.codec_id);
+        self.binary(key("codec_private"), track.codec_private.as_deref());
+        self.opt(key("codec_name"), track.codec_name.as_ref());
+        self.opt(key("codec_settings"), track.codec_settings.as_ref());
+        self.opt(key("codec_info_url"), track.codec_info_url.as_ref());
+        self.opt(key("codec_download_url"), track.codec_download_url.as_ref());
+        self.opt(key("codec_decode_all"), track.codec_decode_all);
+        for (n, uid) in track.attachment_links.iter().enumerate() {
+            self.insert(key(&format!("attachment_link.{}", n)), uid);
+        }
+        match &track.settings {
+            Settings::Video(video) => self.video(&key("video"), video),
+            Settings::Audio(audio) => self.audio(&key("audio"), audio),
+            Settings::None => {}
+        }
+        match &track.operation {
+            Some(TrackOperation::CombinePlanes(planes)) => {
+                for (uid, plane_type) in planes {
+                    self.insert(
+                        format!("track.{}.plane.{}", number, uid),
+                        plane_type.as_str(),
+                    );
+                }
+            }
+            Some(TrackOperation::JoinBlocks(uids)) => {
+                for (n, uid) in uids.iter().enumerate() {
+                    self.insert(format!("track.{}.join.{}", number, n), uid);
+                }
+            }
+            None => {}
+        }
+        for (n, translate) in track.translate.iter().enumerate() {
+            let key = |field: &str| format!("track.{}.translate.{}.{}", number, n, field);
+            for (i, uid) in translate.edition_uids.iter().enumerate() {
+                self.insert(key(&format!("edition_uid.{}", i)), uid);
+            }
+            self.insert(key("codec"), translate.codec);
+            self.binary(key("track_id"), Some(&translate.track_id));
+        }
+        for (n, mapping) in track.block_addition_mappings.iter().enumerate() {
+            let key = |field: &str| format!("track.{}.block_addition.{}.{}", number, n, field);
+            self.opt(key("value"), mapping.value);
+            self.opt(key("name"), mapping.name.as_ref());
+            self.insert(key("type"), mapping.id_type);
+            self.binary(key("extra_data"), mapping.extra_data.as_deref());
+        }
+        for (n, encoding) in track.encodings.iter().enumerate() {
+            let key = |field: &str| format!("track.{}.encoding.{}.{}", number, n, field);
+            self.insert(key("order"), encoding.order);
+            self.insert(key("scope"), encoding.scope);
+            self.insert(key("type"), encoding.encoding_type.as_str());
+            if let Some(compression) = &encoding.compression {
+                self.insert(key("compression.algorithm"), compression.algorithm.as_str());
+                self.binary(key("compression.settings"), compression.settings.as_deref());
+            }
+        }
+    }
+
+    fn video(&mut self, prefix: &str, video: &Video) {
+        let key = |field: &str| format!("{}.{}", prefix, field);
+        self.insert(key("pixel_width"), video.pixel_width);
+        self.insert(key("pixel_height"), video.pixel_height);
+        self.opt(key("display_width"), video.display_width);
+        self.opt(key("display_height"), video.display_height);
+        self.opt(key("display_unit"), video.display_unit.map(|v| v.as_str()));
+        self.opt(key("crop_top"), video.crop_top);
+        self.opt(key("crop_bottom"), video.crop_bottom);
+        self.opt(key("crop_left"), video.crop_left);
+        self.opt(key("crop_right"), video.crop_right);
+        self.opt(
+            key("aspect_ratio_type"),
+            video.aspect_ratio_type.map(|v| v.as_str()),
+        );
+        self.opt(key("interlaced"), video.interlaced);
+        self.opt(key("field_order"), video.field_order.map(|v| v.as_str()));
+        self.opt(key("stereo"), video.stereo.map(|v| v.as_str()));
+        self.opt(key("gamma"), video.gamma);
+        self.binary(
+            key("colour_space"),
+            video.colour_space.as_ref().map(|c| &c[..]),
+        );
+        if let Some(colour) = &video.colour {
+            self.colour(&key("colour"), colour);
+        }
+    }
+
+    fn colour(&mut self, prefix: &str, colour: &Colour) {
+        let key = |field: &str| format!("{}.{}", prefix, field);
+        self.opt(
+            key("matrix_coefficients"),
+            colour.matrix_coefficients.map(|v| v.as_str()),
+        );
+        self.opt(key("bits_per_channel"), colour.bits_per_channel);
+        self.opt(
+            key("chroma_subsampling_horz"),
+            colour.chroma_subsampling_horz,
+        );
+        self.opt(
+            key("chroma_subsampling_vert"),
+            colour.chroma_subsampling_vert,
+        );
+        self.opt(key("cb_subsampling_horz"), colour.cb_subsampling_horz);
+        self.opt(key("cb_subsampling_vert"), colour.cb_subsampling_vert);
+        self.opt(
+            key("chroma_siting_horz"),
+            colour.chroma_siting_horz.map(|v| v.as_str()),
+        );
+        self.opt(
+            key("chroma_siting_vert"),
+            colour.chroma_siting_vert.map(|v| v.as_str()),
+        );
+        self.opt(key("range"), colour.range.map(|v| v.as_str()));
+        self.opt(
+            key("transfer_characteristics"),
+            colour.transfer_characteristics.map(|v| v.as_str()),
+        );
+        self.opt(key("primaries"), colour.primaries.map(|v| v.as_str()));
+        self.opt(key("max_cll"), colour.max_cll);
+        self.opt(key("max_fall"), colour.max_fall);
+        if let Some(m) = &colour.mastering_metadata {
+            let key = |field: &str| format!("{}.mastering_metadata.{}", prefix, field);
+            self.opt(key("primary_r_chromaticity_x"), m.primary_r_chromaticity_x);
+            self.opt(key("primary_r_chromaticity_y"), m.primary_r_chromaticity_y);
+            self.opt(key("primary_g_chromaticity_x"), m.primary_g_chromaticity_x);
+            self.opt(key("primary_g_chromaticity_y"), m.primary_g_chromaticity_y);
+            self.opt(key("primary_b_chromaticity_x"), m.primary_b_chromaticity_x);
+            self.opt(key("primary_b_chromaticity_y"), m.primary_b_chromaticity_y);
+            self.opt(
+                key("white_point_chromaticity_x"),
+                m.white_point_chromaticity_x,
+            );
+            self.opt(
+                key("white_point_chromaticity_y"),
+                m.white_point_chromaticity_y,
+            );
+            self.opt(key("luminance_max"), m.luminance_max);
+            self.opt(key("luminance_min"), m.luminance_min);
+        }
+    }
+
+    fn audio(&mut self, prefix: &str, audio: &Audio) {
+        let key = |field: &str| format!("{}.{}", prefix, field);
+        self.insert(key("sample_rate"), audio.sample_rate);
+        self.insert(key("channels"), audio.channels);
+        self.opt(key("bit_depth"), audio.bit_depth);
+        self.binary(key("channel_positions"), audio.channel_positions.as_deref());
+    }
+
+    fn chapter(&mut self, chapter: &Chapter) {
+        let uid = chapter.uid;
+        let key = |field: &str| format!("chapter.{}.{}", uid, field);
+        self.opt(key("string_uid"), chapter.string_uid.as_ref());
+        self.insert(key("start"), seconds(chapter.time_start));
+        self.opt(key("end"), chapter.time_end.map(seconds));
+        self.insert(key("hidden"), chapter.hidden);
+        self.insert(key("enabled"), chapter.enabled);
+        self.binary(key("segment_uid"), chapter.segment_uid.as_deref());
+        self.opt(key("segment_edition_uid"), chapter.segment_edition_uid);
+        self.opt(
+            key("physical_equiv"),
+            chapter.physical_equiv.map(|v| v.as_str()),
+        );
+        for display in &chapter.display {
+            let title = self.unique(format!(
+                "chapter.{}.title{}",
+                uid,
+                language_suffix(Some(&display.language))
+            ));
+            self.insert(title.clone(), &display.string);
+            for (n, country) in display.countries.iter().enumerate() {
+                self.insert(format!("{}.country.{}", title, n), country);
+            }
+        }
+    }
+
+    fn tag(&mut self, tag: &Tag) {
+        let level = tag
+            .targets
+            .as_ref()
+            .and_then(|t| t.target_type_value)
+            .map(|v| v.as_str())
+            .unwrap_or("episode");
+
+        let mut scopes = Vec::new();
+        if let Some(targets) = &tag.targets {
+            for (kind, uids) in [
+                ("track", &targets.track_uids),
+                ("edition", &targets.edition_uids),
+                ("chapter", &targets.chapter_uids),
+                ("attachment", &targets.attachment_uids),
+            ]
+            .iter()
+            {
+                scopes.extend(uids.iter().map(|uid| format!("{}.{}.{}", level, kind, uid)));
+            }
+        }
+        if scopes.is_empty() {
+            scopes.push(level.to_string());
+        }
+
+        for scope in scopes {
+            for simple in &tag.simple {
+                self.simple_tag(&format!("tag.{}", scope), simple);
+            }
+        }
+    }
+
+    fn simple_tag(&mut self, prefix: &str, simple: &SimpleTag) {
+        let key = self.unique(format!(
+            "{}.{}{}",
+            prefix,
+            simple.name,
+            language_suffix(simple.language.as_ref())
+        ));
+        match &simple.value {
+            Some(TagValue::String(value)) => self.insert(key.clone(), value),
+            Some(TagValue::Binary(value)) => self.binary(key.clone(), Some(value)),
+            None => {}
+        }
+        for nested in &simple.simple {
+            self.simple_tag(&key, nested);
+        }
+    }
+}
+
+fn language_suffix(language: Option<&Language>) -> String {
+    match language {
+        Some(Language::ISO639(l)) | Some(Language::IETF(l)) if !l.is_empty() => format!(".{}", l),
+        _ => String::new(),
+    }
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{}.{:09}", duration.as_secs(), duration.subsec_nanos())
+}
+
+pub(crate) const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
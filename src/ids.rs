@@ -6,92 +6,636 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+//! The IDs of the Matroska elements this crate knows about
+//!
+//! Each constant is documented with the element's name
+//! from the Matroska specification, which [`name`] looks up
+//! so diagnostics can print names rather than numbers.
+//! More IDs may be added over time.
+
+/// The Segment element
 pub const SEGMENT: u32 = 0x1853_8067;
+/// The SeekHead element
 pub const SEEKHEAD: u32 = 0x114D_9B74;
+/// The Seek element
 pub const SEEK: u32 = 0x4DBB;
+/// The SeekID element
 pub const SEEKID: u32 = 0x53AB;
+/// The SeekPosition element
 pub const SEEKPOSITION: u32 = 0x53AC;
+/// The Info element
 pub const INFO: u32 = 0x1549_A966;
+/// The SegmentUUID element
 pub const SEGMENTUID: u32 = 0x73A4;
+/// The PrevUUID element
 pub const PREVUID: u32 = 0x3C_B923;
+/// The NextUUID element
 pub const NEXTUID: u32 = 0x3E_B923;
+/// The SegmentFilename element
+pub const SEGMENTFILENAME: u32 = 0x7384;
+/// The PrevFilename element
+pub const PREVFILENAME: u32 = 0x3C_83AB;
+/// The NextFilename element
+pub const NEXTFILENAME: u32 = 0x3E_83BB;
+/// The SegmentFamily element
 pub const SEGMENTFAMILY: u32 = 0x4444;
+/// The Title element
 pub const TITLE: u32 = 0x7BA9;
+/// The MuxingApp element
 pub const MUXINGAPP: u32 = 0x4D80;
+/// The WritingApp element
 pub const WRITINGAPP: u32 = 0x5741;
+/// The DateUTC element
 pub const DATEUTC: u32 = 0x4461;
+/// The TimestampScale element
 pub const TIMECODESCALE: u32 = 0x2A_D7B1;
+/// The Duration element
 pub const DURATION: u32 = 0x4489;
+/// The Tracks element
 pub const TRACKS: u32 = 0x1654_AE6B;
+/// The TrackEntry element
 pub const TRACKENTRY: u32 = 0xAE;
+/// The TrackNumber element
 pub const TRACKNUMBER: u32 = 0xD7;
+/// The TrackUID element
 pub const TRACKUID: u32 = 0x73C5;
+/// The TrackType element
 pub const TRACKTYPE: u32 = 0x83;
+/// The FlagEnabled element
 pub const FLAGENABLED: u32 = 0xB9;
+/// The FlagDefault element
 pub const FLAGDEFAULT: u32 = 0x88;
+/// The FlagForced element
 pub const FLAGFORCED: u32 = 0x55AA;
+/// The FlagHearingImpaired element
 pub const FLAGHEARINGIMPAIRED: u32 = 0x55AB;
+/// The FlagVisualImpaired element
 pub const FLAGVISUALIMPAIRED: u32 = 0x55AC;
+/// The FlagTextDescriptions element
 pub const FLAGTEXTDESCRIPTIONS: u32 = 0x55AD;
+/// The FlagOriginal element
 pub const FLAGORIGINAL: u32 = 0x55AE;
+/// The FlagCommentary element
 pub const FLAGCOMMENTARY: u32 = 0x55AF;
+/// The FlagLacing element
 pub const FLAGLACING: u32 = 0x9C;
+/// The MinCache element
+pub const MINCACHE: u32 = 0x6DE7;
+/// The MaxCache element
+pub const MAXCACHE: u32 = 0x6DF8;
+/// The DefaultDuration element
 pub const DEFAULTDURATION: u32 = 0x23_E383;
+/// The MaxBlockAdditionID element
+pub const MAXBLOCKADDITIONID: u32 = 0x55EE;
+/// The CodecDelay element
+pub const CODECDELAY: u32 = 0x56AA;
+/// The SeekPreRoll element
+pub const SEEKPREROLL: u32 = 0x56BB;
+/// The Name element
 pub const NAME: u32 = 0x536E;
+/// The Language element
 pub const LANGUAGE: u32 = 0x22_B59C;
+/// The LanguageBCP47 element
 pub const LANGUAGE_IETF: u32 = 0x22_B59D;
+/// The CodecID element
 pub const CODEC_ID: u32 = 0x86;
+/// The CodecPrivate element
 pub const CODEC_PRIVATE: u32 = 0x63A2;
+/// The CodecName element
 pub const CODEC_NAME: u32 = 0x25_8688;
+/// The CodecSettings element
+pub const CODEC_SETTINGS: u32 = 0x3A_9697;
+/// The CodecInfoURL element
+pub const CODEC_INFO_URL: u32 = 0x3B_4040;
+/// The CodecDownloadURL element
+pub const CODEC_DOWNLOAD_URL: u32 = 0x26_B240;
+/// The CodecDecodeAll element
+pub const CODEC_DECODE_ALL: u32 = 0xAA;
+/// The AttachmentLink element
+pub const ATTACHMENTLINK: u32 = 0x7446;
+/// The Video element
 pub const VIDEO: u32 = 0xE0;
+/// The PixelWidth element
 pub const PIXELWIDTH: u32 = 0xB0;
+/// The PixelHeight element
 pub const PIXELHEIGHT: u32 = 0xBA;
+/// The DisplayWidth element
 pub const DISPLAYWIDTH: u32 = 0x54B0;
+/// The DisplayHeight element
 pub const DISPLAYHEIGHT: u32 = 0x54BA;
+/// The PixelCropBottom element
+pub const PIXELCROPBOTTOM: u32 = 0x54AA;
+/// The PixelCropTop element
+pub const PIXELCROPTOP: u32 = 0x54BB;
+/// The PixelCropLeft element
+pub const PIXELCROPLEFT: u32 = 0x54CC;
+/// The PixelCropRight element
+pub const PIXELCROPRIGHT: u32 = 0x54DD;
+/// The AspectRatioType element
+pub const ASPECTRATIOTYPE: u32 = 0x54B3;
+/// The DisplayUnit element
+pub const DISPLAYUNIT: u32 = 0x54B2;
+/// The FieldOrder element
+pub const FIELDORDER: u32 = 0x9D;
+/// The FlagInterlaced element
 pub const INTERLACED: u32 = 0x9A;
+/// The StereoMode element
 pub const STEREOMODE: u32 = 0x53B8;
+/// The Audio element
 pub const AUDIO: u32 = 0xE1;
+/// The SamplingFrequency element
 pub const SAMPLINGFREQUENCY: u32 = 0xB5;
+/// The Channels element
 pub const CHANNELS: u32 = 0x9F;
+/// The BitDepth element
 pub const BITDEPTH: u32 = 0x6264;
+/// The ChannelPositions element
+pub const CHANNELPOSITIONS: u32 = 0x7D7B;
+/// The TrackOperation element
+pub const TRACKOPERATION: u32 = 0xE2;
+/// The TrackCombinePlanes element
+pub const TRACKCOMBINEPLANES: u32 = 0xE3;
+/// The TrackPlane element
+pub const TRACKPLANE: u32 = 0xE4;
+/// The TrackPlaneUID element
+pub const TRACKPLANEUID: u32 = 0xE5;
+/// The TrackPlaneType element
+pub const TRACKPLANETYPE: u32 = 0xE6;
+/// The TrackJoinBlocks element
+pub const TRACKJOINBLOCKS: u32 = 0xE9;
+/// The TrackJoinUID element
+pub const TRACKJOINUID: u32 = 0xED;
+/// The TrackTranslate element
+pub const TRACKTRANSLATE: u32 = 0x6624;
+/// The TrackTranslateEditionUID element
+pub const TRACKTRANSLATEEDITIONUID: u32 = 0x66FC;
+/// The TrackTranslateCodec element
+pub const TRACKTRANSLATECODEC: u32 = 0x66BF;
+/// The TrackTranslateTrackID element
+pub const TRACKTRANSLATETRACKID: u32 = 0x66A5;
+/// The ContentEncodings element
+pub const CONTENTENCODINGS: u32 = 0x6D80;
+/// The ContentEncoding element
+pub const CONTENTENCODING: u32 = 0x6240;
+/// The ContentEncodingOrder element
+pub const CONTENTENCODINGORDER: u32 = 0x5031;
+/// The ContentEncodingScope element
+pub const CONTENTENCODINGSCOPE: u32 = 0x5032;
+/// The ContentEncodingType element
+pub const CONTENTENCODINGTYPE: u32 = 0x5033;
+/// The ContentCompression element
+pub const CONTENTCOMPRESSION: u32 = 0x5034;
+/// The ContentCompAlgo element
+pub const CONTENTCOMPALGO: u32 = 0x4254;
+/// The ContentCompSettings element
+pub const CONTENTCOMPSETTINGS: u32 = 0x4255;
+/// The ContentEncryption element
+pub const CONTENTENCRYPTION: u32 = 0x5035;
+/// The ContentEncAlgo element
+pub const CONTENTENCALGO: u32 = 0x47E1;
+/// The ContentEncKeyID element
+pub const CONTENTENCKEYID: u32 = 0x47E2;
+/// The ContentEncAESSettings element
+pub const CONTENTENCAESSETTINGS: u32 = 0x47E7;
+/// The AESSettingsCipherMode element
+pub const AESSETTINGSCIPHERMODE: u32 = 0x47E8;
+/// The BlockAdditionMapping element
+pub const BLOCKADDITIONMAPPING: u32 = 0x41E4;
+/// The BlockAddIDValue element
+pub const BLOCKADDIDVALUE: u32 = 0x41F0;
+/// The BlockAddIDName element
+pub const BLOCKADDIDNAME: u32 = 0x41A4;
+/// The BlockAddIDType element
+pub const BLOCKADDIDTYPE: u32 = 0x41E7;
+/// The BlockAddIDExtraData element
+pub const BLOCKADDIDEXTRADATA: u32 = 0x41ED;
+/// The Attachments element
 pub const ATTACHMENTS: u32 = 0x1941_A469;
+/// The AttachedFile element
 pub const ATTACHEDFILE: u32 = 0x61A7;
+/// The FileDescription element
 pub const FILEDESCRIPTION: u32 = 0x467E;
+/// The FileName element
 pub const FILENAME: u32 = 0x466E;
+/// The FileMediaType element
 pub const FILEMIMETYPE: u32 = 0x4660;
+/// The FileData element
 pub const FILEDATA: u32 = 0x465C;
+/// The FileUID element
+pub const FILEUID: u32 = 0x46AE;
+/// The FileReferral element
+pub const FILEREFERRAL: u32 = 0x4675;
+/// The FileUsedStartTime element
+pub const FILEUSEDSTARTTIME: u32 = 0x4661;
+/// The FileUsedEndTime element
+pub const FILEUSEDENDTIME: u32 = 0x4662;
+/// The Chapters element
 pub const CHAPTERS: u32 = 0x1043_A770;
+/// The EditionEntry element
 pub const EDITIONENTRY: u32 = 0x45B9;
+/// The EditionUID element
 pub const EDITIONUID: u32 = 0x45BC;
+/// The EditionFlagHidden element
 pub const EDITIONFLAGHIDDEN: u32 = 0x45BD;
+/// The EditionFlagDefault element
 pub const EDITIONFLAGDEFAULT: u32 = 0x45DB;
+/// The EditionFlagOrdered element
 pub const EDITIONFLAGORDERED: u32 = 0x45DD;
+/// The ChapterAtom element
 pub const CHAPTERATOM: u32 = 0xB6;
+/// The ChapterUID element
 pub const CHAPTERUID: u32 = 0x73C4;
+/// The ChapterStringUID element
+pub const CHAPTERSTRINGUID: u32 = 0x5654;
+/// The ChapterTimeStart element
 pub const CHAPTERTIMESTART: u32 = 0x91;
+/// The ChapterTimeEnd element
 pub const CHAPTERTIMEEND: u32 = 0x92;
+/// The ChapterFlagHidden element
 pub const CHAPTERFLAGHIDDEN: u32 = 0x98;
+/// The ChapterFlagEnabled element
 pub const CHAPTERFLAGENABLED: u32 = 0x4598;
+/// The ChapterSegmentUUID element
 pub const CHAPTERSEGMENTUID: u32 = 0x6E67;
+/// The ChapterSegmentEditionUID element
 pub const CHAPTERSEGMENTEDITIONUID: u32 = 0x6EBC;
+/// The ChapterPhysicalEquiv element
+pub const CHAPTERPHYSICALEQUIV: u32 = 0x63C3;
+/// The ChapterDisplay element
 pub const CHAPTERDISPLAY: u32 = 0x80;
+/// The ChapString element
 pub const CHAPSTRING: u32 = 0x85;
+/// The ChapLanguage element
 pub const CHAPLANGUAGE: u32 = 0x437C;
+/// The ChapLanguageBCP47 element
 pub const CHAPLANGUAGE_IETF: u32 = 0x437D;
+/// The ChapCountry element
+pub const CHAPCOUNTRY: u32 = 0x437E;
+/// The ChapProcess element
+pub const CHAPPROCESS: u32 = 0x6944;
+/// The ChapProcessCodecID element
+pub const CHAPPROCESSCODECID: u32 = 0x6955;
+/// The ChapProcessPrivate element
+pub const CHAPPROCESSPRIVATE: u32 = 0x450D;
+/// The ChapProcessCommand element
+pub const CHAPPROCESSCOMMAND: u32 = 0x6911;
+/// The ChapProcessTime element
+pub const CHAPPROCESSTIME: u32 = 0x6922;
+/// The ChapProcessData element
+pub const CHAPPROCESSDATA: u32 = 0x6933;
+/// The Tags element
 pub const TAGS: u32 = 0x1254_C367;
+/// The Tag element
 pub const TAG: u32 = 0x7373;
+/// The Targets element
 pub const TARGETS: u32 = 0x63C0;
+/// The TargetTypeValue element
 pub const TARGETTYPEVALUE: u32 = 0x68CA;
+/// The TargetType element
 pub const TARGETTYPE: u32 = 0x63CA;
+/// The TagTrackUID element
 pub const TAG_TRACK_UID: u32 = 0x63C5;
+/// The TagEditionUID element
 pub const TAG_EDITION_UID: u32 = 0x63C9;
+/// The TagChapterUID element
 pub const TAG_CHAPTER_UID: u32 = 0x63C4;
+/// The TagAttachmentUID element
 pub const TAG_ATTACHMENT_UID: u32 = 0x63C6;
+/// The SimpleTag element
 pub const SIMPLETAG: u32 = 0x67C8;
+/// The TagName element
 pub const TAGNAME: u32 = 0x45A3;
+/// The TagLanguage element
 pub const TAGLANGUAGE: u32 = 0x447A;
+/// The TagLanguageBCP47 element
 pub const TAGLANGUAGE_IETF: u32 = 0x447B;
+/// The TagDefault element
 pub const TAGDEFAULT: u32 = 0x4484;
+/// The TagString element
 pub const TAGSTRING: u32 = 0x4487;
+/// The TagBinary element
 pub const TAGBINARY: u32 = 0x4485;
+/// The GammaValue element
 pub const GAMMA: u32 = 0x2FB523;
+/// The UncompressedFourCC element
+pub const COLOURSPACE: u32 = 0x2E_B524;
+/// The Cues element
+pub const CUES: u32 = 0x1C53_BB6B;
+/// The CuePoint element
+pub const CUEPOINT: u32 = 0xBB;
+/// The CueTime element
+pub const CUETIME: u32 = 0xB3;
+/// The CueTrackPositions element
+pub const CUETRACKPOSITIONS: u32 = 0xB7;
+/// The CueTrack element
+pub const CUETRACK: u32 = 0xF7;
+/// The CueClusterPosition element
+pub const CUECLUSTERPOSITION: u32 = 0xF1;
+/// The CueRelativePosition element
+pub const CUERELATIVEPOSITION: u32 = 0xF0;
+/// The CueDuration element
+pub const CUEDURATION: u32 = 0xB2;
+/// The CueBlockNumber element
+pub const CUEBLOCKNUMBER: u32 = 0x5378;
+/// The Colour element
+pub const COLOUR: u32 = 0x55B0;
+/// The MatrixCoefficients element
+pub const MATRIXCOEFFICIENTS: u32 = 0x55B1;
+/// The BitsPerChannel element
+pub const BITSPERCHANNEL: u32 = 0x55B2;
+/// The ChromaSubsamplingHorz element
+pub const CHROMASUBSAMPLINGHORZ: u32 = 0x55B3;
+/// The ChromaSubsamplingVert element
+pub const CHROMASUBSAMPLINGVERT: u32 = 0x55B4;
+/// The CbSubsamplingHorz element
+pub const CBSUBSAMPLINGHORZ: u32 = 0x55B5;
+/// The CbSubsamplingVert element
+pub const CBSUBSAMPLINGVERT: u32 = 0x55B6;
+/// The ChromaSitingHorz element
+pub const CHROMASITINGHORZ: u32 = 0x55B7;
+/// The ChromaSitingVert element
+pub const CHROMASITINGVERT: u32 = 0x55B8;
+/// The Range element
+pub const RANGE: u32 = 0x55B9;
+/// The TransferCharacteristics element
+pub const TRANSFERCHARACTERISTICS: u32 = 0x55BA;
+/// The Primaries element
+pub const PRIMARIES: u32 = 0x55BB;
+/// The MaxCLL element
+pub const MAXCLL: u32 = 0x55BC;
+/// The MaxFALL element
+pub const MAXFALL: u32 = 0x55BD;
+/// The MasteringMetadata element
+pub const MASTERINGMETADATA: u32 = 0x55D0;
+/// The PrimaryRChromaticityX element
+pub const PRIMARYRCHROMATICITYX: u32 = 0x55D1;
+/// The PrimaryRChromaticityY element
+pub const PRIMARYRCHROMATICITYY: u32 = 0x55D2;
+/// The PrimaryGChromaticityX element
+pub const PRIMARYGCHROMATICITYX: u32 = 0x55D3;
+/// The PrimaryGChromaticityY element
+pub const PRIMARYGCHROMATICITYY: u32 = 0x55D4;
+/// The PrimaryBChromaticityX element
+pub const PRIMARYBCHROMATICITYX: u32 = 0x55D5;
+/// The PrimaryBChromaticityY element
+pub const PRIMARYBCHROMATICITYY: u32 = 0x55D6;
+/// The WhitePointChromaticityX element
+pub const WHITEPOINTCHROMATICITYX: u32 = 0x55D7;
+/// The WhitePointChromaticityY element
+pub const WHITEPOINTCHROMATICITYY: u32 = 0x55D8;
+/// The LuminanceMax element
+pub const LUMINANCEMAX: u32 = 0x55D9;
+/// The LuminanceMin element
+pub const LUMINANCEMIN: u32 = 0x55DA;
+/// The Cluster element
+pub const CLUSTER: u32 = 0x1F43_B675;
+/// The Timestamp element
+pub const TIMECODE: u32 = 0xE7;
+/// The SimpleBlock element
+pub const SIMPLEBLOCK: u32 = 0xA3;
+/// The BlockGroup element
+pub const BLOCKGROUP: u32 = 0xA0;
+/// The Block element
+pub const BLOCK: u32 = 0xA1;
+/// The ReferenceBlock element
+pub const REFERENCEBLOCK: u32 = 0xFB;
+/// The BlockDuration element
+pub const BLOCKDURATION: u32 = 0x9B;
+/// The EBML element
+pub const EBML: u32 = 0x1A45_DFA3;
+/// The EBMLVersion element
+pub const EBMLVERSION: u32 = 0x4286;
+/// The EBMLReadVersion element
+pub const EBMLREADVERSION: u32 = 0x42F7;
+/// The EBMLMaxIDLength element
+pub const EBMLMAXIDLENGTH: u32 = 0x42F2;
+/// The EBMLMaxSizeLength element
+pub const EBMLMAXSIZELENGTH: u32 = 0x42F3;
+/// The DocType element
+pub const DOCTYPE: u32 = 0x4282;
+/// The DocTypeVersion element
+pub const DOCTYPEVERSION: u32 = 0x4287;
+/// The DocTypeReadVersion element
+pub const DOCTYPEREADVERSION: u32 = 0x4285;
+/// The CRC-32 element
+pub const CRC32: u32 = 0xBF;
+/// The Void element
+pub const VOID: u32 = 0xEC;
+
+/// Returns the Matroska specification's name for the element
+/// with the given ID, if it's one this crate knows about
+///
+/// ## Example
+/// ```
+/// assert_eq!(matroska::ids::name(matroska::ids::TRACKENTRY), Some("TrackEntry"));
+/// assert_eq!(matroska::ids::name(0x12), None);
+/// ```
+pub fn name(id: u32) -> Option<&'static str> {
+    match id {
+        SEGMENT => Some("Segment"),
+        SEEKHEAD => Some("SeekHead"),
+        SEEK => Some("Seek"),
+        SEEKID => Some("SeekID"),
+        SEEKPOSITION => Some("SeekPosition"),
+        INFO => Some("Info"),
+        SEGMENTUID => Some("SegmentUUID"),
+        PREVUID => Some("PrevUUID"),
+        NEXTUID => Some("NextUUID"),
+        SEGMENTFILENAME => Some("SegmentFilename"),
+        PREVFILENAME => Some("PrevFilename"),
+        NEXTFILENAME => Some("NextFilename"),
+        SEGMENTFAMILY => Some("SegmentFamily"),
+        TITLE => Some("Title"),
+        MUXINGAPP => Some("MuxingApp"),
+        WRITINGAPP => Some("WritingApp"),
+        DATEUTC => Some("DateUTC"),
+        TIMECODESCALE => Some("TimestampScale"),
+        DURATION => Some("Duration"),
+        TRACKS => Some("Tracks"),
+        TRACKENTRY => Some("TrackEntry"),
+        TRACKNUMBER => Some("TrackNumber"),
+        TRACKUID => Some("TrackUID"),
+        TRACKTYPE => Some("TrackType"),
+        FLAGENABLED => Some("FlagEnabled"),
+        FLAGDEFAULT => Some("FlagDefault"),
+        FLAGFORCED => Some("FlagForced"),
+        FLAGHEARINGIMPAIRED => Some("FlagHearingImpaired"),
+        FLAGVISUALIMPAIRED => Some("FlagVisualImpaired"),
+        FLAGTEXTDESCRIPTIONS => Some("FlagTextDescriptions"),
+        FLAGORIGINAL => Some("FlagOriginal"),
+        FLAGCOMMENTARY => Some("FlagCommentary"),
+        FLAGLACING => Some("FlagLacing"),
+        MINCACHE => Some("MinCache"),
+        MAXCACHE => Some("MaxCache"),
+        DEFAULTDURATION => Some("DefaultDuration"),
+        MAXBLOCKADDITIONID => Some("MaxBlockAdditionID"),
+        CODECDELAY => Some("CodecDelay"),
+        SEEKPREROLL => Some("SeekPreRoll"),
+        NAME => Some("Name"),
+        LANGUAGE => Some("Language"),
+        LANGUAGE_IETF => Some("LanguageBCP47"),
+        CODEC_ID => Some("CodecID"),
+        CODEC_PRIVATE => Some("CodecPrivate"),
+        CODEC_NAME => Some("CodecName"),
+        CODEC_SETTINGS => Some("CodecSettings"),
+        CODEC_INFO_URL => Some("CodecInfoURL"),
+        CODEC_DOWNLOAD_URL => Some("CodecDownloadURL"),
+        CODEC_DECODE_ALL => Some("CodecDecodeAll"),
+        ATTACHMENTLINK => Some("AttachmentLink"),
+        VIDEO => Some("Video"),
+        PIXELWIDTH => Some("PixelWidth"),
+        PIXELHEIGHT => Some("PixelHeight"),
+        DISPLAYWIDTH => Some("DisplayWidth"),
+        DISPLAYHEIGHT => Some("DisplayHeight"),
+        PIXELCROPBOTTOM => Some("PixelCropBottom"),
+        PIXELCROPTOP => Some("PixelCropTop"),
+        PIXELCROPLEFT => Some("PixelCropLeft"),
+        PIXELCROPRIGHT => Some("PixelCropRight"),
+        ASPECTRATIOTYPE => Some("AspectRatioType"),
+        DISPLAYUNIT => Some("DisplayUnit"),
+        FIELDORDER => Some("FieldOrder"),
+        INTERLACED => Some("FlagInterlaced"),
+        STEREOMODE => Some("StereoMode"),
+        AUDIO => Some("Audio"),
+        SAMPLINGFREQUENCY => Some("SamplingFrequency"),
+        CHANNELS => Some("Channels"),
+        BITDEPTH => Some("BitDepth"),
+        CHANNELPOSITIONS => Some("ChannelPositions"),
+        TRACKOPERATION => Some("TrackOperation"),
+        TRACKCOMBINEPLANES => Some("TrackCombinePlanes"),
+        TRACKPLANE => Some("TrackPlane"),
+        TRACKPLANEUID => Some("TrackPlaneUID"),
+        TRACKPLANETYPE => Some("TrackPlaneType"),
+        TRACKJOINBLOCKS => Some("TrackJoinBlocks"),
+        TRACKJOINUID => Some("TrackJoinUID"),
+        TRACKTRANSLATE => Some("TrackTranslate"),
+        TRACKTRANSLATEEDITIONUID => Some("TrackTranslateEditionUID"),
+        TRACKTRANSLATECODEC => Some("TrackTranslateCodec"),
+        TRACKTRANSLATETRACKID => Some("TrackTranslateTrackID"),
+        CONTENTENCODINGS => Some("ContentEncodings"),
+        CONTENTENCODING => Some("ContentEncoding"),
+        CONTENTENCODINGORDER => Some("ContentEncodingOrder"),
+        CONTENTENCODINGSCOPE => Some("ContentEncodingScope"),
+        CONTENTENCODINGTYPE => Some("ContentEncodingType"),
+        CONTENTCOMPRESSION => Some("ContentCompression"),
+        CONTENTCOMPALGO => Some("ContentCompAlgo"),
+        CONTENTCOMPSETTINGS => Some("ContentCompSettings"),
+        CONTENTENCRYPTION => Some("ContentEncryption"),
+        CONTENTENCALGO => Some("ContentEncAlgo"),
+        CONTENTENCKEYID => Some("ContentEncKeyID"),
+        CONTENTENCAESSETTINGS => Some("ContentEncAESSettings"),
+        AESSETTINGSCIPHERMODE => Some("AESSettingsCipherMode"),
+        BLOCKADDITIONMAPPING => Some("BlockAdditionMapping"),
+        BLOCKADDIDVALUE => Some("BlockAddIDValue"),
+        BLOCKADDIDNAME => Some("BlockAddIDName"),
+        BLOCKADDIDTYPE => Some("BlockAddIDType"),
+        BLOCKADDIDEXTRADATA => Some("BlockAddIDExtraData"),
+        ATTACHMENTS => Some("Attachments"),
+        ATTACHEDFILE => Some("AttachedFile"),
+        FILEDESCRIPTION => Some("FileDescription"),
+        FILENAME => Some("FileName"),
+        FILEMIMETYPE => Some("FileMediaType"),
+        FILEDATA => Some("FileData"),
+        FILEUID => Some("FileUID"),
+        FILEREFERRAL => Some("FileReferral"),
+        FILEUSEDSTARTTIME => Some("FileUsedStartTime"),
+        FILEUSEDENDTIME => Some("FileUsedEndTime"),
+        CHAPTERS => Some("Chapters"),
+        EDITIONENTRY => Some("EditionEntry"),
+        EDITIONUID => Some("EditionUID"),
+        EDITIONFLAGHIDDEN => Some("EditionFlagHidden"),
+        EDITIONFLAGDEFAULT => Some("EditionFlagDefault"),
+        EDITIONFLAGORDERED => Some("EditionFlagOrdered"),
+        CHAPTERATOM => Some("ChapterAtom"),
+        CHAPTERUID => Some("ChapterUID"),
+        CHAPTERSTRINGUID => Some("ChapterStringUID"),
+        CHAPTERTIMESTART => Some("ChapterTimeStart"),
+        CHAPTERTIMEEND => Some("ChapterTimeEnd"),
+        CHAPTERFLAGHIDDEN => Some("ChapterFlagHidden"),
+        CHAPTERFLAGENABLED => Some("ChapterFlagEnabled"),
+        CHAPTERSEGMENTUID => Some("ChapterSegmentUUID"),
+        CHAPTERSEGMENTEDITIONUID => Some("ChapterSegmentEditionUID"),
+        CHAPTERPHYSICALEQUIV => Some("ChapterPhysicalEquiv"),
+        CHAPTERDISPLAY => Some("ChapterDisplay"),
+        CHAPSTRING => Some("ChapString"),
+        CHAPLANGUAGE => Some("ChapLanguage"),
+        CHAPLANGUAGE_IETF => Some("ChapLanguageBCP47"),
+        CHAPCOUNTRY => Some("ChapCountry"),
+        CHAPPROCESS => Some("ChapProcess"),
+        CHAPPROCESSCODECID => Some("ChapProcessCodecID"),
+        CHAPPROCESSPRIVATE => Some("ChapProcessPrivate"),
+        CHAPPROCESSCOMMAND => Some("ChapProcessCommand"),
+        CHAPPROCESSTIME => Some("ChapProcessTime"),
+        CHAPPROCESSDATA => Some("ChapProcessData"),
+        TAGS => Some("Tags"),
+        TAG => Some("Tag"),
+        TARGETS => Some("Targets"),
+        TARGETTYPEVALUE => Some("TargetTypeValue"),
+        TARGETTYPE => Some("TargetType"),
+        TAG_TRACK_UID => Some("TagTrackUID"),
+        TAG_EDITION_UID => Some("TagEditionUID"),
+        TAG_CHAPTER_UID => Some("TagChapterUID"),
+        TAG_ATTACHMENT_UID => Some("TagAttachmentUID"),
+        SIMPLETAG => Some("SimpleTag"),
+        TAGNAME => Some("TagName"),
+        TAGLANGUAGE => Some("TagLanguage"),
+        TAGLANGUAGE_IETF => Some("TagLanguageBCP47"),
+        TAGDEFAULT => Some("TagDefault"),
+        TAGSTRING => Some("TagString"),
+        TAGBINARY => Some("TagBinary"),
+        GAMMA => Some("GammaValue"),
+        COLOURSPACE => Some("UncompressedFourCC"),
+        CUES => Some("Cues"),
+        CUEPOINT => Some("CuePoint"),
+        CUETIME => Some("CueTime"),
+        CUETRACKPOSITIONS => Some("CueTrackPositions"),
+        CUETRACK => Some("CueTrack"),
+        CUECLUSTERPOSITION => Some("CueClusterPosition"),
+        CUERELATIVEPOSITION => Some("CueRelativePosition"),
+        CUEDURATION => Some("CueDuration"),
+        CUEBLOCKNUMBER => Some("CueBlockNumber"),
+        COLOUR => Some("Colour"),
+        MATRIXCOEFFICIENTS => Some("MatrixCoefficients"),
+        BITSPERCHANNEL => Some("BitsPerChannel"),
+        CHROMASUBSAMPLINGHORZ => Some("ChromaSubsamplingHorz"),
+        CHROMASUBSAMPLINGVERT => Some("ChromaSubsamplingVert"),
+        CBSUBSAMPLINGHORZ => Some("CbSubsamplingHorz"),
+        CBSUBSAMPLINGVERT => Some("CbSubsamplingVert"),
+        CHROMASITINGHORZ => Some("ChromaSitingHorz"),
+        CHROMASITINGVERT => Some("ChromaSitingVert"),
+        RANGE => Some("Range"),
+        TRANSFERCHARACTERISTICS => Some("TransferCharacteristics"),
+        PRIMARIES => Some("Primaries"),
+        MAXCLL => Some("MaxCLL"),
+        MAXFALL => Some("MaxFALL"),
+        MASTERINGMETADATA => Some("MasteringMetadata"),
+        PRIMARYRCHROMATICITYX => Some("PrimaryRChromaticityX"),
+        PRIMARYRCHROMATICITYY => Some("PrimaryRChromaticityY"),
+        PRIMARYGCHROMATICITYX => Some("PrimaryGChromaticityX"),
+        PRIMARYGCHROMATICITYY => Some("PrimaryGChromaticityY"),
+        PRIMARYBCHROMATICITYX => Some("PrimaryBChromaticityX"),
+        PRIMARYBCHROMATICITYY => Some("PrimaryBChromaticityY"),
+        WHITEPOINTCHROMATICITYX => Some("WhitePointChromaticityX"),
+        WHITEPOINTCHROMATICITYY => Some("WhitePointChromaticityY"),
+        LUMINANCEMAX => Some("LuminanceMax"),
+        LUMINANCEMIN => Some("LuminanceMin"),
+        CLUSTER => Some("Cluster"),
+        TIMECODE => Some("Timestamp"),
+        SIMPLEBLOCK => Some("SimpleBlock"),
+        BLOCKGROUP => Some("BlockGroup"),
+        BLOCK => Some("Block"),
+        REFERENCEBLOCK => Some("ReferenceBlock"),
+        BLOCKDURATION => Some("BlockDuration"),
+        EBML => Some("EBML"),
+        EBMLVERSION => Some("EBMLVersion"),
+        EBMLREADVERSION => Some("EBMLReadVersion"),
+        EBMLMAXIDLENGTH => Some("EBMLMaxIDLength"),
+        EBMLMAXSIZELENGTH => Some("EBMLMaxSizeLength"),
+        DOCTYPE => Some("DocType"),
+        DOCTYPEVERSION => Some("DocTypeVersion"),
+        DOCTYPEREADVERSION => Some("DocTypeReadVersion"),
+        CRC32 => Some("CRC-32"),
+        VOID => Some("Void"),
+        _ => None,
+    }
+}
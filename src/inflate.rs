@@ -0,0 +1,268 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A small zlib decompressor for tracks using zlib content compression
+
+/// The longest a Huffman code may be, in bits
+const MAX_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order code length code lengths are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompresses a zlib stream, returning `None` if it's invalid
+/// or would decompress to more than `limit` bytes
+pub(crate) fn decompress(data: &[u8], limit: usize) -> Option<Vec<u8>> {
+    let (&cmf, &flg) = (data.first()?, data.get(1)?);
+    // only deflate without a preset dictionary is allowed
+    if cmf & 0x0F != 8 || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 || flg & 0x20 != 0 {
+        return None;
+    }
+    let mut bits = Bits {
+        data: &data[2..],
+        position: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let output = inflate(&mut bits, limit)?;
+
+    let trailer = bits.data.get(bits.position..bits.position + 4)?;
+    let checksum = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    (adler32(&output) == checksum).then_some(output)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+/// Reads a deflate stream's bits, least significant first
+struct Bits<'d> {
+    data: &'d [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl Bits<'_> {
+    fn bits(&mut self, needed: u32) -> Option<u32> {
+        while self.count < needed {
+            let byte = *self.data.get(self.position)?;
+            self.position += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1 << needed) - 1);
+        self.buffer >>= needed;
+        self.count -= needed;
+        Some(value)
+    }
+
+    /// Discards bits up to the next byte boundary
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// A canonical Huffman code
+struct Huffman {
+    /// The number of codes of each length
+    counts: [u16; MAX_BITS + 1],
+    /// The symbols, ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds a code from each symbol's code length,
+    /// returning `None` if the lengths are over-subscribed
+    fn new(lengths: &[u8]) -> Option<Huffman> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for length in lengths {
+            counts[usize::from(*length)] += 1;
+        }
+        let mut left = 1i32;
+        for count in &counts[1..] {
+            left = (left << 1) - i32::from(*count);
+            if left < 0 {
+                return None;
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for length in 1..MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                let offset = &mut offsets[usize::from(*length)];
+                symbols[usize::from(*offset)] = symbol as u16;
+                *offset += 1;
+            }
+        }
+        Some(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Option<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = i32::from(*count);
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+fn inflate(bits: &mut Bits, limit: usize) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.align();
+                let header = bits.data.get(bits.position..bits.position + 4)?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if len != !nlen {
+                    return None;
+                }
+                let start = bits.position + 4;
+                let stored = bits.data.get(start..start + usize::from(len))?;
+                if output.len() + stored.len() > limit {
+                    return None;
+                }
+                output.extend_from_slice(stored);
+                bits.position = start + usize::from(len);
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                inflate_block(bits, &literals, &distances, &mut output, limit)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(bits)?;
+                inflate_block(bits, &literals, &distances, &mut output, limit)?;
+            }
+            _ => return None,
+        }
+        if last {
+            break;
+        }
+    }
+    bits.align();
+    Some(output)
+}
+
+/// Reads the Huffman codes which start a dynamic block
+fn dynamic_codes(bits: &mut Bits) -> Option<(Huffman, Huffman)> {
+    let literal_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let code_length_count = bits.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return None;
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[*index] = bits.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last()?, 3 + bits.bits(2)?),
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        for _ in 0..repeat {
+            lengths.push(length);
+        }
+    }
+    if lengths.len() != literal_count + distance_count || lengths[256] == 0 {
+        return None;
+    }
+    Some((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+fn inflate_block(
+    bits: &mut Bits,
+    literals: &Huffman,
+    distances: &Huffman,
+    output: &mut Vec<u8>,
+    limit: usize,
+) -> Option<()> {
+    loop {
+        let symbol = usize::from(literals.decode(bits)?);
+        match symbol {
+            0..=255 => {
+                if output.len() >= limit {
+                    return None;
+                }
+                output.push(symbol as u8);
+            }
+            256 => return Some(()),
+            _ => {
+                let index = symbol - 257;
+                let length = usize::from(*LENGTH_BASE.get(index)?)
+                    + bits.bits(u32::from(LENGTH_EXTRA[index]))? as usize;
+                let index = usize::from(distances.decode(bits)?);
+                let distance = usize::from(*DISTANCE_BASE.get(index)?)
+                    + bits.bits(u32::from(DISTANCE_EXTRA[index]))? as usize;
+                if distance > output.len() || output.len() + length > limit {
+                    return None;
+                }
+                let start = output.len() - distance;
+                // the copy may overlap what it's writing
+                for i in start..start + length {
+                    output.push(output[i]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::io;
+use std::time::Duration;
+
+use crate::cluster::ClusterWalker;
+use crate::ebml::{MatroskaError, Result};
+use crate::Matroska;
+
+/// A keyframe's timestamp and the Cluster holding it,
+/// as returned by [`keyframe_index`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct KeyframeEntry {
+    /// When the keyframe is presented
+    pub pts: Duration,
+    /// The file offset of the Cluster holding the keyframe
+    pub cluster_offset: u64,
+}
+
+/// Returns the keyframes of an open Matroska file's track, in time order
+///
+/// The file's Cues are used if they index the track.
+/// Otherwise, its Clusters are scanned for keyframes,
+/// reading only block headers.
+/// A track number not in the file is [`MatroskaError::UnknownTrack`].
+///
+/// ## Example
+/// ```no_run
+/// let file = std::io::BufReader::new(std::fs::File::open("file.mkv").unwrap());
+/// for keyframe in matroska::keyframe_index(file, 1).unwrap() {
+///     println!("{:?} at {}", keyframe.pts, keyframe.cluster_offset);
+/// }
+/// ```
+pub fn keyframe_index<R: io::Read + io::Seek>(
+    mut r: R,
+    track_number: u64,
+) -> Result<Vec<KeyframeEntry>> {
+    r.seek(io::SeekFrom::Start(0))?;
+    let matroska = Matroska::open(&mut r)?;
+    if !matroska
+        .tracks
+        .iter()
+        .any(|track| track.number == track_number)
+    {
+        return Err(MatroskaError::UnknownTrack {
+            number: track_number,
+        });
+    }
+    let scale = matroska.info.timestamp_scale;
+    let segment_start = matroska.segment_start;
+
+    let mut entries: Vec<KeyframeEntry> = matroska
+        .cues
+        .iter()
+        .flat_map(|cues| cues.points.iter())
+        .flat_map(|point| {
+            point
+                .positions
+                .iter()
+                .filter(|positions| positions.track == track_number)
+                .map(move |positions| KeyframeEntry {
+                    pts: Duration::from_nanos(point.time.saturating_mul(scale)),
+                    cluster_offset: segment_start.saturating_add(positions.cluster_position),
+                })
+        })
+        .collect();
+
+    // Cues which only index other tracks are no help
+    if entries.is_empty() {
+        for cluster in ClusterWalker::new(&mut r, scale, None)? {
+            let cluster = cluster?;
+            entries.extend(
+                cluster
+                    .blocks
+                    .iter()
+                    .filter(|block| block.track == track_number && block.keyframe)
+                    .map(|block| KeyframeEntry {
+                        pts: cluster.block_timestamp(block).unwrap_or_default(),
+                        cluster_offset: cluster.offset,
+                    }),
+            );
+        }
+    }
+
+    entries.sort();
+    entries.dedup();
+    Ok(entries)
+}
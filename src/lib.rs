@@ -25,28 +25,82 @@
 //! }
 //! ```
 //!
+//! ## Optional features
+//!
+//! [`DateTime`] values convert to `SystemTime` with no extra dependencies.
+//! The `time`, `chrono` and `jiff` features each add a conversion
+//! to that crate's calendar type.
+//!
 //! For additional information about the Matroska format, see the
 //! official [specification](https://matroska.org)
 
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
-use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::io;
 use std::time::Duration;
 
+mod block;
+mod cluster;
+mod cue_sheet;
+mod diff;
+mod dump;
+mod duration;
 mod ebml;
-mod ids;
+mod edit;
+mod flat;
+pub mod ids;
+mod inflate;
+mod keyframes;
+mod linked;
+mod mse;
+mod packets;
+mod reader;
+mod remux;
+mod select;
+mod simple_chapters;
+mod stats;
+mod subtitles;
+mod tags;
+mod validate;
+mod xml;
 
-pub use ebml::{DateTime, MatroskaError};
-use ebml::{Element, ElementType, Result};
+pub use block::{Block, Lacing};
+pub use cluster::{clusters, Cluster, ClusterBlock, Clusters};
+pub use diff::{diff, Change, MetadataDiff};
+pub use dump::{dump, dump_all, ElementNode};
+pub use ebml::{DateTime, Element, ElementType, MatroskaError};
+use ebml::{Limits, Result};
+pub use edit::{replace_chapters, replace_tags, set_title};
+pub use flat::BinaryFormat;
+pub use keyframes::{keyframe_index, KeyframeEntry};
+pub use linked::{open_linked, SegmentResolver};
+pub use mse::{check_mse_compat, MseProblem, MseReport, MseViolation};
+pub use packets::{Packet, TrackPackets};
+pub use reader::{ElementHeader, ElementReader};
+pub use remux::{remux, MetadataEdits, TrackEdit};
+pub use select::{burn_in_candidate, burn_in_candidate_with_counts};
+pub use stats::{
+    lacing_statistics, track_sizes, track_sizes_sampled, LacingStatistics, TrackUsage,
+};
+pub use subtitles::{extract_subtitles, to_ass, to_srt, SubtitleEntry};
+pub use tags::TrackStatistics;
+pub use validate::{CueMismatch, CueProblem, Issue};
 
 /// A possible error when reading or parsing a Matroska file
 pub type Error = MatroskaError;
 
 /// A Matroska file
-#[derive(Debug, Clone)]
+///
+/// Files may be compared for equality, but aren't `Eq` or `Hash`
+/// because tracks hold floating-point values such as audio sampling
+/// frequencies, which are compared as parsed rather than normalized.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct Matroska {
+    /// The file's EBML header, if it has one
+    pub ebml_header: Option<EbmlHeader>,
     /// The file's Info segment
     pub info: Info,
     /// The file's Tracks segment
@@ -57,99 +111,259 @@ pub struct Matroska {
     pub chapters: Vec<ChapterEdition>,
     /// The file's Tags segment
     pub tags: Vec<Tag>,
+    /// The file's Cues segment, if any
+    pub cues: Option<Cues>,
+    /// The SeekHead sections were read from, if any
+    pub seek_head: Option<SeekHead>,
+    /// The file offset of the Segment's contents,
+    /// which SeekHead and Cues positions are relative to
+    pub segment_start: u64,
+}
+
+impl Default for Matroska {
+    fn default() -> Self {
+        Matroska::new()
+    }
 }
 
 impl Matroska {
     fn new() -> Matroska {
         Matroska {
+            ebml_header: None,
             info: Info::new(),
             tracks: Vec::new(),
             attachments: Vec::new(),
             chapters: Vec::new(),
             tags: Vec::new(),
+            cues: None,
+            seek_head: None,
+            segment_start: 0,
         }
     }
 
     /// Parses contents of open Matroska file
-    pub fn open<R: io::Read + io::Seek>(mut file: R) -> Result<Matroska> {
-        use std::io::SeekFrom;
+    pub fn open<R: io::Read + io::Seek>(file: R) -> Result<Matroska> {
+        Matroska::open_with(file, &ParseOptions::default())
+    }
 
+    /// Parses contents of open Matroska file using the given options
+    ///
+    /// Sections are read from where the SeekHead points,
+    /// and the rest of the Segment is scanned for any sections
+    /// it doesn't list or points to the wrong place,
+    /// such as Tags appended to the end of the file by an editor.
+    pub fn open_with<R: io::Read + io::Seek>(
+        mut file: R,
+        options: &ParseOptions,
+    ) -> Result<Matroska> {
         let mut matroska = Matroska::new();
+        matroska.populate(&mut file, options, &mut Problems::strict(), None)?;
+        Ok(matroska)
+    }
+
+    /// Parses as much of an open Matroska file as possible
+    ///
+    /// Rather than failing on the first problem, a section which
+    /// fails to parse is skipped and its error recorded, so that
+    /// a corrupt Tags section doesn't keep Tracks from being read.
+    /// Section errors are [`MatroskaError::Located`] at their offsets.
+    /// A file without a readable EBML header or Segment
+    /// yields an empty `Matroska` along with that error.
+    pub fn open_lossy<R: io::Read + io::Seek>(file: R) -> (Matroska, Vec<MatroskaError>) {
+        Matroska::open_lossy_with(file, &ParseOptions::default())
+    }
 
-        let (mut id_0, mut size_0, _) = ebml::read_element_id_size(&mut file)?;
-        while id_0 != ids::SEGMENT {
-            file.seek(SeekFrom::Current(size_0 as i64)).map(|_| ())?;
-            let (id, size, _) = ebml::read_element_id_size(&mut file)?;
-            id_0 = id;
-            size_0 = size;
+    /// Parses as much of an open Matroska file as possible
+    /// using the given options
+    ///
+    /// See [`Matroska::open_lossy`] for how errors are handled.
+    pub fn open_lossy_with<R: io::Read + io::Seek>(
+        mut file: R,
+        options: &ParseOptions,
+    ) -> (Matroska, Vec<MatroskaError>) {
+        let mut matroska = Matroska::new();
+        let mut problems = Problems::lossy();
+        if let Err(err) = matroska.populate(&mut file, options, &mut problems, None) {
+            problems.errors.push(err);
         }
+        (matroska, problems.errors)
+    }
+
+    /// Populates sections from the file,
+    /// passing section errors to `problems` which either
+    /// returns them to fail or `Ok` to skip the section
+    ///
+    /// Given the file's path, the sections its SeekHead points to
+    /// are parsed in parallel from their own handles on the file.
+    fn populate<R: io::Read + io::Seek>(
+        &mut self,
+        file: &mut R,
+        options: &ParseOptions,
+        problems: &mut Problems,
+        path: Option<&std::path::Path>,
+    ) -> Result<()> {
+        let (ebml_header, mut size_0) = find_segment_with_header(file)?;
+        self.ebml_header = ebml_header;
 
         let segment_start = file.stream_position()?;
+        self.segment_start = segment_start;
+        // positions of sections already read, or which failed to read
+        let mut seen = BTreeSet::new();
 
         while size_0 > 0 {
-            let (id_1, size_1, len) = ebml::read_element_id_size(&mut file)?;
+            let (id_1, mut size_1, len) = match read_top_level_header(file)? {
+                Some(header) => header,
+                None => break,
+            };
+            let start = file.stream_position()?;
             match id_1 {
                 ids::SEEKHEAD => {
                     // if seektable encountered, populate file from that
-                    let seektable = Seektable::parse(&mut file, segment_start, size_1)?;
-
-                    if let Some(pos) = seektable.get(ids::INFO)? {
-                        file.seek(SeekFrom::Start(pos))?;
-                        let (i, s, _) = ebml::read_element_id_size(&mut file)?;
-                        assert_eq!(i, ids::INFO);
-                        matroska.info = Info::parse(&mut file, s)?;
+                    let seektable = match parse_located(file, ids::SEEKHEAD, |r| {
+                        SeekHead::parse_chained(r, segment_start, size_1)
+                    }) {
+                        Ok(seektable) => Some(seektable),
+                        Err(err) => problems.recover(err).map(|()| None)?,
+                    };
+                    if let Some(seektable) = seektable {
+                        match seektable.check(stream_len(file)?) {
+                            Ok(()) => {
+                                self.populate_from(
+                                    file, &seektable, options, problems, &mut seen, path,
+                                )?;
+                                self.seek_head.get_or_insert(seektable);
+                            }
+                            Err(err) => problems.warn(err.located(start, Some(ids::SEEKHEAD))),
+                        }
                     }
-                    if let Some(pos) = seektable.get(ids::TRACKS)? {
-                        file.seek(SeekFrom::Start(pos))?;
-                        let (i, s, _) = ebml::read_element_id_size(&mut file)?;
-                        assert_eq!(i, ids::TRACKS);
-                        matroska.tracks = Track::parse(&mut file, s)?;
-                    }
-                    if let Some(pos) = seektable.get(ids::ATTACHMENTS)? {
-                        file.seek(SeekFrom::Start(pos))?;
-                        let (i, s, _) = ebml::read_element_id_size(&mut file)?;
-                        assert_eq!(i, ids::ATTACHMENTS);
-                        matroska.attachments = Attachment::parse(&mut file, s)?;
-                    }
-                    if let Some(pos) = seektable.get(ids::CHAPTERS)? {
-                        file.seek(SeekFrom::Start(pos))?;
-                        let (i, s, _) = ebml::read_element_id_size(&mut file)?;
-                        assert_eq!(i, ids::CHAPTERS);
-                        matroska.chapters = ChapterEdition::parse(&mut file, s)?;
-                    }
-                    if let Some(pos) = seektable.get(ids::TAGS)? {
-                        file.seek(SeekFrom::Start(pos))?;
-                        let (i, s, _) = ebml::read_element_id_size(&mut file)?;
-                        assert_eq!(i, ids::TAGS);
-                        matroska.tags = Tag::parse(&mut file, s)?;
-                    }
-                    return Ok(matroska);
+                    // keep walking for sections the seektable didn't provide
+                    file.seek(io::SeekFrom::Start(start.saturating_add(size_1)))?;
                 }
                 // if no seektable, populate file from parts
-                ids::INFO => {
-                    matroska.info = Info::parse(&mut file, size_1)?;
-                }
-                ids::TRACKS => {
-                    matroska.tracks = Track::parse(&mut file, size_1)?;
-                }
-                ids::ATTACHMENTS => {
-                    matroska.attachments = Attachment::parse(&mut file, size_1)?;
+                id if Section::IDS.contains(&id)
+                    && options.parses(id)
+                    && seen.insert(start - len) =>
+                {
+                    match Section::parse(file, id, size_1, options) {
+                        Ok(section) => {
+                            if !self.insert(section) {
+                                problems.warn(MatroskaError::DuplicateElement {
+                                    id,
+                                    offset: start - len,
+                                });
+                            }
+                        }
+                        Err(err) => {
+                            problems.recover(err)?;
+                            file.seek(io::SeekFrom::Start(start.saturating_add(size_1)))?;
+                        }
+                    }
                 }
-                ids::CHAPTERS => {
-                    matroska.chapters = ChapterEdition::parse(&mut file, size_1)?;
+                _ => {
+                    size_1 = skip_element(file, size_1)?;
                 }
-                ids::TAGS => {
-                    matroska.tags = Tag::parse(&mut file, size_1)?;
+            }
+            // an element running past the Segment's end finishes it
+            size_0 = match size_0.checked_sub(len + size_1) {
+                Some(size) => size,
+                None => break,
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Populates sections from the positions in the given SeekHead,
+    /// adding the positions it reads to `seen`
+    ///
+    /// Given the file's path, sections are parsed in batches
+    /// of [`ParseOptions::threads`], each from its own handle,
+    /// and added in the same order as they would be one at a time.
+    fn populate_from<R: io::Read + io::Seek>(
+        &mut self,
+        file: &mut R,
+        seektable: &SeekHead,
+        options: &ParseOptions,
+        problems: &mut Problems,
+        seen: &mut BTreeSet<u64>,
+        path: Option<&std::path::Path>,
+    ) -> Result<()> {
+        let mut jobs = Vec::new();
+        for id in Section::IDS {
+            if !options.parses(id) {
+                continue;
+            }
+            match seektable.offsets(id) {
+                Ok(positions) => jobs.extend(positions.into_iter().map(|pos| (id, pos))),
+                Err(err) => problems.recover(err)?,
+            }
+        }
+
+        let batch_len = match path {
+            Some(_) => options.threads.max(1),
+            None => 1,
+        };
+        for batch in jobs.chunks(batch_len) {
+            let batch: Vec<_> = batch
+                .iter()
+                .filter(|(_, pos)| !seen.contains(pos))
+                .copied()
+                .collect();
+            let results = match path {
+                Some(path) => Section::parse_parallel(path, file, &batch, options),
+                None => batch
+                    .iter()
+                    .map(|&(id, pos)| Section::parse_at(file, id, pos, options))
+                    .collect(),
+            };
+            for ((id, pos), result) in batch.into_iter().zip(results) {
+                // an earlier section in the batch may have been read from here
+                if seen.contains(&pos) {
+                    continue;
                 }
-                _ => {
-                    file.seek(SeekFrom::Current(size_1 as i64)).map(|_| ())?;
+                match result {
+                    Ok(section) => {
+                        if !self.insert(section) {
+                            problems.warn(MatroskaError::DuplicateElement { id, offset: pos });
+                        }
+                    }
+                    // a section the SeekHead misplaced is left to the scan
+                    Err(MatroskaError::UnexpectedElement { .. }) => continue,
+                    Err(err) => problems.recover(err)?,
                 }
+                seen.insert(pos);
             }
-            size_0 -= len;
-            size_0 -= size_1;
         }
+        Ok(())
+    }
 
-        Ok(matroska)
+    /// Adds a parsed section, with repeated Attachments,
+    /// Chapters and Tags sections adding to those before them
+    ///
+    /// Of the sections which may occur only once, the first is kept,
+    /// except that an Info lacking its mandatory muxing and writing
+    /// applications gives way to a later complete one.
+    /// Returns `false` if the section was discarded as a duplicate,
+    /// but not if it replaced a placeholder or incomplete Info.
+    fn insert(&mut self, section: Section) -> bool {
+        match section {
+            Section::Info(info) => {
+                let complete =
+                    |info: &Info| !info.muxing_app.is_empty() && !info.writing_app.is_empty();
+                if self.info.is_placeholder() || (!complete(&self.info) && complete(&info)) {
+                    self.info = info;
+                } else {
+                    return false;
+                }
+            }
+            Section::Tracks(tracks) if self.tracks.is_empty() => self.tracks = tracks,
+            Section::Attachments(attachments) => self.attachments.extend(attachments),
+            Section::Chapters(chapters) => self.chapters.extend(chapters),
+            Section::Tags(tags) => self.tags.extend(tags),
+            Section::Cues(cues) if self.cues.is_none() => self.cues = Some(cues),
+            Section::Tracks(_) | Section::Cues(_) => return false,
+        }
+        true
     }
 
     /// Returns a single item from the Matroska file such as Info
@@ -162,6 +376,22 @@ impl Matroska {
         get::<R, P>(file)
     }
 
+    /// Returns `true` if the file's EBML header declares it as WebM
+    #[inline]
+    pub fn is_webm(&self) -> bool {
+        self.ebml_header
+            .as_ref()
+            .map(EbmlHeader::is_webm)
+            .unwrap_or(false)
+    }
+
+    /// Returns an index for seeking built from the file's Cues, if any
+    pub fn seek_index(&self) -> Option<SeekIndex> {
+        self.cues
+            .as_ref()
+            .map(|cues| SeekIndex::from_cues(cues, &self.info, self.segment_start))
+    }
+
     /// Returns all tracks with a type of "video"
     pub fn video_tracks(&self) -> impl Iterator<Item = &Track> {
         self.tracks.iter().filter(|t| t.is_video())
@@ -176,86 +406,625 @@ impl Matroska {
     pub fn subtitle_tracks(&self) -> impl Iterator<Item = &Track> {
         self.tracks.iter().filter(|t| t.is_subtitle())
     }
+
+    /// Returns the track with the given number, as used by blocks
+    pub fn track_by_number(&self, number: u64) -> Option<&Track> {
+        self.tracks.iter().find(|t| t.number == number)
+    }
+
+    /// Returns the track with the given UID, as used by tags
+    ///
+    /// A UID of 0 is considered unset and never matches.
+    pub fn track_by_uid(&self, uid: u64) -> Option<&Track> {
+        if uid == 0 {
+            return None;
+        }
+        self.tracks.iter().find(|t| t.uid == uid)
+    }
+
+    /// Returns the tracks in the given language
+    ///
+    /// The language may be an ISO 639 code such as `"eng"`
+    /// or an IETF tag such as `"en"` or `"en-US"`,
+    /// and matches as for [`Language::matches`].
+    /// Tracks whose language is undetermined or missing
+    /// are only matched by `"und"`.
+    pub fn tracks_by_language<'a>(
+        &'a self,
+        language: &'a str,
+    ) -> impl Iterator<Item = &'a Track> + 'a {
+        self.tracks.iter().filter(move |t| match &t.language {
+            Some(l) => l.matches(language),
+            None => language.eq_ignore_ascii_case("und"),
+        })
+    }
+
+    /// Returns the chapter with the given UID, along with its edition
+    ///
+    /// Nested chapters are searched as well.
+    /// A UID of 0 is considered unset and never matches.
+    pub fn chapter_by_uid(&self, uid: u64) -> Option<(&ChapterEdition, &Chapter)> {
+        if uid == 0 {
+            return None;
+        }
+        self.chapters.iter().find_map(|edition| {
+            edition
+                .iter_all()
+                .find(|c| c.uid == uid)
+                .map(|c| (edition, c))
+        })
+    }
+
+    /// Returns the chapter with the given textual UID, along with its edition
+    ///
+    /// Nested chapters are searched as well.
+    pub fn chapter_by_string_uid(&self, string_uid: &str) -> Option<(&ChapterEdition, &Chapter)> {
+        self.chapters.iter().find_map(|edition| {
+            edition
+                .iter_all()
+                .find(|c| c.string_uid.as_deref() == Some(string_uid))
+                .map(|c| (edition, c))
+        })
+    }
+
+    /// Returns the attachment with the given UID
+    ///
+    /// A UID of 0 is considered unset and never matches.
+    pub fn attachment_by_uid(&self, uid: u64) -> Option<&Attachment> {
+        if uid == 0 {
+            return None;
+        }
+        self.attachments.iter().find(|a| a.uid == uid)
+    }
+
+    /// Returns the attachments whose MIME type matches the given pattern
+    ///
+    /// The pattern is either a whole type, such as `"application/xml"`,
+    /// or a type followed by a wildcard, such as `"image/*"`.
+    /// Matching ignores ASCII case and any parameters after a `;`.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// let matroska = matroska::open("file.mkv").unwrap();
+    /// for image in matroska.attachments_by_mime("image/*") {
+    ///     println!("{}", image.name);
+    /// }
+    /// ```
+    pub fn attachments_by_mime<'a>(
+        &'a self,
+        pattern: &'a str,
+    ) -> impl Iterator<Item = &'a Attachment> + 'a {
+        self.attachments
+            .iter()
+            .filter(move |a| mime_matches(&a.mime_type, pattern))
+    }
+
+    /// Returns the attachments which are fonts,
+    /// such as those needed to render SSA/ASS subtitles
+    ///
+    /// Along with `font/*` types, this matches the
+    /// older types fonts are often attached with,
+    /// such as `application/x-truetype-font`.
+    pub fn font_attachments(&self) -> impl Iterator<Item = &Attachment> {
+        self.attachments.iter().filter(|a| {
+            FONT_MIME_TYPES
+                .iter()
+                .any(|pattern| mime_matches(&a.mime_type, pattern))
+        })
+    }
+
+    /// Returns the chapter edition with the given UID
+    ///
+    /// A UID of 0 is considered unset and never matches.
+    pub fn edition_by_uid(&self, uid: u64) -> Option<&ChapterEdition> {
+        if uid == 0 {
+            return None;
+        }
+        self.chapters.iter().find(|e| e.uid == Some(uid))
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Seektable {
-    offset: u64, // The file offset of the Seektable
-    seek: BTreeMap<u32, u64>,
+/// The MIME types fonts are attached with, including the non-standard ones
+const FONT_MIME_TYPES: [&str; 7] = [
+    "font/*",
+    "application/x-truetype-font",
+    "application/x-font-ttf",
+    "application/x-font-otf",
+    "application/vnd.ms-opentype",
+    "application/font-sfnt",
+    "application/font-woff",
+];
+
+/// Whether a MIME type matches a whole type or one ending in `/*`,
+/// ignoring ASCII case and parameters
+fn mime_matches(mime_type: &str, pattern: &str) -> bool {
+    let mime_type = mime_type.split(';').next().unwrap_or_default().trim();
+    match pattern.strip_suffix('*') {
+        Some(prefix) => mime_type
+            .get(..prefix.len())
+            .map(|start| start.eq_ignore_ascii_case(prefix))
+            .unwrap_or(false),
+        None => mime_type.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// Where errors go while populating a `Matroska`
+struct Problems {
+    lossy: bool,
+    errors: Vec<MatroskaError>,
 }
 
-impl Seektable {
-    fn new(offset: u64) -> Seektable {
-        Seektable {
-            offset,
-            seek: BTreeMap::new(),
+impl Problems {
+    fn strict() -> Self {
+        Problems {
+            lossy: false,
+            errors: Vec::new(),
         }
     }
 
-    #[inline]
-    fn get(&self, id: u32) -> Result<Option<u64>> {
-        if let Some(position) = self.seek.get(&id) {
-            if let Some(offset) = self.offset.checked_add(*position) {
-                Ok(Some(offset))
-            } else {
-                Err(MatroskaError::InvalidSeekHead { id })
+    fn lossy() -> Self {
+        Problems {
+            lossy: true,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Records an error if lossy, or returns it otherwise
+    fn recover(&mut self, err: MatroskaError) -> Result<()> {
+        if self.lossy {
+            self.errors.push(err);
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+
+    /// Records an error which was worked around, if lossy
+    fn warn(&mut self, err: MatroskaError) {
+        if self.lossy {
+            self.errors.push(err);
+        }
+    }
+}
+
+/// One of the top-level sections which make up a `Matroska`
+#[allow(clippy::large_enum_variant)]
+enum Section {
+    Info(Info),
+    Tracks(Vec<Track>),
+    Attachments(Vec<Attachment>),
+    Chapters(Vec<ChapterEdition>),
+    Tags(Vec<Tag>),
+    Cues(Cues),
+}
+
+impl Section {
+    /// The element IDs of all sections, in the order they're populated
+    const IDS: [u32; 6] = [
+        ids::INFO,
+        ids::TRACKS,
+        ids::ATTACHMENTS,
+        ids::CHAPTERS,
+        ids::TAGS,
+        ids::CUES,
+    ];
+
+    /// Parses the body of the section with the given ID
+    fn parse<R: io::Read + io::Seek>(
+        r: &mut R,
+        id: u32,
+        size: u64,
+        options: &ParseOptions,
+    ) -> Result<Section> {
+        if options.verify_crc {
+            verify_crc32(r, id, size)?;
+        }
+        parse_located(r, id, |r| Section::parse_body(r, id, size, options))
+    }
+
+    fn parse_body<R: io::Read + io::Seek>(
+        r: &mut R,
+        id: u32,
+        size: u64,
+        options: &ParseOptions,
+    ) -> Result<Section> {
+        match id {
+            ids::INFO => Info::parse_with(r, size, options).map(Section::Info),
+            ids::TRACKS if options.defer_codec_private => {
+                Track::parse_deferred(r, size, &options.limits())
+                    .and_then(|tracks| Track::check(tracks, options))
+                    .map(Section::Tracks)
             }
+            ids::TRACKS => Track::parse_with(r, size, options).map(Section::Tracks),
+            ids::ATTACHMENTS => {
+                Attachment::parse_tracked(r, size, options).map(Section::Attachments)
+            }
+            ids::CHAPTERS => ChapterEdition::parse_with(r, size, options).map(Section::Chapters),
+            ids::TAGS => Tag::parse_with(r, size, options).map(Section::Tags),
+            ids::CUES => Cues::parse_with(r, size, options).map(Section::Cues),
+            _ => unreachable!("not a section ID"),
+        }
+    }
+
+    /// Parses the section with the given ID at the given absolute position
+    fn parse_at<R: io::Read + io::Seek>(
+        r: &mut R,
+        id: u32,
+        pos: u64,
+        options: &ParseOptions,
+    ) -> Result<Section> {
+        let size = read_element_at(r, id, pos)?;
+        Section::parse(r, id, size, options)
+    }
+
+    /// Parses the sections with the given IDs and positions at once,
+    /// each on its own thread from its own handle on the file at `path`
+    ///
+    /// A section whose handle couldn't be opened is parsed from `r`.
+    fn parse_parallel<R: io::Read + io::Seek>(
+        path: &std::path::Path,
+        r: &mut R,
+        jobs: &[(u32, u64)],
+        options: &ParseOptions,
+    ) -> Vec<Result<Section>> {
+        use std::fs::File;
+        use std::io::BufReader;
+
+        let results: Vec<Option<Result<Section>>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = jobs
+                .iter()
+                .map(|&(id, pos)| {
+                    scope.spawn(move || {
+                        File::open(path)
+                            .ok()
+                            .map(|f| Section::parse_at(&mut BufReader::new(f), id, pos, options))
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect()
+        });
+        jobs.iter()
+            .zip(results)
+            .map(|(&(id, pos), result)| {
+                result.unwrap_or_else(|| Section::parse_at(r, id, pos, options))
+            })
+            .collect()
+    }
+}
+
+/// Parses the body of the element with the given ID,
+/// locating any error by where reading had reached
+fn parse_located<R, T, F>(r: &mut R, id: u32, f: F) -> Result<T>
+where
+    R: io::Read + io::Seek,
+    F: FnOnce(&mut ebml::Tracked<R>) -> Result<T>,
+{
+    let mut tracked = ebml::Tracked::new(r)?;
+    f(&mut tracked).map_err(|err| err.located(tracked.position(), Some(id)))
+}
+
+/// Checks the element body starting at the current position
+/// against its leading CRC-32 element, if it has one
+///
+/// The reader is left back at the start of the body either way.
+fn verify_crc32<R: io::Read + io::Seek>(r: &mut R, id: u32, size: u64) -> Result<()> {
+    let start = r.stream_position()?;
+    if size > 0 {
+        if let (ids::CRC32, 4, header_len) = ebml::read_element_id_size(r)? {
+            let mut stored = [0; 4];
+            r.read_exact(&mut stored)?;
+            let covered = ebml::remaining_after(size, ids::CRC32, 4, header_len)?;
+            let mut crc = ebml::Crc32Reader::new(io::Read::take(&mut *r, covered));
+            if io::copy(&mut crc, &mut io::sink())? < covered {
+                return Err(MatroskaError::Io(io::ErrorKind::UnexpectedEof.into()));
+            }
+            if crc.checksum() != u32::from_le_bytes(stored) {
+                return Err(MatroskaError::ChecksumMismatch {
+                    element_id: id,
+                    offset: start,
+                });
+            }
+        }
+    }
+    r.seek(io::SeekFrom::Start(start))?;
+    Ok(())
+}
+
+/// An entry's children, and the offset and size of its data child's body
+type DeferredEntry = (Vec<Element>, Option<(u64, u64)>);
+
+/// Parses each child with the given entry ID of an element
+/// starting at the reader's position, seeking past its other children
+///
+/// Each entry's other children are returned along with the file offset
+/// and size of the body of its child with the given data ID,
+/// which is seeked past rather than read if `defer` is set.
+/// CRC-32 and Void elements within entries are always seeked past.
+fn parse_entries_deferring<R: io::Read + io::Seek>(
+    r: &mut R,
+    mut size: u64,
+    entry_id: u32,
+    data_id: u32,
+    defer: bool,
+    limits: &Limits,
+) -> Result<Vec<DeferredEntry>> {
+    let mut entries = Vec::new();
+    while size > 0 {
+        let (id, entry_size, len) = ebml::read_element_id_size(r)?;
+        if id == entry_id {
+            let mut elements = Vec::new();
+            let mut range = None;
+            let mut remaining = entry_size;
+            while remaining > 0 {
+                let (id, size, len) = ebml::read_element_id_size(r)?;
+                remaining = ebml::remaining_after(remaining, id, size, len)?;
+                if id == data_id {
+                    range = Some((r.stream_position()?, size));
+                }
+                if (id == data_id && defer) || ebml::is_skipped(id) {
+                    r.seek(io::SeekFrom::Current(size as i64))?;
+                } else {
+                    let val = Element::parse_body(r, id, size, Some(entry_id), limits)?;
+                    elements.push(Element {
+                        id,
+                        size: len + size,
+                        val,
+                    });
+                }
+            }
+            entries.push((elements, range));
         } else {
-            Ok(None)
+            skip_element(r, entry_size)?;
+        }
+        size = ebml::remaining_after(size, id, entry_size, len)?;
+    }
+    Ok(entries)
+}
+
+/// Seeks to the given position and reads the header of
+/// the element expected there, returning its size
+fn read_element_at<R: io::Read + io::Seek>(r: &mut R, expected: u32, pos: u64) -> Result<u64> {
+    r.seek(io::SeekFrom::Start(pos))?;
+    match ebml::read_element_id_size(r)? {
+        (id, size, _) if id == expected => Ok(size),
+        (found, _, _) => Err(MatroskaError::UnexpectedElement {
+            expected,
+            found,
+            offset: pos,
+        }),
+    }
+}
+
+/// A SeekHead, listing where the Segment's top-level elements are
+///
+/// This lets a reader find sections such as Chapters
+/// without walking the whole Segment.
+///
+/// ## Example
+/// ```no_run
+/// let file = std::fs::File::open("file.mkv").unwrap();
+/// if let Some(seek_head) = matroska::SeekHead::read(file).unwrap() {
+///     println!("has chapters : {}", seek_head.contains(matroska::ids::CHAPTERS));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SeekHead {
+    /// The file offset of the Segment's contents,
+    /// which entries' positions are relative to
+    pub segment_start: u64,
+    /// The listed elements, in the order they're listed
+    pub entries: Vec<SeekEntry>,
+}
+
+impl SeekHead {
+    fn new(segment_start: u64) -> SeekHead {
+        SeekHead {
+            segment_start,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Reads the SeekHead from an open Matroska file
+    ///
+    /// Any chained SeekHeads are followed and their entries
+    /// added to this one's, with repeated entries listed once.
+    /// Returns `Ok(None)` if the file has no SeekHead.
+    pub fn read<R: io::Read + io::Seek>(mut file: R) -> Result<Option<SeekHead>> {
+        let mut size_0 = match find_segment(&mut file) {
+            Ok(size) => size,
+            Err(MatroskaError::EmptySegment) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let segment_start = file.stream_position()?;
+
+        while size_0 > 0 {
+            let (id_1, mut size_1, len) = match read_top_level_header(&mut file)? {
+                Some(header) => header,
+                None => break,
+            };
+            if id_1 == ids::SEEKHEAD {
+                return parse_located(&mut file, ids::SEEKHEAD, |r| {
+                    SeekHead::parse_chained(r, segment_start, size_1)
+                })
+                .map(Some);
+            }
+            size_1 = skip_element(&mut file, size_1)?;
+            // an element running past the Segment's end finishes it
+            size_0 = match size_0.checked_sub(len + size_1) {
+                Some(size) => size,
+                None => break,
+            };
+        }
+        Ok(None)
+    }
+
+    /// Returns `true` if an element with the given ID is listed
+    pub fn contains(&self, id: u32) -> bool {
+        self.entries.iter().any(|e| e.id == id)
+    }
+
+    /// Returns the file offsets of the elements with the given ID,
+    /// in the order they're listed
+    ///
+    /// A position too large to be an offset is
+    /// [`MatroskaError::InvalidSeekHead`].
+    pub fn offsets(&self, id: u32) -> Result<Vec<u64>> {
+        self.entries
+            .iter()
+            .filter(|e| e.id == id)
+            .map(|e| {
+                self.segment_start
+                    .checked_add(e.position)
+                    .ok_or(MatroskaError::InvalidSeekHead { id })
+            })
+            .collect()
+    }
+
+    /// Checks that every listed position falls within a file
+    /// of the given length, as some muxers leave placeholders
+    /// such as -1 which are never filled in
+    fn check(&self, len: u64) -> Result<()> {
+        for entry in &self.entries {
+            match self.segment_start.checked_add(entry.position) {
+                Some(offset) if offset < len => {}
+                _ => return Err(MatroskaError::InvalidSeekHead { id: entry.id }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a SeekHead's entries, other than those for chained SeekHeads,
+    /// returning the positions of those chained SeekHeads
+    fn add_entries(&mut self, elements: Vec<Element>) -> Vec<u64> {
+        let mut chained = Vec::new();
+        for e in elements {
+            if let Element {
+                id: ids::SEEK,
+                val: ElementType::Master(sub_elements),
+                ..
+            } = e
+            {
+                let entry = SeekEntry::build(sub_elements);
+                if entry.id == ids::SEEKHEAD {
+                    chained.push(entry.position);
+                } else if !self.entries.contains(&entry) {
+                    self.entries.push(entry);
+                }
+            }
         }
+        chained
+    }
+
+    /// Parses a SeekHead body's Seek elements,
+    /// passing over anything else with `skip`
+    /// so that stray payloads aren't read into memory
+    fn parse_seeks<R, S>(r: &mut R, size: u64, skip: &mut S) -> Result<Vec<Element>>
+    where
+        R: io::Read,
+        S: FnMut(&mut R, u64) -> Result<()>,
+    {
+        Element::parse_master_only(
+            r,
+            size,
+            Some(ids::SEGMENT),
+            &[ids::SEEK, ids::SEEKID, ids::SEEKPOSITION],
+            &Limits::default(),
+            skip,
+        )
     }
 
-    fn parse<R>(r: &mut R, segment_start: u64, mut size: u64) -> Result<Seektable>
+    fn parse_chained<R>(r: &mut R, segment_start: u64, mut size: u64) -> Result<SeekHead>
     where
         R: io::Read + io::Seek,
     {
-        let mut seektable = Seektable::new(segment_start);
+        let mut seek_head = SeekHead::new(segment_start);
+        let mut next_tables = std::collections::VecDeque::new();
+        let mut visited = BTreeSet::new();
         loop {
-            for e in Element::parse_master(r, size, Some(ids::SEGMENT))? {
-                if let Element {
-                    id: ids::SEEK,
-                    val: ElementType::Master(sub_elements),
-                    ..
-                } = e
-                {
-                    let seek = Seek::build(sub_elements);
-                    seektable.seek.insert(seek.id(), seek.position);
+            let elements = SeekHead::parse_seeks(r, size, &mut |r, size| {
+                r.seek(io::SeekFrom::Current(size as i64))?;
+                Ok(())
+            })?;
+            // chained seektables are each read once
+            for position in seek_head.add_entries(elements) {
+                if visited.insert(position) {
+                    next_tables.push_back(position);
                 }
             }
 
-            match seektable.seek.remove(&ids::SEEKHEAD) {
+            match next_tables.pop_front() {
                 Some(next_table) => {
-                    r.seek(io::SeekFrom::Start(next_table + segment_start))?;
-                    let (id, new_size, _) = ebml::read_element_id_size(r)?;
-                    assert!(id == ids::SEEKHEAD);
-                    size = new_size;
+                    let pos = next_table
+                        .checked_add(segment_start)
+                        .ok_or(MatroskaError::InvalidSeekHead { id: ids::SEEKHEAD })?;
+                    size = read_element_at(r, ids::SEEKHEAD, pos)?;
                 }
-                None => break Ok(seektable),
+                None => break Ok(seek_head),
+            }
+        }
+    }
+}
+
+/// Parses a single SeekHead element on its own
+///
+/// Since the Segment's position isn't known,
+/// `segment_start` is left at 0 and chained SeekHeads
+/// are listed among the entries rather than followed.
+/// Use [`SeekHead::read`] to do both.
+impl Parseable for SeekHead {
+    type Output = SeekHead;
+
+    const ID: u32 = ids::SEEKHEAD;
+
+    fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<SeekHead> {
+        let mut seek_head = SeekHead::new(0);
+        let elements = SeekHead::parse_seeks(r, size, &mut |r, size| ebml::discard(r, size))?;
+        for position in seek_head.add_entries(elements) {
+            let entry = SeekEntry {
+                id: ids::SEEKHEAD,
+                position,
+            };
+            if !seek_head.entries.contains(&entry) {
+                seek_head.entries.push(entry);
             }
         }
+        Ok(seek_head)
     }
 }
 
+/// An entry in a SeekHead
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Seek {
-    id: Vec<u8>,
-    position: u64,
+#[non_exhaustive]
+pub struct SeekEntry {
+    /// The ID of the element listed
+    pub id: u32,
+    /// The element's position, relative to [`SeekHead::segment_start`]
+    pub position: u64,
 }
 
-impl Seek {
-    fn new() -> Seek {
-        Seek {
-            id: Vec::new(),
-            position: 0,
-        }
+impl SeekEntry {
+    fn new() -> SeekEntry {
+        SeekEntry { id: 0, position: 0 }
     }
 
-    fn id(&self) -> u32 {
-        self.id.iter().fold(0, |acc, i| (acc << 8) | u32::from(*i))
+    fn encode(&self) -> Vec<u8> {
+        let id = self.id.to_be_bytes();
+        let skip = (self.id.leading_zeros() / 8).min(3) as usize;
+        ebml::master_element(
+            ids::SEEK,
+            vec![
+                ebml::element(ids::SEEKID, &id[skip..]),
+                ebml::uint_element(ids::SEEKPOSITION, self.position),
+            ],
+        )
     }
 
-    fn build(elements: Vec<Element>) -> Seek {
-        let mut seek = Seek::new();
+    fn build(elements: Vec<Element>) -> SeekEntry {
+        let mut seek = SeekEntry::new();
         for e in elements {
             match e {
                 Element {
@@ -263,7 +1032,7 @@ impl Seek {
                     val: ElementType::Binary(id),
                     ..
                 } => {
-                    seek.id = id;
+                    seek.id = id.iter().fold(0, |acc, i| (acc << 8) | u32::from(*i));
                 }
                 Element {
                     id: ids::SEEKPOSITION,
@@ -289,10 +1058,84 @@ pub trait Parseable {
 
     /// Performs the actual parsing
     fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<Self::Output>;
+
+    /// Performs the actual parsing using the given options
+    ///
+    /// By default, the options are ignored.
+    fn parse_with<R: io::Read>(
+        r: &mut R,
+        size: u64,
+        options: &ParseOptions,
+    ) -> Result<Self::Output> {
+        let _ = options;
+        Self::parse(r, size)
+    }
+}
+
+/// A tuple of [`Parseable`] elements to retrieve together with [`get_many`]
+///
+/// This is implemented for tuples of up to six elements,
+/// such as `(Info, Track)`, whose output is a tuple of
+/// each element's output, or `None` if it isn't present.
+pub trait ParseableSet {
+    /// A tuple of optional outputs, one for each element
+    type Output: Default;
+
+    /// The element IDs wanted, in tuple order
+    fn ids() -> Vec<u32>;
+
+    /// Parses the element at the given tuple index into the output
+    fn parse_index<R: io::Read>(
+        output: &mut Self::Output,
+        index: usize,
+        r: &mut R,
+        size: u64,
+    ) -> Result<()>;
+}
+
+macro_rules! parseable_set {
+    ($($p:ident $i:tt),+) => {
+        impl<$($p: Parseable),+> ParseableSet for ($($p,)+) {
+            type Output = ($(Option<$p::Output>,)+);
+
+            fn ids() -> Vec<u32> {
+                vec![$($p::ID),+]
+            }
+
+            fn parse_index<R: io::Read>(
+                output: &mut Self::Output,
+                index: usize,
+                r: &mut R,
+                size: u64,
+            ) -> Result<()> {
+                match index {
+                    $($i => output.$i = Some($p::parse(r, size)?),)+
+                    _ => unreachable!("not a tuple index"),
+                }
+                Ok(())
+            }
+        }
+    };
 }
 
+parseable_set!(A 0);
+parseable_set!(A 0, B 1);
+parseable_set!(A 0, B 1, C 2);
+parseable_set!(A 0, B 1, C 2, D 3);
+parseable_set!(A 0, B 1, C 2, D 3, E 4);
+parseable_set!(A 0, B 1, C 2, D 3, E 4, F 5);
+
 /// An Info segment with information pertaining to the entire file
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// Files lacking an Info element are given a placeholder
+/// whose values are all defaults, including the default
+/// timestamp scale of 1,000,000 nanoseconds.
+/// Use [`Info::is_placeholder`] to tell the two apart.
+///
+/// Infos are `Eq` and `Hash` by comparing [`Info::duration_raw`]
+/// bit for bit, so a NaN duration equals itself.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct Info {
     /// The file's UID
     pub uid: Option<Vec<u8>>,
@@ -300,18 +1143,44 @@ pub struct Info {
     pub prev_uid: Option<Vec<u8>>,
     /// Unique ID of the next segment
     pub next_uid: Option<Vec<u8>>,
+    /// The segment's file name
+    pub filename: Option<String>,
+    /// File name of the previous segment
+    pub prev_filename: Option<String>,
+    /// File name of the next segment
+    pub next_filename: Option<String>,
     /// Unique IDs of the families this segment belongs to
     pub family_uids: Vec<Vec<u8>>,
     /// The file's title
     pub title: Option<String>,
     /// The file's duration
+    ///
+    /// This is `None` if the stored Duration is negative, NaN or infinite.
     pub duration: Option<Duration>,
+    /// The file's duration as stored, in timestamp-scale units
+    ///
+    /// Unlike [`Info::duration`], this keeps any fraction of a nanosecond.
+    pub duration_raw: Option<f64>,
     /// Production date
     pub date_utc: Option<DateTime>,
     /// The muxing application or library
     pub muxing_app: String,
     /// The writing application
     pub writing_app: String,
+    /// Nanoseconds per timestamp tick, used by Cluster and Cue timestamps
+    pub timestamp_scale: u64,
+    /// Defects in the Info's values which were worked around
+    pub problems: Vec<InfoProblem>,
+    placeholder: bool,
+}
+
+/// The timestamp scale to use when a file doesn't specify one
+pub const DEFAULT_TIMESTAMP_SCALE: u64 = 1_000_000;
+
+impl Default for Info {
+    fn default() -> Self {
+        Info::new()
+    }
 }
 
 impl Info {
@@ -320,14 +1189,85 @@ impl Info {
             uid: None,
             prev_uid: None,
             next_uid: None,
+            filename: None,
+            prev_filename: None,
+            next_filename: None,
             family_uids: Vec::new(),
             title: None,
             duration: None,
+            duration_raw: None,
             date_utc: None,
             muxing_app: String::new(),
             writing_app: String::new(),
+            timestamp_scale: DEFAULT_TIMESTAMP_SCALE,
+            problems: Vec::new(),
+            placeholder: true,
         }
     }
+
+    /// Returns `true` if the file had no Info element
+    /// and this value is populated entirely by defaults
+    #[inline]
+    pub fn is_placeholder(&self) -> bool {
+        self.placeholder
+    }
+
+    /// Converts a raw timestamp in timestamp-scale units to a `Duration`
+    #[inline]
+    pub fn timestamp(&self, ticks: u64) -> Duration {
+        Duration::from_nanos(ticks.saturating_mul(self.timestamp_scale))
+    }
+
+    /// Returns the file's duration in seconds,
+    /// computed from the stored value without rounding to nanoseconds
+    pub fn duration_seconds(&self) -> Option<f64> {
+        self.duration_raw
+            .map(|d| d * self.timestamp_scale as f64 / 1_000_000_000.0)
+    }
+}
+
+impl PartialEq for Info {
+    fn eq(&self, other: &Self) -> bool {
+        self.uid == other.uid
+            && self.prev_uid == other.prev_uid
+            && self.next_uid == other.next_uid
+            && self.filename == other.filename
+            && self.prev_filename == other.prev_filename
+            && self.next_filename == other.next_filename
+            && self.family_uids == other.family_uids
+            && self.title == other.title
+            && self.duration == other.duration
+            && self.duration_raw.map(f64::to_bits) == other.duration_raw.map(f64::to_bits)
+            && self.date_utc == other.date_utc
+            && self.muxing_app == other.muxing_app
+            && self.writing_app == other.writing_app
+            && self.timestamp_scale == other.timestamp_scale
+            && self.problems == other.problems
+            && self.placeholder == other.placeholder
+    }
+}
+
+impl Eq for Info {}
+
+impl std::hash::Hash for Info {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.uid.hash(state);
+        self.prev_uid.hash(state);
+        self.next_uid.hash(state);
+        self.filename.hash(state);
+        self.prev_filename.hash(state);
+        self.next_filename.hash(state);
+        self.family_uids.hash(state);
+        self.title.hash(state);
+        self.duration.hash(state);
+        self.duration_raw.map(f64::to_bits).hash(state);
+        self.date_utc.hash(state);
+        self.muxing_app.hash(state);
+        self.writing_app.hash(state);
+        self.timestamp_scale.hash(state);
+        self.problems.hash(state);
+        self.placeholder.hash(state);
+    }
 }
 
 impl Parseable for Info {
@@ -336,92 +1276,17 @@ impl Parseable for Info {
     const ID: u32 = ids::INFO;
 
     fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<Info> {
-        let mut info = Info::new();
-        let mut timecode_scale = 1000000;
-        let mut duration = None;
+        Info::parse_limited(r, size, &Limits::default())
+    }
 
-        for e in Element::parse_master(r, size, Some(ids::INFO))? {
-            match e {
-                Element {
-                    id: ids::SEGMENTUID,
-                    val: ElementType::Binary(uid),
-                    ..
-                } => {
-                    info.uid = Some(uid);
-                }
-                Element {
-                    id: ids::PREVUID,
-                    val: ElementType::Binary(uid),
-                    ..
-                } => {
-                    info.prev_uid = Some(uid);
-                }
-                Element {
-                    id: ids::NEXTUID,
-                    val: ElementType::Binary(uid),
-                    ..
-                } => {
-                    info.next_uid = Some(uid);
-                }
-                Element {
-                    id: ids::SEGMENTFAMILY,
-                    val: ElementType::Binary(uid),
-                    ..
-                } => {
-                    info.family_uids.push(uid);
-                }
-                Element {
-                    id: ids::TITLE,
-                    val: ElementType::UTF8(title),
-                    ..
-                } => {
-                    info.title = Some(title);
-                }
-                Element {
-                    id: ids::TIMECODESCALE,
-                    val: ElementType::UInt(scale),
-                    ..
-                } => {
-                    timecode_scale = scale;
-                }
-                Element {
-                    id: ids::DURATION,
-                    val: ElementType::Float(d),
-                    ..
-                } => duration = Some(d),
-                Element {
-                    id: ids::DATEUTC,
-                    val: ElementType::Date(date),
-                    ..
-                } => info.date_utc = Some(date),
-                Element {
-                    id: ids::MUXINGAPP,
-                    val: ElementType::UTF8(app),
-                    ..
-                } => {
-                    info.muxing_app = app;
-                }
-                Element {
-                    id: ids::WRITINGAPP,
-                    val: ElementType::UTF8(app),
-                    ..
-                } => {
-                    info.writing_app = app;
-                }
-                _ => {}
-            }
-        }
-
-        if let Some(d) = duration {
-            info.duration = Some(Duration::from_nanos((d * timecode_scale as f64) as u64))
-        }
-
-        Ok(info)
+    fn parse_with<R: io::Read>(r: &mut R, size: u64, options: &ParseOptions) -> Result<Info> {
+        Info::parse_limited(r, size, &options.limits()).and_then(|info| info.check(options))
     }
 }
 
 /// A TrackEntry segment in the Tracks segment container
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct Track {
     /// The track number, starting from 1
     pub number: u64,
@@ -459,9 +1324,26 @@ pub struct Track {
     /// If the track contains blocks using lacing
     pub interlaced: bool,
 
+    /// The minimum number of frames a player should be able to cache
+    pub min_cache: Option<u64>,
+
+    /// The maximum number of frames a player needs to cache
+    pub max_cache: Option<u64>,
+
+    /// The maximum BlockAddID of any BlockAdditions in the track
+    pub max_block_addition_id: Option<u64>,
+
     /// Duration of each frame
     pub default_duration: Option<Duration>,
 
+    /// Built-in delay the codec adds to decoded samples,
+    /// such as Opus's priming samples
+    pub codec_delay: Option<Duration>,
+
+    /// How much data must be decoded before a seek point
+    /// for playback to be correct from it
+    pub seek_pre_roll: Option<Duration>,
+
     /// A human-readable track name
     pub name: Option<String>,
 
@@ -472,13 +1354,233 @@ pub struct Track {
     pub codec_id: String,
 
     /// Private data known only to the codec
+    ///
+    /// This is `None` if [`ParseOptions::defer_codec_private`] is set,
+    /// in which case it's read by [`Track::read_codec_private`].
     pub codec_private: Option<Vec<u8>>,
 
+    /// The file offset and size of deferred codec private data
+    codec_private_range: Option<(u64, u64)>,
+
     /// The track's codec's human-readable name
     pub codec_name: Option<String>,
 
+    /// The codec's settings, a deprecated legacy field
+    pub codec_settings: Option<String>,
+
+    /// A URL with information about the codec, a deprecated legacy field
+    pub codec_info_url: Option<String>,
+
+    /// A URL to download the codec from, a deprecated legacy field
+    pub codec_download_url: Option<String>,
+
+    /// If the codec can decode damaged data, a deprecated legacy field
+    pub codec_decode_all: Option<bool>,
+
+    /// The UIDs of attachments used by the track,
+    /// resolved with [`Matroska::attachment_by_uid`]
+    pub attachment_links: Vec<u64>,
+
     /// The track's audio or video settings
     pub settings: Settings,
+
+    /// How other tracks are combined to form this virtual track
+    pub operation: Option<TrackOperation>,
+
+    /// Mappings of this track to chapter codec track identifiers
+    pub translate: Vec<TrackTranslate>,
+
+    /// Compression or encryption applied to the track's data,
+    /// in the order it was applied
+    pub encodings: Vec<ContentEncoding>,
+
+    /// The kinds of BlockAdditions the track's blocks may carry
+    pub block_addition_mappings: Vec<BlockAdditionMapping>,
+
+    /// Mandatory elements missing from the track entry
+    pub problems: Vec<TrackProblem>,
+}
+
+impl Info {
+    fn parse_limited<R: io::Read>(r: &mut R, size: u64, limits: &Limits) -> Result<Info> {
+        let mut info = Info::new();
+        let mut duration = None;
+        info.placeholder = false;
+
+        for e in Element::parse_master_with(r, size, Some(ids::INFO), limits)? {
+            match e {
+                Element {
+                    id: ids::SEGMENTUID,
+                    val: ElementType::Binary(uid),
+                    ..
+                } => {
+                    info.uid = Some(uid);
+                }
+                Element {
+                    id: ids::PREVUID,
+                    val: ElementType::Binary(uid),
+                    ..
+                } => {
+                    info.prev_uid = Some(uid);
+                }
+                Element {
+                    id: ids::NEXTUID,
+                    val: ElementType::Binary(uid),
+                    ..
+                } => {
+                    info.next_uid = Some(uid);
+                }
+                Element {
+                    id: ids::SEGMENTFILENAME,
+                    val: ElementType::UTF8(filename),
+                    ..
+                } => {
+                    info.filename = Some(filename);
+                }
+                Element {
+                    id: ids::PREVFILENAME,
+                    val: ElementType::UTF8(filename),
+                    ..
+                } => {
+                    info.prev_filename = Some(filename);
+                }
+                Element {
+                    id: ids::NEXTFILENAME,
+                    val: ElementType::UTF8(filename),
+                    ..
+                } => {
+                    info.next_filename = Some(filename);
+                }
+                Element {
+                    id: ids::SEGMENTFAMILY,
+                    val: ElementType::Binary(uid),
+                    ..
+                } => {
+                    info.family_uids.push(uid);
+                }
+                Element {
+                    id: ids::TITLE,
+                    val: ElementType::UTF8(title),
+                    ..
+                } => {
+                    info.title = Some(title);
+                }
+                Element {
+                    id: ids::TIMECODESCALE,
+                    val: ElementType::UInt(scale),
+                    ..
+                } if scale > 0 => {
+                    info.timestamp_scale = scale;
+                }
+                Element {
+                    id: ids::DURATION,
+                    val: ElementType::Float(d),
+                    ..
+                } => duration = Some(d),
+                Element {
+                    id: ids::DATEUTC,
+                    val: ElementType::Date(date),
+                    ..
+                } => info.date_utc = Some(date),
+                Element {
+                    id: ids::MUXINGAPP,
+                    val: ElementType::UTF8(app),
+                    ..
+                } => {
+                    info.muxing_app = app;
+                }
+                Element {
+                    id: ids::WRITINGAPP,
+                    val: ElementType::UTF8(app),
+                    ..
+                } => {
+                    info.writing_app = app;
+                }
+                _ => {}
+            }
+        }
+
+        info.duration_raw = duration;
+        if let Some(d) = duration {
+            let nanos = d * info.timestamp_scale as f64;
+            if !nanos.is_finite() || nanos < 0.0 {
+                info.problems.push(InfoProblem::InvalidDuration);
+            } else if nanos >= u64::MAX as f64 {
+                info.problems.push(InfoProblem::DurationOverflow);
+                info.duration = Some(Duration::from_nanos(u64::MAX));
+            } else {
+                info.duration = Some(Duration::from_nanos(nanos as u64));
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// Returns an invalid Duration as an error in strict mode
+    fn check(self, options: &ParseOptions) -> Result<Info> {
+        match self.duration_raw {
+            Some(value)
+                if options.strict && self.problems.contains(&InfoProblem::InvalidDuration) =>
+            {
+                Err(MatroskaError::InvalidDuration { value })
+            }
+            _ => Ok(self),
+        }
+    }
+}
+
+/// A defect in an Info segment's values
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InfoProblem {
+    /// The Duration is negative, NaN or infinite,
+    /// so [`Info::duration`] is left as `None`
+    InvalidDuration,
+    /// The Duration is too long to count in nanoseconds,
+    /// so [`Info::duration`] is the longest that can be
+    DurationOverflow,
+}
+
+impl std::fmt::Display for InfoProblem {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InfoProblem::InvalidDuration => write!(f, "invalid duration"),
+            InfoProblem::DurationOverflow => write!(f, "duration overflows nanoseconds"),
+        }
+    }
+}
+
+/// A defect in a track entry
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TrackProblem {
+    /// The TrackNumber element is missing
+    MissingNumber,
+    /// The TrackUID element is missing
+    MissingUid,
+    /// The TrackType element is missing
+    MissingType,
+    /// The CodecID element is missing
+    MissingCodecId,
+}
+
+impl std::fmt::Display for TrackProblem {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TrackProblem::MissingNumber => write!(f, "missing track number"),
+            TrackProblem::MissingUid => write!(f, "missing track UID"),
+            TrackProblem::MissingType => write!(f, "missing track type"),
+            TrackProblem::MissingCodecId => write!(f, "missing codec ID"),
+        }
+    }
+}
+
+impl Default for Track {
+    fn default() -> Self {
+        Track::new()
+    }
 }
 
 impl Track {
@@ -496,13 +1598,29 @@ impl Track {
             original: None,
             commentary: None,
             interlaced: true,
+            min_cache: None,
+            max_cache: None,
+            max_block_addition_id: None,
             default_duration: None,
+            codec_delay: None,
+            seek_pre_roll: None,
             name: None,
             language: None,
             codec_id: String::new(),
             codec_private: None,
+            codec_private_range: None,
             codec_name: None,
+            codec_settings: None,
+            codec_info_url: None,
+            codec_download_url: None,
+            codec_decode_all: None,
+            attachment_links: Vec::new(),
             settings: Settings::None,
+            operation: None,
+            translate: Vec::new(),
+            encodings: Vec::new(),
+            block_addition_mappings: Vec::new(),
+            problems: Vec::new(),
         }
     }
 
@@ -524,9 +1642,105 @@ impl Track {
         matches!(self.tracktype, Tracktype::Subtitle)
     }
 
+    /// Returns the track's codec private data
+    ///
+    /// If reading it was deferred by [`ParseOptions::defer_codec_private`],
+    /// it's read from the given reader, which must be the file
+    /// the track was parsed from.
+    /// Otherwise, this returns a copy of [`Track::codec_private`].
+    pub fn read_codec_private<R: io::Read + io::Seek>(&self, mut r: R) -> Result<Option<Vec<u8>>> {
+        match self.codec_private_range {
+            Some((offset, size)) => {
+                r.seek(io::SeekFrom::Start(offset))?;
+                ebml::read_bin(&mut r, size).map(Some)
+            }
+            None => Ok(self.codec_private.clone()),
+        }
+    }
+
+    /// Undoes the track's content encodings on one frame of its data,
+    /// returning the frame as the codec expects it
+    ///
+    /// Header stripping and zlib compression are supported;
+    /// frames which are otherwise compressed or encrypted
+    /// are [`MatroskaError::UnsupportedEncoding`].
+    pub fn decode_frame(&self, mut frame: Vec<u8>) -> Result<Vec<u8>> {
+        let mut encodings: Vec<&ContentEncoding> = self
+            .encodings
+            .iter()
+            .filter(|encoding| encoding.scope & 1 != 0)
+            .collect();
+        // the last encoding applied is the first undone
+        encodings.sort_by_key(|encoding| std::cmp::Reverse(encoding.order));
+        for encoding in encodings {
+            match (&encoding.encoding_type, &encoding.compression) {
+                (ContentEncodingType::Compression, Some(compression))
+                    if compression.algorithm == CompressionAlgorithm::HeaderStripping =>
+                {
+                    if let Some(header) = &compression.settings {
+                        frame.splice(0..0, header.iter().copied());
+                    }
+                }
+                (ContentEncodingType::Compression, Some(compression))
+                    if compression.algorithm == CompressionAlgorithm::Zlib =>
+                {
+                    frame = inflate::decompress(&frame, Limits::MAX_ELEMENT_SIZE as usize)
+                        .ok_or(MatroskaError::InvalidCompressedData { track: self.number })?;
+                }
+                _ => return Err(MatroskaError::UnsupportedEncoding { track: self.number }),
+            }
+        }
+        Ok(frame)
+    }
+
+    /// Parses a Tracks element starting at the reader's position,
+    /// recording where each CodecPrivate is rather than reading it
+    fn parse_deferred<R: io::Read + io::Seek>(
+        r: &mut R,
+        size: u64,
+        limits: &Limits,
+    ) -> Result<Vec<Track>> {
+        let entries =
+            parse_entries_deferring(r, size, ids::TRACKENTRY, ids::CODEC_PRIVATE, true, limits)?;
+        Ok(entries
+            .into_iter()
+            .map(|(elements, range)| {
+                let mut track = Track::build_entry(elements);
+                track.codec_private_range = range;
+                track
+            })
+            .collect())
+    }
+
+    /// Returns the first track missing its CodecID as an error in strict mode
+    ///
+    /// Its other problems are only recorded, since the
+    /// track can still be identified and decoded without them.
+    fn check(tracks: Vec<Track>, options: &ParseOptions) -> Result<Vec<Track>> {
+        if options.strict {
+            let problem = TrackProblem::MissingCodecId;
+            if let Some(track) = tracks.iter().find(|t| t.problems.contains(&problem)) {
+                return Err(MatroskaError::InvalidTrack {
+                    number: track.number,
+                    problem,
+                });
+            }
+        }
+        Ok(tracks)
+    }
+
     fn build_entry(elements: Vec<Element>) -> Track {
         let mut track = Track::new();
+        let mut seen = [false; 4];
         for e in elements {
+            match e.id {
+                ids::TRACKNUMBER => seen[0] = true,
+                ids::TRACKUID => seen[1] = true,
+                ids::TRACKTYPE => seen[2] = true,
+                ids::CODEC_ID => seen[3] = true,
+                _ => {}
+            }
+
             // although the official specification lists
             // the hearing impaired, visual impaired, text descriptions,
             // original and commentary flags as unsigned ints,
@@ -652,6 +1866,27 @@ impl Track {
                 } => {
                     track.interlaced = lacing != 0;
                 }
+                Element {
+                    id: ids::MINCACHE,
+                    val: ElementType::UInt(min_cache),
+                    ..
+                } => {
+                    track.min_cache = Some(min_cache);
+                }
+                Element {
+                    id: ids::MAXCACHE,
+                    val: ElementType::UInt(max_cache),
+                    ..
+                } => {
+                    track.max_cache = Some(max_cache);
+                }
+                Element {
+                    id: ids::MAXBLOCKADDITIONID,
+                    val: ElementType::UInt(max_id),
+                    ..
+                } => {
+                    track.max_block_addition_id = Some(max_id);
+                }
                 Element {
                     id: ids::DEFAULTDURATION,
                     val: ElementType::UInt(duration),
@@ -659,6 +1894,20 @@ impl Track {
                 } => {
                     track.default_duration = Some(Duration::from_nanos(duration));
                 }
+                Element {
+                    id: ids::CODECDELAY,
+                    val: ElementType::UInt(delay),
+                    ..
+                } => {
+                    track.codec_delay = Some(Duration::from_nanos(delay));
+                }
+                Element {
+                    id: ids::SEEKPREROLL,
+                    val: ElementType::UInt(pre_roll),
+                    ..
+                } => {
+                    track.seek_pre_roll = Some(Duration::from_nanos(pre_roll));
+                }
                 Element {
                     id: ids::NAME,
                     val: ElementType::UTF8(name),
@@ -670,10 +1919,8 @@ impl Track {
                     id: ids::LANGUAGE,
                     val: ElementType::String(language),
                     ..
-                } => {
-                    if !matches!(track.language, Some(Language::IETF(_))) {
-                        track.language = Some(Language::ISO639(language));
-                    }
+                } if !matches!(track.language, Some(Language::IETF(_))) => {
+                    track.language = Some(Language::ISO639(language));
                 }
                 Element {
                     id: ids::LANGUAGE_IETF,
@@ -703,6 +1950,41 @@ impl Track {
                 } => {
                     track.codec_name = Some(codec_name);
                 }
+                Element {
+                    id: ids::CODEC_SETTINGS,
+                    val: ElementType::UTF8(settings),
+                    ..
+                } => {
+                    track.codec_settings = Some(settings);
+                }
+                Element {
+                    id: ids::CODEC_INFO_URL,
+                    val: ElementType::String(url),
+                    ..
+                } => {
+                    track.codec_info_url = Some(url);
+                }
+                Element {
+                    id: ids::CODEC_DOWNLOAD_URL,
+                    val: ElementType::String(url),
+                    ..
+                } => {
+                    track.codec_download_url = Some(url);
+                }
+                Element {
+                    id: ids::CODEC_DECODE_ALL,
+                    val: ElementType::UInt(decode_all),
+                    ..
+                } => {
+                    track.codec_decode_all = Some(decode_all != 0);
+                }
+                Element {
+                    id: ids::ATTACHMENTLINK,
+                    val: ElementType::UInt(uid),
+                    ..
+                } => {
+                    track.attachment_links.push(uid);
+                }
                 Element {
                     id: ids::VIDEO,
                     val: ElementType::Master(sub_elements),
@@ -717,9 +1999,61 @@ impl Track {
                 } => {
                     track.settings = Settings::Audio(Audio::build(sub_elements));
                 }
+                Element {
+                    id: ids::TRACKOPERATION,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    track.operation = TrackOperation::build(sub_elements);
+                }
+                Element {
+                    id: ids::TRACKTRANSLATE,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    track.translate.push(TrackTranslate::build(sub_elements));
+                }
+                Element {
+                    id: ids::BLOCKADDITIONMAPPING,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    track
+                        .block_addition_mappings
+                        .push(BlockAdditionMapping::build(sub_elements));
+                }
+                Element {
+                    id: ids::CONTENTENCODINGS,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    track.encodings = sub_elements
+                        .into_iter()
+                        .filter_map(|e| match e {
+                            Element {
+                                id: ids::CONTENTENCODING,
+                                val: ElementType::Master(sub_elements),
+                                ..
+                            } => Some(ContentEncoding::build(sub_elements)),
+                            _ => None,
+                        })
+                        .collect();
+                }
                 _ => {}
             }
         }
+        for (problem, seen) in [
+            (TrackProblem::MissingNumber, seen[0]),
+            (TrackProblem::MissingUid, seen[1]),
+            (TrackProblem::MissingType, seen[2]),
+            (TrackProblem::MissingCodecId, seen[3]),
+        ]
+        .iter()
+        {
+            if !seen {
+                track.problems.push(*problem);
+            }
+        }
         track
     }
 }
@@ -730,7 +2064,18 @@ impl Parseable for Track {
     const ID: u32 = ids::TRACKS;
 
     fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<Vec<Track>> {
-        Element::parse_master(r, size, Some(ids::TRACKENTRY)).map(|elements| {
+        Track::parse_limited(r, size, &Limits::default())
+    }
+
+    fn parse_with<R: io::Read>(r: &mut R, size: u64, options: &ParseOptions) -> Result<Vec<Track>> {
+        Track::parse_limited(r, size, &options.limits())
+            .and_then(|tracks| Track::check(tracks, options))
+    }
+}
+
+impl Track {
+    fn parse_limited<R: io::Read>(r: &mut R, size: u64, limits: &Limits) -> Result<Vec<Track>> {
+        Element::parse_master_with(r, size, Some(ids::TRACKENTRY), limits).map(|elements| {
             elements
                 .into_iter()
                 .filter_map(|e| match e {
@@ -746,293 +2091,1808 @@ impl Parseable for Track {
     }
 }
 
-/// The type of a given track
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-pub enum Tracktype {
-    /// A video track
-    Video,
-    /// An audio track
-    Audio,
-    /// A complex track
-    Complex,
-    /// A logo track
-    Logo,
-    /// A subtitle track
-    Subtitle,
-    /// A buttons track
-    Buttons,
-    /// A controls track
-    Control,
-    /// An unknown track type
-    Unknown,
+/// How a virtual track is built from other tracks
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TrackOperation {
+    /// video planes combined into a single picture,
+    /// as track UID and plane type pairs
+    CombinePlanes(Vec<(u64, PlaneType)>),
+    /// the UIDs of tracks whose blocks are joined one after another
+    JoinBlocks(Vec<u64>),
 }
 
-impl Tracktype {
-    fn new(tracktype: u64) -> Tracktype {
-        match tracktype {
-            0x01 => Tracktype::Video,
-            0x02 => Tracktype::Audio,
-            0x03 => Tracktype::Complex,
-            0x10 => Tracktype::Logo,
-            0x11 => Tracktype::Subtitle,
-            0x12 => Tracktype::Buttons,
-            0x20 => Tracktype::Control,
-            _ => Tracktype::Unknown,
+impl TrackOperation {
+    fn build(elements: Vec<Element>) -> Option<TrackOperation> {
+        elements.into_iter().find_map(|e| match e {
+            Element {
+                id: ids::TRACKCOMBINEPLANES,
+                val: ElementType::Master(sub_elements),
+                ..
+            } => Some(TrackOperation::CombinePlanes(
+                sub_elements
+                    .into_iter()
+                    .filter_map(|e| match e {
+                        Element {
+                            id: ids::TRACKPLANE,
+                            val: ElementType::Master(plane),
+                            ..
+                        } => TrackOperation::build_plane(plane),
+                        _ => None,
+                    })
+                    .collect(),
+            )),
+            Element {
+                id: ids::TRACKJOINBLOCKS,
+                val: ElementType::Master(sub_elements),
+                ..
+            } => Some(TrackOperation::JoinBlocks(
+                sub_elements
+                    .into_iter()
+                    .filter_map(|e| match e {
+                        Element {
+                            id: ids::TRACKJOINUID,
+                            val: ElementType::UInt(uid),
+                            ..
+                        } => Some(uid),
+                        _ => None,
+                    })
+                    .collect(),
+            )),
+            _ => None,
+        })
+    }
+
+    fn build_plane(elements: Vec<Element>) -> Option<(u64, PlaneType)> {
+        let mut uid = None;
+        let mut plane_type = None;
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::TRACKPLANEUID,
+                    val: ElementType::UInt(u),
+                    ..
+                } => uid = Some(u),
+                Element {
+                    id: ids::TRACKPLANETYPE,
+                    val: ElementType::UInt(t),
+                    ..
+                } => plane_type = Some(PlaneType::from(t)),
+                _ => {}
+            }
         }
+        Some((uid?, plane_type?))
     }
 }
 
-/// The settings a track may have
-#[derive(Debug, Clone, PartialEq)]
-pub enum Settings {
-    /// No settings (for non audio/video tracks)
-    None,
-    /// Video settings
-    Video(Video),
-    /// Audio settings
-    Audio(Audio),
+/// The kind of video plane a track contributes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PlaneType {
+    /// the left eye of a stereo pair
+    LeftEye,
+    /// the right eye of a stereo pair
+    RightEye,
+    /// a background plane
+    Background,
+    /// an unknown value
+    Unknown(u64),
 }
 
-/// A video track's specifications
-#[derive(Debug, Clone, PartialEq)]
-pub struct Video {
-    /// Width of encoded video frames in pixels
-    pub pixel_width: u64,
-    /// Height of encoded video frames in pixels
-    pub pixel_height: u64,
-    /// Width of video frames to display
-    pub display_width: Option<u64>,
-    /// Height of video frames to display
-    pub display_height: Option<u64>,
-    /// Whether video is interlaced
-    pub interlaced: Option<bool>,
-    /// Stereo video mode
-    pub stereo: Option<StereoMode>,
-    /// Gamma
-    pub gamma: Option<f64>,
+impl From<u64> for PlaneType {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => PlaneType::LeftEye,
+            1 => PlaneType::RightEye,
+            2 => PlaneType::Background,
+            val => PlaneType::Unknown(val),
+        }
+    }
 }
 
-impl Video {
-    fn new() -> Video {
-        Video {
-            pixel_width: 0,
-            pixel_height: 0,
-            display_width: None,
-            display_height: None,
-            interlaced: None,
-            stereo: None,
-            gamma: None,
+impl PlaneType {
+    /// Returns the plane type's name, such as `right_eye`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlaneType::LeftEye => "left_eye",
+            PlaneType::RightEye => "right_eye",
+            PlaneType::Background => "background",
+            PlaneType::Unknown(_) => "unknown",
         }
     }
+}
 
-    fn build(elements: Vec<Element>) -> Video {
-        let mut video = Video::new();
+/// A track's identifier within a given chapter codec
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TrackTranslate {
+    /// The edition UIDs this mapping applies to,
+    /// or all editions if empty
+    pub edition_uids: Vec<u64>,
+    /// The chapter codec, where 0 is Matroska Script and 1 is DVD-menu
+    pub codec: u64,
+    /// The codec-specific track identifier
+    pub track_id: Vec<u8>,
+}
+
+impl Default for TrackTranslate {
+    fn default() -> Self {
+        TrackTranslate::new()
+    }
+}
+
+impl TrackTranslate {
+    fn new() -> TrackTranslate {
+        TrackTranslate {
+            edition_uids: Vec::new(),
+            codec: 0,
+            track_id: Vec::new(),
+        }
+    }
+
+    fn build(elements: Vec<Element>) -> TrackTranslate {
+        let mut translate = TrackTranslate::new();
         for e in elements {
             match e {
                 Element {
-                    id: ids::PIXELWIDTH,
-                    val: ElementType::UInt(width),
+                    id: ids::TRACKTRANSLATEEDITIONUID,
+                    val: ElementType::UInt(uid),
                     ..
                 } => {
-                    video.pixel_width = width;
+                    translate.edition_uids.push(uid);
                 }
                 Element {
-                    id: ids::PIXELHEIGHT,
-                    val: ElementType::UInt(height),
+                    id: ids::TRACKTRANSLATECODEC,
+                    val: ElementType::UInt(codec),
                     ..
                 } => {
-                    video.pixel_height = height;
+                    translate.codec = codec;
                 }
                 Element {
-                    id: ids::DISPLAYWIDTH,
-                    val: ElementType::UInt(width),
+                    id: ids::TRACKTRANSLATETRACKID,
+                    val: ElementType::Binary(track_id),
                     ..
                 } => {
-                    video.display_width = Some(width);
+                    translate.track_id = track_id;
                 }
+                _ => {}
+            }
+        }
+        translate
+    }
+}
+
+/// Describes the contents of a track's BlockAdditions
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BlockAdditionMapping {
+    /// The BlockAddID of the BlockAdditions this applies to
+    pub value: Option<u64>,
+    /// A human-readable name for the BlockAdditions
+    pub name: Option<String>,
+    /// The type of data in the BlockAdditions,
+    /// usually a FourCC such as `dvcC` as a big-endian integer
+    pub id_type: u64,
+    /// Extra configuration data, such as a Dolby Vision
+    /// configuration record, kept verbatim
+    pub extra_data: Option<Vec<u8>>,
+}
+
+impl Default for BlockAdditionMapping {
+    fn default() -> Self {
+        BlockAdditionMapping::new()
+    }
+}
+
+impl BlockAdditionMapping {
+    fn new() -> BlockAdditionMapping {
+        BlockAdditionMapping {
+            value: None,
+            name: None,
+            id_type: 0,
+            extra_data: None,
+        }
+    }
+
+    fn build(elements: Vec<Element>) -> BlockAdditionMapping {
+        let mut mapping = BlockAdditionMapping::new();
+        for e in elements {
+            match e {
                 Element {
-                    id: ids::DISPLAYHEIGHT,
-                    val: ElementType::UInt(height),
+                    id: ids::BLOCKADDIDVALUE,
+                    val: ElementType::UInt(value),
                     ..
-                } => video.display_height = Some(height),
+                } => {
+                    mapping.value = Some(value);
+                }
                 Element {
-                    id: ids::INTERLACED,
-                    val: ElementType::UInt(interlaced),
+                    id: ids::BLOCKADDIDNAME,
+                    val: ElementType::String(name),
                     ..
                 } => {
-                    video.interlaced = match interlaced {
-                        1 => Some(true),
-                        2 => Some(false),
-                        _ => None,
-                    }
+                    mapping.name = Some(name);
                 }
                 Element {
-                    id: ids::GAMMA,
-                    val: ElementType::Float(gamma),
+                    id: ids::BLOCKADDIDTYPE,
+                    val: ElementType::UInt(id_type),
                     ..
                 } => {
-                    video.gamma = Some(gamma);
+                    mapping.id_type = id_type;
                 }
                 Element {
-                    id: ids::STEREOMODE,
-                    val: ElementType::UInt(stereo),
+                    id: ids::BLOCKADDIDEXTRADATA,
+                    val: ElementType::Binary(extra_data),
                     ..
                 } => {
-                    video.stereo = match stereo {
-                        0 => Some(StereoMode::Mono),
-                        1 => Some(StereoMode::SideBySide(EyeOrder::LeftFirst)),
-                        2 => Some(StereoMode::TopBottom(EyeOrder::RightFirst)),
-                        3 => Some(StereoMode::TopBottom(EyeOrder::LeftFirst)),
-                        4 => Some(StereoMode::Checkboard(EyeOrder::RightFirst)),
-                        5 => Some(StereoMode::Checkboard(EyeOrder::LeftFirst)),
-                        6 => Some(StereoMode::RowInterleaved(EyeOrder::RightFirst)),
-                        7 => Some(StereoMode::RowInterleaved(EyeOrder::LeftFirst)),
-                        8 => Some(StereoMode::ColumnInterleaved(EyeOrder::RightFirst)),
-                        9 => Some(StereoMode::ColumnInterleaved(EyeOrder::LeftFirst)),
-                        10 => Some(StereoMode::Anaglyph(StereoColors::CyanRed)),
-                        11 => Some(StereoMode::SideBySide(EyeOrder::RightFirst)),
-                        12 => Some(StereoMode::Anaglyph(StereoColors::GreenMagenta)),
-                        13 => Some(StereoMode::Interlaced(EyeOrder::LeftFirst)),
-                        14 => Some(StereoMode::Interlaced(EyeOrder::RightFirst)),
-                        _ => None,
-                    }
+                    mapping.extra_data = Some(extra_data);
                 }
                 _ => {}
             }
         }
-        video
-    }
-}
-
-/// How a video track may be displayed in stereo mode
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub enum StereoMode {
-    /// mono
-    Mono,
-    /// side-by-side
-    SideBySide(EyeOrder),
-    /// top - bottom
-    TopBottom(EyeOrder),
-    /// checkboard
-    Checkboard(EyeOrder),
-    /// row interleaved
-    RowInterleaved(EyeOrder),
-    /// column interleaved
-    ColumnInterleaved(EyeOrder),
-    /// anaglyph
-    Anaglyph(StereoColors),
-    /// interlaced
-    Interlaced(EyeOrder),
-}
-
-impl std::fmt::Display for StereoMode {
-    #[inline]
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            StereoMode::Mono => write!(f, "mono"),
-            StereoMode::SideBySide(o) => write!(f, "side by side ({o})"),
-            StereoMode::TopBottom(o) => write!(f, "top - bottom ({o})"),
-            StereoMode::Checkboard(o) => write!(f, "checkboard ({o})"),
-            StereoMode::RowInterleaved(o) => write!(f, "row interleaved ({o})"),
-            StereoMode::ColumnInterleaved(o) => write!(f, "column interleaved ({o})"),
-            StereoMode::Anaglyph(c) => write!(f, "anaglyph ({c})"),
-            StereoMode::Interlaced(o) => write!(f, "interlaced ({o})"),
-        }
+        mapping
     }
-}
 
-/// Which eye is displayed first
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-pub enum EyeOrder {
-    /// left eye is displayed first
-    LeftFirst,
-    /// right eye is displayed first
-    RightFirst,
-}
-
-impl std::fmt::Display for EyeOrder {
-    #[inline]
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        match self {
-            EyeOrder::LeftFirst => write!(f, "left eye is first"),
-            EyeOrder::RightFirst => write!(f, "right eye is first"),
+    /// Returns the type as a FourCC, such as `dvvC`,
+    /// if it is printable ASCII
+    pub fn id_type_fourcc(&self) -> Option<String> {
+        if self.id_type > u64::from(u32::MAX) {
+            return None;
         }
+        Some((self.id_type as u32).to_be_bytes())
+            .filter(|fourcc| fourcc.iter().all(|b| (0x20..=0x7E).contains(b)))
+            .and_then(|fourcc| String::from_utf8(fourcc.to_vec()).ok())
     }
 }
 
-/// Which colors are used for anaglyph stereo 3D
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-pub enum StereoColors {
-    /// cyan/red
-    CyanRed,
-    /// green/magenta
-    GreenMagenta,
+/// A compression or encryption step applied to a track's data
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContentEncoding {
+    /// The order this encoding was applied in, starting from 0,
+    /// where the highest order must be decoded first
+    pub order: u64,
+    /// A bit field of what is encoded,
+    /// where 1 is all frames, 2 is the codec private data
+    /// and 4 is the next encoding's private data
+    pub scope: u64,
+    /// Whether this is compression or encryption
+    pub encoding_type: ContentEncodingType,
+    /// The compression settings, if this is compression
+    pub compression: Option<ContentCompression>,
+    /// The encryption settings, if this is encryption
+    pub encryption: Option<ContentEncryption>,
 }
 
-impl std::fmt::Display for StereoColors {
-    #[inline]
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        match self {
-            StereoColors::CyanRed => write!(f, "cyan/red"),
-            StereoColors::GreenMagenta => write!(f, "green/magenta"),
-        }
+impl Default for ContentEncoding {
+    fn default() -> Self {
+        ContentEncoding::new()
     }
 }
 
-/// An audio track's specifications
-#[derive(Debug, Clone, PartialEq)]
-pub struct Audio {
-    /// The sample rate in Hz
-    pub sample_rate: f64,
-    /// The number of audio channels
-    pub channels: u64,
-    /// The bit depth of each sample
-    pub bit_depth: Option<u64>,
-}
-
-impl Audio {
-    fn new() -> Audio {
-        Audio {
-            sample_rate: 0.0,
-            channels: 0,
-            bit_depth: None,
+impl ContentEncoding {
+    fn new() -> ContentEncoding {
+        ContentEncoding {
+            order: 0,
+            scope: 1,
+            encoding_type: ContentEncodingType::Compression,
+            compression: None,
+            encryption: None,
         }
     }
 
-    fn build(elements: Vec<Element>) -> Audio {
-        let mut audio = Audio::new();
+    fn build(elements: Vec<Element>) -> ContentEncoding {
+        let mut encoding = ContentEncoding::new();
         for e in elements {
             match e {
                 Element {
-                    id: ids::SAMPLINGFREQUENCY,
-                    val: ElementType::Float(frequency),
+                    id: ids::CONTENTENCODINGORDER,
+                    val: ElementType::UInt(order),
                     ..
                 } => {
-                    audio.sample_rate = frequency;
+                    encoding.order = order;
                 }
                 Element {
-                    id: ids::CHANNELS,
-                    val: ElementType::UInt(channels),
+                    id: ids::CONTENTENCODINGSCOPE,
+                    val: ElementType::UInt(scope),
                     ..
                 } => {
-                    audio.channels = channels;
+                    encoding.scope = scope;
                 }
                 Element {
-                    id: ids::BITDEPTH,
-                    val: ElementType::UInt(bit_depth),
+                    id: ids::CONTENTENCODINGTYPE,
+                    val: ElementType::UInt(encoding_type),
                     ..
                 } => {
-                    audio.bit_depth = Some(bit_depth);
+                    encoding.encoding_type = ContentEncodingType::from(encoding_type);
                 }
-                _ => {}
-            }
-        }
-        audio
-    }
-}
-
+                Element {
+                    id: ids::CONTENTCOMPRESSION,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    encoding.compression = Some(ContentCompression::build(sub_elements));
+                }
+                Element {
+                    id: ids::CONTENTENCRYPTION,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    encoding.encryption = Some(ContentEncryption::build(sub_elements));
+                }
+                _ => {}
+            }
+        }
+        encoding
+    }
+}
+
+/// The kind of a content encoding
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ContentEncodingType {
+    /// the data is compressed
+    Compression,
+    /// the data is encrypted
+    Encryption,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for ContentEncodingType {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => ContentEncodingType::Compression,
+            1 => ContentEncodingType::Encryption,
+            val => ContentEncodingType::Unknown(val),
+        }
+    }
+}
+
+impl ContentEncodingType {
+    /// Returns the encoding type's name, such as `encryption`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentEncodingType::Compression => "compression",
+            ContentEncodingType::Encryption => "encryption",
+            ContentEncodingType::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// How a track's data is compressed
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContentCompression {
+    /// The compression algorithm
+    pub algorithm: CompressionAlgorithm,
+    /// Settings for the algorithm
+    ///
+    /// For header stripping, these are the bytes removed
+    /// from the start of each frame which must be prepended
+    /// to it again before decoding.
+    pub settings: Option<Vec<u8>>,
+}
+
+impl Default for ContentCompression {
+    fn default() -> Self {
+        ContentCompression::new()
+    }
+}
+
+impl ContentCompression {
+    fn new() -> ContentCompression {
+        ContentCompression {
+            algorithm: CompressionAlgorithm::Zlib,
+            settings: None,
+        }
+    }
+
+    fn build(elements: Vec<Element>) -> ContentCompression {
+        let mut compression = ContentCompression::new();
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::CONTENTCOMPALGO,
+                    val: ElementType::UInt(algorithm),
+                    ..
+                } => {
+                    compression.algorithm = CompressionAlgorithm::from(algorithm);
+                }
+                Element {
+                    id: ids::CONTENTCOMPSETTINGS,
+                    val: ElementType::Binary(settings),
+                    ..
+                } => {
+                    compression.settings = Some(settings);
+                }
+                _ => {}
+            }
+        }
+        compression
+    }
+
+    /// Returns the bytes to prepend to each frame
+    /// if this is header stripping
+    pub fn stripped_header(&self) -> Option<&[u8]> {
+        match self.algorithm {
+            CompressionAlgorithm::HeaderStripping => self.settings.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// A content compression algorithm
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CompressionAlgorithm {
+    /// zlib compression
+    Zlib,
+    /// bzip2 compression
+    Bzlib,
+    /// LZO1X compression
+    Lzo1x,
+    /// a fixed prefix removed from each frame
+    HeaderStripping,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for CompressionAlgorithm {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => CompressionAlgorithm::Zlib,
+            1 => CompressionAlgorithm::Bzlib,
+            2 => CompressionAlgorithm::Lzo1x,
+            3 => CompressionAlgorithm::HeaderStripping,
+            val => CompressionAlgorithm::Unknown(val),
+        }
+    }
+}
+
+impl CompressionAlgorithm {
+    /// Returns the compression algorithm's name, such as `zlib`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Zlib => "zlib",
+            CompressionAlgorithm::Bzlib => "bzlib",
+            CompressionAlgorithm::Lzo1x => "lzo1x",
+            CompressionAlgorithm::HeaderStripping => "header_stripping",
+            CompressionAlgorithm::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// How a track's data is encrypted
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContentEncryption {
+    /// The encryption algorithm
+    pub algorithm: EncryptionAlgorithm,
+    /// The ID of the key used to encrypt the data
+    pub key_id: Vec<u8>,
+    /// The block cipher mode, if the algorithm is AES
+    pub cipher_mode: Option<CipherMode>,
+}
+
+impl Default for ContentEncryption {
+    fn default() -> Self {
+        ContentEncryption::new()
+    }
+}
+
+impl ContentEncryption {
+    fn new() -> ContentEncryption {
+        ContentEncryption {
+            algorithm: EncryptionAlgorithm::NotEncrypted,
+            key_id: Vec::new(),
+            cipher_mode: None,
+        }
+    }
+
+    fn build(elements: Vec<Element>) -> ContentEncryption {
+        let mut encryption = ContentEncryption::new();
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::CONTENTENCALGO,
+                    val: ElementType::UInt(algorithm),
+                    ..
+                } => {
+                    encryption.algorithm = EncryptionAlgorithm::from(algorithm);
+                }
+                Element {
+                    id: ids::CONTENTENCKEYID,
+                    val: ElementType::Binary(key_id),
+                    ..
+                } => {
+                    encryption.key_id = key_id;
+                }
+                Element {
+                    id: ids::CONTENTENCAESSETTINGS,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    encryption.cipher_mode = sub_elements.into_iter().find_map(|e| match e {
+                        Element {
+                            id: ids::AESSETTINGSCIPHERMODE,
+                            val: ElementType::UInt(mode),
+                            ..
+                        } => Some(CipherMode::from(mode)),
+                        _ => None,
+                    });
+                }
+                _ => {}
+            }
+        }
+        encryption
+    }
+}
+
+/// A content encryption algorithm
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EncryptionAlgorithm {
+    /// the data is not encrypted
+    NotEncrypted,
+    /// DES
+    DES,
+    /// Triple DES
+    TripleDES,
+    /// Twofish
+    Twofish,
+    /// Blowfish
+    Blowfish,
+    /// AES
+    AES,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for EncryptionAlgorithm {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => EncryptionAlgorithm::NotEncrypted,
+            1 => EncryptionAlgorithm::DES,
+            2 => EncryptionAlgorithm::TripleDES,
+            3 => EncryptionAlgorithm::Twofish,
+            4 => EncryptionAlgorithm::Blowfish,
+            5 => EncryptionAlgorithm::AES,
+            val => EncryptionAlgorithm::Unknown(val),
+        }
+    }
+}
+
+/// An AES block cipher mode
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CipherMode {
+    /// counter mode
+    CTR,
+    /// cipher block chaining
+    CBC,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for CipherMode {
+    fn from(val: u64) -> Self {
+        match val {
+            1 => CipherMode::CTR,
+            2 => CipherMode::CBC,
+            val => CipherMode::Unknown(val),
+        }
+    }
+}
+
+/// The type of a given track
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Tracktype {
+    /// A video track
+    Video,
+    /// An audio track
+    Audio,
+    /// A complex track
+    Complex,
+    /// A logo track
+    Logo,
+    /// A subtitle track
+    Subtitle,
+    /// A buttons track
+    Buttons,
+    /// A controls track
+    Control,
+    /// An unknown track type
+    Unknown,
+}
+
+impl Tracktype {
+    fn new(tracktype: u64) -> Tracktype {
+        match tracktype {
+            0x01 => Tracktype::Video,
+            0x02 => Tracktype::Audio,
+            0x03 => Tracktype::Complex,
+            0x10 => Tracktype::Logo,
+            0x11 => Tracktype::Subtitle,
+            0x12 => Tracktype::Buttons,
+            0x20 => Tracktype::Control,
+            _ => Tracktype::Unknown,
+        }
+    }
+
+    /// Returns the track type's name, as used by the specification
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tracktype::Video => "video",
+            Tracktype::Audio => "audio",
+            Tracktype::Complex => "complex",
+            Tracktype::Logo => "logo",
+            Tracktype::Subtitle => "subtitle",
+            Tracktype::Buttons => "buttons",
+            Tracktype::Control => "control",
+            Tracktype::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for Tracktype {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// The settings a track may have
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Settings {
+    /// No settings (for non audio/video tracks)
+    None,
+    /// Video settings
+    Video(Video),
+    /// Audio settings
+    Audio(Audio),
+}
+
+/// A video track's specifications
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Video {
+    /// Width of encoded video frames in pixels
+    pub pixel_width: u64,
+    /// Height of encoded video frames in pixels
+    pub pixel_height: u64,
+    /// Width of video frames to display
+    pub display_width: Option<u64>,
+    /// Height of video frames to display
+    pub display_height: Option<u64>,
+    /// The units of the display width and height
+    pub display_unit: Option<DisplayUnit>,
+    /// Pixels to remove from the top of encoded frames
+    pub crop_top: Option<u64>,
+    /// Pixels to remove from the bottom of encoded frames
+    pub crop_bottom: Option<u64>,
+    /// Pixels to remove from the left of encoded frames
+    pub crop_left: Option<u64>,
+    /// Pixels to remove from the right of encoded frames
+    pub crop_right: Option<u64>,
+    /// How the display dimensions may change when resized
+    pub aspect_ratio_type: Option<AspectRatioType>,
+    /// Whether video is interlaced
+    pub interlaced: Option<bool>,
+    /// The order of interlaced fields
+    pub field_order: Option<FieldOrder>,
+    /// Stereo video mode
+    pub stereo: Option<StereoMode>,
+    /// Gamma
+    pub gamma: Option<f64>,
+    /// The pixel format of uncompressed video, as a FourCC
+    pub colour_space: Option<[u8; 4]>,
+    /// Colour settings
+    pub colour: Option<Colour>,
+}
+
+impl Default for Video {
+    fn default() -> Self {
+        Video::new()
+    }
+}
+
+impl Video {
+    fn new() -> Video {
+        Video {
+            pixel_width: 0,
+            pixel_height: 0,
+            display_width: None,
+            display_height: None,
+            display_unit: None,
+            crop_top: None,
+            crop_bottom: None,
+            crop_left: None,
+            crop_right: None,
+            aspect_ratio_type: None,
+            interlaced: None,
+            field_order: None,
+            stereo: None,
+            gamma: None,
+            colour_space: None,
+            colour: None,
+        }
+    }
+
+    /// Returns the width and height of encoded frames after cropping
+    ///
+    /// Crops larger than the frame leave a dimension of 0.
+    pub fn cropped_dimensions(&self) -> (u64, u64) {
+        let crop = |crop: Option<u64>| crop.unwrap_or(0);
+        (
+            self.pixel_width
+                .saturating_sub(crop(self.crop_left))
+                .saturating_sub(crop(self.crop_right)),
+            self.pixel_height
+                .saturating_sub(crop(self.crop_top))
+                .saturating_sub(crop(self.crop_bottom)),
+        )
+    }
+
+    /// Returns the width and height of the picture seen by each eye
+    ///
+    /// Side-by-side packing halves the width and top-bottom
+    /// packing halves the height of the cropped frame,
+    /// while other modes pass the cropped dimensions through unchanged.
+    /// Returns `None` if no stereo mode is set.
+    pub fn per_eye_dimensions(&self) -> Option<(u64, u64)> {
+        let (width, height) = self.cropped_dimensions();
+        self.stereo.map(|stereo| match stereo {
+            StereoMode::SideBySide(_) => (width / 2, height),
+            StereoMode::TopBottom(_) => (width, height / 2),
+            StereoMode::Mono
+            | StereoMode::Checkboard(_)
+            | StereoMode::RowInterleaved(_)
+            | StereoMode::ColumnInterleaved(_)
+            | StereoMode::Anaglyph(_)
+            | StereoMode::Interlaced(_) => (width, height),
+        })
+    }
+
+    /// Returns the colour space as a FourCC string such as "UYVY",
+    /// if present and made of printable ASCII characters
+    pub fn colour_space_fourcc(&self) -> Option<&str> {
+        self.colour_space
+            .as_ref()
+            .filter(|fourcc| fourcc.iter().all(|b| (0x20..=0x7E).contains(b)))
+            .and_then(|fourcc| std::str::from_utf8(fourcc).ok())
+    }
+
+    fn build(elements: Vec<Element>) -> Video {
+        let mut video = Video::new();
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::PIXELWIDTH,
+                    val: ElementType::UInt(width),
+                    ..
+                } => {
+                    video.pixel_width = width;
+                }
+                Element {
+                    id: ids::PIXELHEIGHT,
+                    val: ElementType::UInt(height),
+                    ..
+                } => {
+                    video.pixel_height = height;
+                }
+                Element {
+                    id: ids::DISPLAYWIDTH,
+                    val: ElementType::UInt(width),
+                    ..
+                } => {
+                    video.display_width = Some(width);
+                }
+                Element {
+                    id: ids::DISPLAYHEIGHT,
+                    val: ElementType::UInt(height),
+                    ..
+                } => video.display_height = Some(height),
+                Element {
+                    id: ids::DISPLAYUNIT,
+                    val: ElementType::UInt(unit),
+                    ..
+                } => video.display_unit = Some(unit.into()),
+                Element {
+                    id: ids::PIXELCROPTOP,
+                    val: ElementType::UInt(crop),
+                    ..
+                } => video.crop_top = Some(crop),
+                Element {
+                    id: ids::PIXELCROPBOTTOM,
+                    val: ElementType::UInt(crop),
+                    ..
+                } => video.crop_bottom = Some(crop),
+                Element {
+                    id: ids::PIXELCROPLEFT,
+                    val: ElementType::UInt(crop),
+                    ..
+                } => video.crop_left = Some(crop),
+                Element {
+                    id: ids::PIXELCROPRIGHT,
+                    val: ElementType::UInt(crop),
+                    ..
+                } => video.crop_right = Some(crop),
+                Element {
+                    id: ids::ASPECTRATIOTYPE,
+                    val: ElementType::UInt(aspect_ratio_type),
+                    ..
+                } => video.aspect_ratio_type = Some(aspect_ratio_type.into()),
+                Element {
+                    id: ids::INTERLACED,
+                    val: ElementType::UInt(interlaced),
+                    ..
+                } => {
+                    video.interlaced = match interlaced {
+                        1 => Some(true),
+                        2 => Some(false),
+                        _ => None,
+                    }
+                }
+                Element {
+                    id: ids::FIELDORDER,
+                    val: ElementType::UInt(field_order),
+                    ..
+                } => video.field_order = Some(field_order.into()),
+                Element {
+                    id: ids::GAMMA,
+                    val: ElementType::Float(gamma),
+                    ..
+                } => {
+                    video.gamma = Some(gamma);
+                }
+                Element {
+                    id: ids::COLOURSPACE,
+                    val: ElementType::Binary(colour_space),
+                    ..
+                } => {
+                    video.colour_space =
+                        std::convert::TryFrom::try_from(colour_space.as_slice()).ok();
+                }
+                Element {
+                    id: ids::STEREOMODE,
+                    val: ElementType::UInt(stereo),
+                    ..
+                } => {
+                    video.stereo = StereoMode::from_raw(stereo);
+                }
+                Element {
+                    id: ids::COLOUR,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    video.colour = Some(Colour::build(sub_elements));
+                }
+                _ => {}
+            }
+        }
+        video
+    }
+}
+
+/// The units of a video's display dimensions
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DisplayUnit {
+    /// pixels
+    Pixels,
+    /// centimeters
+    Centimeters,
+    /// inches
+    Inches,
+    /// a display aspect ratio, such as 16 by 9
+    DisplayAspectRatio,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for DisplayUnit {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => DisplayUnit::Pixels,
+            1 => DisplayUnit::Centimeters,
+            2 => DisplayUnit::Inches,
+            3 => DisplayUnit::DisplayAspectRatio,
+            val => DisplayUnit::Unknown(val),
+        }
+    }
+}
+
+impl DisplayUnit {
+    /// Returns the unit's name, such as `centimeters`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisplayUnit::Pixels => "pixels",
+            DisplayUnit::Centimeters => "centimeters",
+            DisplayUnit::Inches => "inches",
+            DisplayUnit::DisplayAspectRatio => "display_aspect_ratio",
+            DisplayUnit::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// The order of an interlaced video's fields
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FieldOrder {
+    /// progressive, not interlaced
+    Progressive,
+    /// top field displayed first, top field stored first
+    TopFieldFirst,
+    /// undetermined field order
+    Undetermined,
+    /// bottom field displayed first, bottom field stored first
+    BottomFieldFirst,
+    /// top field displayed first, bottom field stored first
+    TopFieldFirstSwapped,
+    /// bottom field displayed first, top field stored first
+    BottomFieldFirstSwapped,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for FieldOrder {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => FieldOrder::Progressive,
+            1 => FieldOrder::TopFieldFirst,
+            2 => FieldOrder::Undetermined,
+            6 => FieldOrder::BottomFieldFirst,
+            9 => FieldOrder::TopFieldFirstSwapped,
+            14 => FieldOrder::BottomFieldFirstSwapped,
+            val => FieldOrder::Unknown(val),
+        }
+    }
+}
+
+impl FieldOrder {
+    /// Returns the field order's name, such as `top_field_first`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldOrder::Progressive => "progressive",
+            FieldOrder::TopFieldFirst => "top_field_first",
+            FieldOrder::Undetermined => "undetermined",
+            FieldOrder::BottomFieldFirst => "bottom_field_first",
+            FieldOrder::TopFieldFirstSwapped => "top_field_first_swapped",
+            FieldOrder::BottomFieldFirstSwapped => "bottom_field_first_swapped",
+            FieldOrder::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// How a video's aspect ratio is kept when resized
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AspectRatioType {
+    /// the video may be resized freely
+    FreeResizing,
+    /// the display aspect ratio is preserved
+    KeepAspectRatio,
+    /// the display dimensions are fixed
+    Fixed,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for AspectRatioType {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => AspectRatioType::FreeResizing,
+            1 => AspectRatioType::KeepAspectRatio,
+            2 => AspectRatioType::Fixed,
+            val => AspectRatioType::Unknown(val),
+        }
+    }
+}
+
+impl AspectRatioType {
+    /// Returns the aspect ratio type's name, such as `keep_aspect_ratio`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AspectRatioType::FreeResizing => "free_resizing",
+            AspectRatioType::KeepAspectRatio => "keep_aspect_ratio",
+            AspectRatioType::Fixed => "fixed",
+            AspectRatioType::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// A video track's colour settings
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Colour {
+    /// The matrix coefficients of the video
+    pub matrix_coefficients: Option<MatrixCoefficients>,
+    /// Number of decoded bits per colour channel
+    pub bits_per_channel: Option<u64>,
+    /// Number of pixels to remove in the Cr and Cb channels horizontally
+    pub chroma_subsampling_horz: Option<u64>,
+    /// Number of pixels to remove in the Cr and Cb channels vertically
+    pub chroma_subsampling_vert: Option<u64>,
+    /// Number of pixels to remove in the Cb channel horizontally
+    pub cb_subsampling_horz: Option<u64>,
+    /// Number of pixels to remove in the Cb channel vertically
+    pub cb_subsampling_vert: Option<u64>,
+    /// How chroma is subsampled horizontally
+    pub chroma_siting_horz: Option<ChromaSiting>,
+    /// How chroma is subsampled vertically
+    pub chroma_siting_vert: Option<ChromaSiting>,
+    /// The clipping of the colour ranges
+    pub range: Option<ColourRange>,
+    /// The transfer characteristics of the video
+    pub transfer_characteristics: Option<TransferCharacteristics>,
+    /// The colour primaries of the video
+    pub primaries: Option<Primaries>,
+    /// Maximum brightness of a single pixel, in candelas per square meter
+    pub max_cll: Option<u64>,
+    /// Maximum brightness of a single full frame, in candelas per square meter
+    pub max_fall: Option<u64>,
+    /// SMPTE 2086 mastering data
+    pub mastering_metadata: Option<MasteringMetadata>,
+}
+
+impl Default for Colour {
+    fn default() -> Self {
+        Colour::new()
+    }
+}
+
+impl Colour {
+    fn new() -> Colour {
+        Colour {
+            matrix_coefficients: None,
+            bits_per_channel: None,
+            chroma_subsampling_horz: None,
+            chroma_subsampling_vert: None,
+            cb_subsampling_horz: None,
+            cb_subsampling_vert: None,
+            chroma_siting_horz: None,
+            chroma_siting_vert: None,
+            range: None,
+            transfer_characteristics: None,
+            primaries: None,
+            max_cll: None,
+            max_fall: None,
+            mastering_metadata: None,
+        }
+    }
+
+    fn build(elements: Vec<Element>) -> Colour {
+        let mut colour = Colour::new();
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::MATRIXCOEFFICIENTS,
+                    val: ElementType::UInt(matrix),
+                    ..
+                } => {
+                    colour.matrix_coefficients = Some(matrix.into());
+                }
+                Element {
+                    id: ids::BITSPERCHANNEL,
+                    val: ElementType::UInt(bits),
+                    ..
+                } => {
+                    colour.bits_per_channel = Some(bits);
+                }
+                Element {
+                    id: ids::CHROMASUBSAMPLINGHORZ,
+                    val: ElementType::UInt(pixels),
+                    ..
+                } => {
+                    colour.chroma_subsampling_horz = Some(pixels);
+                }
+                Element {
+                    id: ids::CHROMASUBSAMPLINGVERT,
+                    val: ElementType::UInt(pixels),
+                    ..
+                } => {
+                    colour.chroma_subsampling_vert = Some(pixels);
+                }
+                Element {
+                    id: ids::CBSUBSAMPLINGHORZ,
+                    val: ElementType::UInt(pixels),
+                    ..
+                } => {
+                    colour.cb_subsampling_horz = Some(pixels);
+                }
+                Element {
+                    id: ids::CBSUBSAMPLINGVERT,
+                    val: ElementType::UInt(pixels),
+                    ..
+                } => {
+                    colour.cb_subsampling_vert = Some(pixels);
+                }
+                Element {
+                    id: ids::CHROMASITINGHORZ,
+                    val: ElementType::UInt(siting),
+                    ..
+                } => {
+                    colour.chroma_siting_horz = Some(siting.into());
+                }
+                Element {
+                    id: ids::CHROMASITINGVERT,
+                    val: ElementType::UInt(siting),
+                    ..
+                } => {
+                    colour.chroma_siting_vert = Some(siting.into());
+                }
+                Element {
+                    id: ids::RANGE,
+                    val: ElementType::UInt(range),
+                    ..
+                } => {
+                    colour.range = Some(range.into());
+                }
+                Element {
+                    id: ids::TRANSFERCHARACTERISTICS,
+                    val: ElementType::UInt(transfer),
+                    ..
+                } => {
+                    colour.transfer_characteristics = Some(transfer.into());
+                }
+                Element {
+                    id: ids::PRIMARIES,
+                    val: ElementType::UInt(primaries),
+                    ..
+                } => {
+                    colour.primaries = Some(primaries.into());
+                }
+                Element {
+                    id: ids::MAXCLL,
+                    val: ElementType::UInt(max_cll),
+                    ..
+                } => {
+                    colour.max_cll = Some(max_cll);
+                }
+                Element {
+                    id: ids::MAXFALL,
+                    val: ElementType::UInt(max_fall),
+                    ..
+                } => {
+                    colour.max_fall = Some(max_fall);
+                }
+                Element {
+                    id: ids::MASTERINGMETADATA,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    colour.mastering_metadata = Some(MasteringMetadata::build(sub_elements));
+                }
+                _ => {}
+            }
+        }
+        colour
+    }
+}
+
+/// SMPTE 2086 mastering data
+///
+/// Any of the values may be absent if the file only
+/// specifies some of them.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct MasteringMetadata {
+    /// Red X chromaticity coordinate, as defined by CIE 1931
+    pub primary_r_chromaticity_x: Option<f64>,
+    /// Red Y chromaticity coordinate, as defined by CIE 1931
+    pub primary_r_chromaticity_y: Option<f64>,
+    /// Green X chromaticity coordinate, as defined by CIE 1931
+    pub primary_g_chromaticity_x: Option<f64>,
+    /// Green Y chromaticity coordinate, as defined by CIE 1931
+    pub primary_g_chromaticity_y: Option<f64>,
+    /// Blue X chromaticity coordinate, as defined by CIE 1931
+    pub primary_b_chromaticity_x: Option<f64>,
+    /// Blue Y chromaticity coordinate, as defined by CIE 1931
+    pub primary_b_chromaticity_y: Option<f64>,
+    /// White X chromaticity coordinate, as defined by CIE 1931
+    pub white_point_chromaticity_x: Option<f64>,
+    /// White Y chromaticity coordinate, as defined by CIE 1931
+    pub white_point_chromaticity_y: Option<f64>,
+    /// Maximum luminance, in candelas per square meter
+    pub luminance_max: Option<f64>,
+    /// Minimum luminance, in candelas per square meter
+    pub luminance_min: Option<f64>,
+}
+
+impl Default for MasteringMetadata {
+    fn default() -> Self {
+        MasteringMetadata::new()
+    }
+}
+
+impl MasteringMetadata {
+    fn new() -> MasteringMetadata {
+        MasteringMetadata {
+            primary_r_chromaticity_x: None,
+            primary_r_chromaticity_y: None,
+            primary_g_chromaticity_x: None,
+            primary_g_chromaticity_y: None,
+            primary_b_chromaticity_x: None,
+            primary_b_chromaticity_y: None,
+            white_point_chromaticity_x: None,
+            white_point_chromaticity_y: None,
+            luminance_max: None,
+            luminance_min: None,
+        }
+    }
+
+    fn build(elements: Vec<Element>) -> MasteringMetadata {
+        let mut metadata = MasteringMetadata::new();
+        for e in elements {
+            if let Element {
+                id,
+                val: ElementType::Float(value),
+                ..
+            } = e
+            {
+                let field = match id {
+                    ids::PRIMARYRCHROMATICITYX => &mut metadata.primary_r_chromaticity_x,
+                    ids::PRIMARYRCHROMATICITYY => &mut metadata.primary_r_chromaticity_y,
+                    ids::PRIMARYGCHROMATICITYX => &mut metadata.primary_g_chromaticity_x,
+                    ids::PRIMARYGCHROMATICITYY => &mut metadata.primary_g_chromaticity_y,
+                    ids::PRIMARYBCHROMATICITYX => &mut metadata.primary_b_chromaticity_x,
+                    ids::PRIMARYBCHROMATICITYY => &mut metadata.primary_b_chromaticity_y,
+                    ids::WHITEPOINTCHROMATICITYX => &mut metadata.white_point_chromaticity_x,
+                    ids::WHITEPOINTCHROMATICITYY => &mut metadata.white_point_chromaticity_y,
+                    ids::LUMINANCEMAX => &mut metadata.luminance_max,
+                    ids::LUMINANCEMIN => &mut metadata.luminance_min,
+                    _ => continue,
+                };
+                *field = Some(value);
+            }
+        }
+        metadata
+    }
+}
+
+/// The matrix coefficients of a video, as defined by ITU-T H.273
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MatrixCoefficients {
+    /// Identity
+    Identity,
+    /// ITU-R BT.709
+    BT709,
+    /// unspecified
+    Unspecified,
+    /// US FCC 73.682
+    FCC,
+    /// ITU-R BT.470BG
+    BT470BG,
+    /// SMPTE 170M
+    SMPTE170M,
+    /// SMPTE 240M
+    SMPTE240M,
+    /// YCoCg
+    YCoCg,
+    /// BT2020 non-constant luminance
+    BT2020NCL,
+    /// BT2020 constant luminance
+    BT2020CL,
+    /// SMPTE ST 2085
+    SMPTE2085,
+    /// chromaticity-derived non-constant luminance
+    ChromaDerivedNCL,
+    /// chromaticity-derived constant luminance
+    ChromaDerivedCL,
+    /// ITU-R BT.2100-0
+    ICtCp,
+    /// a reserved or unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for MatrixCoefficients {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => MatrixCoefficients::Identity,
+            1 => MatrixCoefficients::BT709,
+            2 => MatrixCoefficients::Unspecified,
+            4 => MatrixCoefficients::FCC,
+            5 => MatrixCoefficients::BT470BG,
+            6 => MatrixCoefficients::SMPTE170M,
+            7 => MatrixCoefficients::SMPTE240M,
+            8 => MatrixCoefficients::YCoCg,
+            9 => MatrixCoefficients::BT2020NCL,
+            10 => MatrixCoefficients::BT2020CL,
+            11 => MatrixCoefficients::SMPTE2085,
+            12 => MatrixCoefficients::ChromaDerivedNCL,
+            13 => MatrixCoefficients::ChromaDerivedCL,
+            14 => MatrixCoefficients::ICtCp,
+            val => MatrixCoefficients::Unknown(val),
+        }
+    }
+}
+
+impl MatrixCoefficients {
+    /// Returns the name of the matrix coefficients, such as `bt709`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatrixCoefficients::Identity => "identity",
+            MatrixCoefficients::BT709 => "bt709",
+            MatrixCoefficients::Unspecified => "unspecified",
+            MatrixCoefficients::FCC => "fcc",
+            MatrixCoefficients::BT470BG => "bt470bg",
+            MatrixCoefficients::SMPTE170M => "smpte170m",
+            MatrixCoefficients::SMPTE240M => "smpte240m",
+            MatrixCoefficients::YCoCg => "ycocg",
+            MatrixCoefficients::BT2020NCL => "bt2020_ncl",
+            MatrixCoefficients::BT2020CL => "bt2020_cl",
+            MatrixCoefficients::SMPTE2085 => "smpte2085",
+            MatrixCoefficients::ChromaDerivedNCL => "chroma_derived_ncl",
+            MatrixCoefficients::ChromaDerivedCL => "chroma_derived_cl",
+            MatrixCoefficients::ICtCp => "ictcp",
+            MatrixCoefficients::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// How chroma is subsampled along an axis
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ChromaSiting {
+    /// unspecified
+    Unspecified,
+    /// left collocated horizontally, or top collocated vertically
+    Collocated,
+    /// half
+    Half,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for ChromaSiting {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => ChromaSiting::Unspecified,
+            1 => ChromaSiting::Collocated,
+            2 => ChromaSiting::Half,
+            val => ChromaSiting::Unknown(val),
+        }
+    }
+}
+
+impl ChromaSiting {
+    /// Returns the chroma siting's name, such as `collocated`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChromaSiting::Unspecified => "unspecified",
+            ChromaSiting::Collocated => "collocated",
+            ChromaSiting::Half => "half",
+            ChromaSiting::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// The clipping of a video's colour ranges
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ColourRange {
+    /// unspecified
+    Unspecified,
+    /// broadcast range
+    Broadcast,
+    /// full range (no clipping)
+    Full,
+    /// defined by the matrix coefficients and transfer characteristics
+    Defined,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for ColourRange {
+    fn from(val: u64) -> Self {
+        match val {
+            0 => ColourRange::Unspecified,
+            1 => ColourRange::Broadcast,
+            2 => ColourRange::Full,
+            3 => ColourRange::Defined,
+            val => ColourRange::Unknown(val),
+        }
+    }
+}
+
+impl ColourRange {
+    /// Returns the colour range's name, such as `broadcast`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColourRange::Unspecified => "unspecified",
+            ColourRange::Broadcast => "broadcast",
+            ColourRange::Full => "full",
+            ColourRange::Defined => "defined",
+            ColourRange::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// The transfer characteristics of a video, as defined by ITU-T H.273
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TransferCharacteristics {
+    /// ITU-R BT.709
+    BT709,
+    /// unspecified
+    Unspecified,
+    /// gamma 2.2 curve - BT.470M
+    Gamma22,
+    /// gamma 2.8 curve - BT.470BG
+    Gamma28,
+    /// SMPTE 170M
+    SMPTE170M,
+    /// SMPTE 240M
+    SMPTE240M,
+    /// linear
+    Linear,
+    /// log
+    Log,
+    /// log sqrt
+    LogSqrt,
+    /// IEC 61966-2-4
+    IEC61966_2_4,
+    /// ITU-R BT.1361 extended colour gamut
+    BT1361,
+    /// IEC 61966-2-1 (sRGB)
+    IEC61966_2_1,
+    /// ITU-R BT.2020 10 bit
+    BT2020_10,
+    /// ITU-R BT.2020 12 bit
+    BT2020_12,
+    /// SMPTE ST 2084 (PQ)
+    SMPTE2084,
+    /// SMPTE ST 428-1
+    SMPTE428,
+    /// ARIB STD-B67 (HLG)
+    HLG,
+    /// a reserved or unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for TransferCharacteristics {
+    fn from(val: u64) -> Self {
+        match val {
+            1 => TransferCharacteristics::BT709,
+            2 => TransferCharacteristics::Unspecified,
+            4 => TransferCharacteristics::Gamma22,
+            5 => TransferCharacteristics::Gamma28,
+            6 => TransferCharacteristics::SMPTE170M,
+            7 => TransferCharacteristics::SMPTE240M,
+            8 => TransferCharacteristics::Linear,
+            9 => TransferCharacteristics::Log,
+            10 => TransferCharacteristics::LogSqrt,
+            11 => TransferCharacteristics::IEC61966_2_4,
+            12 => TransferCharacteristics::BT1361,
+            13 => TransferCharacteristics::IEC61966_2_1,
+            14 => TransferCharacteristics::BT2020_10,
+            15 => TransferCharacteristics::BT2020_12,
+            16 => TransferCharacteristics::SMPTE2084,
+            17 => TransferCharacteristics::SMPTE428,
+            18 => TransferCharacteristics::HLG,
+            val => TransferCharacteristics::Unknown(val),
+        }
+    }
+}
+
+impl TransferCharacteristics {
+    /// Returns the name of the transfer characteristics, such as `smpte2084`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferCharacteristics::BT709 => "bt709",
+            TransferCharacteristics::Unspecified => "unspecified",
+            TransferCharacteristics::Gamma22 => "gamma22",
+            TransferCharacteristics::Gamma28 => "gamma28",
+            TransferCharacteristics::SMPTE170M => "smpte170m",
+            TransferCharacteristics::SMPTE240M => "smpte240m",
+            TransferCharacteristics::Linear => "linear",
+            TransferCharacteristics::Log => "log",
+            TransferCharacteristics::LogSqrt => "log_sqrt",
+            TransferCharacteristics::IEC61966_2_4 => "iec61966_2_4",
+            TransferCharacteristics::BT1361 => "bt1361",
+            TransferCharacteristics::IEC61966_2_1 => "iec61966_2_1",
+            TransferCharacteristics::BT2020_10 => "bt2020_10",
+            TransferCharacteristics::BT2020_12 => "bt2020_12",
+            TransferCharacteristics::SMPTE2084 => "smpte2084",
+            TransferCharacteristics::SMPTE428 => "smpte428",
+            TransferCharacteristics::HLG => "hlg",
+            TransferCharacteristics::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// The colour primaries of a video, as defined by ITU-T H.273
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Primaries {
+    /// ITU-R BT.709
+    BT709,
+    /// unspecified
+    Unspecified,
+    /// ITU-R BT.470M
+    BT470M,
+    /// ITU-R BT.470BG - BT.601 625
+    BT470BG,
+    /// ITU-R BT.601 525 - SMPTE 170M
+    SMPTE170M,
+    /// SMPTE 240M
+    SMPTE240M,
+    /// FILM
+    Film,
+    /// ITU-R BT.2020
+    BT2020,
+    /// SMPTE ST 428-1
+    SMPTE428,
+    /// SMPTE RP 432-2 (DCI-P3)
+    SMPTE431,
+    /// SMPTE EG 432-2 (Display P3)
+    SMPTE432,
+    /// EBU Tech. 3213-E - JEDEC P22 phosphors
+    EBU3213,
+    /// a reserved or unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for Primaries {
+    fn from(val: u64) -> Self {
+        match val {
+            1 => Primaries::BT709,
+            2 => Primaries::Unspecified,
+            4 => Primaries::BT470M,
+            5 => Primaries::BT470BG,
+            6 => Primaries::SMPTE170M,
+            7 => Primaries::SMPTE240M,
+            8 => Primaries::Film,
+            9 => Primaries::BT2020,
+            10 => Primaries::SMPTE428,
+            11 => Primaries::SMPTE431,
+            12 => Primaries::SMPTE432,
+            22 => Primaries::EBU3213,
+            val => Primaries::Unknown(val),
+        }
+    }
+}
+
+impl Primaries {
+    /// Returns the name of the primaries, such as `bt2020`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Primaries::BT709 => "bt709",
+            Primaries::Unspecified => "unspecified",
+            Primaries::BT470M => "bt470m",
+            Primaries::BT470BG => "bt470bg",
+            Primaries::SMPTE170M => "smpte170m",
+            Primaries::SMPTE240M => "smpte240m",
+            Primaries::Film => "film",
+            Primaries::BT2020 => "bt2020",
+            Primaries::SMPTE428 => "smpte428",
+            Primaries::SMPTE431 => "smpte431",
+            Primaries::SMPTE432 => "smpte432",
+            Primaries::EBU3213 => "ebu3213",
+            Primaries::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// How a video track may be displayed in stereo mode
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StereoMode {
+    /// mono
+    Mono,
+    /// side-by-side
+    SideBySide(EyeOrder),
+    /// top - bottom
+    TopBottom(EyeOrder),
+    /// checkboard
+    Checkboard(EyeOrder),
+    /// row interleaved
+    RowInterleaved(EyeOrder),
+    /// column interleaved
+    ColumnInterleaved(EyeOrder),
+    /// anaglyph
+    Anaglyph(StereoColors),
+    /// interlaced
+    Interlaced(EyeOrder),
+}
+
+impl StereoMode {
+    /// Converts a raw StereoMode element value, if it's a defined one
+    pub fn from_raw(raw: u64) -> Option<StereoMode> {
+        match raw {
+            0 => Some(StereoMode::Mono),
+            1 => Some(StereoMode::SideBySide(EyeOrder::LeftFirst)),
+            2 => Some(StereoMode::TopBottom(EyeOrder::RightFirst)),
+            3 => Some(StereoMode::TopBottom(EyeOrder::LeftFirst)),
+            4 => Some(StereoMode::Checkboard(EyeOrder::RightFirst)),
+            5 => Some(StereoMode::Checkboard(EyeOrder::LeftFirst)),
+            6 => Some(StereoMode::RowInterleaved(EyeOrder::RightFirst)),
+            7 => Some(StereoMode::RowInterleaved(EyeOrder::LeftFirst)),
+            8 => Some(StereoMode::ColumnInterleaved(EyeOrder::RightFirst)),
+            9 => Some(StereoMode::ColumnInterleaved(EyeOrder::LeftFirst)),
+            10 => Some(StereoMode::Anaglyph(StereoColors::CyanRed)),
+            11 => Some(StereoMode::SideBySide(EyeOrder::RightFirst)),
+            12 => Some(StereoMode::Anaglyph(StereoColors::GreenMagenta)),
+            13 => Some(StereoMode::Interlaced(EyeOrder::LeftFirst)),
+            14 => Some(StereoMode::Interlaced(EyeOrder::RightFirst)),
+            _ => None,
+        }
+    }
+
+    /// Converts to the raw StereoMode element value
+    pub fn to_raw(self) -> u64 {
+        match self {
+            StereoMode::Mono => 0,
+            StereoMode::SideBySide(EyeOrder::LeftFirst) => 1,
+            StereoMode::TopBottom(EyeOrder::RightFirst) => 2,
+            StereoMode::TopBottom(EyeOrder::LeftFirst) => 3,
+            StereoMode::Checkboard(EyeOrder::RightFirst) => 4,
+            StereoMode::Checkboard(EyeOrder::LeftFirst) => 5,
+            StereoMode::RowInterleaved(EyeOrder::RightFirst) => 6,
+            StereoMode::RowInterleaved(EyeOrder::LeftFirst) => 7,
+            StereoMode::ColumnInterleaved(EyeOrder::RightFirst) => 8,
+            StereoMode::ColumnInterleaved(EyeOrder::LeftFirst) => 9,
+            StereoMode::Anaglyph(StereoColors::CyanRed) => 10,
+            StereoMode::SideBySide(EyeOrder::RightFirst) => 11,
+            StereoMode::Anaglyph(StereoColors::GreenMagenta) => 12,
+            StereoMode::Interlaced(EyeOrder::LeftFirst) => 13,
+            StereoMode::Interlaced(EyeOrder::RightFirst) => 14,
+        }
+    }
+
+    /// Returns the stereo mode's name, such as `side_by_side_left_first`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StereoMode::Mono => "mono",
+            StereoMode::SideBySide(EyeOrder::LeftFirst) => "side_by_side_left_first",
+            StereoMode::SideBySide(EyeOrder::RightFirst) => "side_by_side_right_first",
+            StereoMode::TopBottom(EyeOrder::LeftFirst) => "top_bottom_left_first",
+            StereoMode::TopBottom(EyeOrder::RightFirst) => "top_bottom_right_first",
+            StereoMode::Checkboard(EyeOrder::LeftFirst) => "checkboard_left_first",
+            StereoMode::Checkboard(EyeOrder::RightFirst) => "checkboard_right_first",
+            StereoMode::RowInterleaved(EyeOrder::LeftFirst) => "row_interleaved_left_first",
+            StereoMode::RowInterleaved(EyeOrder::RightFirst) => "row_interleaved_right_first",
+            StereoMode::ColumnInterleaved(EyeOrder::LeftFirst) => "column_interleaved_left_first",
+            StereoMode::ColumnInterleaved(EyeOrder::RightFirst) => "column_interleaved_right_first",
+            StereoMode::Interlaced(EyeOrder::LeftFirst) => "interlaced_left_first",
+            StereoMode::Interlaced(EyeOrder::RightFirst) => "interlaced_right_first",
+            StereoMode::Anaglyph(StereoColors::CyanRed) => "anaglyph_cyan_red",
+            StereoMode::Anaglyph(StereoColors::GreenMagenta) => "anaglyph_green_magenta",
+        }
+    }
+}
+
+impl std::fmt::Display for StereoMode {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StereoMode::Mono => write!(f, "mono"),
+            StereoMode::SideBySide(o) => write!(f, "side by side ({o})"),
+            StereoMode::TopBottom(o) => write!(f, "top - bottom ({o})"),
+            StereoMode::Checkboard(o) => write!(f, "checkboard ({o})"),
+            StereoMode::RowInterleaved(o) => write!(f, "row interleaved ({o})"),
+            StereoMode::ColumnInterleaved(o) => write!(f, "column interleaved ({o})"),
+            StereoMode::Anaglyph(c) => write!(f, "anaglyph ({c})"),
+            StereoMode::Interlaced(o) => write!(f, "interlaced ({o})"),
+        }
+    }
+}
+
+/// Which eye is displayed first
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum EyeOrder {
+    /// left eye is displayed first
+    LeftFirst,
+    /// right eye is displayed first
+    RightFirst,
+}
+
+impl std::fmt::Display for EyeOrder {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            EyeOrder::LeftFirst => write!(f, "left eye is first"),
+            EyeOrder::RightFirst => write!(f, "right eye is first"),
+        }
+    }
+}
+
+/// Which colors are used for anaglyph stereo 3D
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum StereoColors {
+    /// cyan/red
+    CyanRed,
+    /// green/magenta
+    GreenMagenta,
+}
+
+impl std::fmt::Display for StereoColors {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            StereoColors::CyanRed => write!(f, "cyan/red"),
+            StereoColors::GreenMagenta => write!(f, "green/magenta"),
+        }
+    }
+}
+
+/// An audio track's specifications
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Audio {
+    /// The sample rate in Hz
+    pub sample_rate: f64,
+    /// The number of audio channels
+    pub channels: u64,
+    /// The bit depth of each sample
+    pub bit_depth: Option<u64>,
+    /// The raw channel positions, if the muxer wrote them
+    ///
+    /// The specification defines these as a table of
+    /// horizontal and vertical angles for each channel,
+    /// which is left undecoded.
+    pub channel_positions: Option<Vec<u8>>,
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Audio::new()
+    }
+}
+
+impl Audio {
+    fn new() -> Audio {
+        Audio {
+            sample_rate: 0.0,
+            channels: 0,
+            bit_depth: None,
+            channel_positions: None,
+        }
+    }
+
+    fn build(elements: Vec<Element>) -> Audio {
+        let mut audio = Audio::new();
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::SAMPLINGFREQUENCY,
+                    val: ElementType::Float(frequency),
+                    ..
+                } => {
+                    audio.sample_rate = frequency;
+                }
+                Element {
+                    id: ids::CHANNELS,
+                    val: ElementType::UInt(channels),
+                    ..
+                } => {
+                    audio.channels = channels;
+                }
+                Element {
+                    id: ids::BITDEPTH,
+                    val: ElementType::UInt(bit_depth),
+                    ..
+                } => {
+                    audio.bit_depth = Some(bit_depth);
+                }
+                Element {
+                    id: ids::CHANNELPOSITIONS,
+                    val: ElementType::Binary(positions),
+                    ..
+                } => {
+                    audio.channel_positions = Some(positions);
+                }
+                _ => {}
+            }
+        }
+        audio
+    }
+}
+
 /// An attached file (often used for cover art)
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Attachment {
     /// A human-friendly name for the file
     pub description: Option<String>,
@@ -1041,7 +3901,31 @@ pub struct Attachment {
     /// The file's MIME type
     pub mime_type: String,
     /// The file's raw data
+    ///
+    /// This is empty if [`ParseOptions::defer_attachment_data`] is set,
+    /// in which case it's read by [`Attachment::read_data`].
     pub data: Vec<u8>,
+    /// The absolute file offset of the file's data,
+    /// or 0 if the attachment was parsed by [`get`]
+    pub data_offset: u64,
+    /// The size of the file's data in bytes
+    pub data_len: u64,
+    /// The file offset and size of deferred data
+    data_range: Option<(u64, u64)>,
+    /// The file's UID
+    pub uid: u64,
+    /// Binary data linking to an external file, a DivX extension
+    pub referral: Option<Vec<u8>>,
+    /// When the file starts being used, a DivX extension
+    pub used_start_time: Option<Duration>,
+    /// When the file stops being used, a DivX extension
+    pub used_end_time: Option<Duration>,
+}
+
+impl Default for Attachment {
+    fn default() -> Self {
+        Attachment::new()
+    }
 }
 
 impl Attachment {
@@ -1051,9 +3935,154 @@ impl Attachment {
             name: String::new(),
             mime_type: String::new(),
             data: Vec::new(),
+            data_offset: 0,
+            data_len: 0,
+            data_range: None,
+            uid: 0,
+            referral: None,
+            used_start_time: None,
+            used_end_time: None,
+        }
+    }
+
+    /// Sets the file's human-friendly name
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the file's name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the file's MIME type
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = mime_type.into();
+        self
+    }
+
+    /// Sets the file's raw data, along with its size
+    ///
+    /// This replaces any data whose reading was deferred.
+    pub fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data_len = data.len() as u64;
+        self.data_offset = 0;
+        self.data_range = None;
+        self.data = data;
+        self
+    }
+
+    /// Sets the file's UID
+    pub fn with_uid(mut self, uid: u64) -> Self {
+        self.uid = uid;
+        self
+    }
+
+    /// Sets the binary data linking to an external file
+    pub fn with_referral(mut self, referral: Vec<u8>) -> Self {
+        self.referral = Some(referral);
+        self
+    }
+
+    /// Sets when the file starts being used
+    pub fn with_used_start_time(mut self, used_start_time: Duration) -> Self {
+        self.used_start_time = Some(used_start_time);
+        self
+    }
+
+    /// Sets when the file stops being used
+    pub fn with_used_end_time(mut self, used_end_time: Duration) -> Self {
+        self.used_end_time = Some(used_end_time);
+        self
+    }
+
+    /// Returns the attached file's data
+    ///
+    /// If reading it was deferred by [`ParseOptions::defer_attachment_data`],
+    /// it's read from the given reader, which must be the file
+    /// the attachment was parsed from.
+    /// Otherwise, this returns a copy of [`Attachment::data`].
+    pub fn read_data<R: io::Read + io::Seek>(&self, mut r: R) -> Result<Vec<u8>> {
+        match self.data_range {
+            Some((offset, len)) => {
+                r.seek(io::SeekFrom::Start(offset))?;
+                ebml::read_bin(&mut r, len)
+            }
+            None => Ok(self.data.clone()),
+        }
+    }
+
+    /// Copies the attached file's data into the given writer,
+    /// returning the number of bytes written
+    ///
+    /// If reading it was deferred by [`ParseOptions::defer_attachment_data`],
+    /// it's copied from the given reader in fixed-size chunks,
+    /// which must be the file the attachment was parsed from.
+    pub fn write_data<R, W>(&self, mut r: R, mut w: W) -> Result<u64>
+    where
+        R: io::Read + io::Seek,
+        W: io::Write,
+    {
+        match self.data_range {
+            Some((offset, len)) => {
+                r.seek(io::SeekFrom::Start(offset))?;
+                let copied = io::copy(&mut r.take(len), &mut w)?;
+                if copied == len {
+                    Ok(copied)
+                } else {
+                    Err(MatroskaError::Io(io::ErrorKind::UnexpectedEof.into()))
+                }
+            }
+            None => {
+                w.write_all(&self.data)?;
+                Ok(self.data.len() as u64)
+            }
+        }
+    }
+
+    /// Returns the size of the file's data in bytes,
+    /// whether it was read or deferred
+    pub(crate) fn stored_len(&self) -> u64 {
+        match self.data_range {
+            Some((_, len)) => len,
+            None => self.data.len() as u64,
         }
     }
 
+    /// Parses an Attachments element starting at the reader's position,
+    /// recording where each file's data is and reading it unless deferred
+    fn parse_tracked<R: io::Read + io::Seek>(
+        r: &mut R,
+        size: u64,
+        options: &ParseOptions,
+    ) -> Result<Vec<Attachment>> {
+        let defer = options.defer_attachment_data;
+        let entries = parse_entries_deferring(
+            r,
+            size,
+            ids::ATTACHEDFILE,
+            ids::FILEDATA,
+            defer,
+            &options.limits(),
+        )?;
+        Ok(entries
+            .into_iter()
+            .map(|(elements, range)| {
+                let mut attachment = Attachment::build_entry(elements);
+                if let Some((offset, size)) = range {
+                    attachment.data_offset = offset;
+                    attachment.data_len = size;
+                    if defer {
+                        attachment.data_range = range;
+                    }
+                }
+                attachment
+            })
+            .collect())
+    }
+
     fn build_entry(elements: Vec<Element>) -> Attachment {
         let mut attachment = Attachment::new();
         for e in elements {
@@ -1070,21 +4099,50 @@ impl Attachment {
                     val: ElementType::UTF8(filename),
                     ..
                 } => {
-                    attachment.name = filename;
+                    attachment.name = filename;
+                }
+                Element {
+                    id: ids::FILEMIMETYPE,
+                    val: ElementType::String(mime_type),
+                    ..
+                } => {
+                    attachment.mime_type = mime_type;
+                }
+                Element {
+                    id: ids::FILEDATA,
+                    val: ElementType::Binary(data),
+                    ..
+                } => {
+                    attachment.data_len = data.len() as u64;
+                    attachment.data = data;
+                }
+                Element {
+                    id: ids::FILEUID,
+                    val: ElementType::UInt(uid),
+                    ..
+                } => {
+                    attachment.uid = uid;
+                }
+                Element {
+                    id: ids::FILEREFERRAL,
+                    val: ElementType::Binary(referral),
+                    ..
+                } => {
+                    attachment.referral = Some(referral);
                 }
                 Element {
-                    id: ids::FILEMIMETYPE,
-                    val: ElementType::String(mime_type),
+                    id: ids::FILEUSEDSTARTTIME,
+                    val: ElementType::UInt(start),
                     ..
                 } => {
-                    attachment.mime_type = mime_type;
+                    attachment.used_start_time = Some(Duration::from_nanos(start));
                 }
                 Element {
-                    id: ids::FILEDATA,
-                    val: ElementType::Binary(data),
+                    id: ids::FILEUSEDENDTIME,
+                    val: ElementType::UInt(end),
                     ..
                 } => {
-                    attachment.data = data;
+                    attachment.used_end_time = Some(Duration::from_nanos(end));
                 }
                 _ => {}
             }
@@ -1099,24 +4157,21 @@ impl Parseable for Attachment {
     const ID: u32 = ids::ATTACHMENTS;
 
     fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<Vec<Attachment>> {
-        Element::parse_master(r, size, Some(ids::ATTACHEDFILE)).map(|elements| {
-            elements
-                .into_iter()
-                .filter_map(|e| match e {
-                    Element {
-                        id: ids::ATTACHEDFILE,
-                        val: ElementType::Master(sub_elements),
-                        ..
-                    } => Some(Attachment::build_entry(sub_elements)),
-                    _ => None,
-                })
-                .collect()
-        })
+        Attachment::parse_limited(r, size, &Limits::default())
+    }
+
+    fn parse_with<R: io::Read>(
+        r: &mut R,
+        size: u64,
+        options: &ParseOptions,
+    ) -> Result<Vec<Attachment>> {
+        Attachment::parse_limited(r, size, &options.limits())
     }
 }
 
 /// A complete set of chapters
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ChapterEdition {
     /// The edition's UID
     pub uid: Option<u64>,
@@ -1130,6 +4185,34 @@ pub struct ChapterEdition {
     pub chapters: Vec<Chapter>,
 }
 
+impl Attachment {
+    fn parse_limited<R: io::Read>(
+        r: &mut R,
+        size: u64,
+        limits: &Limits,
+    ) -> Result<Vec<Attachment>> {
+        Element::parse_master_with(r, size, Some(ids::ATTACHEDFILE), limits).map(|elements| {
+            elements
+                .into_iter()
+                .filter_map(|e| match e {
+                    Element {
+                        id: ids::ATTACHEDFILE,
+                        val: ElementType::Master(sub_elements),
+                        ..
+                    } => Some(Attachment::build_entry(sub_elements)),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+}
+
+impl Default for ChapterEdition {
+    fn default() -> Self {
+        ChapterEdition::new()
+    }
+}
+
 impl ChapterEdition {
     fn new() -> ChapterEdition {
         ChapterEdition {
@@ -1141,6 +4224,144 @@ impl ChapterEdition {
         }
     }
 
+    /// Sets the edition's UID
+    pub fn with_uid(mut self, uid: u64) -> Self {
+        self.uid = Some(uid);
+        self
+    }
+
+    /// Sets whether the chapters are hidden
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Sets whether the edition is the default
+    pub fn with_default(mut self, default: bool) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Sets whether the chapter order is enforced
+    pub fn with_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// Appends a chapter
+    pub fn with_chapter(mut self, chapter: Chapter) -> Self {
+        self.chapters.push(chapter);
+        self
+    }
+
+    /// Returns how long the edition takes to play
+    ///
+    /// For ordered editions, this is the sum of the lengths
+    /// of its top-level chapters, which may be shorter or longer than
+    /// the segment itself, or `None` if any of them lacks an end time.
+    /// Nested chapters are not counted separately,
+    /// since they play as part of their parent.
+    /// For other editions, this is the last chapter's end time, if any.
+    pub fn virtual_duration(&self) -> Option<Duration> {
+        if self.ordered {
+            self.chapters
+                .iter()
+                .map(|c| c.time_end.map(|end| end.saturating_sub(c.time_start)))
+                .sum()
+        } else {
+            self.chapters.last().and_then(|c| c.time_end)
+        }
+    }
+
+    /// Iterates over all the edition's chapters, including nested ones
+    pub fn iter_all(&self) -> impl Iterator<Item = &Chapter> {
+        self.chapters.iter().flat_map(|c| c.iter_all())
+    }
+
+    /// Writes editions as a Chapters element, returning its size
+    ///
+    /// Chapter UIDs of 0, and edition UIDs of `Some(0)`,
+    /// are replaced by random UIDs unused by any other
+    /// chapter or edition, since the specification forbids 0.
+    /// Editions without a UID are written without one.
+    ///
+    /// ## Example
+    /// ```
+    /// use matroska::{Chapter, ChapterDisplay, ChapterEdition, Parseable};
+    /// use std::time::Duration;
+    ///
+    /// let editions = vec![ChapterEdition::default().with_uid(1).with_chapter(
+    ///     Chapter::default()
+    ///         .with_uid(2)
+    ///         .with_time_start(Duration::from_secs(90))
+    ///         .with_display(ChapterDisplay::default().with_string("Intro")),
+    /// )];
+    /// let mut data = Vec::new();
+    /// ChapterEdition::write_all(&editions, &mut data).unwrap();
+    /// // skip the Chapters element's 4 byte ID and 1 byte size
+    /// let parsed = ChapterEdition::parse(&mut &data[5..], data.len() as u64 - 5).unwrap();
+    /// assert_eq!(parsed, editions);
+    /// ```
+    pub fn write_all<W: io::Write>(editions: &[ChapterEdition], w: &mut W) -> Result<u64> {
+        let element = ebml::master_element(ids::CHAPTERS, vec![Self::encode_all(editions)]);
+        w.write_all(&element)?;
+        Ok(element.len() as u64)
+    }
+
+    /// Encodes editions as the body of a Chapters element,
+    /// generating any missing UIDs
+    fn encode_all(editions: &[ChapterEdition]) -> Vec<u8> {
+        fn fill_chapter(chapter: &mut Chapter, taken: &mut BTreeSet<u64>) {
+            if chapter.uid == 0 {
+                chapter.uid = unused_uid(taken);
+            }
+            for nested in &mut chapter.chapters {
+                fill_chapter(nested, taken);
+            }
+        }
+
+        let mut taken: BTreeSet<u64> = editions
+            .iter()
+            .filter_map(|e| e.uid)
+            .chain(editions.iter().flat_map(|e| e.iter_all().map(|c| c.uid)))
+            .collect();
+        let mut editions = editions.to_vec();
+        for edition in &mut editions {
+            if edition.uid == Some(0) {
+                edition.uid = Some(unused_uid(&mut taken));
+            }
+            for chapter in &mut edition.chapters {
+                fill_chapter(chapter, &mut taken);
+            }
+        }
+        editions
+            .iter()
+            .map(|e| e.encode())
+            .collect::<Vec<_>>()
+            .concat()
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut children = Vec::new();
+        if let Some(uid) = self.uid {
+            children.push(ebml::uint_element(ids::EDITIONUID, uid));
+        }
+        children.push(ebml::uint_element(
+            ids::EDITIONFLAGHIDDEN,
+            self.hidden as u64,
+        ));
+        children.push(ebml::uint_element(
+            ids::EDITIONFLAGDEFAULT,
+            self.default as u64,
+        ));
+        children.push(ebml::uint_element(
+            ids::EDITIONFLAGORDERED,
+            self.ordered as u64,
+        ));
+        children.extend(self.chapters.iter().map(|c| c.encode()));
+        ebml::master_element(ids::EDITIONENTRY, children)
+    }
+
     fn build_entry(elements: Vec<Element>) -> ChapterEdition {
         let mut chapteredition = ChapterEdition::new();
         for e in elements {
@@ -1187,33 +4408,48 @@ impl ChapterEdition {
     }
 }
 
+/// Returns a random UID which isn't 0 or already taken, and takes it
+fn unused_uid(taken: &mut BTreeSet<u64>) -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let state = RandomState::new();
+    (0u64..)
+        .map(|n| {
+            let mut hasher = state.build_hasher();
+            hasher.write_u64(n);
+            hasher.finish()
+        })
+        .find(|uid| *uid != 0 && taken.insert(*uid))
+        .expect("some UID is unused")
+}
+
 impl Parseable for ChapterEdition {
     type Output = Vec<ChapterEdition>;
 
     const ID: u32 = ids::CHAPTERS;
 
     fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<Vec<ChapterEdition>> {
-        Element::parse_master(r, size, Some(ids::EDITIONENTRY)).map(|elements| {
-            elements
-                .into_iter()
-                .filter_map(|e| match e {
-                    Element {
-                        id: ids::EDITIONENTRY,
-                        val: ElementType::Master(sub_elements),
-                        ..
-                    } => Some(ChapterEdition::build_entry(sub_elements)),
-                    _ => None,
-                })
-                .collect()
-        })
+        ChapterEdition::parse_limited(r, size, &Limits::default())
+    }
+
+    fn parse_with<R: io::Read>(
+        r: &mut R,
+        size: u64,
+        options: &ParseOptions,
+    ) -> Result<Vec<ChapterEdition>> {
+        ChapterEdition::parse_limited(r, size, &options.limits())
     }
 }
 
 /// An individual chapter point
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Chapter {
     /// The chapter's UID
     pub uid: u64,
+    /// The chapter's textual UID, such as a WebVTT cue identifier
+    pub string_uid: Option<String>,
     /// Timestamp of the start of the chapter
     pub time_start: Duration,
     /// Timestamp of the end of the chapter
@@ -1226,22 +4462,178 @@ pub struct Chapter {
     pub segment_uid: Option<Vec<u8>>,
     /// Unique ID of the edition to play from the linked segment
     pub segment_edition_uid: Option<u64>,
+    /// The physical level the chapter corresponds to
+    pub physical_equiv: Option<PhysicalEquiv>,
     /// Contains all strings to use for displaying chapter
     pub display: Vec<ChapterDisplay>,
+    /// Chapters nested within this one
+    pub chapters: Vec<Chapter>,
+    /// Commands to run for menu navigation
+    pub process: Vec<ChapterProcess>,
+}
+
+impl ChapterEdition {
+    fn parse_limited<R: io::Read>(
+        r: &mut R,
+        size: u64,
+        limits: &Limits,
+    ) -> Result<Vec<ChapterEdition>> {
+        Element::parse_master_with(r, size, Some(ids::EDITIONENTRY), limits).map(|elements| {
+            elements
+                .into_iter()
+                .filter_map(|e| match e {
+                    Element {
+                        id: ids::EDITIONENTRY,
+                        val: ElementType::Master(sub_elements),
+                        ..
+                    } => Some(ChapterEdition::build_entry(sub_elements)),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+}
+
+impl Default for Chapter {
+    fn default() -> Self {
+        Chapter::new()
+    }
 }
 
 impl Chapter {
     fn new() -> Chapter {
         Chapter {
             uid: 0,
+            string_uid: None,
             time_start: Duration::default(),
             time_end: None,
             hidden: false,
-            enabled: false,
+            enabled: true,
             segment_uid: None,
             segment_edition_uid: None,
+            physical_equiv: None,
             display: Vec::new(),
+            chapters: Vec::new(),
+            process: Vec::new(),
+        }
+    }
+
+    /// Sets the chapter's UID
+    pub fn with_uid(mut self, uid: u64) -> Self {
+        self.uid = uid;
+        self
+    }
+
+    /// Sets the chapter's textual UID
+    pub fn with_string_uid(mut self, string_uid: impl Into<String>) -> Self {
+        self.string_uid = Some(string_uid.into());
+        self
+    }
+
+    /// Sets the chapter's start time
+    pub fn with_time_start(mut self, time_start: Duration) -> Self {
+        self.time_start = time_start;
+        self
+    }
+
+    /// Sets the chapter's end time
+    pub fn with_time_end(mut self, time_end: Duration) -> Self {
+        self.time_end = Some(time_end);
+        self
+    }
+
+    /// Sets whether the chapter is hidden
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Sets whether the chapter is enabled
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Appends a display string
+    pub fn with_display(mut self, display: ChapterDisplay) -> Self {
+        self.display.push(display);
+        self
+    }
+
+    /// Appends a nested chapter
+    pub fn with_chapter(mut self, chapter: Chapter) -> Self {
+        self.chapters.push(chapter);
+        self
+    }
+
+    /// Appends a set of menu commands
+    pub fn with_process(mut self, process: ChapterProcess) -> Self {
+        self.process.push(process);
+        self
+    }
+
+    /// Iterates over this chapter and all chapters nested within it
+    pub fn iter_all(&self) -> impl Iterator<Item = &Chapter> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let chapter = stack.pop()?;
+            stack.extend(chapter.chapters.iter().rev());
+            Some(chapter)
+        })
+    }
+
+    /// Writes the chapter as a ChapterAtom element, returning its size
+    ///
+    /// Unlike Cluster and Cue timestamps, chapter times are
+    /// always stored in nanoseconds regardless of the file's
+    /// timestamp scale, so they're written from the
+    /// `Duration` values verbatim without any scaling.
+    pub fn write<W: io::Write>(&self, w: &mut W) -> Result<u64> {
+        let atom = self.encode();
+        w.write_all(&atom)?;
+        Ok(atom.len() as u64)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        fn nanos(d: Duration) -> u64 {
+            d.as_nanos().min(u64::MAX.into()) as u64
+        }
+
+        let mut children = vec![ebml::uint_element(ids::CHAPTERUID, self.uid)];
+        if let Some(string_uid) = &self.string_uid {
+            children.push(ebml::string_element(ids::CHAPTERSTRINGUID, string_uid));
+        }
+        children.push(ebml::uint_element(
+            ids::CHAPTERTIMESTART,
+            nanos(self.time_start),
+        ));
+        if let Some(end) = self.time_end {
+            children.push(ebml::uint_element(ids::CHAPTERTIMEEND, nanos(end)));
+        }
+        children.push(ebml::uint_element(
+            ids::CHAPTERFLAGHIDDEN,
+            self.hidden as u64,
+        ));
+        children.push(ebml::uint_element(
+            ids::CHAPTERFLAGENABLED,
+            self.enabled as u64,
+        ));
+        if let Some(uid) = &self.segment_uid {
+            children.push(ebml::element(ids::CHAPTERSEGMENTUID, uid));
+        }
+        if let Some(uid) = self.segment_edition_uid {
+            children.push(ebml::uint_element(ids::CHAPTERSEGMENTEDITIONUID, uid));
+        }
+        if let Some(equiv) = self.physical_equiv {
+            children.push(ebml::uint_element(
+                ids::CHAPTERPHYSICALEQUIV,
+                equiv.to_raw(),
+            ));
         }
+        children.extend(self.chapters.iter().map(|c| c.encode()));
+        children.extend(self.display.iter().map(|d| d.encode()));
+        children.extend(self.process.iter().map(|p| p.encode()));
+        ebml::master_element(ids::CHAPTERATOM, children)
     }
 
     fn build(elements: Vec<Element>) -> Chapter {
@@ -1255,6 +4647,13 @@ impl Chapter {
                 } => {
                     chapter.uid = uid;
                 }
+                Element {
+                    id: ids::CHAPTERSTRINGUID,
+                    val: ElementType::UTF8(string_uid),
+                    ..
+                } => {
+                    chapter.string_uid = Some(string_uid);
+                }
                 Element {
                     id: ids::CHAPTERTIMESTART,
                     val: ElementType::UInt(start),
@@ -1297,6 +4696,13 @@ impl Chapter {
                 } => {
                     chapter.segment_edition_uid = Some(uid);
                 }
+                Element {
+                    id: ids::CHAPTERPHYSICALEQUIV,
+                    val: ElementType::UInt(equiv),
+                    ..
+                } => {
+                    chapter.physical_equiv = Some(PhysicalEquiv::from(equiv));
+                }
                 Element {
                     id: ids::CHAPTERDISPLAY,
                     val: ElementType::Master(sub_elements),
@@ -1304,6 +4710,20 @@ impl Chapter {
                 } => {
                     chapter.display.push(ChapterDisplay::build(sub_elements));
                 }
+                Element {
+                    id: ids::CHAPTERATOM,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    chapter.chapters.push(Chapter::build(sub_elements));
+                }
+                Element {
+                    id: ids::CHAPPROCESS,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    chapter.process.push(ChapterProcess::build(sub_elements));
+                }
                 _ => {}
             }
         }
@@ -1311,13 +4731,94 @@ impl Chapter {
     }
 }
 
+/// The physical level a chapter corresponds to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PhysicalEquiv {
+    /// a set of media or package
+    Set,
+    /// a CD, DVD or other medium
+    Medium,
+    /// a side of a medium
+    Side,
+    /// a layer of a medium
+    Layer,
+    /// a session of a medium
+    Session,
+    /// a track
+    Track,
+    /// an index within a track
+    Index,
+    /// an unknown value
+    Unknown(u64),
+}
+
+impl From<u64> for PhysicalEquiv {
+    fn from(val: u64) -> Self {
+        match val {
+            70 => PhysicalEquiv::Set,
+            60 => PhysicalEquiv::Medium,
+            50 => PhysicalEquiv::Side,
+            40 => PhysicalEquiv::Layer,
+            30 => PhysicalEquiv::Session,
+            20 => PhysicalEquiv::Track,
+            10 => PhysicalEquiv::Index,
+            val => PhysicalEquiv::Unknown(val),
+        }
+    }
+}
+
+impl PhysicalEquiv {
+    /// Converts to the raw ChapterPhysicalEquiv element value
+    pub fn to_raw(self) -> u64 {
+        match self {
+            PhysicalEquiv::Set => 70,
+            PhysicalEquiv::Medium => 60,
+            PhysicalEquiv::Side => 50,
+            PhysicalEquiv::Layer => 40,
+            PhysicalEquiv::Session => 30,
+            PhysicalEquiv::Track => 20,
+            PhysicalEquiv::Index => 10,
+            PhysicalEquiv::Unknown(val) => val,
+        }
+    }
+
+    /// Returns the physical level's name, such as `medium`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PhysicalEquiv::Set => "set",
+            PhysicalEquiv::Medium => "medium",
+            PhysicalEquiv::Side => "side",
+            PhysicalEquiv::Layer => "layer",
+            PhysicalEquiv::Session => "session",
+            PhysicalEquiv::Track => "track",
+            PhysicalEquiv::Index => "index",
+            PhysicalEquiv::Unknown(_) => "unknown",
+        }
+    }
+}
+
 /// The display string for a chapter point entry
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ChapterDisplay {
     /// The user interface string
     pub string: String,
     /// The string's language
+    ///
+    /// If the display has several languages,
+    /// this is the last IETF one if any, or the last ISO 639 one otherwise.
     pub language: Language,
+    /// All of the string's languages, in the order they're stored
+    pub languages: Vec<Language>,
+    /// The countries the string applies to, as ccTLD codes
+    pub countries: Vec<String>,
+}
+
+impl Default for ChapterDisplay {
+    fn default() -> Self {
+        ChapterDisplay::new()
+    }
 }
 
 impl ChapterDisplay {
@@ -1325,7 +4826,52 @@ impl ChapterDisplay {
         ChapterDisplay {
             string: String::new(),
             language: Language::ISO639(String::new()),
+            languages: Vec::new(),
+            countries: Vec::new(),
+        }
+    }
+
+    /// Sets the user interface string
+    pub fn with_string(mut self, string: impl Into<String>) -> Self {
+        self.string = string.into();
+        self
+    }
+
+    /// Sets the string's language, replacing any others
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.languages = vec![language.clone()];
+        self.language = language;
+        self
+    }
+
+    /// Appends a country the string applies to
+    pub fn with_country(mut self, country: impl Into<String>) -> Self {
+        self.countries.push(country.into());
+        self
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut children = vec![ebml::string_element(ids::CHAPSTRING, &self.string)];
+        let languages = if self.languages.is_empty() {
+            std::slice::from_ref(&self.language)
+        } else {
+            &self.languages[..]
+        };
+        for language in languages {
+            match language {
+                Language::ISO639(language) if language.is_empty() => {}
+                Language::ISO639(language) => {
+                    children.push(ebml::string_element(ids::CHAPLANGUAGE, language))
+                }
+                Language::IETF(language) => {
+                    children.push(ebml::string_element(ids::CHAPLANGUAGE_IETF, language))
+                }
+            }
         }
+        for country in &self.countries {
+            children.push(ebml::string_element(ids::CHAPCOUNTRY, country));
+        }
+        ebml::master_element(ids::CHAPTERDISPLAY, children)
     }
 
     fn build(elements: Vec<Element>) -> ChapterDisplay {
@@ -1344,16 +4890,27 @@ impl ChapterDisplay {
                     val: ElementType::String(language),
                     ..
                 } => {
+                    let language = Language::ISO639(language);
                     if !matches!(display.language, Language::IETF(_)) {
-                        display.language = Language::ISO639(language);
+                        display.language = language.clone();
                     }
+                    display.languages.push(language);
                 }
                 Element {
                     id: ids::CHAPLANGUAGE_IETF,
                     val: ElementType::String(language),
                     ..
                 } => {
-                    display.language = Language::IETF(language);
+                    let language = Language::IETF(language);
+                    display.language = language.clone();
+                    display.languages.push(language);
+                }
+                Element {
+                    id: ids::CHAPCOUNTRY,
+                    val: ElementType::String(country),
+                    ..
+                } => {
+                    display.countries.push(country);
                 }
                 _ => {}
             }
@@ -1362,8 +4919,124 @@ impl ChapterDisplay {
     }
 }
 
+/// Commands a chapter codec runs for a chapter
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ChapterProcess {
+    /// The chapter codec, where 0 is Matroska Script and 1 is DVD-menu
+    pub codec_id: u64,
+    /// Data private to the codec
+    pub private: Option<Vec<u8>>,
+    /// The commands as pairs of when they run and their data,
+    /// where 0 is during the whole chapter,
+    /// 1 is before it starts and 2 is after it ends
+    pub commands: Vec<(u64, Vec<u8>)>,
+}
+
+impl Default for ChapterProcess {
+    fn default() -> Self {
+        ChapterProcess::new()
+    }
+}
+
+impl ChapterProcess {
+    fn new() -> ChapterProcess {
+        ChapterProcess {
+            codec_id: 0,
+            private: None,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Sets the chapter codec
+    pub fn with_codec_id(mut self, codec_id: u64) -> Self {
+        self.codec_id = codec_id;
+        self
+    }
+
+    /// Sets the codec's private data
+    pub fn with_private(mut self, private: Vec<u8>) -> Self {
+        self.private = Some(private);
+        self
+    }
+
+    /// Appends a command with when it runs and its data
+    pub fn with_command(mut self, time: u64, data: Vec<u8>) -> Self {
+        self.commands.push((time, data));
+        self
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut children = vec![ebml::uint_element(ids::CHAPPROCESSCODECID, self.codec_id)];
+        if let Some(private) = &self.private {
+            children.push(ebml::element(ids::CHAPPROCESSPRIVATE, private));
+        }
+        children.extend(self.commands.iter().map(|(time, data)| {
+            ebml::master_element(
+                ids::CHAPPROCESSCOMMAND,
+                vec![
+                    ebml::uint_element(ids::CHAPPROCESSTIME, *time),
+                    ebml::element(ids::CHAPPROCESSDATA, data),
+                ],
+            )
+        }));
+        ebml::master_element(ids::CHAPPROCESS, children)
+    }
+
+    fn build(elements: Vec<Element>) -> ChapterProcess {
+        let mut process = ChapterProcess::new();
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::CHAPPROCESSCODECID,
+                    val: ElementType::UInt(codec_id),
+                    ..
+                } => {
+                    process.codec_id = codec_id;
+                }
+                Element {
+                    id: ids::CHAPPROCESSPRIVATE,
+                    val: ElementType::Binary(private),
+                    ..
+                } => {
+                    process.private = Some(private);
+                }
+                Element {
+                    id: ids::CHAPPROCESSCOMMAND,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    let mut time = None;
+                    let mut data = None;
+                    for e in sub_elements {
+                        match e {
+                            Element {
+                                id: ids::CHAPPROCESSTIME,
+                                val: ElementType::UInt(t),
+                                ..
+                            } => time = Some(t),
+                            Element {
+                                id: ids::CHAPPROCESSDATA,
+                                val: ElementType::Binary(d),
+                                ..
+                            } => data = Some(d),
+                            _ => {}
+                        }
+                    }
+                    if let (Some(time), Some(data)) = (time, data) {
+                        process.commands.push((time, data));
+                    }
+                }
+                _ => {}
+            }
+        }
+        process
+    }
+}
+
 /// An attached tag
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Tag {
     /// which elements the metadata's tag applies to
     pub targets: Option<Target>,
@@ -1371,6 +5044,12 @@ pub struct Tag {
     pub simple: Vec<SimpleTag>,
 }
 
+impl Default for Tag {
+    fn default() -> Self {
+        Tag::new()
+    }
+}
+
 impl Tag {
     fn new() -> Tag {
         Tag {
@@ -1379,6 +5058,53 @@ impl Tag {
         }
     }
 
+    /// Sets what the tag applies to
+    pub fn with_targets(mut self, targets: Target) -> Self {
+        self.targets = Some(targets);
+        self
+    }
+
+    /// Appends a simple tag
+    pub fn with_simple(mut self, simple: SimpleTag) -> Self {
+        self.simple.push(simple);
+        self
+    }
+
+    /// Writes tags as a Tags element, returning its size
+    ///
+    /// A target without a TargetTypeValue is written without one,
+    /// and so reads back as the default [`TargetTypeValue::Episode`].
+    ///
+    /// ## Example
+    /// ```
+    /// use matroska::{Parseable, SimpleTag, Tag, TagValue};
+    ///
+    /// let tags = vec![Tag::default().with_simple(
+    ///     SimpleTag::default()
+    ///         .with_name("TITLE")
+    ///         .with_value(TagValue::String("Title".to_owned())),
+    /// )];
+    /// let mut data = Vec::new();
+    /// Tag::write_all(&tags, &mut data).unwrap();
+    /// // skip the Tags element's 4 byte ID and 1 byte size
+    /// let parsed = Tag::parse(&mut &data[5..], data.len() as u64 - 5).unwrap();
+    /// assert_eq!(parsed, tags);
+    /// ```
+    pub fn write_all<W: io::Write>(tags: &[Tag], w: &mut W) -> Result<u64> {
+        let element = ebml::master_element(ids::TAGS, tags.iter().map(|t| t.encode()).collect());
+        w.write_all(&element)?;
+        Ok(element.len() as u64)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut children = Vec::new();
+        if let Some(targets) = &self.targets {
+            children.push(targets.encode());
+        }
+        children.extend(self.simple.iter().map(|s| s.encode()));
+        ebml::master_element(ids::TAG, children)
+    }
+
     fn build_entry(elements: Vec<Element>) -> Tag {
         let mut tag = Tag::new();
         for e in elements {
@@ -1410,24 +5136,17 @@ impl Parseable for Tag {
     const ID: u32 = ids::TAGS;
 
     fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<Vec<Tag>> {
-        Element::parse_master(r, size, Some(ids::TAG)).map(|elements| {
-            elements
-                .into_iter()
-                .filter_map(|e| match e {
-                    Element {
-                        id: ids::TAG,
-                        val: ElementType::Master(sub_elements),
-                        ..
-                    } => Some(Tag::build_entry(sub_elements)),
-                    _ => None,
-                })
-                .collect()
-        })
+        Tag::parse_limited(r, size, &Limits::default())
+    }
+
+    fn parse_with<R: io::Read>(r: &mut R, size: u64, options: &ParseOptions) -> Result<Vec<Tag>> {
+        Tag::parse_limited(r, size, &options.limits())
     }
 }
 
 /// Which elements the metadata's tag applies to
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Target {
     /// Logical level of target
     pub target_type_value: Option<TargetTypeValue>,
@@ -1443,8 +5162,27 @@ pub struct Target {
     pub attachment_uids: Vec<u64>,
 }
 
+impl Tag {
+    fn parse_limited<R: io::Read>(r: &mut R, size: u64, limits: &Limits) -> Result<Vec<Tag>> {
+        Element::parse_master_with(r, size, Some(ids::TAG), limits).map(|elements| {
+            elements
+                .into_iter()
+                .filter_map(|e| match e {
+                    Element {
+                        id: ids::TAG,
+                        val: ElementType::Master(sub_elements),
+                        ..
+                    } => Some(Tag::build_entry(sub_elements)),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+}
+
 /// The type of value the tag is for
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum TargetTypeValue {
     /// collection
     Collection,
@@ -1478,6 +5216,21 @@ impl TargetTypeValue {
             TargetTypeValue::Unknown => "unknown",
         }
     }
+
+    /// Converts to the raw TargetTypeValue element value,
+    /// with [`TargetTypeValue::Unknown`] as 0
+    pub fn to_raw(self) -> u64 {
+        match self {
+            TargetTypeValue::Collection => 70,
+            TargetTypeValue::Season => 60,
+            TargetTypeValue::Episode => 50,
+            TargetTypeValue::Part => 40,
+            TargetTypeValue::Chapter => 30,
+            TargetTypeValue::Scene => 20,
+            TargetTypeValue::Shot => 10,
+            TargetTypeValue::Unknown => 0,
+        }
+    }
 }
 
 impl std::fmt::Display for TargetTypeValue {
@@ -1502,6 +5255,12 @@ impl From<u64> for TargetTypeValue {
     }
 }
 
+impl Default for Target {
+    fn default() -> Self {
+        Target::new()
+    }
+}
+
 impl Target {
     fn new() -> Target {
         Target {
@@ -1514,6 +5273,62 @@ impl Target {
         }
     }
 
+    /// Sets the target's logical level
+    pub fn with_target_type_value(mut self, target_type_value: TargetTypeValue) -> Self {
+        self.target_type_value = Some(target_type_value);
+        self
+    }
+
+    /// Sets the target level's informational string
+    pub fn with_target_type(mut self, target_type: impl Into<String>) -> Self {
+        self.target_type = Some(target_type.into());
+        self
+    }
+
+    /// Appends a track UID
+    pub fn with_track_uid(mut self, track_uid: u64) -> Self {
+        self.track_uids.push(track_uid);
+        self
+    }
+
+    /// Appends an edition UID
+    pub fn with_edition_uid(mut self, edition_uid: u64) -> Self {
+        self.edition_uids.push(edition_uid);
+        self
+    }
+
+    /// Appends a chapter UID
+    pub fn with_chapter_uid(mut self, chapter_uid: u64) -> Self {
+        self.chapter_uids.push(chapter_uid);
+        self
+    }
+
+    /// Appends an attachment UID
+    pub fn with_attachment_uid(mut self, attachment_uid: u64) -> Self {
+        self.attachment_uids.push(attachment_uid);
+        self
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut children = Vec::new();
+        if let Some(value) = self.target_type_value {
+            children.push(ebml::uint_element(ids::TARGETTYPEVALUE, value.to_raw()));
+        }
+        if let Some(target_type) = &self.target_type {
+            children.push(ebml::string_element(ids::TARGETTYPE, target_type));
+        }
+        let uids = [
+            (ids::TAG_TRACK_UID, &self.track_uids),
+            (ids::TAG_EDITION_UID, &self.edition_uids),
+            (ids::TAG_CHAPTER_UID, &self.chapter_uids),
+            (ids::TAG_ATTACHMENT_UID, &self.attachment_uids),
+        ];
+        for (id, uids) in uids {
+            children.extend(uids.iter().map(|uid| ebml::uint_element(id, *uid)));
+        }
+        ebml::master_element(ids::TARGETS, children)
+    }
+
     fn build_entry(elements: Vec<Element>) -> Target {
         let mut target = Target::new();
         for e in elements {
@@ -1563,162 +5378,1013 @@ impl Target {
                 _ => {}
             }
         }
-        target
+        target
+    }
+}
+
+/// General information about the target
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SimpleTag {
+    /// The tag's name
+    pub name: String,
+    /// The tag's language
+    pub language: Option<Language>,
+    /// Whether this is the default/original language to use
+    pub default: bool,
+    /// The tag's value
+    pub value: Option<TagValue>,
+    /// Simple tags nested within this one, which describe it further,
+    /// such as the URL of an artist
+    pub simple: Vec<SimpleTag>,
+}
+
+impl Default for SimpleTag {
+    fn default() -> Self {
+        SimpleTag::new()
+    }
+}
+
+impl SimpleTag {
+    fn new() -> SimpleTag {
+        SimpleTag {
+            name: String::new(),
+            language: None,
+            default: false,
+            value: None,
+            simple: Vec::new(),
+        }
+    }
+
+    /// Sets the tag's name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the tag's language
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Sets whether this is the default language
+    pub fn with_default(mut self, default: bool) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Sets the tag's value
+    pub fn with_value(mut self, value: TagValue) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Adds a simple tag nested within this one
+    pub fn with_simple(mut self, simple: SimpleTag) -> Self {
+        self.simple.push(simple);
+        self
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut children = vec![ebml::string_element(ids::TAGNAME, &self.name)];
+        match &self.language {
+            Some(Language::ISO639(language)) => {
+                children.push(ebml::string_element(ids::TAGLANGUAGE, language))
+            }
+            Some(Language::IETF(language)) => {
+                children.push(ebml::string_element(ids::TAGLANGUAGE_IETF, language))
+            }
+            None => {}
+        }
+        // written even when unset, since it defaults to set
+        children.push(ebml::uint_element(ids::TAGDEFAULT, self.default as u64));
+        match &self.value {
+            Some(TagValue::String(string)) => {
+                children.push(ebml::string_element(ids::TAGSTRING, string))
+            }
+            Some(TagValue::Binary(binary)) => children.push(ebml::element(ids::TAGBINARY, binary)),
+            None => {}
+        }
+        children.extend(self.simple.iter().map(|s| s.encode()));
+        ebml::master_element(ids::SIMPLETAG, children)
+    }
+
+    fn build_entry(elements: Vec<Element>) -> SimpleTag {
+        let mut tag = SimpleTag::new();
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::TAGNAME,
+                    val: ElementType::UTF8(string),
+                    ..
+                } => {
+                    tag.name = string;
+                }
+                Element {
+                    id: ids::TAGLANGUAGE,
+                    val: ElementType::String(string),
+                    ..
+                } if !matches!(tag.language, Some(Language::IETF(_))) => {
+                    tag.language = Some(Language::ISO639(string));
+                }
+                Element {
+                    id: ids::TAGLANGUAGE_IETF,
+                    val: ElementType::String(string),
+                    ..
+                } => {
+                    tag.language = Some(Language::IETF(string));
+                }
+                Element {
+                    id: ids::TAGDEFAULT,
+                    val: ElementType::UInt(default),
+                    ..
+                } => {
+                    tag.default = default != 0;
+                }
+                Element {
+                    id: ids::TAGSTRING,
+                    val: ElementType::UTF8(string),
+                    ..
+                } => {
+                    tag.value = Some(TagValue::String(string));
+                }
+                Element {
+                    id: ids::TAGBINARY,
+                    val: ElementType::Binary(binary),
+                    ..
+                } => {
+                    tag.value = Some(TagValue::Binary(binary));
+                }
+                Element {
+                    id: ids::SIMPLETAG,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    tag.simple.push(SimpleTag::build_entry(sub_elements));
+                }
+                _ => {}
+            }
+        }
+        tag
+    }
+}
+
+/// Which form of language is in use
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Language {
+    /// Language formatted as ISO-639
+    ISO639(String),
+    /// Lanuage formatted as IETF
+    IETF(String),
+}
+
+/// ISO 639-2 codes of common languages, in both their bibliographic
+/// and terminology forms, along with their ISO 639-1 codes
+static ISO639_2_TO_1: phf::Map<&'static str, &'static str> = phf::phf_map! {
+    "alb" => "sq", "sqi" => "sq",
+    "ara" => "ar",
+    "arm" => "hy", "hye" => "hy",
+    "baq" => "eu", "eus" => "eu",
+    "ben" => "bn",
+    "bul" => "bg",
+    "bur" => "my", "mya" => "my",
+    "cat" => "ca",
+    "chi" => "zh", "zho" => "zh",
+    "cze" => "cs", "ces" => "cs",
+    "dan" => "da",
+    "dut" => "nl", "nld" => "nl",
+    "eng" => "en",
+    "est" => "et",
+    "fin" => "fi",
+    "fre" => "fr", "fra" => "fr",
+    "geo" => "ka", "kat" => "ka",
+    "ger" => "de", "deu" => "de",
+    "gle" => "ga",
+    "glg" => "gl",
+    "gre" => "el", "ell" => "el",
+    "heb" => "he",
+    "hin" => "hi",
+    "hrv" => "hr",
+    "hun" => "hu",
+    "ice" => "is", "isl" => "is",
+    "ind" => "id",
+    "ita" => "it",
+    "jpn" => "ja",
+    "kor" => "ko",
+    "lat" => "la",
+    "lav" => "lv",
+    "lit" => "lt",
+    "mac" => "mk", "mkd" => "mk",
+    "may" => "ms", "msa" => "ms",
+    "nob" => "nb",
+    "nno" => "nn",
+    "nor" => "no",
+    "per" => "fa", "fas" => "fa",
+    "pol" => "pl",
+    "por" => "pt",
+    "rum" => "ro", "ron" => "ro",
+    "rus" => "ru",
+    "slo" => "sk", "slk" => "sk",
+    "slv" => "sl",
+    "spa" => "es",
+    "srp" => "sr",
+    "swe" => "sv",
+    "tam" => "ta",
+    "tel" => "te",
+    "tha" => "th",
+    "tib" => "bo", "bod" => "bo",
+    "tur" => "tr",
+    "ukr" => "uk",
+    "urd" => "ur",
+    "vie" => "vi",
+    "wel" => "cy", "cym" => "cy",
+};
+
+impl Language {
+    /// Returns the language's tag as stored
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::ISO639(s) | Self::IETF(s) => s,
+        }
+    }
+
+    /// Returns the language's primary subtag, such as `"en"` of `"en-US"`
+    ///
+    /// ISO 639 codes are returned whole.
+    pub fn primary_code(&self) -> &str {
+        primary_subtag(self.as_str())
+    }
+
+    /// Whether the language is the same as one given as an
+    /// ISO 639-1 or 639-2 code or an IETF tag
+    ///
+    /// Only primary subtags are compared, ignoring ASCII case,
+    /// and common languages match across their two and three letter codes,
+    /// so `"eng"`, `"en"` and `"en-US"` all match each other.
+    ///
+    /// ## Example
+    /// ```
+    /// use matroska::Language;
+    ///
+    /// let language = Language::ISO639("ger".to_owned());
+    /// assert!(language.matches("de-AT"));
+    /// assert!(language.matches("deu"));
+    /// assert!(!language.matches("en"));
+    /// ```
+    pub fn matches(&self, tag: &str) -> bool {
+        fn normalized(tag: &str) -> String {
+            let primary = primary_subtag(tag).to_ascii_lowercase();
+            match ISO639_2_TO_1.get(primary.as_str()) {
+                Some(code) => (*code).to_owned(),
+                None => primary,
+            }
+        }
+        normalized(self.as_str()) == normalized(tag)
+    }
+
+    /// Whether the language is undetermined (`und`)
+    /// or there's no linguistic content (`zxx`)
+    pub fn is_undetermined(&self) -> bool {
+        let primary = self.primary_code();
+        primary.is_empty()
+            || primary.eq_ignore_ascii_case("und")
+            || primary.eq_ignore_ascii_case("zxx")
+    }
+}
+
+/// Returns a language tag's primary subtag
+fn primary_subtag(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// Displays the language's tag,
+/// followed by the kind of tag in the alternate `{:#}` form
+impl std::fmt::Display for Language {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::ISO639(s) if f.alternate() => write!(f, "{s} (ISO 639-2)"),
+            Self::IETF(s) if f.alternate() => write!(f, "{s} (BCP 47)"),
+            Self::ISO639(s) | Self::IETF(s) => s.fmt(f),
+        }
+    }
+}
+
+/// A tag's value
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TagValue {
+    /// Tag's value as string
+    String(String),
+    /// Tag's value as binary
+    Binary(Vec<u8>),
+}
+
+/// An index of cue points used for seeking
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Cues {
+    /// The individual cue points, in timestamp order
+    pub points: Vec<CuePoint>,
+}
+
+impl Parseable for Cues {
+    type Output = Cues;
+
+    const ID: u32 = ids::CUES;
+
+    fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<Cues> {
+        Cues::parse_limited(r, size, &Limits::default())
+    }
+
+    fn parse_with<R: io::Read>(r: &mut R, size: u64, options: &ParseOptions) -> Result<Cues> {
+        Cues::parse_limited(r, size, &options.limits())
+    }
+}
+
+/// A single seek point to a cluster
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct CuePoint {
+    /// The cue's timestamp, in timestamp-scale units
+    pub time: u64,
+    /// The positions of the cue within each track
+    pub positions: Vec<CueTrackPositions>,
+}
+
+impl Cues {
+    fn parse_limited<R: io::Read>(r: &mut R, size: u64, limits: &Limits) -> Result<Cues> {
+        Element::parse_master_with(r, size, Some(ids::CUES), limits).map(|elements| Cues {
+            points: elements
+                .into_iter()
+                .filter_map(|e| match e {
+                    Element {
+                        id: ids::CUEPOINT,
+                        val: ElementType::Master(sub_elements),
+                        ..
+                    } => Some(CuePoint::build(sub_elements)),
+                    _ => None,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl Default for CuePoint {
+    fn default() -> Self {
+        CuePoint::new()
+    }
+}
+
+impl CuePoint {
+    fn new() -> CuePoint {
+        CuePoint {
+            time: 0,
+            positions: Vec::new(),
+        }
+    }
+
+    /// The cue's timestamp, scaled by the Info's timestamp scale
+    #[inline]
+    pub fn timestamp(&self, info: &Info) -> Duration {
+        info.timestamp(self.time)
+    }
+
+    fn build(elements: Vec<Element>) -> CuePoint {
+        let mut point = CuePoint::new();
+        for e in elements {
+            match e {
+                Element {
+                    id: ids::CUETIME,
+                    val: ElementType::UInt(time),
+                    ..
+                } => {
+                    point.time = time;
+                }
+                Element {
+                    id: ids::CUETRACKPOSITIONS,
+                    val: ElementType::Master(sub_elements),
+                    ..
+                } => {
+                    point.positions.push(CueTrackPositions::build(sub_elements));
+                }
+                _ => {}
+            }
+        }
+        point
+    }
+}
+
+/// An index from timestamps to the Clusters containing them,
+/// built from a file's Cues
+///
+/// ## Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// let matroska = matroska::open("file.mkv").unwrap();
+/// if let Some(index) = matroska.seek_index() {
+///     println!("{:?}", index.offset_for(Duration::from_secs(90), None));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SeekIndex {
+    segment_start: u64,
+    timestamp_scale: u64,
+    // cue times, tracks and cluster positions, in time order
+    entries: Vec<(u64, u64, u64)>,
+}
+
+impl SeekIndex {
+    /// Builds an index from Cues, the Info giving their timestamp scale
+    /// and the file offset of the Segment's contents,
+    /// which cluster positions are relative to
+    pub fn from_cues(cues: &Cues, info: &Info, segment_start: u64) -> SeekIndex {
+        let mut entries: Vec<_> = cues
+            .points
+            .iter()
+            .flat_map(|point| {
+                point
+                    .positions
+                    .iter()
+                    .map(move |p| (point.time, p.track, p.cluster_position))
+            })
+            .collect();
+        entries.sort_by_key(|(time, _, _)| *time);
+        SeekIndex {
+            segment_start,
+            timestamp_scale: info.timestamp_scale.max(1),
+            entries,
+        }
+    }
+
+    /// Returns the file offset of the Cluster holding
+    /// the nearest cue point at or before the given time
+    ///
+    /// If a track is given, that track's cue points are used,
+    /// unless it has none, in which case all tracks' are.
+    /// Returns `None` if no cue point precedes the time.
+    pub fn offset_for(&self, time: Duration, track: Option<u64>) -> Option<u64> {
+        let ticks: u64 =
+            std::convert::TryFrom::try_from(time.as_nanos() / u128::from(self.timestamp_scale))
+                .unwrap_or(u64::MAX);
+        let track = track.filter(|track| self.entries.iter().any(|(_, t, _)| t == track));
+        let end = self.entries.partition_point(|(time, _, _)| *time <= ticks);
+        self.entries[..end]
+            .iter()
+            .rev()
+            .find(|(_, t, _)| track.map(|track| *t == track).unwrap_or(true))
+            .and_then(|(_, _, position)| self.segment_start.checked_add(*position))
     }
 }
 
-/// General information about the target
+/// A cue point's position within a given track
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct SimpleTag {
-    /// The tag's name
-    pub name: String,
-    /// The tag's language
-    pub language: Option<Language>,
-    /// Whether this is the default/original language to use
-    pub default: bool,
-    /// The tag's value
-    pub value: Option<TagValue>,
+#[non_exhaustive]
+pub struct CueTrackPositions {
+    /// The track number
+    pub track: u64,
+    /// The Cluster's position relative to the start of the Segment's data
+    pub cluster_position: u64,
+    /// The block's position relative to the start of the Cluster's data
+    pub relative_position: Option<u64>,
+    /// The block's duration, in timestamp-scale units
+    pub duration: Option<u64>,
+    /// The block's number within the Cluster, starting from 1
+    pub block_number: Option<u64>,
 }
 
-impl SimpleTag {
-    fn new() -> SimpleTag {
-        SimpleTag {
-            name: String::new(),
-            language: None,
-            default: false,
-            value: None,
+impl Default for CueTrackPositions {
+    fn default() -> Self {
+        CueTrackPositions::new()
+    }
+}
+
+impl CueTrackPositions {
+    fn new() -> CueTrackPositions {
+        CueTrackPositions {
+            track: 0,
+            cluster_position: 0,
+            relative_position: None,
+            duration: None,
+            block_number: None,
         }
     }
 
-    fn build_entry(elements: Vec<Element>) -> SimpleTag {
-        let mut tag = SimpleTag::new();
+    /// The block's duration, scaled by the Info's timestamp scale
+    #[inline]
+    pub fn scaled_duration(&self, info: &Info) -> Option<Duration> {
+        self.duration.map(|d| info.timestamp(d))
+    }
+
+    fn build(elements: Vec<Element>) -> CueTrackPositions {
+        let mut positions = CueTrackPositions::new();
         for e in elements {
             match e {
                 Element {
-                    id: ids::TAGNAME,
-                    val: ElementType::UTF8(string),
-                    ..
-                } => {
-                    tag.name = string;
-                }
-                Element {
-                    id: ids::TAGLANGUAGE,
-                    val: ElementType::String(string),
+                    id: ids::CUETRACK,
+                    val: ElementType::UInt(track),
                     ..
                 } => {
-                    if !matches!(tag.language, Some(Language::IETF(_))) {
-                        tag.language = Some(Language::ISO639(string));
-                    }
+                    positions.track = track;
                 }
                 Element {
-                    id: ids::TAGLANGUAGE_IETF,
-                    val: ElementType::String(string),
+                    id: ids::CUECLUSTERPOSITION,
+                    val: ElementType::UInt(position),
                     ..
                 } => {
-                    tag.language = Some(Language::IETF(string));
+                    positions.cluster_position = position;
                 }
                 Element {
-                    id: ids::TAGDEFAULT,
-                    val: ElementType::UInt(default),
+                    id: ids::CUERELATIVEPOSITION,
+                    val: ElementType::UInt(position),
                     ..
                 } => {
-                    tag.default = default != 0;
+                    positions.relative_position = Some(position);
                 }
                 Element {
-                    id: ids::TAGSTRING,
-                    val: ElementType::UTF8(string),
+                    id: ids::CUEDURATION,
+                    val: ElementType::UInt(duration),
                     ..
                 } => {
-                    tag.value = Some(TagValue::String(string));
+                    positions.duration = Some(duration);
                 }
                 Element {
-                    id: ids::TAGBINARY,
-                    val: ElementType::Binary(binary),
+                    id: ids::CUEBLOCKNUMBER,
+                    val: ElementType::UInt(number),
                     ..
                 } => {
-                    tag.value = Some(TagValue::Binary(binary));
+                    positions.block_number = Some(number);
                 }
                 _ => {}
             }
         }
-        tag
-    }
-}
-
-/// Which form of language is in use
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Language {
-    /// Language formatted as ISO-639
-    ISO639(String),
-    /// Lanuage formatted as IETF
-    IETF(String),
-}
-
-impl std::fmt::Display for Language {
-    #[inline]
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            Self::ISO639(s) | Self::IETF(s) => s.fmt(f),
-        }
+        positions
     }
 }
 
-/// A tag's value
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum TagValue {
-    /// Tag's value as string
-    String(String),
-    /// Tag's value as binary
-    Binary(Vec<u8>),
-}
-
 /// Returns a single item from open Matroska file such as `Info`
+///
+/// Returns `Ok(None)` if the item isn't present in the file,
+/// or [`MatroskaError::UnexpectedElement`] if the SeekHead
+/// points to something else in its place.
 pub fn get<R, P>(mut file: R) -> Result<Option<P::Output>>
 where
     R: io::Read + io::Seek,
     P: Parseable,
 {
-    use std::io::SeekFrom;
-
-    let (mut id_0, mut size_0, _) = ebml::read_element_id_size(&mut file)?;
-    while id_0 != ids::SEGMENT {
-        file.seek(SeekFrom::Current(size_0 as i64)).map(|_| ())?;
-        let (id, size, _) = ebml::read_element_id_size(&mut file)?;
-        id_0 = id;
-        size_0 = size;
-    }
+    let mut size_0 = match find_segment(&mut file) {
+        Ok(size) => size,
+        Err(MatroskaError::EmptySegment) => return Ok(None),
+        Err(err) => return Err(err),
+    };
 
     let segment_start = file.stream_position()?;
 
     while size_0 > 0 {
-        let (id_1, size_1, len) = ebml::read_element_id_size(&mut file)?;
+        let (id_1, mut size_1, len) = match read_top_level_header(&mut file)? {
+            Some(header) => header,
+            None => break,
+        };
         match id_1 {
+            // the SeekHead itself is parsed like any other part
+            ids::SEEKHEAD if P::ID == ids::SEEKHEAD => {
+                return parse_located(&mut file, P::ID, |r| P::parse(r, size_1)).map(Some);
+            }
             ids::SEEKHEAD => {
                 // if seektable encountered, find part from that
-                let seektable = Seektable::parse(&mut file, segment_start, size_1)?;
+                let start = file.stream_position()?;
+                let seektable = parse_located(&mut file, ids::SEEKHEAD, |r| {
+                    SeekHead::parse_chained(r, segment_start, size_1)
+                })?;
 
-                if let Some(pos) = seektable.get(P::ID)? {
-                    file.seek(SeekFrom::Start(pos))?;
-                    let (i, s, _) = ebml::read_element_id_size(&mut file)?;
-                    assert_eq!(i, P::ID);
-                    return P::parse(&mut file, s).map(Some);
+                // an unusable seektable falls back to walking the sections
+                if seektable.check(stream_len(&mut file)?).is_ok() {
+                    if let Some(&pos) = seektable.offsets(P::ID)?.first() {
+                        let size = read_element_at(&mut file, P::ID, pos)?;
+                        return parse_located(&mut file, P::ID, |r| P::parse(r, size)).map(Some);
+                    }
                 }
+                file.seek(io::SeekFrom::Start(start.saturating_add(size_1)))?;
             }
             // if no seektable, try to find part separately
             id if id == P::ID => {
-                return P::parse(&mut file, size_1).map(Some);
+                return parse_located(&mut file, P::ID, |r| P::parse(r, size_1)).map(Some);
             }
             _ => {
-                file.seek(SeekFrom::Current(size_1 as i64)).map(|_| ())?;
+                size_1 = skip_element(&mut file, size_1)?;
             }
         }
-        size_0 -= len;
-        size_0 -= size_1;
+        // an element running past the Segment's end finishes it
+        size_0 = match size_0.checked_sub(len + size_1) {
+            Some(size) => size,
+            None => break,
+        };
     }
 
     Ok(None)
 }
 
+/// Returns several items from open Matroska file in a single pass,
+/// such as `(Info, Track)`
+///
+/// The SeekHead is read once, if present, and the rest of the Segment
+/// is scanned for any items it doesn't point to.
+/// Each item is `None` if it isn't present in the file.
+///
+/// ## Example
+/// ```no_run
+/// use matroska::{Info, Track};
+/// let file = std::fs::File::open("file.mkv").unwrap();
+/// let (info, tracks) = matroska::get_many::<_, (Info, Track)>(file).unwrap();
+/// ```
+pub fn get_many<R, S>(mut file: R) -> Result<S::Output>
+where
+    R: io::Read + io::Seek,
+    S: ParseableSet,
+{
+    let ids = S::ids();
+    let mut found = vec![false; ids.len()];
+    let mut output = S::Output::default();
+
+    let mut size_0 = match find_segment(&mut file) {
+        Ok(size) => size,
+        Err(MatroskaError::EmptySegment) => return Ok(output),
+        Err(err) => return Err(err),
+    };
+
+    let segment_start = file.stream_position()?;
+
+    while size_0 > 0 && found.contains(&false) {
+        let (id_1, mut size_1, len) = match read_top_level_header(&mut file)? {
+            Some(header) => header,
+            None => break,
+        };
+        let start = file.stream_position()?;
+        if id_1 == ids::SEEKHEAD {
+            let seektable = parse_located(&mut file, ids::SEEKHEAD, |r| {
+                SeekHead::parse_chained(r, segment_start, size_1)
+            })?;
+
+            // an unusable seektable falls back to walking the sections
+            if seektable.check(stream_len(&mut file)?).is_ok() {
+                for (index, id) in ids.iter().enumerate() {
+                    if found[index] {
+                        continue;
+                    }
+                    if let Some(&pos) = seektable.offsets(*id)?.first() {
+                        let size = match read_element_at(&mut file, *id, pos) {
+                            Ok(size) => size,
+                            // a misplaced item is left to the scan
+                            Err(MatroskaError::UnexpectedElement { .. }) => continue,
+                            Err(err) => return Err(err),
+                        };
+                        parse_located(&mut file, *id, |r| {
+                            S::parse_index(&mut output, index, r, size)
+                        })?;
+                        found[index] = true;
+                    }
+                }
+            }
+            file.seek(io::SeekFrom::Start(start.saturating_add(size_1)))?;
+        } else if ids
+            .iter()
+            .zip(&found)
+            .any(|(id, found)| *id == id_1 && !found)
+        {
+            for (index, id) in ids.iter().enumerate() {
+                if *id == id_1 && !found[index] {
+                    file.seek(io::SeekFrom::Start(start))?;
+                    parse_located(&mut file, id_1, |r| {
+                        S::parse_index(&mut output, index, r, size_1)
+                    })?;
+                    found[index] = true;
+                }
+            }
+            file.seek(io::SeekFrom::Start(start.saturating_add(size_1)))?;
+        } else {
+            size_1 = skip_element(&mut file, size_1)?;
+        }
+        // an element running past the Segment's end finishes it
+        size_0 = match size_0.checked_sub(len + size_1) {
+            Some(size) => size,
+            None => break,
+        };
+    }
+
+    Ok(output)
+}
+
+/// Advances the reader to the start of the Segment's contents,
+/// returning the Segment's size
+///
+/// Files which end before any Segment contents,
+/// but are otherwise well-formed, are [`MatroskaError::EmptySegment`].
+fn find_segment<R: io::Read + io::Seek>(r: &mut R) -> Result<u64> {
+    find_segment_with_header(r).map(|(_, size)| size)
+}
+
+/// Advances the reader to the start of the Segment's contents,
+/// returning the EBML header if one preceded it and the Segment's size
+fn find_segment_with_header<R: io::Read + io::Seek>(
+    r: &mut R,
+) -> Result<(Option<EbmlHeader>, u64)> {
+    use std::io::SeekFrom;
+
+    fn eof_is_empty<T>(result: Result<T>) -> Result<T> {
+        match result {
+            Err(MatroskaError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                Err(MatroskaError::EmptySegment)
+            }
+            result => result,
+        }
+    }
+
+    // a zero-byte file is as empty as it gets
+    let (mut id, mut size, _) = eof_is_empty(ebml::read_element_id_size(r))?;
+    let header = id == ids::EBML;
+    let mut ebml_header = None;
+    while id != ids::SEGMENT {
+        if id == ids::EBML && ebml_header.is_none() {
+            ebml_header = Some(eof_is_empty(EbmlHeader::parse(r, size))?);
+        } else {
+            r.seek(SeekFrom::Current(size as i64))?;
+        }
+        let next = ebml::read_element_id_size(r);
+        // only a file which starts out as EBML may be empty,
+        // anything else is simply truncated
+        (id, size, _) = if header { eof_is_empty(next)? } else { next? };
+    }
+
+    let start = r.stream_position()?;
+    let end = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(start))?;
+    if size == 0 || start >= end {
+        Err(MatroskaError::EmptySegment)
+    } else if size == ebml::UNKNOWN_SIZE {
+        // a live or unfinished Segment runs to the end of the file
+        Ok((ebml_header, end - start))
+    } else {
+        Ok((ebml_header, size))
+    }
+}
+
+/// The IDs of elements which may appear at the top level of a Segment,
+/// along with those which may follow it, and so end any unknown-size element
+const TOP_LEVEL_IDS: [u32; 10] = [
+    ids::SEEKHEAD,
+    ids::INFO,
+    ids::TRACKS,
+    ids::CLUSTER,
+    ids::CUES,
+    ids::ATTACHMENTS,
+    ids::CHAPTERS,
+    ids::TAGS,
+    ids::SEGMENT,
+    ids::EBML,
+];
+
+/// Reads the ID, size and header length of the next top-level element,
+/// or returns `None` if the file ends before it,
+/// as with truncated recordings
+fn read_top_level_header<R: io::Read + io::Seek>(r: &mut R) -> Result<Option<(u32, u64, u64)>> {
+    let offset = r.stream_position()?;
+    match ebml::read_element_id_size(r) {
+        Ok(header) => Ok(Some(header)),
+        Err(MatroskaError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(err.located(offset, Some(ids::SEGMENT))),
+    }
+}
+
+/// Reads the ID, size and header length of the next child
+/// of an unknown-size element
+///
+/// Returns `None` at the end of the file, or at the start of
+/// the next top-level element, which the reader is left at.
+pub(crate) fn read_unknown_size_child<R: io::Read + io::Seek>(
+    r: &mut R,
+) -> Result<Option<(u32, u64, u64)>> {
+    let start = r.stream_position()?;
+    match ebml::read_element_id_size(r) {
+        Ok((id, _, _)) if TOP_LEVEL_IDS.contains(&id) => {
+            r.seek(io::SeekFrom::Start(start))?;
+            Ok(None)
+        }
+        Ok(header) => Ok(Some(header)),
+        Err(MatroskaError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the stream's total length, leaving its position unchanged
+fn stream_len<R: io::Seek>(r: &mut R) -> Result<u64> {
+    let position = r.stream_position()?;
+    let len = r.seek(io::SeekFrom::End(0))?;
+    r.seek(io::SeekFrom::Start(position))?;
+    Ok(len)
+}
+
+/// Skips past an element of the given size,
+/// returning the number of bytes skipped
+///
+/// Unknown-size elements are skipped child by child, as for
+/// [`read_unknown_size_child`], since they have no known end.
+/// An unknown-size child ends where its parent does, so it's walked
+/// by the same loop, up to [`Limits::MAX_DEPTH`] levels deep.
+pub(crate) fn skip_element<R: io::Read + io::Seek>(r: &mut R, size: u64) -> Result<u64> {
+    if size != ebml::UNKNOWN_SIZE {
+        r.seek(io::SeekFrom::Current(size as i64))?;
+        return Ok(size);
+    }
+    let mut skipped = 0;
+    let mut depth = 0;
+    while let Some((id, size, len)) = read_unknown_size_child(r)? {
+        skipped += len;
+        if size == ebml::UNKNOWN_SIZE {
+            depth += 1;
+            if depth > Limits::MAX_DEPTH {
+                return Err(MatroskaError::NestingTooDeep { id });
+            }
+        } else {
+            r.seek(io::SeekFrom::Current(size as i64))?;
+            skipped += size;
+        }
+    }
+    Ok(skipped)
+}
+
+/// The EBML header which starts a Matroska file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct EbmlHeader {
+    /// The EBML version used to create the file
+    pub ebml_version: u64,
+    /// The minimum EBML version a parser needs to read the file
+    pub read_version: u64,
+    /// The maximum length of element IDs, in bytes
+    pub max_id_length: u64,
+    /// The maximum length of element sizes, in bytes
+    pub max_size_length: u64,
+    /// The document type, such as `matroska` or `webm`
+    pub doc_type: String,
+    /// The version of the document type's specification used
+    pub doc_type_version: u64,
+    /// The minimum document type version a parser needs to read the file
+    pub doc_type_read_version: u64,
+}
+
+impl Default for EbmlHeader {
+    fn default() -> Self {
+        EbmlHeader::new()
+    }
+}
+
+impl EbmlHeader {
+    fn new() -> EbmlHeader {
+        EbmlHeader {
+            ebml_version: 1,
+            read_version: 1,
+            max_id_length: 4,
+            max_size_length: 8,
+            doc_type: "matroska".to_string(),
+            doc_type_version: 1,
+            doc_type_read_version: 1,
+        }
+    }
+
+    fn parse<R: io::Read>(r: &mut R, size: u64) -> Result<EbmlHeader> {
+        let mut header = EbmlHeader::new();
+        for e in Element::parse_master(r, size, Some(ids::EBML))? {
+            match e {
+                Element {
+                    id: ids::EBMLVERSION,
+                    val: ElementType::UInt(v),
+                    ..
+                } => header.ebml_version = v,
+                Element {
+                    id: ids::EBMLREADVERSION,
+                    val: ElementType::UInt(v),
+                    ..
+                } => header.read_version = v,
+                Element {
+                    id: ids::EBMLMAXIDLENGTH,
+                    val: ElementType::UInt(v),
+                    ..
+                } => header.max_id_length = v,
+                Element {
+                    id: ids::EBMLMAXSIZELENGTH,
+                    val: ElementType::UInt(v),
+                    ..
+                } => header.max_size_length = v,
+                Element {
+                    id: ids::DOCTYPE,
+                    val: ElementType::String(v),
+                    ..
+                } => header.doc_type = v,
+                Element {
+                    id: ids::DOCTYPEVERSION,
+                    val: ElementType::UInt(v),
+                    ..
+                } => header.doc_type_version = v,
+                Element {
+                    id: ids::DOCTYPEREADVERSION,
+                    val: ElementType::UInt(v),
+                    ..
+                } => header.doc_type_read_version = v,
+                _ => {}
+            }
+        }
+        Ok(header)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        ebml::master_element(
+            ids::EBML,
+            vec![
+                ebml::uint_element(ids::EBMLVERSION, self.ebml_version),
+                ebml::uint_element(ids::EBMLREADVERSION, self.read_version),
+                ebml::uint_element(ids::EBMLMAXIDLENGTH, self.max_id_length),
+                ebml::uint_element(ids::EBMLMAXSIZELENGTH, self.max_size_length),
+                ebml::string_element(ids::DOCTYPE, &self.doc_type),
+                ebml::uint_element(ids::DOCTYPEVERSION, self.doc_type_version),
+                ebml::uint_element(ids::DOCTYPEREADVERSION, self.doc_type_read_version),
+            ],
+        )
+    }
+
+    /// Returns `true` if the document type is `webm`
+    #[inline]
+    pub fn is_webm(&self) -> bool {
+        self.doc_type == "webm"
+    }
+}
+
+/// What kind of file a reader contains, as determined by [`probe`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Probe {
+    /// Not a Matroska or WebM file
+    NotMatroska,
+    /// A Matroska file with no Segment contents,
+    /// or a zero-byte file,
+    /// such as from a recording that never started
+    Empty,
+    /// A Matroska file with Segment contents
+    Matroska,
+}
+
+/// Determines whether a reader contains a Matroska file
+/// without parsing the file's contents
+///
+/// ## Example
+/// ```no_run
+/// let file = std::fs::File::open("file.mkv").unwrap();
+/// match matroska::probe(file).unwrap() {
+///     matroska::Probe::Empty => println!("empty recording"),
+///     matroska::Probe::Matroska => println!("Matroska"),
+///     _ => println!("not Matroska"),
+/// }
+/// ```
+pub fn probe<R: io::Read + io::Seek>(mut r: R) -> Result<Probe> {
+    r.seek(io::SeekFrom::Start(0))?;
+    let (id, size, _) = match ebml::read_element_id_size(&mut r) {
+        Ok(header) => header,
+        Err(MatroskaError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            return Ok(Probe::Empty)
+        }
+        Err(MatroskaError::InvalidID) | Err(MatroskaError::InvalidSize) => {
+            return Ok(Probe::NotMatroska)
+        }
+        Err(err) => return Err(err),
+    };
+    if id != ids::EBML {
+        return Ok(Probe::NotMatroska);
+    }
+    let doc_type = Element::parse_master(&mut r, size, Some(ids::EBML))?
+        .into_iter()
+        .find_map(|e| match e {
+            Element {
+                id: ids::DOCTYPE,
+                val: ElementType::String(doc_type),
+                ..
+            } => Some(doc_type),
+            _ => None,
+        });
+    if !matches!(doc_type.as_deref(), Some("matroska") | Some("webm")) {
+        return Ok(Probe::NotMatroska);
+    }
+
+    r.seek(io::SeekFrom::Start(0))?;
+    match find_segment(&mut r) {
+        Ok(_) => Ok(Probe::Matroska),
+        Err(MatroskaError::EmptySegment) => Ok(Probe::Empty),
+        Err(err) => Err(err),
+    }
+}
+
 /// Returns a single item from Matroska file on disk, such as `Info`
 pub fn get_from<P, R>(path: P) -> Result<Option<R::Output>>
 where
@@ -1731,6 +6397,19 @@ where
         .and_then(get::<_, R>)
 }
 
+/// Returns several items from Matroska file on disk in a single pass,
+/// such as `(Info, Track)`
+pub fn get_many_from<P, S>(path: P) -> Result<S::Output>
+where
+    P: AsRef<std::path::Path>,
+    S: ParseableSet,
+{
+    std::fs::File::open(path)
+        .map(std::io::BufReader::new)
+        .map_err(MatroskaError::Io)
+        .and_then(get_many::<_, S>)
+}
+
 /// Opens Matroska file on disk
 pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Matroska> {
     std::fs::File::open(path)
@@ -1738,3 +6417,294 @@ pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Matroska> {
         .map_err(MatroskaError::Io)
         .and_then(Matroska::open)
 }
+
+/// Options controlling how a Matroska file is parsed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// The maximum number of sections parsed concurrently by [`open_parallel`]
+    pub threads: usize,
+    /// Whether elements missing mandatory children,
+    /// such as a track without a CodecID,
+    /// or an Info with an invalid Duration, are errors
+    ///
+    /// When `false`, such problems are recorded alongside
+    /// the parsed value instead, such as in [`Track::problems`]
+    /// or [`Info::problems`].
+    /// Track problems other than [`TrackProblem::MissingCodecId`]
+    /// are recorded either way.
+    pub strict: bool,
+    /// Whether to skip reading tracks' CodecPrivate data
+    ///
+    /// When `true`, [`Track::codec_private`] is left empty and
+    /// the data is read on demand by [`Track::read_codec_private`],
+    /// which saves memory when only the other track metadata is needed.
+    pub defer_codec_private: bool,
+    /// Whether to skip reading attachments' FileData
+    ///
+    /// When `true`, [`Attachment::data`] is left empty and
+    /// the data is read on demand by [`Attachment::read_data`]
+    /// from [`Attachment::data_offset`] and [`Attachment::data_len`].
+    pub defer_attachment_data: bool,
+    /// Whether to parse the Info section
+    ///
+    /// When `false`, [`Matroska::info`] is left as a placeholder.
+    /// This and the other section flags let callers skip
+    /// sections they don't need, which are then seeked past unread.
+    pub info: bool,
+    /// Whether to parse the Tracks section
+    pub tracks: bool,
+    /// Whether to parse the Attachments section
+    pub attachments: bool,
+    /// Whether to parse the Chapters section
+    pub chapters: bool,
+    /// Whether to parse the Tags section
+    pub tags: bool,
+    /// Whether to parse the Cues section
+    pub cues: bool,
+    /// The largest element body to read, in bytes
+    ///
+    /// Larger elements are [`MatroskaError::ElementTooLarge`],
+    /// which keeps crafted files from exhausting memory.
+    /// This defaults to 64 MiB, so files with larger attachments
+    /// need it raised or [`ParseOptions::defer_attachment_data`] set.
+    pub max_element_size: u64,
+    /// How deeply master elements may nest within a section
+    ///
+    /// Deeper elements are [`MatroskaError::NestingTooDeep`],
+    /// which keeps crafted files from exhausting the stack.
+    /// This defaults to 32, well beyond legitimate files,
+    /// though deeply nested chapters might need more.
+    pub max_depth: usize,
+    /// The largest body of an element whose type isn't known to keep, in bytes
+    ///
+    /// Nothing in the parsed sections comes from such elements,
+    /// so larger ones are passed over rather than held in memory
+    /// while their section is parsed.
+    /// This defaults to 64 KiB.
+    pub max_unknown_size: u64,
+    /// Whether to verify sections' CRC-32 elements
+    ///
+    /// When `true`, any top-level section beginning with a CRC-32
+    /// is checked against it before parsing, and a section which
+    /// doesn't match is [`MatroskaError::ChecksumMismatch`].
+    /// CRC-32 elements are skipped whether verified or not.
+    pub verify_crc: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            threads: 4,
+            strict: false,
+            defer_codec_private: false,
+            defer_attachment_data: false,
+            info: true,
+            tracks: true,
+            attachments: true,
+            chapters: true,
+            tags: true,
+            cues: true,
+            max_element_size: Limits::MAX_ELEMENT_SIZE,
+            max_depth: Limits::MAX_DEPTH,
+            max_unknown_size: Limits::MAX_UNKNOWN_SIZE,
+            verify_crc: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Sets the maximum number of sections parsed concurrently
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Sets whether elements missing mandatory children are errors
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets whether to skip reading tracks' CodecPrivate data
+    pub fn with_defer_codec_private(mut self, defer: bool) -> Self {
+        self.defer_codec_private = defer;
+        self
+    }
+
+    /// Sets whether to skip reading attachments' FileData
+    pub fn with_defer_attachment_data(mut self, defer: bool) -> Self {
+        self.defer_attachment_data = defer;
+        self
+    }
+
+    /// Sets whether to parse the Info section
+    pub fn with_info(mut self, info: bool) -> Self {
+        self.info = info;
+        self
+    }
+
+    /// Sets whether to parse the Tracks section
+    pub fn with_tracks(mut self, tracks: bool) -> Self {
+        self.tracks = tracks;
+        self
+    }
+
+    /// Sets whether to parse the Attachments section
+    pub fn with_attachments(mut self, attachments: bool) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    /// Sets whether to parse the Chapters section
+    pub fn with_chapters(mut self, chapters: bool) -> Self {
+        self.chapters = chapters;
+        self
+    }
+
+    /// Sets whether to parse the Tags section
+    pub fn with_tags(mut self, tags: bool) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets whether to parse the Cues section
+    pub fn with_cues(mut self, cues: bool) -> Self {
+        self.cues = cues;
+        self
+    }
+
+    /// Sets the largest element body to read, in bytes
+    pub fn with_max_element_size(mut self, max_element_size: u64) -> Self {
+        self.max_element_size = max_element_size;
+        self
+    }
+
+    /// Sets how deeply master elements may nest within a section
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the largest unknown element body to keep, in bytes
+    pub fn with_max_unknown_size(mut self, max_unknown_size: u64) -> Self {
+        self.max_unknown_size = max_unknown_size;
+        self
+    }
+
+    /// Sets whether to verify sections' CRC-32 elements
+    pub fn with_verify_crc(mut self, verify_crc: bool) -> Self {
+        self.verify_crc = verify_crc;
+        self
+    }
+
+    fn limits(&self) -> Limits {
+        Limits::default()
+            .with_max_element_size(self.max_element_size)
+            .with_max_depth(self.max_depth)
+            .with_max_unknown_size(self.max_unknown_size)
+    }
+
+    /// Whether the section with the given ID should be parsed
+    fn parses(&self, id: u32) -> bool {
+        match id {
+            ids::INFO => self.info,
+            ids::TRACKS => self.tracks,
+            ids::ATTACHMENTS => self.attachments,
+            ids::CHAPTERS => self.chapters,
+            ids::TAGS => self.tags,
+            ids::CUES => self.cues,
+            _ => false,
+        }
+    }
+}
+
+/// Opens Matroska file on disk, parsing its sections in parallel
+///
+/// When the file has a SeekHead, each section it lists is parsed
+/// on its own thread from its own file handle, which helps when
+/// per-section latency dominates, such as on network filesystems.
+/// Files without a SeekHead, or whose extra handles can't be opened,
+/// are parsed sequentially.
+/// In either case, the result is the same as [`Matroska::open_with`]
+/// with the same options.
+pub fn open_parallel<P: AsRef<std::path::Path>>(
+    path: P,
+    options: ParseOptions,
+) -> Result<Matroska> {
+    let path = path.as_ref();
+    let mut file = io::BufReader::new(std::fs::File::open(path)?);
+    let mut matroska = Matroska::new();
+    matroska.populate(&mut file, &options, &mut Problems::strict(), Some(path))?;
+    Ok(matroska)
+}
+
+/// Copies each attached file's data from an open Matroska file
+/// into writers chosen by a callback, returning how many were copied
+///
+/// Only the Attachments section is parsed, and each file's data
+/// is copied in fixed-size chunks without being buffered whole.
+/// The callback is given each attachment, whose name and MIME type
+/// are populated but whose data is empty, and returns the writer
+/// to copy it into or `None` to skip it.
+///
+/// ## Example
+/// ```no_run
+/// let file = std::fs::File::open("file.mkv").unwrap();
+/// matroska::extract_attachments(file, |attachment| {
+///     if attachment.mime_type.starts_with("font/") {
+///         std::fs::File::create(&attachment.name).map(Some)
+///     } else {
+///         Ok(None)
+///     }
+/// })
+/// .unwrap();
+/// ```
+pub fn extract_attachments<R, F, W>(mut r: R, mut f: F) -> Result<usize>
+where
+    R: io::Read + io::Seek,
+    F: FnMut(&Attachment) -> io::Result<Option<W>>,
+    W: io::Write,
+{
+    let options = ParseOptions::default()
+        .with_info(false)
+        .with_tracks(false)
+        .with_chapters(false)
+        .with_tags(false)
+        .with_cues(false)
+        .with_defer_attachment_data(true);
+    let attachments = Matroska::open_with(&mut r, &options)?.attachments;
+    let mut extracted = 0;
+    for attachment in &attachments {
+        if let Some(w) = f(attachment)? {
+            attachment.write_data(&mut r, w)?;
+            extracted += 1;
+        }
+    }
+    Ok(extracted)
+}
+
+/// Opens Matroska file on disk using pre-configured `OpenOptions`
+///
+/// This is useful for platform-specific flags, such as
+/// Windows share modes which allow reading a file
+/// that another process still holds open for writing.
+/// The options should have read access enabled.
+///
+/// ## Example
+/// ```no_run
+/// let mut options = std::fs::OpenOptions::new();
+/// options.read(true);
+/// let matroska = matroska::open_with_file_options("file.mkv", &options).unwrap();
+/// println!("title : {:?}", matroska.info.title);
+/// ```
+pub fn open_with_file_options<P: AsRef<std::path::Path>>(
+    path: P,
+    options: &std::fs::OpenOptions,
+) -> Result<Matroska> {
+    options
+        .open(path)
+        .map(std::io::BufReader::new)
+        .map_err(MatroskaError::Io)
+        .and_then(Matroska::open)
+}
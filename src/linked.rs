@@ -0,0 +1,165 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::{get_from, open, Info, Matroska, MatroskaError, Result};
+
+/// The file extensions considered by [`SegmentResolver::add_dir`]
+const EXTENSIONS: [&str; 5] = ["mkv", "mka", "mks", "mk3d", "webm"];
+
+/// Finds the files holding linked segments by their SegmentUIDs
+///
+/// Segments are linked to their neighbors by
+/// [`Info::prev_uid`] and [`Info::next_uid`], and ordered chapters
+/// may refer to other segments by [`Chapter::segment_uid`](crate::Chapter::segment_uid).
+/// Each is a 128-bit SegmentUID, which this maps to the file on disk
+/// whose own [`Info::uid`] matches it byte-for-byte.
+///
+/// ## Example
+/// ```no_run
+/// let mut resolver = matroska::SegmentResolver::new();
+/// resolver.add_dir("recordings").unwrap();
+/// for (path, matroska) in resolver.resolve("recordings/capture-002.mkv").unwrap() {
+///     println!("{} : {:?}", path.display(), matroska.info.duration);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SegmentResolver {
+    segments: BTreeMap<Vec<u8>, PathBuf>,
+}
+
+impl SegmentResolver {
+    /// Creates a resolver with no candidate files
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a candidate file, reading only its Info
+    ///
+    /// Returns `true` if the file has a SegmentUID and so was added.
+    /// If several files share a SegmentUID, the first one added is kept.
+    pub fn add<P: AsRef<Path>>(&mut self, path: P) -> Result<bool> {
+        let path = path.as_ref();
+        match get_from::<_, Info>(path)?.and_then(|info| info.uid) {
+            Some(uid) => {
+                self.segments
+                    .entry(uid)
+                    .or_insert_with(|| path.to_path_buf());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Adds every Matroska or WebM file in the given directory
+    ///
+    /// Files are recognized by their extension, such as `.mkv` or `.webm`,
+    /// and those which fail to parse are skipped.
+    /// Subdirectories are not searched.
+    /// Returns the number of files added.
+    pub fn add_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize> {
+        let mut paths = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        // keep the first-added file deterministic among duplicates
+        paths.sort();
+        let mut added = 0;
+        for path in paths.into_iter().filter(|path| has_extension(path)) {
+            if let Ok(true) = self.add(&path) {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Returns the path of the file with the given SegmentUID
+    pub fn path(&self, uid: &[u8]) -> Option<&Path> {
+        self.segments.get(uid).map(PathBuf::as_path)
+    }
+
+    /// Opens the chain of linked segments the given file belongs to
+    ///
+    /// The chain is followed backward from the file by its previous
+    /// SegmentUIDs, then forward by its next SegmentUIDs,
+    /// and is returned in playback order.
+    /// Any UIDs without a matching file are reported together as
+    /// [`MatroskaError::UnresolvedSegments`].
+    /// A chain which loops back on itself ends where it repeats.
+    pub fn resolve<P: AsRef<Path>>(&self, start: P) -> Result<Vec<(PathBuf, Matroska)>> {
+        let start = start.as_ref().to_path_buf();
+        let first = open(&start)?;
+        let mut seen = BTreeSet::new();
+        seen.extend(first.info.uid.clone());
+        let mut missing = Vec::new();
+
+        let mut before = Vec::new();
+        let mut prev = first.info.prev_uid.clone();
+        while let Some((path, matroska)) = self.follow(prev, &mut seen, &mut missing)? {
+            prev = matroska.info.prev_uid.clone();
+            before.push((path, matroska));
+        }
+
+        let mut after = Vec::new();
+        let mut next = first.info.next_uid.clone();
+        while let Some((path, matroska)) = self.follow(next, &mut seen, &mut missing)? {
+            next = matroska.info.next_uid.clone();
+            after.push((path, matroska));
+        }
+
+        if !missing.is_empty() {
+            return Err(MatroskaError::UnresolvedSegments { uids: missing });
+        }
+
+        before.reverse();
+        before.push((start, first));
+        before.extend(after);
+        Ok(before)
+    }
+
+    fn follow(
+        &self,
+        uid: Option<Vec<u8>>,
+        seen: &mut BTreeSet<Vec<u8>>,
+        missing: &mut Vec<Vec<u8>>,
+    ) -> Result<Option<(PathBuf, Matroska)>> {
+        let uid = match uid {
+            Some(uid) if seen.insert(uid.clone()) => uid,
+            _ => return Ok(None),
+        };
+        match self.segments.get(&uid) {
+            Some(path) => open(path).map(|matroska| Some((path.clone(), matroska))),
+            None => {
+                missing.push(uid);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Opens the chain of linked segments the given file belongs to,
+/// searching the file's own directory for the others
+///
+/// See [`SegmentResolver::resolve`] for how the chain is followed.
+pub fn open_linked<P: AsRef<Path>>(path: P) -> Result<Vec<(PathBuf, Matroska)>> {
+    let path = path.as_ref();
+    let mut resolver = SegmentResolver::new();
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => resolver.add_dir(dir)?,
+        _ => resolver.add_dir(".")?,
+    };
+    resolver.resolve(path)
+}
+
+fn has_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        .unwrap_or(false)
+}
@@ -0,0 +1,315 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{self, SeekFrom};
+
+use crate::cluster::{read_block, read_block_group};
+use crate::ebml::{self, Element, ElementType, Result};
+use crate::{ids, read_unknown_size_child, skip_element, SeekEntry, Track, Tracktype};
+
+/// The codecs permitted by the WebM byte stream format
+const WEBM_CODECS: [&str; 5] = ["V_VP8", "V_VP9", "V_AV1", "A_VORBIS", "A_OPUS"];
+
+/// The result of checking a file for Media Source Extensions compatibility
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MseReport {
+    /// The first violation found in the file, if any
+    pub violation: Option<MseViolation>,
+}
+
+impl MseReport {
+    /// Returns `true` if no violations were found
+    #[inline]
+    pub fn is_compatible(&self) -> bool {
+        self.violation.is_none()
+    }
+}
+
+/// A structural problem preventing a file from being used with MSE
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MseViolation {
+    /// The file offset of the offending element
+    pub offset: u64,
+    /// What's wrong with the element
+    pub problem: MseProblem,
+}
+
+impl fmt::Display for MseViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.problem, self.offset)
+    }
+}
+
+/// The kinds of MSE compatibility violations
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MseProblem {
+    /// The EBML header's DocType isn't "webm"
+    DocType(String),
+    /// A Cluster appears before the Info or Tracks it depends on
+    ClusterBeforeInit,
+    /// The SeekHead places part of the initialization segment after the first Cluster
+    SeekBeyondInit {
+        /// The ID of the element the SeekHead refers to
+        id: u32,
+        /// The element's referenced file offset
+        position: u64,
+    },
+    /// An audio or video track uses a codec outside the WebM whitelist
+    UnsupportedCodec {
+        /// The track's number
+        track: u64,
+        /// The track's codec ID
+        codec_id: String,
+    },
+    /// A video track's first block in a Cluster isn't a keyframe
+    ClusterWithoutKeyframe {
+        /// The video track's number
+        track: u64,
+    },
+}
+
+impl fmt::Display for MseProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MseProblem::DocType(doc_type) => write!(f, "DocType {:?} is not \"webm\"", doc_type),
+            MseProblem::ClusterBeforeInit => write!(f, "Cluster before Info and Tracks"),
+            MseProblem::SeekBeyondInit { id, position } => write!(
+                f,
+                "SeekHead places element {:X} at {}, after the first Cluster",
+                id, position
+            ),
+            MseProblem::UnsupportedCodec { track, codec_id } => {
+                write!(f, "track {} uses unsupported codec {}", track, codec_id)
+            }
+            MseProblem::ClusterWithoutKeyframe { track } => {
+                write!(
+                    f,
+                    "Cluster doesn't start with a keyframe for track {}",
+                    track
+                )
+            }
+        }
+    }
+}
+
+/// Checks whether a file can be appended to a Media Source Extensions buffer
+///
+/// This verifies that the file is WebM,
+/// that its Info and Tracks precede every Cluster
+/// and aren't referenced by the SeekHead beyond the first one,
+/// that all audio and video tracks use WebM codecs,
+/// and that each Cluster starts every video track with a keyframe.
+/// Checking stops at the first violation found, in file order.
+///
+/// ## Example
+/// ```no_run
+/// let file = std::fs::File::open("file.webm").unwrap();
+/// match matroska::check_mse_compat(file).unwrap().violation {
+///     Some(violation) => println!("incompatible: {}", violation),
+///     None => println!("compatible"),
+/// }
+/// ```
+pub fn check_mse_compat<R: io::Read + io::Seek>(mut r: R) -> Result<MseReport> {
+    Ok(MseReport {
+        violation: check(&mut r)?,
+    })
+}
+
+fn check<R: io::Read + io::Seek>(r: &mut R) -> Result<Option<MseViolation>> {
+    macro_rules! violation {
+        ($offset:expr, $problem:expr) => {
+            return Ok(Some(MseViolation {
+                offset: $offset,
+                problem: $problem,
+            }))
+        };
+    }
+
+    r.seek(SeekFrom::Start(0))?;
+    let (id, size, _) = ebml::read_element_id_size(r)?;
+    if id == ids::EBML {
+        let doc_type = Element::parse_master(r, size, Some(ids::EBML))?
+            .into_iter()
+            .find_map(|e| match e {
+                Element {
+                    id: ids::DOCTYPE,
+                    val: ElementType::String(doc_type),
+                    ..
+                } => Some(doc_type),
+                _ => None,
+            })
+            .unwrap_or_default();
+        if doc_type != "webm" {
+            violation!(0, MseProblem::DocType(doc_type));
+        }
+    } else {
+        violation!(0, MseProblem::DocType(String::new()));
+    }
+
+    let (mut id, mut size, _) = ebml::read_element_id_size(r)?;
+    while id != ids::SEGMENT {
+        skip_element(r, size)?;
+        let (next_id, next_size, _) = ebml::read_element_id_size(r)?;
+        id = next_id;
+        size = next_size;
+    }
+    let segment_start = r.stream_position()?;
+
+    let mut seeks = Vec::new();
+    let mut seen_info = false;
+    let mut video_tracks = BTreeSet::new();
+    let mut seen_tracks = false;
+    let mut seen_cluster = false;
+
+    let mut remaining = size;
+    while remaining > 0 {
+        let offset = r.stream_position()?;
+        let (id, mut size, len) = match ebml::read_element_id_size(r) {
+            Ok(header) => header,
+            Err(ebml::MatroskaError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(err) => return Err(err),
+        };
+        match id {
+            ids::SEEKHEAD => {
+                for e in Element::parse_master(r, size, Some(ids::SEGMENT))? {
+                    if let Element {
+                        id: ids::SEEK,
+                        val: ElementType::Master(sub_elements),
+                        ..
+                    } = e
+                    {
+                        let seek = SeekEntry::build(sub_elements);
+                        seeks.push((seek.id, segment_start.saturating_add(seek.position)));
+                    }
+                }
+            }
+            ids::INFO => {
+                seen_info = true;
+                size = skip_element(r, size)?;
+            }
+            ids::TRACKS => {
+                seen_tracks = true;
+                let mut tracks_remaining = size;
+                while tracks_remaining > 0 {
+                    let entry_offset = r.stream_position()?;
+                    let (id, mut size, len) = ebml::read_element_id_size(r)?;
+                    if id == ids::TRACKENTRY {
+                        let track = Track::build_entry(Element::parse_master(
+                            r,
+                            size,
+                            Some(ids::TRACKENTRY),
+                        )?);
+                        match track.tracktype {
+                            Tracktype::Video | Tracktype::Audio
+                                if !WEBM_CODECS.contains(&track.codec_id.as_str()) =>
+                            {
+                                violation!(
+                                    entry_offset,
+                                    MseProblem::UnsupportedCodec {
+                                        track: track.number,
+                                        codec_id: track.codec_id,
+                                    }
+                                );
+                            }
+                            Tracktype::Video => {
+                                video_tracks.insert(track.number);
+                            }
+                            _ => {}
+                        }
+                    } else {
+                        size = skip_element(r, size)?;
+                    }
+                    tracks_remaining = ebml::remaining_after(tracks_remaining, id, size, len)?;
+                }
+            }
+            ids::CLUSTER => {
+                if !seen_cluster {
+                    if !(seen_info && seen_tracks) {
+                        violation!(offset, MseProblem::ClusterBeforeInit);
+                    }
+                    if let Some((id, position)) = seeks
+                        .iter()
+                        .find(|(id, position)| {
+                            (*id == ids::INFO || *id == ids::TRACKS) && *position > offset
+                        })
+                        .copied()
+                    {
+                        violation!(offset, MseProblem::SeekBeyondInit { id, position });
+                    }
+                    seen_cluster = true;
+                }
+                if let Some(track) = check_cluster(r, size, &video_tracks)? {
+                    violation!(offset, MseProblem::ClusterWithoutKeyframe { track });
+                }
+                if size == ebml::UNKNOWN_SIZE {
+                    size = r.stream_position()? - offset - len;
+                }
+            }
+            _ => {
+                size = skip_element(r, size)?;
+            }
+        }
+        remaining = ebml::remaining_after(remaining, id, size, len)?;
+    }
+
+    Ok(None)
+}
+
+/// Returns the first video track whose first block
+/// in the Cluster isn't a keyframe
+fn check_cluster<R: io::Read + io::Seek>(
+    r: &mut R,
+    mut remaining: u64,
+    video_tracks: &BTreeSet<u64>,
+) -> Result<Option<u64>> {
+    let mut started = BTreeSet::new();
+    let mut found = None;
+    // an unknown-size Cluster ends where the next top-level element begins
+    let unknown = remaining == ebml::UNKNOWN_SIZE;
+    while remaining > 0 {
+        let (id, mut size, len) = if unknown {
+            match read_unknown_size_child(r)? {
+                Some(header) => header,
+                None => break,
+            }
+        } else {
+            ebml::read_element_id_size(r)?
+        };
+        let block = match id {
+            ids::SIMPLEBLOCK => {
+                let (header, _, _) = read_block(r, size, None)?;
+                Some((header.track, header.flags & 0x80 != 0))
+            }
+            ids::BLOCKGROUP => {
+                read_block_group(r, size, None)?.map(|(block, _)| (block.track, block.keyframe))
+            }
+            _ => {
+                size = skip_element(r, size)?;
+                None
+            }
+        };
+        if let Some((track, keyframe)) = block {
+            if video_tracks.contains(&track)
+                && started.insert(track)
+                && !keyframe
+                && found.is_none()
+            {
+                found = Some(track);
+            }
+        }
+        remaining = ebml::remaining_after(remaining, id, size, len)?;
+    }
+    Ok(found)
+}
@@ -0,0 +1,148 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+use crate::cluster::{Cluster, ClusterWalker};
+use crate::ebml::{MatroskaError, Result};
+use crate::{Matroska, SeekIndex, Track};
+
+/// A single frame of a track's data, as returned by [`TrackPackets`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Packet {
+    /// When the frame is presented
+    ///
+    /// Frames timestamped before the start of the file are given zero.
+    pub pts: Duration,
+    /// Whether the frame can be decoded on its own
+    pub keyframe: bool,
+    /// The frame's data, with the track's content encodings undone
+    pub data: Vec<u8>,
+}
+
+/// An iterator over the frames of a single track,
+/// for feeding a decoder or remuxing
+///
+/// Blocks of other tracks are skipped without reading their frame data.
+/// Laced frames are returned as separate packets,
+/// and frames are ordered by presentation time within each Cluster.
+///
+/// ## Example
+/// ```no_run
+/// use matroska::TrackPackets;
+///
+/// let file = std::io::BufReader::new(std::fs::File::open("file.mkv").unwrap());
+/// for packet in TrackPackets::new(file, 1).unwrap() {
+///     let packet = packet.unwrap();
+///     println!("{:?} : {} bytes", packet.pts, packet.data.len());
+/// }
+/// ```
+pub struct TrackPackets<R> {
+    walker: ClusterWalker<R>,
+    track: Track,
+    index: Option<SeekIndex>,
+    pending: VecDeque<Packet>,
+}
+
+impl<R: io::Read + io::Seek> TrackPackets<R> {
+    /// Opens a Matroska file and starts reading the packets
+    /// of the track with the given number
+    ///
+    /// A track number not in the file is [`MatroskaError::UnknownTrack`].
+    pub fn new(mut r: R, track_number: u64) -> Result<Self> {
+        r.seek(io::SeekFrom::Start(0))?;
+        let matroska = Matroska::open(&mut r)?;
+        let index = matroska.seek_index();
+        let track = matroska
+            .tracks
+            .into_iter()
+            .find(|track| track.number == track_number)
+            .ok_or(MatroskaError::UnknownTrack {
+                number: track_number,
+            })?;
+        Ok(TrackPackets {
+            walker: ClusterWalker::new(r, matroska.info.timestamp_scale, Some(track_number))?,
+            track,
+            index,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Returns the track whose packets are read
+    #[inline]
+    pub fn track(&self) -> &Track {
+        &self.track
+    }
+
+    /// Continues reading from the Cluster holding the
+    /// nearest cue point at or before the given time,
+    /// using the file's Cues
+    ///
+    /// Packets before the time within that Cluster are still returned.
+    /// Returns `false` and leaves the position unchanged
+    /// if the file has no Cues or none precede the time.
+    pub fn seek(&mut self, time: Duration) -> Result<bool> {
+        let offset = self
+            .index
+            .as_ref()
+            .and_then(|index| index.offset_for(time, Some(self.track.number)));
+        match offset {
+            Some(offset) => {
+                self.pending.clear();
+                self.walker.seek_to(offset)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn queue(&mut self, cluster: Cluster) -> Result<()> {
+        let frame_duration = self.track.default_duration.unwrap_or_default();
+        let mut packets = Vec::new();
+        for block in cluster.track_blocks.iter() {
+            let mut pts = cluster
+                .relative_timestamp(block.timestamp)
+                .unwrap_or_default();
+            for frame in &block.frames {
+                packets.push(Packet {
+                    pts,
+                    keyframe: block.keyframe,
+                    data: self.track.decode_frame(frame.clone())?,
+                });
+                // laced frames follow one another
+                pts += frame_duration;
+            }
+        }
+        packets.sort_by_key(|packet| packet.pts);
+        self.pending.extend(packets);
+        Ok(())
+    }
+}
+
+impl<R: io::Read + io::Seek> Iterator for TrackPackets<R> {
+    type Item = Result<Packet>;
+
+    fn next(&mut self) -> Option<Result<Packet>> {
+        loop {
+            if let Some(packet) = self.pending.pop_front() {
+                return Some(Ok(packet));
+            }
+            match self.walker.next()? {
+                Ok(cluster) => {
+                    if let Err(err) = self.queue(cluster) {
+                        return Some(Err(err));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::io;
+
+use crate::ebml::{self, Element, ElementType, Limits, MatroskaError, Result, UNKNOWN_SIZE};
+
+/// The header of an element read by an [`ElementReader`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ElementHeader {
+    /// The element's ID
+    pub id: u32,
+    /// The file offset of the element's header
+    pub offset: u64,
+    /// The length of the element's ID and size, in bytes
+    pub header_len: u64,
+    /// The length of the element's body in bytes,
+    /// or `None` if the element has an unknown size
+    pub size: Option<u64>,
+}
+
+impl ElementHeader {
+    /// The file offset of the element's body
+    #[inline]
+    pub fn body_offset(&self) -> u64 {
+        self.offset + self.header_len
+    }
+
+    /// The file offset just past the element's body,
+    /// or `None` if the element has an unknown size
+    #[inline]
+    pub fn end(&self) -> Option<u64> {
+        self.size.map(|size| self.body_offset() + size)
+    }
+}
+
+/// A master element being read through
+#[derive(Debug, Copy, Clone)]
+struct Level {
+    id: u32,
+    // None for unknown-size elements which run to their parent's end
+    end: Option<u64>,
+}
+
+/// A pull parser over the raw EBML elements of a stream
+///
+/// Elements are returned one at a time by [`ElementReader::next_element`],
+/// after which each may be descended into with [`ElementReader::children`],
+/// parsed with [`ElementReader::read_body`], read as raw bytes,
+/// or simply skipped by asking for the next element.
+/// Every element is checked to fit within its parent,
+/// and bodies are never read unless asked for.
+///
+/// ## Example
+/// ```no_run
+/// use matroska::ElementReader;
+///
+/// let file = std::io::BufReader::new(std::fs::File::open("file.mkv").unwrap());
+/// let mut reader = ElementReader::new(file).unwrap();
+/// while let Some(header) = reader.next_element().unwrap() {
+///     println!("{:X} at {}", header.id, header.offset);
+///     if header.id == matroska::ids::SEGMENT {
+///         reader.children().unwrap();
+///     }
+/// }
+/// ```
+pub struct ElementReader<R> {
+    inner: R,
+    position: u64,
+    // whether the inner reader may have moved from `position`
+    stale: bool,
+    levels: Vec<Level>,
+    current: Option<ElementHeader>,
+    limits: Limits,
+}
+
+impl<R: io::Read + io::Seek> ElementReader<R> {
+    /// Creates a reader starting from the stream's current position
+    pub fn new(mut inner: R) -> Result<Self> {
+        let position = inner.stream_position()?;
+        Ok(ElementReader {
+            inner,
+            position,
+            stale: false,
+            levels: Vec::new(),
+            current: None,
+            limits: Limits::default(),
+        })
+    }
+
+    /// Sets the largest element body to read, in bytes
+    ///
+    /// Larger bodies are [`MatroskaError::ElementTooLarge`]
+    /// when read, though they may still be skipped.
+    /// This defaults to 64 MiB.
+    pub fn with_max_element_size(mut self, max_element_size: u64) -> Self {
+        self.limits.max_element_size = max_element_size;
+        self
+    }
+
+    /// Sets how deeply elements may be descended into
+    ///
+    /// Deeper elements are [`MatroskaError::NestingTooDeep`].
+    /// This defaults to 32.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.limits.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the largest unknown element body to read, in bytes
+    ///
+    /// Larger bodies of elements whose type isn't known are
+    /// [`ElementType::Skipped`] by [`ElementReader::read_body`],
+    /// giving their offset in the file rather than their contents.
+    /// This defaults to 64 KiB.
+    pub fn with_max_unknown_size(mut self, max_unknown_size: u64) -> Self {
+        self.limits.max_unknown_size = max_unknown_size;
+        self
+    }
+
+    /// Returns the file offset the reader has reached
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns how many elements the reader is inside of
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Returns the reader, positioned wherever reading left it
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads the header of the next element,
+    /// skipping the body of the previous one if it wasn't read
+    ///
+    /// Returns `Ok(None)` at the end of the element being
+    /// descended into, which then returns to its parent,
+    /// or at the end of the stream.
+    pub fn next_element(&mut self) -> Result<Option<ElementHeader>> {
+        if let Some(current) = self.current.take() {
+            match current.end() {
+                Some(end) => self.seek_to(end)?,
+                None => return Err(MatroskaError::UnknownSizeElement { id: current.id }),
+            }
+        }
+        self.sync()?;
+
+        let end = self.levels.last().and_then(|level| level.end);
+        if end.map(|end| self.position >= end).unwrap_or(false) {
+            self.levels.pop();
+            return Ok(None);
+        }
+
+        let offset = self.position;
+        let (id, size, header_len) = match ebml::read_element_id_size(&mut self.inner) {
+            Ok(header) => header,
+            Err(MatroskaError::Io(err))
+                if err.kind() == io::ErrorKind::UnexpectedEof && end.is_none() =>
+            {
+                self.stale = true;
+                self.levels.pop();
+                return Ok(None);
+            }
+            Err(err) => {
+                self.stale = true;
+                return Err(err.located(offset, self.levels.last().map(|level| level.id)));
+            }
+        };
+        self.position += header_len;
+
+        if let Some(end) = end {
+            if size != UNKNOWN_SIZE {
+                ebml::remaining_after(end - offset, id, size, header_len)?;
+            }
+        }
+
+        let header = ElementHeader {
+            id,
+            offset,
+            header_len,
+            size: if size == UNKNOWN_SIZE {
+                None
+            } else {
+                Some(size)
+            },
+        };
+        self.current = Some(header);
+        Ok(Some(header))
+    }
+
+    /// Descends into the element last returned by
+    /// [`ElementReader::next_element`], whose children
+    /// are then returned until it ends
+    ///
+    /// An element of unknown size is taken to run
+    /// to the end of its parent.
+    pub fn children(&mut self) -> Result<()> {
+        let current = self.take_current()?;
+        if self.levels.len() >= self.limits.max_depth {
+            self.current = Some(current);
+            return Err(MatroskaError::NestingTooDeep { id: current.id });
+        }
+        let parent_end = self.levels.last().and_then(|level| level.end);
+        self.levels.push(Level {
+            id: current.id,
+            end: current.end().or(parent_end),
+        });
+        Ok(())
+    }
+
+    /// Skips the rest of the element being descended into,
+    /// returning to its parent
+    ///
+    /// Does nothing at the top level.
+    pub fn skip(&mut self) -> Result<()> {
+        self.current = None;
+        match self.levels.pop() {
+            Some(Level { end: Some(end), .. }) => self.seek_to(end),
+            Some(Level { id, end: None }) => Err(MatroskaError::UnknownSizeElement { id }),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses the body of the element last returned by
+    /// [`ElementReader::next_element`]
+    ///
+    /// Master elements are parsed along with all their children.
+    pub fn read_body(&mut self) -> Result<ElementType> {
+        let (header, size) = self.take_sized()?;
+        self.sync()?;
+        let parent = self.levels.last().map(|level| level.id);
+        let result = Element::parse_body_at(
+            &mut self.inner,
+            header.id,
+            size,
+            parent,
+            &self.limits,
+            header.body_offset(),
+        );
+        self.finish(header, size, result)
+    }
+
+    /// Reads the raw body of the element last returned by
+    /// [`ElementReader::next_element`]
+    pub fn read_raw(&mut self) -> Result<Vec<u8>> {
+        let (header, size) = self.take_sized()?;
+        if size > self.limits.max_element_size {
+            self.current = Some(header);
+            return Err(MatroskaError::ElementTooLarge {
+                id: header.id,
+                size,
+                limit: self.limits.max_element_size,
+            });
+        }
+        self.sync()?;
+        let result = ebml::read_bin(&mut self.inner, size);
+        self.finish(header, size, result)
+    }
+
+    /// Returns a reader over the raw body of the element last
+    /// returned by [`ElementReader::next_element`],
+    /// for streaming large bodies without holding them in memory
+    ///
+    /// Reading needn't finish; the next element is found regardless.
+    pub fn raw_reader(&mut self) -> Result<io::Take<&mut R>> {
+        let (header, size) = self.take_sized()?;
+        self.sync()?;
+        self.position = header.body_offset() + size;
+        self.stale = true;
+        Ok(io::Read::take(&mut self.inner, size))
+    }
+
+    fn take_current(&mut self) -> Result<ElementHeader> {
+        self.current.take().ok_or_else(|| {
+            MatroskaError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no element has been read",
+            ))
+        })
+    }
+
+    fn take_sized(&mut self) -> Result<(ElementHeader, u64)> {
+        let header = self.take_current()?;
+        match header.size {
+            Some(size) => Ok((header, size)),
+            None => {
+                self.current = Some(header);
+                Err(MatroskaError::UnknownSizeElement { id: header.id })
+            }
+        }
+    }
+
+    fn finish<T>(&mut self, header: ElementHeader, size: u64, result: Result<T>) -> Result<T> {
+        self.position = header.body_offset() + size;
+        // a failed read may have stopped anywhere in the body
+        self.stale = result.is_err();
+        result.map_err(|err| err.located(header.offset, Some(header.id)))
+    }
+
+    fn seek_to(&mut self, position: u64) -> Result<()> {
+        if self.stale || position != self.position {
+            self.inner.seek(io::SeekFrom::Start(position))?;
+            self.position = position;
+            self.stale = false;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        if self.stale {
+            self.inner.seek(io::SeekFrom::Start(self.position))?;
+            self.stale = false;
+        }
+        Ok(())
+    }
+}
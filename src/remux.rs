@@ -0,0 +1,570 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rewriting a file's metadata around copies of its Clusters
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, SeekFrom};
+
+use crate::cluster::ClusterWalker;
+use crate::ebml::{self, MatroskaError, Result};
+use crate::edit::{rewrite_master, split_children, Child, Layout};
+use crate::{
+    ids, unused_uid, Attachment, ChapterEdition, EbmlHeader, Language, Matroska, SeekEntry, Tag,
+};
+
+/// The most room a SeekHead's header takes
+const SEEK_HEAD_HEADER_SPACE: u64 = 12;
+
+/// The most room a SeekHead entry takes
+const SEEK_ENTRY_SPACE: u64 = 21;
+
+/// Changes made to a file's metadata by [`remux`]
+///
+/// Anything not changed is copied from the source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MetadataEdits {
+    /// The new title, or `Some(None)` to remove it
+    pub title: Option<Option<String>>,
+    /// Changes to individual tracks, by track number
+    pub tracks: BTreeMap<u64, TrackEdit>,
+    /// The new chapters, replacing all the existing ones
+    pub chapters: Option<Vec<ChapterEdition>>,
+    /// The new tags, replacing all the existing ones
+    pub tags: Option<Vec<Tag>>,
+    /// The new attachments, replacing all the existing ones
+    ///
+    /// Attachments whose data was deferred by
+    /// [`crate::ParseOptions::defer_attachment_data`]
+    /// have it copied from the source file.
+    pub attachments: Option<Vec<Attachment>>,
+}
+
+impl Default for MetadataEdits {
+    fn default() -> Self {
+        MetadataEdits::new()
+    }
+}
+
+impl MetadataEdits {
+    fn new() -> MetadataEdits {
+        MetadataEdits {
+            title: None,
+            tracks: BTreeMap::new(),
+            chapters: None,
+            tags: None,
+            attachments: None,
+        }
+    }
+
+    /// Sets the new title, or removes it if `None`
+    pub fn with_title(mut self, title: Option<&str>) -> Self {
+        self.title = Some(title.map(|title| title.to_owned()));
+        self
+    }
+
+    /// Sets the changes to the track with the given number
+    pub fn with_track(mut self, number: u64, edit: TrackEdit) -> Self {
+        self.tracks.insert(number, edit);
+        self
+    }
+
+    /// Sets the new chapters
+    pub fn with_chapters(mut self, chapters: Vec<ChapterEdition>) -> Self {
+        self.chapters = Some(chapters);
+        self
+    }
+
+    /// Sets the new tags
+    pub fn with_tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    /// Sets the new attachments
+    pub fn with_attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.attachments = Some(attachments);
+        self
+    }
+}
+
+/// Changes made to a single track by [`remux`]
+///
+/// The track's other elements are copied unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct TrackEdit {
+    /// The new name, or `Some(None)` to remove it
+    pub name: Option<Option<String>>,
+    /// The new language
+    ///
+    /// This replaces both the Language and LanguageIETF elements,
+    /// so a BCP 47 language leaves the track without the former.
+    pub language: Option<Language>,
+    /// The new enabled flag
+    pub enabled: Option<bool>,
+    /// The new default flag
+    pub default: Option<bool>,
+    /// The new forced flag
+    pub forced: Option<bool>,
+}
+
+impl Default for TrackEdit {
+    fn default() -> Self {
+        TrackEdit::new()
+    }
+}
+
+impl TrackEdit {
+    fn new() -> TrackEdit {
+        TrackEdit {
+            name: None,
+            language: None,
+            enabled: None,
+            default: None,
+            forced: None,
+        }
+    }
+
+    /// Sets the new name, or removes it if `None`
+    pub fn with_name(mut self, name: Option<&str>) -> Self {
+        self.name = Some(name.map(|name| name.to_owned()));
+        self
+    }
+
+    /// Sets the new language
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Sets the new enabled flag
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    /// Sets the new default flag
+    pub fn with_default(mut self, default: bool) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Sets the new forced flag
+    pub fn with_forced(mut self, forced: bool) -> Self {
+        self.forced = Some(forced);
+        self
+    }
+
+    /// Returns the TrackEntry children to replace, as for [`rewrite_master`]
+    fn replacements(&self) -> Vec<(u32, Option<Vec<u8>>)> {
+        let mut replacements = Vec::new();
+        if let Some(name) = &self.name {
+            replacements.push((
+                ids::NAME,
+                name.as_deref()
+                    .map(|name| ebml::string_element(ids::NAME, name)),
+            ));
+        }
+        match &self.language {
+            Some(Language::ISO639(code)) => {
+                replacements.push((
+                    ids::LANGUAGE,
+                    Some(ebml::string_element(ids::LANGUAGE, code)),
+                ));
+                replacements.push((ids::LANGUAGE_IETF, None));
+            }
+            Some(Language::IETF(tag)) => {
+                replacements.push((ids::LANGUAGE, None));
+                replacements.push((
+                    ids::LANGUAGE_IETF,
+                    Some(ebml::string_element(ids::LANGUAGE_IETF, tag)),
+                ));
+            }
+            None => {}
+        }
+        let flags = [
+            (ids::FLAGENABLED, self.enabled),
+            (ids::FLAGDEFAULT, self.default),
+            (ids::FLAGFORCED, self.forced),
+        ];
+        for (id, flag) in flags.iter() {
+            if let Some(flag) = flag {
+                replacements.push((*id, Some(ebml::uint_element(*id, *flag as u64))));
+            }
+        }
+        replacements
+    }
+}
+
+/// Writes a copy of a Matroska file with its metadata changed
+///
+/// The new file has a fresh EBML header and SeekHead,
+/// followed by the Info, Tracks, Chapters, Attachments and Tags
+/// with the given edits made, the source's Clusters
+/// copied byte for byte, and newly generated Cues.
+/// Cluster and attachment data is copied in fixed-size chunks,
+/// so the source's size doesn't matter,
+/// and timestamps are left as they are.
+///
+/// Cues list the first keyframe of each video track in every Cluster,
+/// or of every track if there are no video tracks.
+/// The source's Cues, SeekHeads, Void elements
+/// and any unknown top-level elements aren't copied,
+/// nor is anything after its first Segment.
+///
+/// Editing a track number not in the file is
+/// [`MatroskaError::UnknownTrack`].
+///
+/// ## Example
+/// ```no_run
+/// use matroska::MetadataEdits;
+///
+/// let src = std::fs::File::open("file.mkv").unwrap();
+/// let dst = std::fs::File::create("remuxed.mkv").unwrap();
+/// let edits = MetadataEdits::default()
+///     .with_title(Some("New Title"))
+///     .with_tags(Vec::new());
+/// matroska::remux(src, dst, edits).unwrap();
+/// ```
+pub fn remux<R, W>(mut src: R, mut dst: W, edits: MetadataEdits) -> Result<()>
+where
+    R: io::Read + io::Seek,
+    W: io::Write + io::Seek,
+{
+    let matroska = Matroska::open(&mut src)?;
+    if let Some(number) = edits
+        .tracks
+        .keys()
+        .find(|number| !matroska.tracks.iter().any(|t| t.number == **number))
+    {
+        return Err(MatroskaError::UnknownTrack { number: *number });
+    }
+    let layout = Layout::read(&mut src)?;
+    let mut cue_points = cue_points(&mut src, &matroska)?;
+
+    let header = EbmlHeader {
+        doc_type: matroska
+            .ebml_header
+            .as_ref()
+            .map(|h| h.doc_type.clone())
+            .unwrap_or_else(|| "matroska".to_owned()),
+        doc_type_version: matroska
+            .ebml_header
+            .as_ref()
+            .map_or(4, |h| h.doc_type_version),
+        doc_type_read_version: matroska
+            .ebml_header
+            .as_ref()
+            .map_or(2, |h| h.doc_type_read_version),
+        ..EbmlHeader::default()
+    };
+    dst.write_all(&header.encode())?;
+    let size_offset = dst.stream_position()? + 4;
+    dst.write_all(&ebml::element_header_sized(ids::SEGMENT, 0, 8).expect("sizes fit 8 bytes"))?;
+    let segment_start = dst.stream_position()?;
+    let seek_head_space = seek_head_space(&layout, &edits);
+    let mut void = ebml::void_header(seek_head_space).expect("reserved space fits a Void");
+    void.resize(seek_head_space as usize, 0);
+    dst.write_all(&void)?;
+
+    // every section is listed, including repeated ones
+    let mut entries = Vec::new();
+    let mut position = |dst: &mut W, id: u32| -> Result<()> {
+        let position = dst.stream_position()? - segment_start;
+        entries.push(SeekEntry { id, position });
+        Ok(())
+    };
+
+    if let Some(info) = layout.children_of(ids::INFO).next() {
+        let mut body = read_body(&mut src, info)?;
+        if let Some(title) = &edits.title {
+            let title = title
+                .as_deref()
+                .map(|title| ebml::string_element(ids::TITLE, title));
+            body = rewrite_master(&body, vec![(ids::TITLE, title)])?;
+        }
+        position(&mut dst, ids::INFO)?;
+        dst.write_all(&ebml::master_element(ids::INFO, vec![body]))?;
+    }
+
+    if let Some(tracks) = layout.children_of(ids::TRACKS).next() {
+        position(&mut dst, ids::TRACKS)?;
+        if edits.tracks.is_empty() {
+            copy(&mut src, &mut dst, tracks)?;
+        } else {
+            let body = read_body(&mut src, tracks)?;
+            let mut track_entries = Vec::new();
+            for (id, child) in split_children(&body)? {
+                let entry = &child[header_len(child)?..];
+                let edit = match id {
+                    ids::TRACKENTRY => track_number(entry)?.and_then(|n| edits.tracks.get(&n)),
+                    _ => None,
+                };
+                match edit {
+                    Some(edit) => track_entries.push(ebml::master_element(
+                        ids::TRACKENTRY,
+                        vec![rewrite_master(entry, edit.replacements())?],
+                    )),
+                    None => track_entries.push(child.to_vec()),
+                }
+            }
+            // a CRC-32 over the old entries is recalculated over the new
+            let body = rewrite_master(&track_entries.concat(), Vec::new())?;
+            dst.write_all(&ebml::master_element(ids::TRACKS, vec![body]))?;
+        }
+    }
+
+    match &edits.chapters {
+        Some(editions) => {
+            if !editions.is_empty() {
+                position(&mut dst, ids::CHAPTERS)?;
+                ChapterEdition::write_all(editions, &mut dst)?;
+            }
+        }
+        None => {
+            for chapters in layout.children_of(ids::CHAPTERS) {
+                position(&mut dst, ids::CHAPTERS)?;
+                copy(&mut src, &mut dst, chapters)?;
+            }
+        }
+    }
+
+    match &edits.attachments {
+        Some(attachments) => {
+            if !attachments.is_empty() {
+                position(&mut dst, ids::ATTACHMENTS)?;
+                write_attachments(&mut src, &mut dst, attachments)?;
+            }
+        }
+        None => {
+            for attachments in layout.children_of(ids::ATTACHMENTS) {
+                position(&mut dst, ids::ATTACHMENTS)?;
+                copy(&mut src, &mut dst, attachments)?;
+            }
+        }
+    }
+
+    match &edits.tags {
+        Some(tags) => {
+            if !tags.is_empty() {
+                position(&mut dst, ids::TAGS)?;
+                Tag::write_all(tags, &mut dst)?;
+            }
+        }
+        None => {
+            for tags in layout.children_of(ids::TAGS) {
+                position(&mut dst, ids::TAGS)?;
+                copy(&mut src, &mut dst, tags)?;
+            }
+        }
+    }
+
+    let mut cues = Vec::new();
+    for cluster in layout.children_of(ids::CLUSTER) {
+        let cluster_position = dst.stream_position()? - segment_start;
+        for (time, track) in cue_points.remove(&cluster.offset).unwrap_or_default() {
+            cues.push((time, track, cluster_position));
+        }
+        copy(&mut src, &mut dst, cluster)?;
+    }
+    if !cues.is_empty() {
+        // Clusters are usually in time order already
+        cues.sort_by_key(|(time, _, _)| *time);
+        let points = cues
+            .into_iter()
+            .map(|(time, track, cluster_position)| {
+                ebml::master_element(
+                    ids::CUEPOINT,
+                    vec![
+                        ebml::uint_element(ids::CUETIME, time),
+                        ebml::master_element(
+                            ids::CUETRACKPOSITIONS,
+                            vec![
+                                ebml::uint_element(ids::CUETRACK, track),
+                                ebml::uint_element(ids::CUECLUSTERPOSITION, cluster_position),
+                            ],
+                        ),
+                    ],
+                )
+            })
+            .collect();
+        position(&mut dst, ids::CUES)?;
+        dst.write_all(&ebml::master_element(ids::CUES, points))?;
+    }
+
+    let end = dst.stream_position()?;
+    let seek_head = entries
+        .iter()
+        .map(|e| e.encode())
+        .collect::<Vec<_>>()
+        .concat();
+    dst.seek(SeekFrom::Start(segment_start))?;
+    dst.write_all(
+        &ebml::padded_element(ids::SEEKHEAD, &seek_head, seek_head_space)
+            .expect("reserved space fits every entry"),
+    )?;
+    dst.seek(SeekFrom::Start(size_offset))?;
+    dst.write_all(&ebml::encode_size(end - segment_start, 8).expect("sizes fit 8 bytes"))?;
+    dst.seek(SeekFrom::Start(end))?;
+    dst.flush().map_err(MatroskaError::Io)
+}
+
+/// Returns the room to reserve for the SeekHead,
+/// which lists every section written
+fn seek_head_space(layout: &Layout, edits: &MetadataEdits) -> u64 {
+    let sections = |id: u32, edited: Option<bool>| match edited {
+        Some(empty) => u64::from(!empty),
+        None => layout.children_of(id).count() as u64,
+    };
+    // the Info, Tracks and Cues are listed once at most
+    let entries = 3
+        + sections(ids::CHAPTERS, edits.chapters.as_ref().map(Vec::is_empty))
+        + sections(
+            ids::ATTACHMENTS,
+            edits.attachments.as_ref().map(Vec::is_empty),
+        )
+        + sections(ids::TAGS, edits.tags.as_ref().map(Vec::is_empty));
+    SEEK_HEAD_HEADER_SPACE + entries * SEEK_ENTRY_SPACE
+}
+
+/// Returns the cue times and tracks of each Cluster, by file offset
+fn cue_points<R: io::Read + io::Seek>(
+    r: &mut R,
+    matroska: &Matroska,
+) -> Result<BTreeMap<u64, Vec<(u64, u64)>>> {
+    let video: BTreeSet<u64> = matroska.video_tracks().map(|t| t.number).collect();
+    let scale = matroska.info.timestamp_scale.max(1);
+    let mut points = BTreeMap::new();
+    for cluster in ClusterWalker::new(r, scale, None)? {
+        let cluster = cluster?;
+        let ticks: u64 =
+            std::convert::TryFrom::try_from(cluster.timestamp.as_nanos() / u128::from(scale))
+                .unwrap_or(u64::MAX);
+        let mut indexed = BTreeSet::new();
+        let cues = cluster
+            .blocks
+            .iter()
+            .filter(|b| b.keyframe && (video.is_empty() || video.contains(&b.track)))
+            .filter(|b| indexed.insert(b.track))
+            .map(|b| {
+                let offset = u64::from(b.timestamp.unsigned_abs());
+                let time = if b.timestamp < 0 {
+                    ticks.saturating_sub(offset)
+                } else {
+                    ticks.saturating_add(offset)
+                };
+                (time, b.track)
+            })
+            .collect();
+        points.insert(cluster.offset, cues);
+    }
+    Ok(points)
+}
+
+/// Writes an Attachments element, copying any deferred data from the source
+fn write_attachments<R, W>(src: &mut R, dst: &mut W, attachments: &[Attachment]) -> Result<()>
+where
+    R: io::Read + io::Seek,
+    W: io::Write,
+{
+    let mut taken: BTreeSet<u64> = attachments.iter().map(|a| a.uid).collect();
+    let files: Vec<(Vec<u8>, &Attachment)> = attachments
+        .iter()
+        .map(|attachment| {
+            let uid = match attachment.uid {
+                0 => unused_uid(&mut taken),
+                uid => uid,
+            };
+            let mut children = Vec::new();
+            if let Some(description) = &attachment.description {
+                children.push(ebml::string_element(ids::FILEDESCRIPTION, description));
+            }
+            children.push(ebml::string_element(ids::FILENAME, &attachment.name));
+            children.push(ebml::string_element(
+                ids::FILEMIMETYPE,
+                &attachment.mime_type,
+            ));
+            children.push(ebml::uint_element(ids::FILEUID, uid));
+            if let Some(referral) = &attachment.referral {
+                children.push(ebml::element(ids::FILEREFERRAL, referral));
+            }
+            let times = [
+                (ids::FILEUSEDSTARTTIME, attachment.used_start_time),
+                (ids::FILEUSEDENDTIME, attachment.used_end_time),
+            ];
+            for (id, time) in times.iter() {
+                if let Some(time) = time {
+                    let nanos: u64 =
+                        std::convert::TryFrom::try_from(time.as_nanos()).unwrap_or(u64::MAX);
+                    children.push(ebml::uint_element(*id, nanos));
+                }
+            }
+            // the data goes last, so deferred data can be streamed
+            let data_len = attachment.stored_len();
+            children.push(ebml::element_header(ids::FILEDATA, data_len));
+            let mut head = children.concat();
+            let mut header = ebml::element_header(ids::ATTACHEDFILE, head.len() as u64 + data_len);
+            header.append(&mut head);
+            (header, attachment)
+        })
+        .collect();
+
+    let size = files
+        .iter()
+        .map(|(head, attachment)| head.len() as u64 + attachment.stored_len())
+        .sum();
+    dst.write_all(&ebml::element_header(ids::ATTACHMENTS, size))?;
+    for (head, attachment) in files {
+        dst.write_all(&head)?;
+        attachment.write_data(&mut *src, &mut *dst)?;
+    }
+    Ok(())
+}
+
+/// Returns the TrackNumber of an encoded TrackEntry's body
+fn track_number(entry: &[u8]) -> Result<Option<u64>> {
+    for (id, child) in split_children(entry)? {
+        if id == ids::TRACKNUMBER {
+            let header_len = header_len(child)?;
+            let size = (child.len() - header_len) as u64;
+            return ebml::read_uint(&mut &child[header_len..], size).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the length of the header of an encoded element
+fn header_len(element: &[u8]) -> Result<usize> {
+    match ebml::decode_element_id_size(element)? {
+        Some((_, _, header_len)) => Ok(header_len as usize),
+        None => Err(MatroskaError::Io(io::ErrorKind::UnexpectedEof.into())),
+    }
+}
+
+/// Reads the body of a top-level element
+fn read_body<R: io::Read + io::Seek>(r: &mut R, child: &Child) -> Result<Vec<u8>> {
+    r.seek(SeekFrom::Start(child.offset + child.header_len))?;
+    ebml::read_bin(r, child.len - child.header_len)
+}
+
+/// Copies a whole top-level element, a chunk at a time
+fn copy<R, W>(src: &mut R, dst: &mut W, child: &Child) -> Result<()>
+where
+    R: io::Read + io::Seek,
+    W: io::Write,
+{
+    src.seek(SeekFrom::Start(child.offset))?;
+    let copied = io::copy(&mut io::Read::take(src, child.len), dst)?;
+    if copied == child.len {
+        Ok(())
+    } else {
+        Err(MatroskaError::Io(io::ErrorKind::UnexpectedEof.into()))
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+
+use crate::{ChapterEdition, Matroska, Track};
+
+/// Subtitle tracks with fewer entries than this fraction
+/// of the language's largest track are presumed to be forced
+const FORCED_ENTRY_RATIO: u64 = 4;
+
+/// Returns the subtitle track to burn into video with the given audio language
+///
+/// This is the first of these to be found among the
+/// enabled subtitle tracks in the audio's language:
+///
+/// 1. a track with its forced flag set
+/// 2. a track whose name contains "forced"
+///
+/// Full subtitle tracks are never chosen, so if no
+/// track qualifies, no subtitles should be burned in.
+///
+/// ## Example
+/// ```no_run
+/// let matroska = matroska::open("file.mkv").unwrap();
+/// if let Some(track) = matroska::burn_in_candidate(&matroska, "eng") {
+///     println!("burning in track {}", track.number);
+/// }
+/// ```
+pub fn burn_in_candidate<'m>(matroska: &'m Matroska, audio_lang: &str) -> Option<&'m Track> {
+    burn_in_candidate_with_counts(matroska, audio_lang, &BTreeMap::new())
+}
+
+/// Returns the subtitle track to burn in, using known entry counts
+///
+/// `entry_counts` maps track numbers to the number of subtitle
+/// entries in each track, such as from mkvmerge's statistics tags.
+/// In addition to the rules of [`burn_in_candidate`],
+/// when several tracks share the audio's language,
+/// one with under a quarter of the entries of the largest is
+/// presumed to be forced, which catches files that set the
+/// default flag on their forced subtitles instead.
+/// Among several such tracks, a default one is preferred.
+pub fn burn_in_candidate_with_counts<'m>(
+    matroska: &'m Matroska,
+    audio_lang: &str,
+    entry_counts: &BTreeMap<u64, u64>,
+) -> Option<&'m Track> {
+    let candidates: Vec<&Track> = matroska
+        .subtitle_tracks()
+        .filter(|t| t.enabled)
+        .filter(|t| {
+            t.language
+                .as_ref()
+                .map(|l| l.matches(audio_lang))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    prefer_default(candidates.iter().copied().filter(|t| t.forced))
+        .or_else(|| {
+            prefer_default(candidates.iter().copied().filter(|t| {
+                t.name
+                    .as_ref()
+                    .map(|n| n.to_ascii_lowercase().contains("forced"))
+                    .unwrap_or(false)
+            }))
+        })
+        .or_else(|| {
+            if candidates.len() < 2 {
+                return None;
+            }
+            let largest = candidates
+                .iter()
+                .filter_map(|t| entry_counts.get(&t.number))
+                .max()?;
+            prefer_default(candidates.iter().copied().filter(|t| {
+                entry_counts
+                    .get(&t.number)
+                    .map(|count| count * FORCED_ENTRY_RATIO < *largest)
+                    .unwrap_or(false)
+            }))
+        })
+}
+
+impl Matroska {
+    /// Returns the chapter edition to use by default,
+    /// which is the first edition flagged as default,
+    /// or the first edition if none are
+    pub fn default_edition(&self) -> Option<&ChapterEdition> {
+        self.chapters
+            .iter()
+            .find(|e| e.default)
+            .or_else(|| self.chapters.first())
+    }
+
+    /// Returns the video track to play by default
+    ///
+    /// See [`Matroska::default_audio_track`] for how it's chosen.
+    pub fn default_video_track(&self) -> Option<&Track> {
+        default_track(self.video_tracks()).or_else(|| self.video_tracks().find(|t| t.enabled))
+    }
+
+    /// Returns the audio track to play by default
+    ///
+    /// Only enabled tracks are considered.
+    /// The first forced track is chosen, then the first default track,
+    /// and otherwise the first track of its type.
+    pub fn default_audio_track(&self) -> Option<&Track> {
+        default_track(self.audio_tracks()).or_else(|| self.audio_tracks().find(|t| t.enabled))
+    }
+
+    /// Returns the subtitle track to show by default, if any
+    ///
+    /// Only enabled tracks are considered.
+    /// The first forced track is chosen, then the first default track.
+    /// Unlike video and audio, subtitles are otherwise left off.
+    pub fn default_subtitle_track(&self) -> Option<&Track> {
+        default_track(self.subtitle_tracks())
+    }
+
+    /// Returns the forced subtitle track in the given language, if any,
+    /// such as to show with audio in that language
+    ///
+    /// Only enabled tracks are considered, and a default one
+    /// is preferred over others.
+    /// Languages match as for [`Matroska::tracks_by_language`].
+    pub fn forced_subtitle_track(&self, language: &str) -> Option<&Track> {
+        let forced = self
+            .subtitle_tracks()
+            .filter(|t| t.enabled && t.forced)
+            .filter(|t| {
+                t.language
+                    .as_ref()
+                    .map(|l| l.matches(language))
+                    .unwrap_or(false)
+            });
+        prefer_default(forced)
+    }
+}
+
+/// Returns the first forced track, or else the first default track,
+/// of those enabled
+fn default_track<'m>(tracks: impl Iterator<Item = &'m Track>) -> Option<&'m Track> {
+    let enabled: Vec<&Track> = tracks.filter(|t| t.enabled).collect();
+    enabled
+        .iter()
+        .find(|t| t.forced)
+        .or_else(|| enabled.iter().find(|t| t.default))
+        .copied()
+}
+
+/// Returns the first default track, or else the first track
+fn prefer_default<'m>(tracks: impl Iterator<Item = &'m Track>) -> Option<&'m Track> {
+    let tracks: Vec<&Track> = tracks.collect();
+    tracks
+        .iter()
+        .find(|t| t.default)
+        .or_else(|| tracks.first())
+        .copied()
+}
@@ -0,0 +1,171 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The OGM "simple chapters" text format
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::ebml::{MatroskaError, Result};
+use crate::{Chapter, ChapterDisplay, ChapterEdition};
+
+impl ChapterEdition {
+    /// Returns the edition's chapters in the OGM "simple chapters" format,
+    /// as accepted by mkvmerge
+    ///
+    /// Each chapter is a `CHAPTER01=00:00:00.000` line giving its start,
+    /// followed by a `CHAPTER01NAME=Intro` line giving its first display string,
+    /// which is empty if it has none.
+    /// Chapters are numbered from 1 in the order of [`ChapterEdition::iter_all`],
+    /// so nested chapters are flattened, and numbers past 99 take 3 digits.
+    ///
+    /// ## Example
+    /// ```
+    /// use matroska::{Chapter, ChapterDisplay, ChapterEdition};
+    /// use std::time::Duration;
+    ///
+    /// let edition = ChapterEdition::default().with_chapter(
+    ///     Chapter::default()
+    ///         .with_time_start(Duration::from_millis(90_500))
+    ///         .with_display(ChapterDisplay::default().with_string("Intro")),
+    /// );
+    /// assert_eq!(
+    ///     edition.to_simple_chapters(),
+    ///     "CHAPTER01=00:01:30.500\nCHAPTER01NAME=Intro\n"
+    /// );
+    /// ```
+    pub fn to_simple_chapters(&self) -> String {
+        let mut text = String::new();
+        for (i, chapter) in self.iter_all().enumerate() {
+            let secs = chapter.time_start.as_secs();
+            let name = chapter.display.first().map_or("", |d| d.string.as_str());
+            // writing to a String can't fail
+            let _ = write!(
+                text,
+                "CHAPTER{n:02}={:02}:{:02}:{:02}.{:03}\nCHAPTER{n:02}NAME={}\n",
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60,
+                chapter.time_start.subsec_millis(),
+                name,
+                n = i + 1,
+            );
+        }
+        text
+    }
+
+    /// Parses chapters in the OGM "simple chapters" format
+    ///
+    /// Chapters are taken in the order of their `CHAPTERnn` lines,
+    /// whose times may have any number of fractional digits, or none.
+    /// A chapter without a `CHAPTERnnNAME` line has no display string,
+    /// and a byte order mark, blank lines and `\r\n` line endings are ignored.
+    /// The chapters' UIDs are 0, to be generated when they're written.
+    ///
+    /// Any other line, or a name for a chapter with no time,
+    /// is [`MatroskaError::InvalidSimpleChapters`].
+    ///
+    /// ## Example
+    /// ```
+    /// use matroska::ChapterEdition;
+    /// use std::time::Duration;
+    ///
+    /// let edition = ChapterEdition::from_simple_chapters(
+    ///     "CHAPTER01=00:00:00.000\nCHAPTER01NAME=Intro\nCHAPTER02=00:01:30.500\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(edition.chapters.len(), 2);
+    /// assert_eq!(edition.chapters[0].display[0].string, "Intro");
+    /// assert_eq!(edition.chapters[1].time_start, Duration::from_millis(90_500));
+    /// assert!(edition.chapters[1].display.is_empty());
+    /// ```
+    pub fn from_simple_chapters(text: &str) -> Result<ChapterEdition> {
+        let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+        // chapter numbers and the chapters they've started
+        let mut numbers: Vec<u64> = Vec::new();
+        let mut chapters: Vec<Chapter> = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let invalid = || MatroskaError::InvalidSimpleChapters { line: i + 1 };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .strip_prefix("CHAPTER")
+                .and_then(|line| line.split_once('='))
+                .ok_or_else(invalid)?;
+            match key.strip_suffix("NAME") {
+                Some(number) => {
+                    let number = parse_number(number).ok_or_else(invalid)?;
+                    let index = numbers
+                        .iter()
+                        .rposition(|n| *n == number)
+                        .ok_or_else(invalid)?;
+                    chapters[index].display = vec![ChapterDisplay::default().with_string(value)];
+                }
+                None => {
+                    numbers.push(parse_number(key).ok_or_else(invalid)?);
+                    chapters.push(
+                        Chapter::default()
+                            .with_time_start(parse_time(value.trim()).ok_or_else(invalid)?),
+                    );
+                }
+            }
+        }
+        Ok(chapters
+            .into_iter()
+            .fold(ChapterEdition::default(), |edition, chapter| {
+                edition.with_chapter(chapter)
+            }))
+    }
+}
+
+/// Parses a time such as `01:23:45.678`
+fn parse_time(time: &str) -> Option<Duration> {
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) => (time, fraction),
+        None => (time, ""),
+    };
+    let mut fields = time.split(':');
+    let (hours, minutes, seconds) = (fields.next()?, fields.next()?, fields.next()?);
+    if fields.next().is_some() {
+        return None;
+    }
+    let (minutes, seconds) = (parse_number(minutes)?, parse_number(seconds)?);
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    let secs = parse_number(hours)?
+        .checked_mul(3600)?
+        .checked_add(minutes * 60 + seconds)?;
+
+    // fractions are truncated to nanoseconds
+    let nanos = match fraction {
+        "" => 0,
+        fraction => {
+            if !is_number(fraction) {
+                return None;
+            }
+            let digits = &fraction[..fraction.len().min(9)];
+            parse_number(digits)? * 10u64.pow(9 - digits.len() as u32)
+        }
+    };
+    Some(Duration::new(secs, nanos as u32))
+}
+
+/// Parses a number made only of ASCII digits
+fn parse_number(number: &str) -> Option<u64> {
+    if is_number(number) {
+        number.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn is_number(number: &str) -> bool {
+    !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())
+}
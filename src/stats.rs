@@ -0,0 +1,166 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+use std::io::{self, SeekFrom};
+use std::time::Duration;
+
+use crate::cluster::ClusterWalker;
+use crate::ebml::Result;
+use crate::Matroska;
+
+/// How a track's frames are laced into blocks
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LacingStatistics {
+    /// The number of blocks holding each number of frames
+    pub frames_per_block: BTreeMap<u64, u64>,
+    /// The longest block duration implied by its frame count
+    /// and the track's default duration, if it has one
+    pub max_block_duration: Option<Duration>,
+}
+
+/// Scans a file's blocks and returns how each track's frames are laced,
+/// keyed by track number
+///
+/// Only block headers and lace sizes are read;
+/// frame data is skipped.
+///
+/// ## Example
+/// ```no_run
+/// let file = std::fs::File::open("file.mkv").unwrap();
+/// for (track, stats) in matroska::lacing_statistics(file).unwrap() {
+///     println!("track {} : {:?}", track, stats.frames_per_block);
+/// }
+/// ```
+pub fn lacing_statistics<R: io::Read + io::Seek>(
+    mut r: R,
+) -> Result<BTreeMap<u64, LacingStatistics>> {
+    let matroska = Matroska::open(&mut r)?;
+    let default_durations: BTreeMap<u64, Duration> = matroska
+        .tracks
+        .iter()
+        .filter_map(|t| t.default_duration.map(|d| (t.number, d)))
+        .collect();
+
+    let mut statistics: BTreeMap<u64, LacingStatistics> = BTreeMap::new();
+    for cluster in ClusterWalker::new(&mut r, matroska.info.timestamp_scale, None)? {
+        for block in cluster?.blocks {
+            let stats = statistics.entry(block.track).or_default();
+            *stats.frames_per_block.entry(block.frames).or_default() += 1;
+            if let Some(duration) = default_durations.get(&block.track) {
+                let block_duration = duration.saturating_mul(block.frames as u32);
+                stats.max_block_duration = stats.max_block_duration.max(Some(block_duration));
+            }
+        }
+    }
+    Ok(statistics)
+}
+
+/// How much of a file a track's frame data takes up,
+/// as returned by [`track_sizes`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TrackUsage {
+    /// The total size of the track's frame data, in bytes
+    pub bytes: u64,
+    /// The number of blocks in the track
+    pub packets: u64,
+    /// The timestamp of the track's earliest block
+    pub first: Option<Duration>,
+    /// The timestamp of the track's latest block
+    pub last: Option<Duration>,
+}
+
+impl TrackUsage {
+    /// Returns the track's average bitrate in bits per second,
+    /// over the time between its first and last blocks
+    ///
+    /// Returns `None` if the track has less than
+    /// that much time's worth of blocks.
+    pub fn bits_per_second(&self) -> Option<f64> {
+        let span = self.last?.checked_sub(self.first?)?;
+        if span.is_zero() {
+            None
+        } else {
+            Some(self.bytes as f64 * 8.0 / span.as_secs_f64())
+        }
+    }
+}
+
+/// Scans a file's blocks and returns how much of it each track uses,
+/// keyed by track number
+///
+/// Only block headers are read; frame data is skipped.
+///
+/// ## Example
+/// ```no_run
+/// let file = std::io::BufReader::new(std::fs::File::open("file.mkv").unwrap());
+/// for (track, usage) in matroska::track_sizes(file).unwrap() {
+///     println!("track {} : {} bytes, {:?} bps", track, usage.bytes, usage.bits_per_second());
+/// }
+/// ```
+pub fn track_sizes<R: io::Read + io::Seek>(r: R) -> Result<BTreeMap<u64, TrackUsage>> {
+    track_sizes_sampled(r, 1)
+}
+
+/// Estimates how much of a file each track uses, keyed by track number,
+/// from the blocks of every `every`th Cluster
+///
+/// The other Clusters are skipped without reading their blocks,
+/// which saves a good deal of I/O with large files.
+/// Sizes and packet counts are scaled up by the proportion
+/// of Clusters skipped, while first and last timestamps
+/// come from the sampled Clusters alone.
+/// An `every` of 0 or 1 samples every Cluster,
+/// as with [`track_sizes`].
+pub fn track_sizes_sampled<R: io::Read + io::Seek>(
+    mut r: R,
+    every: u64,
+) -> Result<BTreeMap<u64, TrackUsage>> {
+    r.seek(SeekFrom::Start(0))?;
+    let matroska = Matroska::open(&mut r)?;
+    let mut walker = ClusterWalker::new(&mut r, matroska.info.timestamp_scale, None)?;
+
+    let every = every.max(1);
+    let mut usage: BTreeMap<u64, TrackUsage> = BTreeMap::new();
+    let (mut total, mut sampled) = (0u64, 0u64);
+    loop {
+        if total % every != 0 {
+            if !walker.skip_cluster()? {
+                break;
+            }
+            total += 1;
+            continue;
+        }
+        let cluster = match walker.next() {
+            Some(cluster) => cluster?,
+            None => break,
+        };
+        total += 1;
+        sampled += 1;
+        for block in &cluster.blocks {
+            let track = usage.entry(block.track).or_default();
+            track.bytes += block.size;
+            track.packets += 1;
+            if let Some(timestamp) = cluster.block_timestamp(block) {
+                track.first = Some(track.first.map_or(timestamp, |t| t.min(timestamp)));
+                track.last = track.last.max(Some(timestamp));
+            }
+        }
+    }
+
+    if sampled < total {
+        let scale = |n: u64| (u128::from(n) * u128::from(total) / u128::from(sampled)) as u64;
+        for track in usage.values_mut() {
+            track.bytes = scale(track.bytes);
+            track.packets = scale(track.packets);
+        }
+    }
+    Ok(usage)
+}
@@ -0,0 +1,169 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt::Write;
+use std::io;
+use std::time::Duration;
+
+use crate::cluster::ClusterWalker;
+use crate::ebml::{MatroskaError, Result};
+use crate::Matroska;
+
+/// A single subtitle from a text subtitle track
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SubtitleEntry {
+    /// When the subtitle is shown
+    pub start: Duration,
+    /// How long the subtitle is shown, if known,
+    /// from its BlockDuration or the track's default duration
+    pub duration: Option<Duration>,
+    /// The subtitle's text
+    ///
+    /// For SSA/ASS tracks, this is the event's fields
+    /// starting from its ReadOrder, as stored in the block.
+    pub text: String,
+}
+
+impl SubtitleEntry {
+    /// When the subtitle stops being shown, if known
+    pub fn end(&self) -> Option<Duration> {
+        self.duration.map(|duration| self.start + duration)
+    }
+}
+
+/// Reads every subtitle from an open Matroska file's text subtitle track,
+/// such as an `S_TEXT/UTF8` or `S_TEXT/ASS` track, in order of start time
+///
+/// Header stripping is undone as the track specifies.
+/// A track number not in the file is [`MatroskaError::UnknownTrack`].
+///
+/// ## Example
+/// ```no_run
+/// let file = std::fs::File::open("file.mkv").unwrap();
+/// let subtitles = matroska::extract_subtitles(file, 3).unwrap();
+/// print!("{}", matroska::to_srt(&subtitles));
+/// ```
+pub fn extract_subtitles<R: io::Read + io::Seek>(
+    mut r: R,
+    track_number: u64,
+) -> Result<Vec<SubtitleEntry>> {
+    r.seek(io::SeekFrom::Start(0))?;
+    let matroska = Matroska::open(&mut r)?;
+    let track = matroska
+        .tracks
+        .iter()
+        .find(|track| track.number == track_number)
+        .ok_or(MatroskaError::UnknownTrack {
+            number: track_number,
+        })?;
+
+    let mut entries = Vec::new();
+    for cluster in ClusterWalker::new(&mut r, matroska.info.timestamp_scale, Some(track_number))? {
+        let cluster = cluster?;
+        for block in &cluster.track_blocks {
+            // subtitles before the start of the file are never shown
+            let mut start = match cluster.relative_timestamp(block.timestamp) {
+                Some(start) => start,
+                None => continue,
+            };
+            let duration = match block.duration {
+                Some(ticks) => Some(cluster.ticks(ticks)),
+                None => track.default_duration,
+            };
+            for frame in &block.frames {
+                let text = String::from_utf8(track.decode_frame(frame.clone())?)
+                    .map_err(MatroskaError::UTF8)?;
+                entries.push(SubtitleEntry {
+                    start,
+                    duration,
+                    text,
+                });
+                // laced frames follow one another
+                start += track.default_duration.unwrap_or_default();
+            }
+        }
+    }
+    entries.sort_by_key(|entry| entry.start);
+    Ok(entries)
+}
+
+/// Renders subtitles as an SRT file
+///
+/// Subtitles without a duration are shown until the next one starts.
+pub fn to_srt(entries: &[SubtitleEntry]) -> String {
+    let mut srt = String::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let end = until(entries, i);
+        let _ = writeln!(srt, "{}", i + 1);
+        let _ = writeln!(srt, "{} --> {}", srt_time(entry.start), srt_time(end));
+        let _ = writeln!(srt, "{}", entry.text.replace("\r\n", "\n").trim_end());
+        srt.push('\n');
+    }
+    srt
+}
+
+/// Renders subtitles from an SSA/ASS track as an SSA/ASS file,
+/// given the track's codec private data which holds the file's header
+///
+/// Subtitles without a duration are shown until the next one starts.
+pub fn to_ass(codec_private: &[u8], entries: &[SubtitleEntry]) -> String {
+    let mut ass = String::from_utf8_lossy(codec_private)
+        .trim_end_matches('\0')
+        .to_owned();
+    if !ass.is_empty() && !ass.ends_with('\n') {
+        ass.push('\n');
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        // blocks hold ReadOrder, Layer, Style, Name, MarginL,
+        // MarginR, MarginV, Effect and Text, while Dialogue lines
+        // hold Layer, Start and End in place of ReadOrder
+        let mut fields = entry.text.splitn(3, ',');
+        let (_read_order, layer, rest) = (fields.next(), fields.next(), fields.next());
+        let _ = writeln!(
+            ass,
+            "Dialogue: {},{},{},{}",
+            layer.unwrap_or("0"),
+            ass_time(entry.start),
+            ass_time(until(entries, i)),
+            rest.unwrap_or_default()
+        );
+    }
+    ass
+}
+
+/// Returns when the subtitle at the given index ends
+fn until(entries: &[SubtitleEntry], i: usize) -> Duration {
+    let entry = &entries[i];
+    entry
+        .end()
+        .or_else(|| entries.get(i + 1).map(|next| next.start))
+        .unwrap_or(entry.start)
+}
+
+fn srt_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        time.subsec_millis()
+    )
+}
+
+fn ass_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        time.subsec_millis() / 10
+    )
+}
@@ -0,0 +1,221 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::time::Duration;
+
+use crate::{Info, Matroska, SimpleTag, TagValue, Target};
+
+/// Per-track statistics written as tags by mkvmerge
+///
+/// Each field is `None` if its tag is missing or unparseable.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct TrackStatistics {
+    /// The average bits per second, from `BPS`
+    pub bps: Option<u64>,
+    /// The track's duration, from `DURATION`
+    pub duration: Option<Duration>,
+    /// The number of frames, from `NUMBER_OF_FRAMES`
+    pub number_of_frames: Option<u64>,
+    /// The number of bytes, from `NUMBER_OF_BYTES`
+    pub number_of_bytes: Option<u64>,
+    /// The application which wrote the statistics,
+    /// from `_STATISTICS_WRITING_APP`
+    pub writing_app: Option<String>,
+    /// When the statistics were written, in UTC,
+    /// from `_STATISTICS_WRITING_DATE_UTC`
+    pub writing_date: Option<String>,
+    /// The names of the statistics tags, from `_STATISTICS_TAGS`
+    pub tags: Vec<String>,
+}
+
+impl TrackStatistics {
+    /// Whether the statistics were written by the application
+    /// which wrote the file, and so are likely accurate
+    ///
+    /// Statistics carried over by a different application,
+    /// which may have altered the track, are likely stale.
+    /// Statistics without a writing application are presumed current.
+    pub fn is_current(&self, info: &Info) -> bool {
+        self.writing_app
+            .as_ref()
+            .map(|app| *app == info.writing_app)
+            .unwrap_or(true)
+    }
+}
+
+impl Matroska {
+    /// Returns the simple tags which apply to the track with the given UID
+    ///
+    /// These are tags whose targets list the track's UID,
+    /// including those which also list chapters or editions,
+    /// along with tags that apply to the whole segment
+    /// because their targets are absent or list no UIDs at all.
+    /// A listed UID of 0 applies to every track.
+    /// Tags are returned in the order they're stored,
+    /// so several tags at the same target level are all included.
+    pub fn tags_for_track(&self, track_uid: u64) -> impl Iterator<Item = &SimpleTag> {
+        self.tags_where(move |target| listed(&target.track_uids, track_uid))
+    }
+
+    /// Returns the simple tags which apply to the chapter with the given UID
+    ///
+    /// The rules are the same as for [`Matroska::tags_for_track`],
+    /// using the targets' chapter UIDs.
+    pub fn tags_for_chapter(&self, chapter_uid: u64) -> impl Iterator<Item = &SimpleTag> {
+        self.tags_where(move |target| listed(&target.chapter_uids, chapter_uid))
+    }
+
+    /// Returns the simple tag with the given name, preferring the given language
+    ///
+    /// Names are matched case-insensitively, since they're
+    /// conventionally uppercase ASCII. Languages are matched by
+    /// their primary subtag, so `"en"` matches an IETF `en-US`.
+    /// Without a language, or if none match it, the first tag
+    /// flagged as the default is chosen, or else the first tag
+    /// with the name at all.
+    /// All of the file's tags are searched, whatever their targets.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// let matroska = matroska::open("file.mkv").unwrap();
+    /// if let Some(title) = matroska.simple_tag("TITLE", Some("eng")) {
+    ///     println!("title : {:?}", title.value);
+    /// }
+    /// ```
+    pub fn simple_tag(&self, name: &str, lang: Option<&str>) -> Option<&SimpleTag> {
+        let mut named = self.simple_tags(name, None);
+        let first = named.next()?;
+        let mut default = None;
+        for tag in std::iter::once(first).chain(named) {
+            match lang {
+                Some(lang) if has_language(tag, lang) => return Some(tag),
+                Some(_) if default.is_none() && tag.default => default = Some(tag),
+                None if tag.default => return Some(tag),
+                _ => {}
+            }
+        }
+        default.or(Some(first))
+    }
+
+    /// Returns all simple tags with the given name and, if given, language
+    ///
+    /// Names and languages are matched as in [`Matroska::simple_tag`].
+    pub fn simple_tags<'m, 'n>(
+        &'m self,
+        name: &'n str,
+        lang: Option<&'n str>,
+    ) -> impl Iterator<Item = &'m SimpleTag> + 'n
+    where
+        'm: 'n,
+    {
+        self.tags
+            .iter()
+            .flat_map(|tag| tag.simple.iter())
+            .filter(move |tag| tag.name.eq_ignore_ascii_case(name))
+            .filter(move |tag| lang.map(|lang| has_language(tag, lang)).unwrap_or(true))
+    }
+
+    /// Returns the statistics mkvmerge wrote for the track with the given UID
+    ///
+    /// These come from the tags targeting the track's UID,
+    /// including names with a language suffix such as `BPS-eng`
+    /// as written by some remuxers. Returns `None` if
+    /// there are no statistics tags for the track at all.
+    /// Use [`TrackStatistics::is_current`] to detect stale statistics.
+    pub fn track_statistics(&self, track_uid: u64) -> Option<TrackStatistics> {
+        let mut stats = TrackStatistics::default();
+        let mut found = false;
+        let simple = self
+            .tags
+            .iter()
+            .filter(|tag| {
+                tag.targets
+                    .as_ref()
+                    .map(|target| target.track_uids.contains(&track_uid))
+                    .unwrap_or(false)
+            })
+            .flat_map(|tag| tag.simple.iter());
+        for tag in simple {
+            let value = match &tag.value {
+                Some(TagValue::String(value)) => value.trim(),
+                _ => continue,
+            };
+            let name = tag.name.split('-').next().unwrap_or(&tag.name);
+            match name {
+                "BPS" => stats.bps = value.parse().ok(),
+                "DURATION" => stats.duration = parse_duration(value),
+                "NUMBER_OF_FRAMES" => stats.number_of_frames = value.parse().ok(),
+                "NUMBER_OF_BYTES" => stats.number_of_bytes = value.parse().ok(),
+                "_STATISTICS_WRITING_APP" => stats.writing_app = Some(value.to_string()),
+                "_STATISTICS_WRITING_DATE_UTC" => stats.writing_date = Some(value.to_string()),
+                "_STATISTICS_TAGS" => {
+                    stats.tags = value.split_whitespace().map(str::to_string).collect()
+                }
+                _ => continue,
+            }
+            found = true;
+        }
+        Some(stats).filter(|_| found)
+    }
+
+    pub(crate) fn tags_where<F>(&self, applies: F) -> impl Iterator<Item = &SimpleTag>
+    where
+        F: Fn(&Target) -> bool,
+    {
+        self.tags
+            .iter()
+            .filter(move |tag| match &tag.targets {
+                None => true,
+                Some(target) => is_global(target) || applies(target),
+            })
+            .flat_map(|tag| tag.simple.iter())
+    }
+}
+
+/// Whether a simple tag is in the given language
+fn has_language(tag: &SimpleTag, lang: &str) -> bool {
+    tag.language
+        .as_ref()
+        .map(|l| l.matches(lang))
+        .unwrap_or(false)
+}
+
+/// Whether a target lists no UIDs and so applies to the whole segment
+fn is_global(target: &Target) -> bool {
+    target.track_uids.is_empty()
+        && target.edition_uids.is_empty()
+        && target.chapter_uids.is_empty()
+        && target.attachment_uids.is_empty()
+}
+
+/// Whether a list of target UIDs includes the given UID
+fn listed(uids: &[u64], uid: u64) -> bool {
+    uids.iter().any(|u| *u == uid || *u == 0)
+}
+
+/// Parses a duration formatted as `HH:MM:SS.nnnnnnnnn`,
+/// with any number of fractional digits up to nine
+fn parse_duration(s: &str) -> Option<Duration> {
+    let mut parts = s.splitn(3, ':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds = parts.next()?;
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let seconds: u64 = seconds.parse().ok()?;
+    if minutes >= 60
+        || seconds >= 60
+        || fraction.len() > 9
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let nanos = format!("{:0<9}", fraction).parse().ok()?;
+    let seconds = hours.checked_mul(3600)? + minutes * 60 + seconds;
+    Some(Duration::new(seconds, nanos))
+}
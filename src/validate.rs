@@ -0,0 +1,245 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+use std::io::{self, SeekFrom};
+use std::time::Duration;
+
+use crate::cluster::{read_block, read_block_group};
+use crate::ebml::{self, MatroskaError, Result};
+use crate::{find_segment, ids, CuePoint, CueTrackPositions, Cues, Matroska};
+
+/// How far a chapter may run past the segment's duration
+/// before it's presumed to have been written in the wrong units
+const CHAPTER_SLACK: u32 = 2;
+
+/// A likely mistake in a parsed file's metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Issue {
+    /// A chapter time lies far beyond the segment's duration,
+    /// such as one scaled by the timestamp scale when it
+    /// should have been written in nanoseconds
+    ChapterBeyondDuration {
+        /// The UID of the chapter's edition
+        edition_uid: Option<u64>,
+        /// The chapter's UID
+        uid: u64,
+        /// The offending start or end time
+        time: Duration,
+        /// The segment's duration
+        duration: Duration,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::ChapterBeyondDuration {
+                uid,
+                time,
+                duration,
+                ..
+            } => write!(
+                f,
+                "chapter {} at {:?} is far beyond segment duration {:?}",
+                uid, time, duration
+            ),
+        }
+    }
+}
+
+impl Matroska {
+    /// Checks the file's metadata for likely mistakes
+    ///
+    /// Chapter times are checked against the segment's duration,
+    /// if known, which catches chapters whose times were mistakenly
+    /// written in timestamp ticks rather than nanoseconds.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = Vec::new();
+
+        if let Some(duration) = self.info.duration {
+            let limit = duration.saturating_mul(CHAPTER_SLACK);
+            for edition in &self.chapters {
+                for chapter in edition.iter_all() {
+                    let times = std::iter::once(chapter.time_start).chain(chapter.time_end);
+                    if let Some(time) = times.filter(|t| *t > limit).max() {
+                        issues.push(Issue::ChapterBeyondDuration {
+                            edition_uid: edition.uid,
+                            uid: chapter.uid,
+                            time,
+                            duration,
+                        });
+                    }
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+/// A cue point which doesn't match the file's contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CueMismatch {
+    /// The cue's timestamp, in timestamp-scale units
+    pub time: u64,
+    /// The cue's track number
+    pub track: u64,
+    /// The absolute file offset the cue points to
+    pub offset: u64,
+    /// What was found at that offset instead
+    pub problem: CueProblem,
+}
+
+/// What's wrong with a cue point's position
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CueProblem {
+    /// No Cluster begins at the cue's cluster position,
+    /// where some other element was found instead
+    NotACluster {
+        /// The ID of the element found, if it could be read at all
+        found: Option<u32>,
+    },
+    /// No block for the cue's track begins at its relative position
+    NotABlock {
+        /// The track of the block found, if any
+        found: Option<u64>,
+    },
+}
+
+impl fmt::Display for CueProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CueProblem::NotACluster { found: Some(id) } => {
+                write!(f, "element {:X} instead of a Cluster", id)
+            }
+            CueProblem::NotACluster { found: None } => write!(f, "no Cluster"),
+            CueProblem::NotABlock { found: Some(track) } => {
+                write!(f, "block for track {} instead", track)
+            }
+            CueProblem::NotABlock { found: None } => write!(f, "no block"),
+        }
+    }
+}
+
+impl Cues {
+    /// Checks that cue points point at Clusters and blocks
+    /// within the given file, which must be the one the
+    /// cues were parsed from
+    ///
+    /// Every `step`th cue point is checked, starting with the first,
+    /// so a `step` of 1 checks them all.
+    /// Checking stops after `limit` mismatches are found,
+    /// which are returned in cue order.
+    ///
+    /// A cue's cluster position must point to a Cluster
+    /// and, if it has a relative position, that must point
+    /// to a SimpleBlock or BlockGroup for the cue's track.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// let mut file = std::fs::File::open("file.mkv").unwrap();
+    /// let matroska = matroska::Matroska::open(&mut file).unwrap();
+    /// if let Some(cues) = &matroska.cues {
+    ///     for mismatch in cues.verify(&mut file, 10, 5).unwrap() {
+    ///         println!("cue at offset {} : {}", mismatch.offset, mismatch.problem);
+    ///     }
+    /// }
+    /// ```
+    pub fn verify<R: io::Read + io::Seek>(
+        &self,
+        mut r: R,
+        step: usize,
+        limit: usize,
+    ) -> Result<Vec<CueMismatch>> {
+        r.seek(SeekFrom::Start(0))?;
+        find_segment(&mut r)?;
+        let segment_start = r.stream_position()?;
+
+        let mut mismatches = Vec::new();
+        let positions = self
+            .points
+            .iter()
+            .step_by(step.max(1))
+            .flat_map(|point| point.positions.iter().map(move |p| (point, p)));
+        for (point, position) in positions {
+            if mismatches.len() >= limit {
+                break;
+            }
+            if let Some(mismatch) = verify_position(&mut r, segment_start, point, position)? {
+                mismatches.push(mismatch);
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+fn verify_position<R: io::Read + io::Seek>(
+    r: &mut R,
+    segment_start: u64,
+    point: &CuePoint,
+    position: &CueTrackPositions,
+) -> Result<Option<CueMismatch>> {
+    let mismatch = |offset, problem| {
+        Some(CueMismatch {
+            time: point.time,
+            track: position.track,
+            offset,
+            problem,
+        })
+    };
+
+    let cluster = segment_start.saturating_add(position.cluster_position);
+    r.seek(SeekFrom::Start(cluster))?;
+    let cluster_size = match read_header(r)? {
+        Some((ids::CLUSTER, size)) => size,
+        Some((id, _)) => {
+            return Ok(mismatch(
+                cluster,
+                CueProblem::NotACluster { found: Some(id) },
+            ))
+        }
+        None => return Ok(mismatch(cluster, CueProblem::NotACluster { found: None })),
+    };
+
+    let relative = match position.relative_position {
+        Some(relative) => relative,
+        None => return Ok(None),
+    };
+    let cluster_data = r.stream_position()?;
+    let block = cluster_data.saturating_add(relative);
+    if relative >= cluster_size {
+        return Ok(mismatch(block, CueProblem::NotABlock { found: None }));
+    }
+    r.seek(SeekFrom::Start(block))?;
+    let track = match read_header(r)? {
+        Some((ids::SIMPLEBLOCK, size)) => Some(read_block(r, size, None)?.0.track),
+        Some((ids::BLOCKGROUP, size)) => {
+            read_block_group(r, size, None)?.map(|(block, _)| block.track)
+        }
+        _ => None,
+    };
+    if track == Some(position.track) {
+        Ok(None)
+    } else {
+        Ok(mismatch(block, CueProblem::NotABlock { found: track }))
+    }
+}
+
+/// Reads an element's ID and size, or `None` if they're invalid or truncated
+fn read_header<R: io::Read>(r: &mut R) -> Result<Option<(u32, u64)>> {
+    match ebml::read_element_id_size(r) {
+        Ok((id, size, _)) => Ok(Some((id, size))),
+        Err(MatroskaError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(MatroskaError::InvalidID) | Err(MatroskaError::InvalidSize) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
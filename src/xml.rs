@@ -0,0 +1,500 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Tags in mkvtoolnix's XML format, along with just enough XML to read it
+
+use std::fmt::Write;
+
+use crate::ebml::{Limits, MatroskaError, Result};
+use crate::flat::{base64, BASE64_ALPHABET};
+use crate::{Language, SimpleTag, Tag, TagValue, Target, TargetTypeValue};
+
+impl Tag {
+    /// Returns tags as XML in the format used by mkvextract and mkvmerge
+    ///
+    /// Every tag's Targets is written if it has one,
+    /// and every simple tag has a DefaultLanguage.
+    /// Binary values are written as base64.
+    /// Reading the XML back with [`Tag::from_xml`] gives the same tags.
+    ///
+    /// ## Example
+    /// ```
+    /// use matroska::{SimpleTag, Tag, TagValue};
+    ///
+    /// let tags = vec![Tag::default().with_simple(
+    ///     SimpleTag::default()
+    ///         .with_name("TITLE")
+    ///         .with_value(TagValue::String("Fish & Chips".to_owned())),
+    /// )];
+    /// let xml = Tag::to_xml(&tags);
+    /// assert!(xml.contains("<String>Fish &amp; Chips</String>"));
+    /// assert_eq!(Tag::from_xml(&xml).unwrap(), tags);
+    /// ```
+    pub fn to_xml(tags: &[Tag]) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\"?>\n<!DOCTYPE Tags SYSTEM \"matroskatags.dtd\">\n<Tags>\n",
+        );
+        for tag in tags {
+            xml.push_str("  <Tag>\n");
+            if let Some(targets) = &tag.targets {
+                write_targets(&mut xml, targets);
+            }
+            for simple in &tag.simple {
+                write_simple(&mut xml, simple, 2);
+            }
+            xml.push_str("  </Tag>\n");
+        }
+        xml.push_str("</Tags>\n");
+        xml
+    }
+
+    /// Parses tags from XML in the format used by mkvextract and mkvmerge
+    ///
+    /// Binary values may be in base64, which is the default,
+    /// or in hex or ASCII as given by their `format` attribute.
+    /// A simple tag without a DefaultLanguage is the default,
+    /// as in a file.
+    /// Malformed XML, or an element or value which doesn't belong,
+    /// is [`MatroskaError::InvalidXml`], as are elements nested
+    /// more deeply than a Tags section may be when read from a file.
+    pub fn from_xml(xml: &str) -> Result<Vec<Tag>> {
+        let root = Parser::new(xml).document()?;
+        if root.name != "Tags" {
+            return Err(root.invalid());
+        }
+        root.children.iter().map(read_tag).collect()
+    }
+}
+
+fn write_targets(xml: &mut String, targets: &Target) {
+    xml.push_str("    <Targets>\n");
+    if let Some(value) = targets.target_type_value {
+        write_leaf(xml, 3, "TargetTypeValue", &value.to_raw().to_string());
+    }
+    if let Some(target_type) = &targets.target_type {
+        write_leaf(xml, 3, "TargetType", target_type);
+    }
+    let uids = [
+        ("TrackUID", &targets.track_uids),
+        ("EditionUID", &targets.edition_uids),
+        ("ChapterUID", &targets.chapter_uids),
+        ("AttachmentUID", &targets.attachment_uids),
+    ];
+    for (name, uids) in uids.iter() {
+        for uid in uids.iter() {
+            write_leaf(xml, 3, name, &uid.to_string());
+        }
+    }
+    xml.push_str("    </Targets>\n");
+}
+
+fn write_simple(xml: &mut String, simple: &SimpleTag, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let _ = writeln!(xml, "{}<Simple>", indent);
+    write_leaf(xml, depth + 1, "Name", &simple.name);
+    match &simple.value {
+        Some(TagValue::String(string)) => write_leaf(xml, depth + 1, "String", string),
+        Some(TagValue::Binary(binary)) => {
+            let _ = writeln!(
+                xml,
+                "{}  <Binary format=\"base64\">{}</Binary>",
+                indent,
+                base64(binary)
+            );
+        }
+        None => {}
+    }
+    match &simple.language {
+        Some(Language::ISO639(language)) => write_leaf(xml, depth + 1, "TagLanguage", language),
+        Some(Language::IETF(language)) => write_leaf(xml, depth + 1, "TagLanguageIETF", language),
+        None => {}
+    }
+    let default = if simple.default { "1" } else { "0" };
+    write_leaf(xml, depth + 1, "DefaultLanguage", default);
+    for nested in &simple.simple {
+        write_simple(xml, nested, depth + 1);
+    }
+    let _ = writeln!(xml, "{}</Simple>", indent);
+}
+
+fn write_leaf(xml: &mut String, depth: usize, name: &str, value: &str) {
+    // writing to a String can't fail
+    let _ = writeln!(
+        xml,
+        "{}<{name}>{}</{name}>",
+        "  ".repeat(depth),
+        escape(value),
+        name = name
+    );
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            // kept from being normalized away by readers
+            '\r' => escaped.push_str("&#13;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn read_tag(element: &XmlElement) -> Result<Tag> {
+    if element.name != "Tag" {
+        return Err(element.invalid());
+    }
+    let mut tag = Tag::default();
+    for child in &element.children {
+        match child.name.as_str() {
+            "Targets" if tag.targets.is_none() => tag.targets = Some(read_targets(child)?),
+            "Simple" => tag.simple.push(read_simple(child)?),
+            _ => return Err(child.invalid()),
+        }
+    }
+    Ok(tag)
+}
+
+fn read_targets(element: &XmlElement) -> Result<Target> {
+    let mut targets = Target::default();
+    for child in &element.children {
+        match child.name.as_str() {
+            "TargetTypeValue" => {
+                targets.target_type_value = Some(TargetTypeValue::from(child.uint()?))
+            }
+            "TargetType" => targets.target_type = Some(child.leaf()?.to_owned()),
+            "TrackUID" => targets.track_uids.push(child.uint()?),
+            "EditionUID" => targets.edition_uids.push(child.uint()?),
+            "ChapterUID" => targets.chapter_uids.push(child.uint()?),
+            "AttachmentUID" => targets.attachment_uids.push(child.uint()?),
+            _ => return Err(child.invalid()),
+        }
+    }
+    Ok(targets)
+}
+
+fn read_simple(element: &XmlElement) -> Result<SimpleTag> {
+    let mut simple = SimpleTag::default().with_default(true);
+    for child in &element.children {
+        match child.name.as_str() {
+            "Name" => simple.name = child.leaf()?.to_owned(),
+            "String" => simple.value = Some(TagValue::String(child.leaf()?.to_owned())),
+            "Binary" => {
+                let text = child.leaf()?;
+                let binary = match child.attribute("format").unwrap_or("base64") {
+                    "base64" => base64_decode(text),
+                    "hex" => hex_decode(text),
+                    "ascii" => Some(text.as_bytes().to_vec()),
+                    _ => None,
+                };
+                simple.value = Some(TagValue::Binary(binary.ok_or_else(|| child.invalid())?));
+            }
+            "TagLanguage" if !matches!(simple.language, Some(Language::IETF(_))) => {
+                simple.language = Some(Language::ISO639(child.leaf()?.to_owned()))
+            }
+            "TagLanguage" => {}
+            "TagLanguageIETF" => simple.language = Some(Language::IETF(child.leaf()?.to_owned())),
+            "DefaultLanguage" => {
+                simple.default = match child.uint()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(child.invalid()),
+                }
+            }
+            "Simple" => simple.simple.push(read_simple(child)?),
+            _ => return Err(child.invalid()),
+        }
+    }
+    Ok(simple)
+}
+
+/// Decodes base64, ignoring whitespace such as line breaks
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let text: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !text.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(text.len() / 4 * 3);
+    let chunks = text.len() / 4;
+    for (i, chunk) in text.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|b| **b == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != chunks) {
+            return None;
+        }
+        let mut bits = 0u32;
+        for b in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET.iter().position(|c| c == b)?;
+            bits = bits << 6 | value as u32;
+        }
+        bits <<= 6 * padding;
+        decoded.extend_from_slice(&bits.to_be_bytes()[1..4 - padding]);
+    }
+    Some(decoded)
+}
+
+/// Decodes hex digits, ignoring whitespace
+fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| c.to_digit(16))
+        .collect::<Option<Vec<u32>>>()?;
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] << 4 | pair[1]) as u8)
+            .collect(),
+    )
+}
+
+/// An element of an XML document
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+    /// The line the element starts on
+    line: usize,
+}
+
+impl XmlElement {
+    fn invalid(&self) -> MatroskaError {
+        MatroskaError::InvalidXml { line: self.line }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the text of an element which has no children
+    fn leaf(&self) -> Result<&str> {
+        if self.children.is_empty() {
+            Ok(&self.text)
+        } else {
+            Err(self.invalid())
+        }
+    }
+
+    fn uint(&self) -> Result<u64> {
+        self.leaf()?.trim().parse().map_err(|_| self.invalid())
+    }
+}
+
+/// Reads the subset of XML used by mkvtoolnix,
+/// without namespaces or entities beyond the predefined ones
+struct Parser<'x> {
+    xml: &'x str,
+    position: usize,
+}
+
+impl<'x> Parser<'x> {
+    fn new(xml: &'x str) -> Self {
+        Parser {
+            xml: xml.strip_prefix('\u{FEFF}').unwrap_or(xml),
+            position: 0,
+        }
+    }
+
+    fn invalid(&self) -> MatroskaError {
+        MatroskaError::InvalidXml { line: self.line() }
+    }
+
+    fn line(&self) -> usize {
+        self.xml[..self.position].matches('\n').count() + 1
+    }
+
+    fn rest(&self) -> &'x str {
+        &self.xml[self.position..]
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.position += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skips past the given string, returning what came before it
+    fn until(&mut self, s: &str) -> Result<&'x str> {
+        let rest = self.rest();
+        match rest.find(s) {
+            Some(end) => {
+                self.position += end + s.len();
+                Ok(&rest[..end])
+            }
+            None => Err(self.invalid()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips comments, processing instructions and the DOCTYPE,
+    /// returning whether there were any
+    fn skip_misc(&mut self) -> Result<bool> {
+        if self.eat("<!--") {
+            self.until("-->")?;
+        } else if self.eat("<?") {
+            self.until("?>")?;
+        } else if self.eat("<!DOCTYPE") {
+            self.until(">")?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn document(mut self) -> Result<XmlElement> {
+        loop {
+            self.skip_whitespace();
+            if !self.skip_misc()? {
+                break;
+            }
+        }
+        let root = self.element(0)?;
+        loop {
+            self.skip_whitespace();
+            if !self.skip_misc()? {
+                break;
+            }
+        }
+        if self.rest().is_empty() {
+            Ok(root)
+        } else {
+            Err(self.invalid())
+        }
+    }
+
+    fn name(&mut self) -> Result<&'x str> {
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.invalid());
+        }
+        self.position += end;
+        Ok(&rest[..end])
+    }
+
+    /// Reads an element nested the given number of levels
+    /// below the root, along with everything within it
+    fn element(&mut self, depth: usize) -> Result<XmlElement> {
+        let line = self.line();
+        if depth > Limits::MAX_DEPTH || !self.eat("<") {
+            return Err(self.invalid());
+        }
+        let mut element = XmlElement {
+            name: self.name()?.to_owned(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+            line,
+        };
+        loop {
+            self.skip_whitespace();
+            if self.eat("/>") {
+                return Ok(element);
+            } else if self.eat(">") {
+                break;
+            }
+            let name = self.name()?.to_owned();
+            self.skip_whitespace();
+            if !self.eat("=") {
+                return Err(self.invalid());
+            }
+            self.skip_whitespace();
+            let value = if self.eat("\"") {
+                self.until("\"")?
+            } else if self.eat("'") {
+                self.until("'")?
+            } else {
+                return Err(self.invalid());
+            };
+            let value = unescape(value).ok_or_else(|| self.invalid())?;
+            element.attributes.push((name, value));
+        }
+
+        loop {
+            if self.eat("</") {
+                let name = self.name()?;
+                self.skip_whitespace();
+                if name != element.name || !self.eat(">") {
+                    return Err(self.invalid());
+                }
+                // text alongside child elements may only be indentation
+                if !element.children.is_empty() {
+                    if !element.text.trim().is_empty() {
+                        return Err(element.invalid());
+                    }
+                    element.text.clear();
+                }
+                return Ok(element);
+            }
+            if self.eat("<![CDATA[") {
+                element.text.push_str(self.until("]]>")?);
+                continue;
+            }
+            if self.skip_misc()? {
+                continue;
+            }
+            let rest = self.rest();
+            match rest.find('<') {
+                Some(0) => element.children.push(self.element(depth + 1)?),
+                Some(end) => {
+                    let text = unescape(&rest[..end]).ok_or_else(|| self.invalid())?;
+                    element.text.push_str(&text);
+                    self.position += end;
+                }
+                None => return Err(self.invalid()),
+            }
+        }
+    }
+}
+
+/// Replaces character and predefined entity references
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..].find(';')? + start;
+        let entity = &rest[start + 1..end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = match entity.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => entity.strip_prefix('#')?.parse().ok()?,
+                };
+                std::char::from_u32(code)?
+            }
+        };
+        unescaped.push(c);
+        rest = &rest[end + 1..];
+    }
+    unescaped.push_str(rest);
+    Some(unescaped)
+}
@@ -0,0 +1,77 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+use std::time::Duration;
+
+use matroska::{Audio, Settings};
+
+mod common;
+
+fn audio(children: &[Vec<u8>]) -> Audio {
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[common::track(
+            1,
+            2,
+            "A_PCM/INT/LIT",
+            &[common::master(0xE1, children)],
+        )],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    match m.tracks.into_iter().next().unwrap().settings {
+        Settings::Audio(audio) => audio,
+        _ => panic!("unexpected track settings"),
+    }
+}
+
+#[test]
+fn channel_positions() {
+    // 5.1 and 6.0 both have six channels
+    let five_one = audio(&[
+        common::uint(0x9F, 6),
+        common::element(0x7D7B, &[0x3F, 0x00, 0x00, 0x00]),
+    ]);
+    let six_zero = audio(&[
+        common::uint(0x9F, 6),
+        common::element(0x7D7B, &[0x37, 0x01, 0x00, 0x00]),
+    ]);
+    assert_eq!(five_one.channel_positions, Some(vec![0x3F, 0, 0, 0]));
+    assert_eq!(six_zero.channel_positions, Some(vec![0x37, 1, 0, 0]));
+
+    // not guessed from the channel count
+    let stereo = audio(&[common::uint(0x9F, 2)]);
+    assert_eq!(stereo.channel_positions, None);
+}
+
+#[test]
+fn codec_delay() {
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[
+            common::track(
+                1,
+                2,
+                "A_OPUS",
+                &[
+                    common::uint(0x56AA, 6_500_000),
+                    common::uint(0x56BB, 80_000_000),
+                ],
+            ),
+            // permitted on any kind of track
+            common::track(2, 0x11, "S_TEXT/UTF8", &[common::uint(0x56BB, 1_000)]),
+            common::track(3, 2, "A_FLAC", &[]),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    assert_eq!(m.tracks[0].codec_delay, Some(Duration::from_micros(6_500)));
+    assert_eq!(m.tracks[0].seek_pre_roll, Some(Duration::from_millis(80)));
+    assert_eq!(m.tracks[1].codec_delay, None);
+    assert_eq!(m.tracks[1].seek_pre_roll, Some(Duration::from_micros(1)));
+    assert_eq!(m.tracks[2].codec_delay, None);
+    assert_eq!(m.tracks[2].seek_pre_roll, None);
+}
@@ -0,0 +1,77 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use matroska::{Block, Lacing, MatroskaError};
+
+mod common;
+
+#[test]
+fn block_headers() {
+    // an unlaced, invisible, discardable block on a two-byte track number
+    let mut data = vec![0x40, 0x80, 0xFF, 0xF6, 0x09];
+    data.extend_from_slice(&[0; 12]);
+    let block = Block::parse_header(&data).unwrap();
+    assert_eq!(block.track, 128);
+    assert_eq!(block.timestamp, -10);
+    assert!(!block.keyframe);
+    assert!(block.invisible);
+    assert!(block.discardable);
+    assert_eq!(block.lacing, Lacing::None);
+    assert_eq!(block.header_len, 5);
+    assert_eq!(block.frame_sizes(), &[12]);
+
+    // Xiph lacing, frames of 300, 10 and 5 bytes
+    let data = common::block_body(1, 20, 0x82, &[2, 255, 45, 10], &[0; 315]);
+    let block = Block::parse_header(&data).unwrap();
+    assert_eq!(block.lacing, Lacing::Xiph);
+    assert_eq!(block.frame_sizes(), &[300, 10, 5]);
+    assert_eq!(
+        block.frames(&data).map(<[u8]>::len).collect::<Vec<_>>(),
+        vec![300, 10, 5]
+    );
+
+    // EBML lacing with a negative delta, frames of 100, 90, 95 and 7 bytes
+    let data = common::block_body(1, 0, 0x86, &[3, 0xE4, 0xB5, 0xC4], &[0; 292]);
+    let block = Block::parse_header(&data).unwrap();
+    assert!(block.keyframe);
+    assert_eq!(block.lacing, Lacing::Ebml);
+    assert_eq!(block.frame_sizes(), &[100, 90, 95, 7]);
+
+    // fixed-size lacing, two 10 byte frames
+    let data = common::block_body(1, 0, 0x84, &[1], &[0; 20]);
+    let block = Block::parse_header(&data).unwrap();
+    assert_eq!(block.lacing, Lacing::FixedSize);
+    assert_eq!(block.frame_sizes(), &[10, 10]);
+}
+
+#[test]
+fn invalid_block_headers() {
+    // Xiph lace sizes adding up to more than the payload
+    let data = common::block_body(1, 0, 0x82, &[1, 200], &[0; 100]);
+    assert!(matches!(
+        Block::parse_header(&data),
+        Err(MatroskaError::InvalidLacing)
+    ));
+
+    // EBML lace sizes falling below zero
+    let data = common::block_body(1, 0, 0x86, &[2, 0x85, 0x80], &[0; 100]);
+    assert!(matches!(
+        Block::parse_header(&data),
+        Err(MatroskaError::InvalidLacing)
+    ));
+
+    // fixed-size lacing that doesn't divide the payload evenly
+    let data = common::block_body(1, 0, 0x84, &[1], &[0; 21]);
+    assert!(matches!(
+        Block::parse_header(&data),
+        Err(MatroskaError::InvalidLacing)
+    ));
+
+    // a header cut off before its flags
+    assert!(Block::parse_header(&[0x81, 0x00]).is_err());
+    assert!(Block::parse_header(&[]).is_err());
+}
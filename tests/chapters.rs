@@ -0,0 +1,615 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+use std::time::Duration;
+
+use matroska::{ChapterEdition, Matroska};
+
+mod common;
+
+const SECOND: u64 = 1_000_000_000;
+
+fn editions(editions: &[Vec<u8>]) -> Vec<ChapterEdition> {
+    let data = common::file(&[common::master(0x1043_A770, editions)]);
+    Matroska::open(Cursor::new(data)).unwrap().chapters
+}
+
+#[test]
+fn nested_chapters() {
+    let editions = editions(&[common::edition(
+        1,
+        &[common::timed_chapter(
+            1,
+            0,
+            10 * SECOND,
+            &[common::timed_chapter(2, 0, 5 * SECOND, &[])],
+        )],
+    )]);
+    let edition = &editions[0];
+    assert_eq!(edition.chapters.len(), 1);
+    assert_eq!(edition.chapters[0].chapters[0].uid, 2);
+    assert_eq!(
+        edition.iter_all().map(|c| c.uid).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn virtual_duration() {
+    // a recap (0-10s) replayed after the main part (10-60s)
+    let ordered = common::master(
+        0x45B9,
+        &[
+            common::uint(0x45BC, 1),
+            common::uint(0x45DD, 1),
+            common::timed_chapter(1, 0, 10 * SECOND, &[]),
+            common::timed_chapter(
+                2,
+                10 * SECOND,
+                60 * SECOND,
+                &[common::timed_chapter(3, 20 * SECOND, 30 * SECOND, &[])],
+            ),
+            common::timed_chapter(4, 0, 10 * SECOND, &[]),
+            // zero-length
+            common::timed_chapter(5, 60 * SECOND, 60 * SECOND, &[]),
+        ],
+    );
+    let unordered = common::edition(
+        2,
+        &[
+            common::timed_chapter(1, 0, 10 * SECOND, &[]),
+            common::timed_chapter(2, 10 * SECOND, 60 * SECOND, &[]),
+        ],
+    );
+    let open_ended = common::master(
+        0x45B9,
+        &[
+            common::uint(0x45DD, 1),
+            common::timed_chapter(1, 0, 10 * SECOND, &[]),
+            common::chapter(2, 10 * SECOND, "No end"),
+        ],
+    );
+    let editions = editions(&[ordered, unordered, open_ended]);
+
+    assert_eq!(
+        editions[0].virtual_duration(),
+        Some(Duration::from_secs(70))
+    );
+    assert_eq!(
+        editions[1].virtual_duration(),
+        Some(Duration::from_secs(60))
+    );
+    assert_eq!(editions[2].virtual_duration(), None);
+}
+
+#[test]
+fn nested_lookup() {
+    let data = common::file(&[common::master(
+        0x1043_A770,
+        &[common::edition(
+            1,
+            &[common::timed_chapter(
+                1,
+                0,
+                SECOND,
+                &[common::chapter(7, 0, "Nested")],
+            )],
+        )],
+    )]);
+    let m = Matroska::open(Cursor::new(data)).unwrap();
+    let (edition, chapter) = m.chapter_by_uid(7).unwrap();
+    assert_eq!(edition.uid, Some(1));
+    assert_eq!(chapter.display[0].string, "Nested");
+}
+
+#[test]
+fn write_raw_nanoseconds() {
+    let editions = editions(&[common::edition(
+        1,
+        &[common::timed_chapter(1, 0, SECOND, &[])],
+    )]);
+    let mut atom = Vec::new();
+    let len = editions[0].chapters[0].write(&mut atom).unwrap();
+    assert_eq!(len, atom.len() as u64);
+    assert_eq!(
+        atom,
+        vec![
+            0xB6, 0x94, // ChapterAtom
+            0x73, 0xC4, 0x81, 0x01, // ChapterUID
+            0x91, 0x81, 0x00, // ChapterTimeStart
+            0x92, 0x84, 0x3B, 0x9A, 0xCA, 0x00, // ChapterTimeEnd, 1s
+            0x98, 0x81, 0x00, // ChapterFlagHidden
+            0x45, 0x98, 0x81, 0x01, // ChapterFlagEnabled
+        ]
+    );
+}
+
+#[test]
+fn write_round_trip() {
+    let original = editions(&[common::edition(
+        1,
+        &[common::timed_chapter(
+            1,
+            90 * SECOND,
+            3600 * SECOND + 1,
+            &[common::chapter(2, 95 * SECOND, "Nested")],
+        )],
+    )]);
+    let mut atom = Vec::new();
+    original[0].chapters[0].write(&mut atom).unwrap();
+    let written = editions(&[common::edition(1, &[atom])]);
+    assert_eq!(original, written);
+    assert_eq!(
+        written[0].chapters[0].time_end,
+        Some(Duration::from_nanos(3600 * SECOND + 1))
+    );
+}
+
+#[test]
+fn chapter_beyond_duration() {
+    // a 60 second file whose second chapter was mistakenly
+    // multiplied by the timestamp scale
+    let data = common::file(&[
+        common::master(
+            0x1549_A966,
+            &[
+                common::uint(0x2A_D7B1, 1_000_000),
+                common::float(0x4489, 60_000.0),
+                common::string(0x4D80, "test"),
+                common::string(0x5741, "test"),
+            ],
+        ),
+        common::master(
+            0x1043_A770,
+            &[common::edition(
+                1,
+                &[
+                    common::chapter(1, 0, "Start"),
+                    common::chapter(2, 30 * SECOND * 1_000_000, "Scaled"),
+                ],
+            )],
+        ),
+    ]);
+    let m = Matroska::open(Cursor::new(data)).unwrap();
+    assert_eq!(
+        m.validate(),
+        vec![matroska::Issue::ChapterBeyondDuration {
+            edition_uid: Some(1),
+            uid: 2,
+            time: Duration::from_secs(30_000_000),
+            duration: Duration::from_secs(60),
+        }]
+    );
+}
+
+#[test]
+fn constructed_chapter() {
+    use matroska::{Chapter, ChapterDisplay, Language};
+
+    let chapter = Chapter::default()
+        .with_uid(1)
+        .with_time_start(Duration::from_secs(90))
+        .with_time_end(Duration::from_secs(120))
+        .with_display(
+            ChapterDisplay::default()
+                .with_string("Intro")
+                .with_language(Language::ISO639("eng".into())),
+        );
+    let mut atom = Vec::new();
+    chapter.write(&mut atom).unwrap();
+    let parsed = editions(&[common::edition(1, &[atom])]);
+    assert_eq!(parsed[0].chapters, vec![chapter]);
+}
+
+#[test]
+fn chapter_process() {
+    use matroska::ChapterProcess;
+
+    let command = |time, data: &[u8]| {
+        common::master(
+            0x6911,
+            &[common::uint(0x6922, time), common::element(0x6933, data)],
+        )
+    };
+    let parsed = editions(&[common::edition(
+        1,
+        &[common::timed_chapter(
+            1,
+            0,
+            SECOND,
+            &[common::master(
+                0x6944,
+                &[
+                    common::uint(0x6955, 1),
+                    common::element(0x450D, &[0x30, 0x00, 0x01]),
+                    command(1, &[0x71, 0x02]),
+                    command(2, &[0x30, 0x02]),
+                ],
+            )],
+        )],
+    )]);
+    let chapter = &parsed[0].chapters[0];
+    assert_eq!(
+        chapter.process,
+        vec![ChapterProcess::default()
+            .with_codec_id(1)
+            .with_private(vec![0x30, 0x00, 0x01])
+            .with_command(1, vec![0x71, 0x02])
+            .with_command(2, vec![0x30, 0x02])]
+    );
+
+    let mut atom = Vec::new();
+    chapter.write(&mut atom).unwrap();
+    assert_eq!(
+        parsed[0].chapters,
+        editions(&[common::edition(1, &[atom])])[0].chapters
+    );
+}
+
+#[test]
+fn string_uid() {
+    let data = common::file(&[common::master(
+        0x1043_A770,
+        &[common::edition(
+            1,
+            &[common::timed_chapter(
+                1,
+                0,
+                SECOND,
+                &[
+                    common::string(0x5654, "intro"),
+                    common::timed_chapter(2, 0, SECOND, &[common::string(0x5654, "cold-open")]),
+                ],
+            )],
+        )],
+    )]);
+    let m = Matroska::open(Cursor::new(data)).unwrap();
+    let (edition, chapter) = m.chapter_by_string_uid("cold-open").unwrap();
+    assert_eq!(edition.uid, Some(1));
+    assert_eq!(chapter.uid, 2);
+    assert_eq!(
+        m.chapters[0].chapters[0].string_uid.as_deref(),
+        Some("intro")
+    );
+    assert!(m.chapter_by_string_uid("credits").is_none());
+
+    let mut atom = Vec::new();
+    m.chapters[0].chapters[0].write(&mut atom).unwrap();
+    assert_eq!(
+        editions(&[common::edition(1, &[atom])])[0].chapters,
+        m.chapters[0].chapters
+    );
+}
+
+#[test]
+fn display_countries() {
+    use matroska::Language;
+
+    let display = common::master(
+        0x80,
+        &[
+            common::string(0x85, "Chapter 1"),
+            common::string(0x437C, "eng"),
+            common::string(0x437C, "fre"),
+            common::string(0x437E, "us"),
+            common::string(0x437E, "ca"),
+        ],
+    );
+    let parsed = editions(&[common::edition(
+        1,
+        &[common::timed_chapter(1, 0, SECOND, &[display])],
+    )]);
+    let display = &parsed[0].chapters[0].display[0];
+    assert_eq!(
+        display.languages,
+        vec![
+            Language::ISO639("eng".into()),
+            Language::ISO639("fre".into())
+        ]
+    );
+    assert_eq!(display.language, Language::ISO639("fre".into()));
+    assert_eq!(display.countries, vec!["us", "ca"]);
+
+    let mut atom = Vec::new();
+    parsed[0].chapters[0].write(&mut atom).unwrap();
+    let written = editions(&[common::edition(1, &[atom])]);
+    assert_eq!(written[0].chapters, parsed[0].chapters);
+}
+
+#[test]
+fn physical_equiv() {
+    use matroska::PhysicalEquiv;
+
+    let parsed = editions(&[common::edition(
+        1,
+        &[
+            common::timed_chapter(1, 0, SECOND, &[common::uint(0x63C3, 50)]),
+            common::timed_chapter(2, SECOND, 2 * SECOND, &[common::uint(0x63C3, 20)]),
+            common::timed_chapter(3, 2 * SECOND, 3 * SECOND, &[common::uint(0x63C3, 15)]),
+            common::timed_chapter(4, 3 * SECOND, 4 * SECOND, &[]),
+        ],
+    )]);
+    let equivs: Vec<_> = parsed[0]
+        .chapters
+        .iter()
+        .map(|c| c.physical_equiv)
+        .collect();
+    assert_eq!(
+        equivs,
+        vec![
+            Some(PhysicalEquiv::Side),
+            Some(PhysicalEquiv::Track),
+            Some(PhysicalEquiv::Unknown(15)),
+            None
+        ]
+    );
+
+    let mut atom = Vec::new();
+    parsed[0].chapters[2].write(&mut atom).unwrap();
+    let written = editions(&[common::edition(1, &[atom])]);
+    assert_eq!(written[0].chapters[0], parsed[0].chapters[2]);
+}
+
+#[test]
+fn nesting_too_deep() {
+    fn nested(depth: usize) -> Vec<u8> {
+        let mut atom = common::uint(0x73C4, 1);
+        for _ in 0..depth {
+            atom = common::master(0xB6, &[common::uint(0x73C4, 1), atom]);
+        }
+        common::file(&[common::master(
+            0x1043_A770,
+            &[common::master(0x45B9, &[atom])],
+        )])
+    }
+
+    // Chapters and EditionEntry take two levels of their own
+    let parsed = Matroska::open(Cursor::new(nested(30))).unwrap().chapters;
+    assert_eq!(parsed[0].iter_all().count(), 30);
+
+    match Matroska::open(Cursor::new(nested(10_000))) {
+        Err(err) => match err.root() {
+            matroska::MatroskaError::NestingTooDeep { id } => assert_eq!(*id, 0xB6),
+            err => panic!("unexpected error {:?}", err),
+        },
+        Ok(m) => panic!("unexpected chapters {:?}", m.chapters.len()),
+    }
+
+    let options = matroska::ParseOptions::default().with_max_depth(40);
+    let parsed = Matroska::open_with(Cursor::new(nested(35)), &options)
+        .unwrap()
+        .chapters;
+    assert_eq!(parsed[0].iter_all().count(), 35);
+}
+
+#[test]
+fn default_edition() {
+    let open = |editions: &[Vec<u8>]| {
+        let data = common::file(&[common::master(0x1043_A770, editions)]);
+        Matroska::open(Cursor::new(data)).unwrap()
+    };
+    let flagged = |uid: u64, default: bool| {
+        common::master(
+            0x45B9,
+            &[
+                common::uint(0x45BC, uid),
+                common::uint(0x45DB, default as u64),
+                common::chapter(uid * 10, 0, "Start"),
+            ],
+        )
+    };
+
+    let m = open(&[flagged(1, false), flagged(2, true), flagged(3, true)]);
+    assert_eq!(m.default_edition().and_then(|e| e.uid), Some(2));
+    let m = open(&[flagged(1, false), flagged(2, false)]);
+    assert_eq!(m.default_edition().and_then(|e| e.uid), Some(1));
+    let m = Matroska::open(Cursor::new(common::file(&[common::info("None")]))).unwrap();
+    assert!(m.default_edition().is_none());
+}
+
+#[test]
+fn write_editions() {
+    let display = |string: &str, languages: &[&str]| {
+        common::master(
+            0x80,
+            &std::iter::once(common::string(0x85, string))
+                .chain(languages.iter().map(|l| common::string(0x437C, l)))
+                .collect::<Vec<_>>(),
+        )
+    };
+    let original = editions(&[
+        common::edition(
+            1,
+            &[common::timed_chapter(
+                1,
+                0,
+                10 * SECOND,
+                &[
+                    display("Chapter 1", &["eng", "fre"]),
+                    display("Kapitel 1", &["ger"]),
+                    common::timed_chapter(2, 0, 5 * SECOND, &[display("Nested", &["eng"])]),
+                ],
+            )],
+        ),
+        common::edition(2, &[common::chapter(3, 0, "Other")]),
+    ]);
+    let mut data = Vec::new();
+    let len = ChapterEdition::write_all(&original, &mut data).unwrap();
+    assert_eq!(len, data.len() as u64);
+    let written = Matroska::open(Cursor::new(common::file(&[data])))
+        .unwrap()
+        .chapters;
+    assert_eq!(written, original);
+    assert_eq!(written[0].chapters[0].display[0].languages.len(), 2);
+}
+
+#[test]
+fn write_generated_uids() {
+    use matroska::Chapter;
+
+    let original = vec![ChapterEdition::default()
+        .with_uid(0)
+        .with_chapter(Chapter::default().with_chapter(Chapter::default()))
+        .with_chapter(Chapter::default().with_uid(7))];
+    let mut data = Vec::new();
+    ChapterEdition::write_all(&original, &mut data).unwrap();
+    let written = Matroska::open(Cursor::new(common::file(&[data])))
+        .unwrap()
+        .chapters;
+    let mut uids: Vec<u64> = written[0].iter_all().map(|c| c.uid).collect();
+    uids.extend(written[0].uid);
+    assert_eq!(uids.len(), 4);
+    assert!(uids.iter().all(|uid| *uid != 0));
+    assert_eq!(uids[2], 7);
+    uids.sort_unstable();
+    uids.dedup();
+    assert_eq!(uids.len(), 4);
+}
+
+#[test]
+fn simple_chapters() {
+    use matroska::{Chapter, ChapterDisplay, MatroskaError};
+
+    let mut edition = ChapterEdition::default();
+    for i in 0..120 {
+        let chapter = Chapter::default().with_time_start(Duration::from_millis(i * 61_001));
+        edition = edition.with_chapter(match i {
+            0 => chapter.with_display(ChapterDisplay::default().with_string("Überblick")),
+            1 => chapter,
+            _ => chapter.with_display(ChapterDisplay::default().with_string(format!("Part {}", i))),
+        });
+    }
+    let text = edition.to_simple_chapters();
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 240);
+    assert_eq!(lines[0], "CHAPTER01=00:00:00.000");
+    assert_eq!(lines[1], "CHAPTER01NAME=Überblick");
+    assert_eq!(lines[2], "CHAPTER02=00:01:01.001");
+    assert_eq!(lines[3], "CHAPTER02NAME=");
+    assert_eq!(lines[198], "CHAPTER100=01:40:39.099");
+    assert_eq!(lines[199], "CHAPTER100NAME=Part 99");
+
+    let parsed = ChapterEdition::from_simple_chapters(&text).unwrap();
+    assert_eq!(parsed.chapters.len(), 120);
+    for (parsed, original) in parsed.chapters.iter().zip(&edition.chapters) {
+        assert_eq!(parsed.time_start, original.time_start);
+        assert_eq!(parsed.uid, 0);
+    }
+    assert_eq!(parsed.chapters[0].display[0].string, "Überblick");
+    // an empty name is kept as one
+    assert_eq!(parsed.chapters[1].display[0].string, "");
+
+    let parsed = ChapterEdition::from_simple_chapters(
+        "\u{FEFF}CHAPTER1=0:00:01\r\n\r\nCHAPTER2=00:00:02.5\r\nCHAPTER2NAME=a=b\r\n\
+         CHAPTER3=100:00:00.123456789123\r\n",
+    )
+    .unwrap();
+    let starts: Vec<_> = parsed.chapters.iter().map(|c| c.time_start).collect();
+    assert_eq!(
+        starts,
+        vec![
+            Duration::from_secs(1),
+            Duration::from_millis(2_500),
+            Duration::new(360_000, 123_456_789),
+        ]
+    );
+    assert!(parsed.chapters[0].display.is_empty());
+    assert_eq!(parsed.chapters[1].display[0].string, "a=b");
+
+    for (text, line) in [
+        ("CHAPTER01=00:00:00.000\nCHAPTER02NAME=Orphan\n", 2),
+        ("CHAPTER01=00:61:00.000\n", 1),
+        ("CHAPTER01=00:00:00.000\nchapter02=00:00:01.000\n", 2),
+        ("CHAPTERXX=00:00:00.000\n", 1),
+        ("CHAPTER01=00:00:00.0x0\n", 1),
+    ]
+    .iter()
+    {
+        match ChapterEdition::from_simple_chapters(text) {
+            Err(MatroskaError::InvalidSimpleChapters { line: l }) => assert_eq!(l, *line),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn cue_sheet() {
+    use matroska::{Chapter, ChapterDisplay};
+
+    let chapter = |millis: u64, title: &str| {
+        Chapter::default()
+            .with_time_start(Duration::from_millis(millis))
+            .with_display(ChapterDisplay::default().with_string(title))
+    };
+    let mut edition = ChapterEdition::default()
+        .with_chapter(chapter(6_000_000, "Too \"Late\""))
+        .with_chapter(chapter(61_999, "Second"))
+        .with_chapter(chapter(0, "First"))
+        .with_chapter(chapter(30_000, "Hidden").with_hidden(true));
+    let sheet = edition.to_cue_sheet("album.flac");
+    assert_eq!(
+        sheet,
+        "FILE \"album.flac\" WAVE\n\
+         \x20 TRACK 01 AUDIO\n\
+         \x20   TITLE \"First\"\n\
+         \x20   INDEX 01 00:00:00\n\
+         \x20 TRACK 02 AUDIO\n\
+         \x20   TITLE \"Second\"\n\
+         \x20   INDEX 01 01:01:74\n\
+         REM OMITTED 1:40:00.000 \"Too 'Late'\"\n"
+    );
+
+    // 99:59:74 is the last time which fits, and 99 the most tracks
+    edition = ChapterEdition::default().with_chapter(chapter(5_999_999, "Last"));
+    for i in 0..100 {
+        edition = edition.with_chapter(Chapter::default().with_time_start(Duration::from_secs(i)));
+    }
+    let sheet = edition.to_cue_sheet("long.flac");
+    assert_eq!(sheet.matches("INDEX 01").count(), 99);
+    assert!(sheet.contains("  TRACK 99 AUDIO\n    INDEX 01 01:38:00\n"));
+    assert!(sheet.ends_with("REM OMITTED 0:01:39.000\nREM OMITTED 1:39:59.999 \"Last\"\n"));
+
+    let data = common::file(&[
+        common::info("Info Title"),
+        common::master(
+            0x1043_A770,
+            &[common::edition(1, &[common::chapter(1, 0, "Intro")])],
+        ),
+        common::master(
+            0x1254_C367,
+            &[
+                common::tag(
+                    &[common::uint(0x63C5, 1)],
+                    &[common::simple_tag("TITLE", "Track Title", None)],
+                ),
+                common::tag(
+                    &[common::uint(0x68CA, 50)],
+                    &[
+                        common::simple_tag("ARTIST", "The Band", None),
+                        common::simple_tag("GENRE", "Rock", None),
+                        common::simple_tag("DATE_RELEASED", "1999", None),
+                    ],
+                ),
+            ],
+        ),
+    ]);
+    let m = Matroska::open(Cursor::new(data)).unwrap();
+    assert_eq!(
+        m.to_cue_sheet("album.mka").unwrap(),
+        "REM GENRE \"Rock\"\n\
+         REM DATE \"1999\"\n\
+         PERFORMER \"The Band\"\n\
+         TITLE \"Info Title\"\n\
+         FILE \"album.mka\" WAVE\n\
+         \x20 TRACK 01 AUDIO\n\
+         \x20   TITLE \"Intro\"\n\
+         \x20   INDEX 01 00:00:00\n"
+    );
+    let m = Matroska::open(Cursor::new(common::file(&[common::info("Title")]))).unwrap();
+    assert_eq!(m.to_cue_sheet("none.mka"), None);
+}
@@ -0,0 +1,228 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+use std::time::Duration;
+
+use matroska::Matroska;
+
+mod common;
+
+#[test]
+fn clusters() {
+    let first = common::cluster(
+        0,
+        &[
+            common::simple_block(1, 0, 0x80, &[], &[0; 8]),
+            common::simple_block(1, 20, 0x00, &[], &[0; 5]),
+            // a BlockGroup referencing an earlier block
+            common::master(
+                0xA0,
+                &[
+                    common::element(0xA1, &common::block_body(2, -10, 0, &[], &[0; 50])),
+                    common::int(0xFB, -10),
+                ],
+            ),
+        ],
+    );
+    let second = common::unknown_size(
+        0x1F43_B675,
+        &[
+            common::uint(0xE7, 1000),
+            common::master(
+                0xA0,
+                &[common::element(
+                    0xA1,
+                    &common::block_body(2, 0, 0, &[], &[0; 30]),
+                )],
+            ),
+        ],
+    );
+    // a Cluster cut off partway through its second block
+    let third = common::cluster(
+        2000,
+        &[
+            common::simple_block(1, 0, 0x80, &[], &[0; 4]),
+            common::simple_block(1, 20, 0x80, &[], &[0; 4]),
+        ],
+    );
+
+    let mut data = common::file(&[
+        common::info("Clusters"),
+        first.clone(),
+        second.clone(),
+        third.clone(),
+    ]);
+    let first_offset = (data.len() - first.len() - second.len() - third.len()) as u64;
+    data.truncate(data.len() - 6);
+
+    let mut file = Cursor::new(data);
+    let matroska = Matroska::open(&mut file).unwrap();
+    let clusters = matroska::clusters(&mut file, &matroska.info)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(clusters.len(), 3);
+
+    assert_eq!(clusters[0].offset, first_offset);
+    assert_eq!(clusters[0].timestamp, Duration::ZERO);
+    let blocks = &clusters[0].blocks;
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(
+        (
+            blocks[0].track,
+            blocks[0].timestamp,
+            blocks[0].keyframe,
+            blocks[0].size
+        ),
+        (1, 0, true, 8)
+    );
+    assert_eq!(
+        (
+            blocks[1].track,
+            blocks[1].timestamp,
+            blocks[1].keyframe,
+            blocks[1].size
+        ),
+        (1, 20, false, 5)
+    );
+    assert_eq!(
+        (
+            blocks[2].track,
+            blocks[2].timestamp,
+            blocks[2].keyframe,
+            blocks[2].size
+        ),
+        (2, -10, false, 50)
+    );
+    assert_eq!(
+        clusters[0].block_timestamp(&blocks[1]),
+        Some(Duration::from_millis(20))
+    );
+    assert_eq!(clusters[0].block_timestamp(&blocks[2]), None);
+
+    assert_eq!(clusters[1].offset, first_offset + first.len() as u64);
+    assert_eq!(clusters[1].timestamp, Duration::from_secs(1));
+    assert_eq!(clusters[1].blocks.len(), 1);
+    assert!(clusters[1].blocks[0].keyframe);
+    assert_eq!(clusters[1].blocks[0].size, 30);
+
+    assert_eq!(clusters[2].timestamp, Duration::from_secs(2));
+    assert_eq!(clusters[2].blocks.len(), 1);
+}
+
+#[test]
+fn unknown_size_in_block_group() {
+    // BlockAdditions of unknown size, running to the end of its BlockGroup,
+    // with a ReferenceBlock of unknown size in the next Cluster
+    let first = common::cluster(
+        0,
+        &[common::master(
+            0xA0,
+            &[
+                common::element(0xA1, &common::block_body(1, 0, 0, &[], &[0; 10])),
+                common::unknown_size(
+                    0x75A1,
+                    &[common::master(
+                        0xA6,
+                        &[common::uint(0xEE, 1), common::element(0xA5, &[0; 4])],
+                    )],
+                ),
+            ],
+        )],
+    );
+    let second = common::cluster(
+        1000,
+        &[common::master(
+            0xA0,
+            &[
+                common::element(0xA1, &common::block_body(1, 20, 0, &[], &[0; 6])),
+                common::unknown_size(0xFB, &[]),
+            ],
+        )],
+    );
+
+    let mut file = Cursor::new(common::file(&[common::info("Groups"), first, second]));
+    let matroska = Matroska::open(&mut file).unwrap();
+    let clusters = matroska::clusters(&mut file, &matroska.info)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(clusters.len(), 2);
+    assert_eq!(clusters[0].blocks.len(), 1);
+    assert_eq!(clusters[0].blocks[0].size, 10);
+    assert!(clusters[0].blocks[0].keyframe);
+    assert_eq!(clusters[0].blocks[0].frames, 1);
+    assert_eq!(clusters[1].blocks.len(), 1);
+    assert_eq!(clusters[1].blocks[0].size, 6);
+    assert!(!clusters[1].blocks[0].keyframe);
+}
+
+#[test]
+fn scan_duration() {
+    let tracks = common::master(
+        0x1654_AE6B,
+        &[
+            common::track(1, 1, "V_MPEG4/ISO/AVC", &[]),
+            // 20 ms of audio per frame
+            common::track(2, 2, "A_OPUS", &[common::uint(0x23_E383, 20_000_000)]),
+        ],
+    );
+    let first = common::cluster(0, &[common::simple_block(1, 0, 0x80, &[], &[0; 64])]);
+    let last = common::cluster(
+        1000,
+        &[
+            common::master(
+                0xA0,
+                &[
+                    common::element(0xA1, &common::block_body(1, 0, 0, &[], &[0; 16])),
+                    common::uint(0x9B, 100),
+                ],
+            ),
+            common::simple_block(2, 30, 0x80, &[], &[0; 16]),
+            common::simple_block(1, 90, 0x00, &[], &[0; 16]),
+        ],
+    );
+
+    let mut file = Cursor::new(common::file(&[
+        common::info("Unfinished"),
+        tracks.clone(),
+        first.clone(),
+        last,
+    ]));
+    let matroska = Matroska::open(&mut file).unwrap();
+    assert_eq!(matroska.info.duration, None);
+    assert_eq!(
+        matroska.scan_duration(&mut file).unwrap(),
+        Some(Duration::from_millis(1100))
+    );
+
+    // a truncated final Cluster still has its first blocks
+    let mut data = common::file(&[
+        common::info("Truncated"),
+        tracks.clone(),
+        first,
+        common::cluster(
+            2000,
+            &[
+                common::simple_block(2, 0, 0x80, &[], &[0; 16]),
+                common::simple_block(2, 20, 0x80, &[], &[0; 16]),
+            ],
+        ),
+    ]);
+    data.truncate(data.len() - 10);
+    let mut file = Cursor::new(data);
+    let matroska = Matroska::open(&mut file).unwrap();
+    assert_eq!(
+        matroska.scan_duration(&mut file).unwrap(),
+        Some(Duration::from_millis(2040))
+    );
+
+    let mut file = Cursor::new(common::file(&[common::info("Empty"), tracks]));
+    let matroska = Matroska::open(&mut file).unwrap();
+    assert_eq!(matroska.scan_duration(&mut file).unwrap(), None);
+}
@@ -0,0 +1,224 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for crafting small Matroska files in tests
+
+#![allow(dead_code)]
+
+/// Encodes an element ID, which already carries its length marker
+pub fn id(id: u32) -> Vec<u8> {
+    let bytes = id.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count();
+    bytes[skip..].to_vec()
+}
+
+/// Encodes an element size using the 8-byte form
+pub fn size(size: u64) -> Vec<u8> {
+    let mut v = (size | (1 << 56)).to_be_bytes().to_vec();
+    v[0] = 0x01;
+    v
+}
+
+/// An element with an arbitrary body
+pub fn element(element_id: u32, body: &[u8]) -> Vec<u8> {
+    let mut v = id(element_id);
+    v.extend(size(body.len() as u64));
+    v.extend_from_slice(body);
+    v
+}
+
+/// A master element of unknown size containing the given children,
+/// as written by live streams and unfinished recordings
+pub fn unknown_size(element_id: u32, children: &[Vec<u8>]) -> Vec<u8> {
+    let mut v = id(element_id);
+    v.extend([0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    v.extend(children.concat());
+    v
+}
+
+/// A master element containing the given children
+pub fn master(element_id: u32, children: &[Vec<u8>]) -> Vec<u8> {
+    element(element_id, &children.concat())
+}
+
+/// An unsigned integer element
+pub fn uint(element_id: u32, value: u64) -> Vec<u8> {
+    element(element_id, &value.to_be_bytes())
+}
+
+/// A signed integer element
+pub fn int(element_id: u32, value: i64) -> Vec<u8> {
+    element(element_id, &value.to_be_bytes())
+}
+
+/// A floating point element
+pub fn float(element_id: u32, value: f64) -> Vec<u8> {
+    element(element_id, &value.to_bits().to_be_bytes())
+}
+
+/// A string or UTF-8 element
+pub fn string(element_id: u32, value: &str) -> Vec<u8> {
+    element(element_id, value.as_bytes())
+}
+
+/// A minimal EBML header declaring the given doc type
+pub fn ebml_header(doc_type: &str) -> Vec<u8> {
+    master(
+        0x1A45_DFA3,
+        &[
+            uint(0x4286, 1),
+            uint(0x42F7, 1),
+            uint(0x42F2, 4),
+            uint(0x42F3, 8),
+            string(0x4282, doc_type),
+            uint(0x4287, 4),
+            uint(0x4285, 2),
+        ],
+    )
+}
+
+/// A complete file consisting of an EBML header and a Segment
+pub fn file(segment_children: &[Vec<u8>]) -> Vec<u8> {
+    file_of_type("matroska", segment_children)
+}
+
+/// A complete file with the given doc type
+pub fn file_of_type(doc_type: &str, segment_children: &[Vec<u8>]) -> Vec<u8> {
+    let mut v = ebml_header(doc_type);
+    v.extend(master(0x1853_8067, segment_children));
+    v
+}
+
+/// An Info element with a title
+pub fn info(title: &str) -> Vec<u8> {
+    master(
+        0x1549_A966,
+        &[
+            uint(0x2A_D7B1, 1_000_000),
+            string(0x4D80, "test"),
+            string(0x5741, "test"),
+            string(0x7BA9, title),
+        ],
+    )
+}
+
+/// A ChapterAtom with a UID, start time and display string
+pub fn chapter(uid: u64, start: u64, title: &str) -> Vec<u8> {
+    master(
+        0xB6,
+        &[
+            uint(0x73C4, uid),
+            uint(0x91, start),
+            master(0x80, &[string(0x85, title), string(0x437C, "eng")]),
+        ],
+    )
+}
+
+/// An EditionEntry containing the given chapter atoms
+pub fn edition(uid: u64, chapters: &[Vec<u8>]) -> Vec<u8> {
+    let mut children = vec![uint(0x45BC, uid)];
+    children.extend_from_slice(chapters);
+    master(0x45B9, &children)
+}
+
+/// A Tracks element with a single video TrackEntry
+pub fn video_track(video_children: &[Vec<u8>]) -> Vec<u8> {
+    master(
+        0x1654_AE6B,
+        &[master(
+            0xAE,
+            &[
+                uint(0xD7, 1),
+                uint(0x73C5, 1),
+                uint(0x83, 1),
+                string(0x86, "V_MPEG4/ISO/AVC"),
+                master(0xE0, video_children),
+            ],
+        )],
+    )
+}
+
+/// A TrackEntry with the given number, type, codec and extra children
+pub fn track(number: u64, tracktype: u64, codec: &str, children: &[Vec<u8>]) -> Vec<u8> {
+    let mut entry = vec![
+        uint(0xD7, number),
+        uint(0x73C5, number),
+        uint(0x83, tracktype),
+        string(0x86, codec),
+    ];
+    entry.extend_from_slice(children);
+    master(0xAE, &entry)
+}
+
+/// A subtitle TrackEntry with a language, flags and optional name
+pub fn subtitle_track(
+    number: u64,
+    language: &str,
+    default: bool,
+    forced: bool,
+    name: Option<&str>,
+) -> Vec<u8> {
+    let mut children = vec![
+        string(0x22_B59C, language),
+        uint(0x88, default as u64),
+        uint(0x55AA, forced as u64),
+    ];
+    children.extend(name.map(|n| string(0x536E, n)));
+    track(number, 0x11, "S_TEXT/UTF8", &children)
+}
+
+/// A ChapterAtom with a start and end time and extra children
+pub fn timed_chapter(uid: u64, start: u64, end: u64, children: &[Vec<u8>]) -> Vec<u8> {
+    let mut atom = vec![uint(0x73C4, uid), uint(0x91, start), uint(0x92, end)];
+    atom.extend_from_slice(children);
+    master(0xB6, &atom)
+}
+
+/// A Cluster with a timestamp and the given blocks
+pub fn cluster(timestamp: u64, blocks: &[Vec<u8>]) -> Vec<u8> {
+    let mut children = vec![uint(0xE7, timestamp)];
+    children.extend_from_slice(blocks);
+    master(0x1F43_B675, &children)
+}
+
+/// A SimpleBlock for a track below 127 with the given flags,
+/// lacing header bytes and frame data
+pub fn simple_block(track: u8, timestamp: i16, flags: u8, lacing: &[u8], data: &[u8]) -> Vec<u8> {
+    element(0xA3, &block_body(track, timestamp, flags, lacing, data))
+}
+
+/// The body of a Block or SimpleBlock for a track below 127
+pub fn block_body(track: u8, timestamp: i16, flags: u8, lacing: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut body = vec![0x80 | track];
+    body.extend_from_slice(&timestamp.to_be_bytes());
+    body.push(flags);
+    body.extend_from_slice(lacing);
+    body.extend_from_slice(data);
+    body
+}
+
+/// A CuePoint with a single CueTrackPositions
+pub fn cue_point(time: u64, track: u64, cluster: u64, relative: Option<u64>) -> Vec<u8> {
+    let mut positions = vec![uint(0xF7, track), uint(0xF1, cluster)];
+    positions.extend(relative.map(|r| uint(0xF0, r)));
+    master(0xBB, &[uint(0xB3, time), master(0xB7, &positions)])
+}
+
+/// A SimpleTag with a name, string value and optional language
+pub fn simple_tag(name: &str, value: &str, language: Option<&str>) -> Vec<u8> {
+    let mut children = vec![string(0x45A3, name), string(0x4487, value)];
+    children.extend(language.map(|l| string(0x447A, l)));
+    master(0x67C8, &children)
+}
+
+/// A Tag with the given Targets children and simple tags
+pub fn tag(targets: &[Vec<u8>], simple: &[Vec<u8>]) -> Vec<u8> {
+    let mut children = vec![master(0x63C0, targets)];
+    children.extend_from_slice(simple);
+    master(0x7373, &children)
+}
@@ -0,0 +1,292 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+
+use matroska::{CueProblem, Matroska};
+
+mod common;
+
+#[test]
+fn verify() {
+    let info = common::info("Cues");
+    let first = common::cluster(
+        0,
+        &[
+            common::simple_block(1, 0, 0x80, &[], &[0; 16]),
+            common::simple_block(2, 0, 0x80, &[], &[0; 16]),
+        ],
+    );
+    let second = common::cluster(
+        100,
+        &[common::master(
+            0xA0,
+            &[common::element(
+                0xA1,
+                &common::block_body(1, 0, 0, &[], &[0; 16]),
+            )],
+        )],
+    );
+    let first_position = info.len() as u64;
+    let second_position = first_position + first.len() as u64;
+    // the Cluster's Timestamp element precedes its blocks
+    let first_block = common::uint(0xE7, 0).len() as u64;
+    let second_block = first_block + common::simple_block(1, 0, 0x80, &[], &[0; 16]).len() as u64;
+
+    let cues = common::master(
+        0x1C53_BB6B,
+        &[
+            common::cue_point(0, 1, first_position, Some(first_block)),
+            // shifted a few bytes into the Cluster
+            common::cue_point(10, 1, first_position + 3, None),
+            // the relative position lands on the other track's block
+            common::cue_point(20, 1, first_position, Some(second_block)),
+            common::cue_point(100, 1, second_position, Some(first_block)),
+            // beyond the end of the file
+            common::cue_point(200, 2, 1 << 20, None),
+        ],
+    );
+    let data = common::file(&[info, first, second, cues]);
+    let segment_start = (common::ebml_header("matroska").len() + 12) as u64;
+
+    let m = Matroska::open(Cursor::new(&data)).unwrap();
+    let cues = m.cues.unwrap();
+
+    let mismatches = cues.verify(Cursor::new(&data), 1, 10).unwrap();
+    assert_eq!(
+        mismatches
+            .iter()
+            .map(|m| (m.time, m.offset, m.problem.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                10,
+                segment_start + first_position + 3,
+                CueProblem::NotACluster { found: None }
+            ),
+            (
+                20,
+                segment_start + first_position + 12 + second_block,
+                CueProblem::NotABlock { found: Some(2) }
+            ),
+            (
+                200,
+                segment_start + (1 << 20),
+                CueProblem::NotACluster { found: None }
+            ),
+        ]
+    );
+
+    // only the first and third cues are sampled
+    let sampled = cues.verify(Cursor::new(&data), 2, 10).unwrap();
+    assert_eq!(
+        sampled.iter().map(|m| m.time).collect::<Vec<_>>(),
+        vec![20, 200]
+    );
+
+    let limited = cues.verify(Cursor::new(&data), 1, 1).unwrap();
+    assert_eq!(limited.len(), 1);
+    assert_eq!(limited[0].time, 10);
+}
+
+#[test]
+fn verify_block_group() {
+    let info = common::info("Cues");
+    // the Block follows a BlockDuration, and BlockAdditions
+    // of unknown size run to the end of the BlockGroup
+    let cluster = common::cluster(
+        0,
+        &[common::master(
+            0xA0,
+            &[
+                common::uint(0x9B, 40),
+                common::element(0xA1, &common::block_body(2, 0, 0, &[], &[0; 16])),
+                common::unknown_size(
+                    0x75A1,
+                    &[common::master(
+                        0xA6,
+                        &[common::uint(0xEE, 1), common::element(0xA5, &[0; 4])],
+                    )],
+                ),
+            ],
+        )],
+    );
+    let position = info.len() as u64;
+    let block = common::uint(0xE7, 0).len() as u64;
+    let cues = common::master(
+        0x1C53_BB6B,
+        &[
+            common::cue_point(0, 2, position, Some(block)),
+            common::cue_point(0, 1, position, Some(block)),
+        ],
+    );
+    let data = common::file(&[info, cluster, cues]);
+
+    let m = Matroska::open(Cursor::new(&data)).unwrap();
+    let mismatches = m.cues.unwrap().verify(Cursor::new(&data), 1, 10).unwrap();
+    assert_eq!(
+        mismatches
+            .iter()
+            .map(|m| m.problem.clone())
+            .collect::<Vec<_>>(),
+        vec![CueProblem::NotABlock { found: Some(2) }]
+    );
+}
+
+#[test]
+fn seek_index() {
+    use std::time::Duration;
+
+    // timestamps in milliseconds, as with the default scale
+    let cues = common::master(
+        0x1C53_BB6B,
+        &[
+            common::cue_point(0, 1, 100, None),
+            common::cue_point(0, 2, 150, None),
+            common::cue_point(1000, 1, 200, None),
+            common::cue_point(2000, 1, 300, None),
+            common::cue_point(2500, 2, 350, None),
+        ],
+    );
+    let data = common::file(&[common::info("Index"), cues]);
+    let m = Matroska::open(Cursor::new(&data)).unwrap();
+    let start = m.segment_start;
+    assert_eq!(
+        start,
+        (common::ebml_header("matroska").len() + 4 + 8) as u64
+    );
+
+    let index = m.seek_index().unwrap();
+    let at = |ms: u64, track: Option<u64>| index.offset_for(Duration::from_millis(ms), track);
+    assert_eq!(at(0, Some(1)), Some(start + 100));
+    assert_eq!(at(999, Some(1)), Some(start + 100));
+    assert_eq!(at(1000, Some(1)), Some(start + 200));
+    assert_eq!(at(60_000, Some(1)), Some(start + 300));
+    assert_eq!(at(2100, Some(2)), Some(start + 150));
+    assert_eq!(at(2600, None), Some(start + 350));
+    // a track without cues of its own uses everyone's
+    assert_eq!(at(1500, Some(3)), Some(start + 200));
+
+    // the timestamp scale applies
+    let scaled = common::master(
+        0x1549_A966,
+        &[
+            common::uint(0x2A_D7B1, 1_000_000_000),
+            common::string(0x4D80, "test"),
+            common::string(0x5741, "test"),
+        ],
+    );
+    let cues = common::master(
+        0x1C53_BB6B,
+        &[
+            common::cue_point(0, 1, 0, None),
+            common::cue_point(2, 1, 500, None),
+        ],
+    );
+    let m = Matroska::open(Cursor::new(common::file(&[scaled, cues]))).unwrap();
+    let index = m.seek_index().unwrap();
+    assert_eq!(
+        index.offset_for(Duration::from_millis(1999), None),
+        Some(m.segment_start)
+    );
+    assert_eq!(
+        index.offset_for(Duration::from_secs(2), None),
+        Some(m.segment_start + 500)
+    );
+
+    let m = Matroska::open(Cursor::new(common::file(&[common::info("None")]))).unwrap();
+    assert!(m.seek_index().is_none());
+}
+
+#[test]
+fn keyframe_index() {
+    use std::time::Duration;
+
+    let info = common::info("Keyframes");
+    let tracks = common::master(
+        0x1654_AE6B,
+        &[
+            common::track(1, 1, "V_MPEG4/ISO/AVC", &[]),
+            common::track(2, 2, "A_OPUS", &[]),
+        ],
+    );
+    // stored out of order, with a repeated keyframe timestamp
+    let first = common::cluster(
+        2000,
+        &[
+            common::simple_block(1, 0, 0x80, &[], &[0; 8]),
+            common::simple_block(1, 40, 0x00, &[], &[0; 8]),
+        ],
+    );
+    let second = common::cluster(
+        0,
+        &[
+            common::simple_block(2, 0, 0x80, &[], &[0; 8]),
+            common::simple_block(1, 0, 0x80, &[], &[0; 8]),
+            common::simple_block(1, 500, 0x80, &[], &[0; 8]),
+        ],
+    );
+    let third = common::cluster(500, &[common::simple_block(1, 0, 0x80, &[], &[0; 8])]);
+    let start = (common::ebml_header("matroska").len() + 4 + 8) as u64;
+    let first_offset = start + (info.len() + tracks.len()) as u64;
+    let second_offset = first_offset + first.len() as u64;
+    let third_offset = second_offset + second.len() as u64;
+
+    // Cues indexing only the audio track are ignored
+    let cues = common::master(
+        0x1C53_BB6B,
+        &[common::cue_point(0, 2, second_offset - start, None)],
+    );
+    let data = common::file(&[
+        info.clone(),
+        tracks.clone(),
+        first.clone(),
+        second.clone(),
+        third.clone(),
+        cues,
+    ]);
+    let index = matroska::keyframe_index(Cursor::new(&data), 1).unwrap();
+    let entries: Vec<_> = index
+        .iter()
+        .map(|entry| (entry.pts, entry.cluster_offset))
+        .collect();
+    let ms = Duration::from_millis;
+    assert_eq!(
+        entries,
+        vec![
+            (ms(0), second_offset),
+            (ms(500), second_offset),
+            (ms(500), third_offset),
+            (ms(2000), first_offset),
+        ]
+    );
+
+    // Cues indexing the track are used as they are
+    let cues = common::master(
+        0x1C53_BB6B,
+        &[
+            common::cue_point(2000, 1, first_offset - start, None),
+            common::cue_point(0, 1, second_offset - start, None),
+            common::cue_point(0, 2, second_offset - start, None),
+        ],
+    );
+    let data = common::file(&[info, tracks, first, second, third, cues]);
+    let index = matroska::keyframe_index(Cursor::new(&data), 1).unwrap();
+    let entries: Vec<_> = index
+        .iter()
+        .map(|entry| (entry.pts, entry.cluster_offset))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![(ms(0), second_offset), (ms(2000), first_offset)]
+    );
+
+    assert!(matches!(
+        matroska::keyframe_index(Cursor::new(&data), 3),
+        Err(matroska::MatroskaError::UnknownTrack { number: 3 })
+    ));
+}
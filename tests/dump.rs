@@ -0,0 +1,91 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+
+use matroska::{ids, ElementType};
+
+mod common;
+
+#[test]
+fn dump_tree() {
+    let cluster = common::cluster(0, &[common::simple_block(1, 0, 0x80, &[], &[0; 32])]);
+    let data = common::file(&[common::info("Dumped"), cluster]);
+
+    let nodes = matroska::dump(Cursor::new(&data), 8).unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0].name, Some("EBML"));
+    assert_eq!(nodes[1].id, ids::SEGMENT);
+    assert_eq!(nodes[1].header_len, 4 + 8);
+
+    let segment = &nodes[1].children;
+    let info = &segment[0];
+    assert_eq!(info.name, Some("Info"));
+    assert_eq!(info.offset, nodes[1].offset + 4 + 8);
+    let title = info.children.iter().find(|n| n.id == ids::TITLE).unwrap();
+    assert!(matches!(&title.value, Some(ElementType::UTF8(t)) if t == "Dumped"));
+
+    // Clusters are listed but not descended into by default
+    assert_eq!(segment[1].name, Some("Cluster"));
+    assert!(segment[1].children.is_empty());
+    let nodes = matroska::dump_all(Cursor::new(&data), 8).unwrap();
+    let cluster = &nodes[1].children[1];
+    assert_eq!(cluster.children.len(), 2);
+    // large binary bodies are left unread
+    assert_eq!(cluster.children[1].name, Some("SimpleBlock"));
+    assert!(cluster.children[1].value.is_none());
+
+    // the depth limit stops descent
+    let nodes = matroska::dump(Cursor::new(&data), 1).unwrap();
+    assert!(nodes[1].children.iter().all(|n| n.children.is_empty()));
+
+    let tree = nodes[1].to_string();
+    let mut lines = tree.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("+ Segment (0x18538067) at "));
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("|+ Info (0x1549A966) at "));
+}
+
+#[test]
+fn narrow_numbers() {
+    let info = common::master(
+        ids::INFO,
+        &[
+            common::element(ids::TIMECODESCALE, &[0x0F, 0x42, 0x40]),
+            common::element(ids::DURATION, &1.5f32.to_be_bytes()),
+        ],
+    );
+    let group = common::master(
+        ids::BLOCKGROUP,
+        &[
+            common::element(ids::BLOCK, &common::block_body(1, 0, 0, &[], &[])),
+            common::element(ids::REFERENCEBLOCK, &[0xFE]),
+            common::element(ids::REFERENCEBLOCK, &[0xFF, 0x00, 0x01]),
+            common::element(ids::REFERENCEBLOCK, &[0x7F, 0xFF]),
+        ],
+    );
+    let data = common::file(&[info, common::cluster(0, &[group])]);
+
+    let nodes = matroska::dump_all(Cursor::new(&data), 8).unwrap();
+    let info = &nodes[1].children[0].children;
+    assert!(matches!(info[0].value, Some(ElementType::UInt(1_000_000))));
+    assert!(matches!(info[1].value, Some(ElementType::Float(f)) if f == 1.5));
+    let group = &nodes[1].children[1].children[1].children;
+    let references: Vec<_> = group[1..]
+        .iter()
+        .map(|node| match node.value {
+            Some(ElementType::Int(i)) => i,
+            ref other => panic!("unexpected value {:?}", other),
+        })
+        .collect();
+    assert_eq!(references, vec![-2, -65535, 32767]);
+}
@@ -0,0 +1,510 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use matroska::{
+    ids, Language, Matroska, MatroskaError, Parseable, SeekHead, SimpleTag, Tag, TagValue, Target,
+    TargetTypeValue,
+};
+
+mod common;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("matroska-{}-{}", std::process::id(), name))
+}
+
+fn seek_head(entries: &[(u32, usize)]) -> Vec<u8> {
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|(id, position)| {
+            common::master(
+                0x4DBB,
+                &[
+                    common::element(0x53AB, &id.to_be_bytes()),
+                    common::uint(0x53AC, *position as u64),
+                ],
+            )
+        })
+        .collect();
+    common::master(0x114D_9B74, &entries)
+}
+
+/// A Void element of the given length, which includes its 9 byte header
+fn void(len: usize) -> Vec<u8> {
+    common::element(0xEC, &vec![0; len - 9])
+}
+
+fn title_tag(title: &str) -> Tag {
+    Tag::default().with_simple(
+        SimpleTag::default()
+            .with_name("TITLE")
+            .with_value(TagValue::String(title.to_owned())),
+    )
+}
+
+#[test]
+fn write_tags() {
+    let tags = vec![
+        title_tag("Title"),
+        Tag::default()
+            .with_targets(
+                Target::default()
+                    .with_target_type_value(TargetTypeValue::Chapter)
+                    .with_target_type("TRACK")
+                    .with_track_uid(1)
+                    .with_track_uid(2)
+                    .with_edition_uid(3)
+                    .with_chapter_uid(4)
+                    .with_attachment_uid(5),
+            )
+            .with_simple(
+                SimpleTag::default()
+                    .with_name("COMMENT")
+                    .with_language(Language::ISO639("ger".to_owned()))
+                    .with_value(TagValue::String("Kommentar".to_owned())),
+            )
+            .with_simple(
+                SimpleTag::default()
+                    .with_name("COMMENT")
+                    .with_language(Language::IETF("en-US".to_owned()))
+                    .with_default(true)
+                    .with_value(TagValue::String("Comment".to_owned())),
+            )
+            .with_simple(
+                SimpleTag::default()
+                    .with_name("DATA")
+                    .with_value(TagValue::Binary(vec![0, 1, 2])),
+            )
+            .with_simple(SimpleTag::default().with_name("EMPTY")),
+        Tag::default()
+            .with_targets(Target::default().with_target_type_value(TargetTypeValue::Unknown)),
+    ];
+    let mut data = Vec::new();
+    let len = Tag::write_all(&tags, &mut data).unwrap();
+    assert_eq!(len, data.len() as u64);
+
+    assert_eq!(data[..4], ids::TAGS.to_be_bytes());
+    let header_len = 4 + data[4].leading_zeros() as usize + 1;
+    let body = &data[header_len..];
+    assert_eq!(Tag::parse(&mut &body[..], body.len() as u64).unwrap(), tags);
+}
+
+#[test]
+fn replace_tags_in_place() {
+    let path = temp_path("replace-in-place.mkv");
+    let original = common::file(&[
+        common::info("Title"),
+        common::master(ids::TAGS, &[common::tag(&[], &[])]),
+        void(256),
+        common::cluster(0, &[]),
+    ]);
+    std::fs::write(&path, &original).unwrap();
+
+    let tags = vec![title_tag("New Title"), title_tag("Another")];
+    matroska::replace_tags(&path, &tags).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    assert_eq!(data.len(), original.len());
+    let m = Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.tags, tags);
+    assert_eq!(m.info.title.as_deref(), Some("Title"));
+
+    // leaving exactly one byte over lengthens the Tags' size instead
+    let mut sized = None;
+    for len in 1..512 {
+        std::fs::write(&path, &original).unwrap();
+        let tags = vec![title_tag(&"x".repeat(len))];
+        matroska::replace_tags(&path, &tags).unwrap();
+        let m = matroska::open(&path).unwrap();
+        assert_eq!(m.tags, tags);
+        if std::fs::read(&path).unwrap().len() != original.len() {
+            sized = Some(len);
+            break;
+        }
+    }
+    assert!(sized.unwrap() > 200);
+
+    matroska::replace_tags(&path, &[]).unwrap();
+    assert!(matroska::open(&path).unwrap().tags.is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn replace_tags_appended() {
+    let path = temp_path("replace-appended.mkv");
+    let info = common::info("Title");
+    let seek_head_len = seek_head(&[(ids::INFO, 0), (ids::TAGS, 0)]).len();
+    let padding = 64;
+    let tags_at = seek_head_len + padding + info.len();
+    let original = common::file(&[
+        seek_head(&[(ids::INFO, seek_head_len + padding), (ids::TAGS, tags_at)]),
+        void(padding),
+        info,
+        common::master(ids::TAGS, &[common::tag(&[], &[])]),
+        common::cluster(0, &[]),
+    ]);
+    std::fs::write(&path, &original).unwrap();
+
+    let tags = vec![title_tag(&"x".repeat(300))];
+    matroska::replace_tags(&path, &tags).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    assert!(data.len() > original.len());
+    let m = Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.tags, tags);
+    assert_eq!(m.info.title.as_deref(), Some("Title"));
+    let seek_head = SeekHead::read(Cursor::new(&data)).unwrap().unwrap();
+    assert_eq!(
+        seek_head.offsets(ids::TAGS).unwrap(),
+        vec![original.len() as u64]
+    );
+    assert_eq!(
+        seek_head.offsets(ids::INFO).unwrap(),
+        vec![m.segment_start + (seek_head_len + padding) as u64]
+    );
+
+    // removing the tags removes their SeekHead entry
+    matroska::replace_tags(&path, &[]).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    assert!(Matroska::open(Cursor::new(&data)).unwrap().tags.is_empty());
+    let seek_head = SeekHead::read(Cursor::new(&data)).unwrap().unwrap();
+    assert!(!seek_head.contains(ids::TAGS));
+    assert!(seek_head.contains(ids::INFO));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn replace_tags_chained_seek_head() {
+    let path = temp_path("replace-chained.mkv");
+    let info = common::info("Title");
+    let tags = common::master(ids::TAGS, &[common::tag(&[], &[])]);
+    let cluster = common::cluster(0, &[]);
+    let first_len = seek_head(&[(ids::INFO, 0), (ids::SEEKHEAD, 0)]).len();
+    let tags_at = first_len + info.len();
+    let chained_at = tags_at + tags.len() + cluster.len();
+    // the Tags are only listed by a SeekHead chained from the first
+    let original = common::file(&[
+        seek_head(&[(ids::INFO, first_len), (ids::SEEKHEAD, chained_at)]),
+        info,
+        tags,
+        cluster,
+        seek_head(&[(ids::TAGS, tags_at)]),
+    ]);
+    std::fs::write(&path, &original).unwrap();
+
+    let tags = vec![title_tag(&"x".repeat(300))];
+    matroska::replace_tags(&path, &tags).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    let m = Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.tags, tags);
+    let seek_head = SeekHead::read(Cursor::new(&data)).unwrap().unwrap();
+    assert_eq!(
+        seek_head.offsets(ids::TAGS).unwrap(),
+        vec![original.len() as u64]
+    );
+    // and the entry is still in the chained SeekHead
+    let first = matroska::get::<_, SeekHead>(Cursor::new(&data))
+        .unwrap()
+        .unwrap();
+    assert!(!first.contains(ids::TAGS));
+
+    // removing the tags removes the chained entry
+    matroska::replace_tags(&path, &[]).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    assert!(Matroska::open(Cursor::new(&data)).unwrap().tags.is_empty());
+    let seek_head = SeekHead::read(Cursor::new(&data)).unwrap().unwrap();
+    assert!(!seek_head.contains(ids::TAGS));
+    assert!(seek_head.contains(ids::INFO));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn replace_tags_requires_remux() {
+    let path = temp_path("replace-remux.mkv");
+    // the Segment's size has no room to grow
+    let info = common::info("Title");
+    let mut original = common::ebml_header("matroska");
+    original.extend(common::id(0x1853_8067));
+    original.push(0x80 | info.len() as u8);
+    original.extend(info);
+    std::fs::write(&path, &original).unwrap();
+    let tags = vec![title_tag(&"x".repeat(300))];
+    assert!(matches!(
+        matroska::replace_tags(&path, &tags),
+        Err(MatroskaError::RequiresRemux)
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+
+    // nor can a Segment followed by another
+    let mut original = common::file(&[common::info("Title")]);
+    original.extend(common::master(0x1853_8067, &[]));
+    std::fs::write(&path, &original).unwrap();
+    assert!(matches!(
+        matroska::replace_tags(&path, &tags),
+        Err(MatroskaError::RequiresRemux)
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn set_title() {
+    let path = temp_path("set-title.mkv");
+    let info = common::info("Title");
+    let tracks = common::master(ids::TRACKS, &[common::track(1, 2, "A_OPUS", &[])]);
+    let seek_head_len = seek_head(&[(ids::INFO, 0), (ids::TRACKS, 0)]).len();
+    let original = common::file(&[
+        seek_head(&[
+            (ids::INFO, seek_head_len),
+            (ids::TRACKS, seek_head_len + info.len() + 32),
+        ]),
+        info,
+        void(32),
+        tracks,
+    ]);
+    std::fs::write(&path, &original).unwrap();
+
+    let title = |path: &PathBuf| matroska::open(path).unwrap().info.title;
+    matroska::set_title(&path, Some("A")).unwrap();
+    assert_eq!(title(&path).as_deref(), Some("A"));
+    // the following Void makes room for a longer title
+    matroska::set_title(&path, Some("A Much Longer Title")).unwrap();
+    assert_eq!(title(&path).as_deref(), Some("A Much Longer Title"));
+    matroska::set_title(&path, None).unwrap();
+    assert_eq!(title(&path), None);
+    // a title is added to an Info which had none
+    matroska::set_title(&path, Some("Title Again")).unwrap();
+
+    let data = std::fs::read(&path).unwrap();
+    assert_eq!(data.len(), original.len());
+    let m = Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Title Again"));
+    assert_eq!(m.info.muxing_app, "test");
+    assert_eq!(m.tracks.len(), 1);
+
+    // the Info and Void can't hold this
+    assert!(matches!(
+        matroska::set_title(&path, Some(&"x".repeat(100))),
+        Err(MatroskaError::RequiresRemux)
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), data);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn set_title_checksummed() {
+    let path = temp_path("set-title-crc.mkv");
+    std::fs::write(
+        &path,
+        common::file(&[common::master(
+            ids::INFO,
+            &[
+                common::element(0xBF, &[0; 4]),
+                common::uint(0x2A_D7B1, 1_000_000),
+                void(16),
+            ],
+        )]),
+    )
+    .unwrap();
+
+    matroska::set_title(&path, Some("Title")).unwrap();
+    let options = matroska::ParseOptions::default().with_verify_crc(true);
+    let m = Matroska::open_with(std::fs::File::open(&path).unwrap(), &options).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Title"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn replace_chapters() {
+    use matroska::{Chapter, ChapterDisplay, ChapterEdition};
+    use std::time::Duration;
+
+    let path = temp_path("replace-chapters.mkv");
+    let original = common::file(&[
+        common::info("Title"),
+        common::master(
+            ids::CHAPTERS,
+            &[common::edition(1, &[common::chapter(1, 0, "Old")])],
+        ),
+        common::cluster(0, &[]),
+    ]);
+    std::fs::write(&path, &original).unwrap();
+
+    let chapter = |uid: u64, secs: u64, title: &str| {
+        Chapter::default()
+            .with_uid(uid)
+            .with_time_start(Duration::from_secs(secs))
+            .with_display(ChapterDisplay::default().with_string(title))
+    };
+    let editions = vec![ChapterEdition::default()
+        .with_uid(1)
+        .with_chapter(chapter(1, 0, "Intro").with_chapter(chapter(2, 30, "Credits")))];
+    matroska::replace_chapters(&path, &editions).unwrap();
+    let m = matroska::open(&path).unwrap();
+    assert_eq!(m.chapters, editions);
+    assert_eq!(m.info.title.as_deref(), Some("Title"));
+    // the new chapters are smaller, so they're written in place
+    assert_eq!(std::fs::read(&path).unwrap().len(), original.len());
+
+    matroska::replace_chapters(&path, &[]).unwrap();
+    assert!(matroska::open(&path).unwrap().chapters.is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn remux() {
+    use matroska::{MetadataEdits, TrackEdit};
+    use std::time::Duration;
+
+    let clusters = [
+        common::cluster(
+            0,
+            &[
+                common::simple_block(1, 0, 0x80, &[], b"key"),
+                common::simple_block(2, 0, 0x80, &[], b"audio"),
+            ],
+        ),
+        common::cluster(
+            1000,
+            &[
+                common::simple_block(1, 10, 0x00, &[], b"delta"),
+                common::simple_block(1, 20, 0x80, &[], b"key"),
+            ],
+        ),
+    ];
+    let original = common::file(&[
+        // a SeekHead pointing nowhere
+        seek_head(&[(ids::TAGS, 1 << 20)]),
+        common::info("Title"),
+        common::master(
+            ids::TRACKS,
+            &[
+                common::track(1, 1, "V_VP9", &[]),
+                common::subtitle_track(2, "eng", true, false, Some("English")),
+            ],
+        ),
+        common::master(ids::TAGS, &[common::tag(&[], &[])]),
+        clusters[0].clone(),
+        clusters[1].clone(),
+        common::master(ids::CUES, &[common::cue_point(0, 1, 0, None)]),
+    ]);
+
+    let tags = vec![title_tag(&"x".repeat(5000))];
+    let edits = MetadataEdits::default()
+        .with_title(Some("Remuxed"))
+        .with_track(
+            2,
+            TrackEdit::default()
+                .with_name(None)
+                .with_language(Language::IETF("de-DE".to_owned()))
+                .with_default(false),
+        )
+        .with_tags(tags.clone());
+    let mut remuxed = Cursor::new(Vec::new());
+    matroska::remux(Cursor::new(&original), &mut remuxed, edits).unwrap();
+    let data = remuxed.into_inner();
+
+    let m = Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Remuxed"));
+    assert_eq!(m.info.muxing_app, "test");
+    assert_eq!(m.tracks.len(), 2);
+    assert_eq!(m.tracks[0].codec_id, "V_VP9");
+    assert_eq!(m.tracks[1].name, None);
+    assert_eq!(
+        m.tracks[1].language,
+        Some(Language::IETF("de-DE".to_owned()))
+    );
+    assert!(!m.tracks[1].default);
+    assert_eq!(m.tags, tags);
+
+    // the Clusters are copied unchanged
+    let find = |element: &[u8]| {
+        data.windows(element.len())
+            .position(|w| w == element)
+            .unwrap() as u64
+    };
+    let positions = [find(&clusters[0]), find(&clusters[1])];
+
+    // with Cues pointing at them
+    let index = m.seek_index().unwrap();
+    assert_eq!(
+        index.offset_for(Duration::from_millis(500), Some(1)),
+        Some(positions[0])
+    );
+    assert_eq!(
+        index.offset_for(Duration::from_millis(1500), Some(1)),
+        Some(positions[1])
+    );
+    let cues = m.cues.unwrap();
+    assert_eq!(cues.points.len(), 2);
+
+    // and a SeekHead listing every section
+    let seek_head = SeekHead::read(Cursor::new(&data)).unwrap().unwrap();
+    for id in [ids::INFO, ids::TRACKS, ids::TAGS, ids::CUES].iter() {
+        let offset = seek_head.offsets(*id).unwrap()[0] as usize;
+        assert_eq!(data[offset..offset + 4], id.to_be_bytes());
+    }
+    assert!(!seek_head.contains(ids::CHAPTERS));
+
+    // editing a track that doesn't exist does nothing
+    let edits = MetadataEdits::default().with_track(3, TrackEdit::default());
+    let mut remuxed = Cursor::new(Vec::new());
+    assert!(matches!(
+        matroska::remux(Cursor::new(&original), &mut remuxed, edits),
+        Err(MatroskaError::UnknownTrack { number: 3 })
+    ));
+    assert!(remuxed.into_inner().is_empty());
+}
+
+#[test]
+fn remux_checksummed() {
+    use matroska::{MetadataEdits, TrackEdit};
+
+    let tags = |title: &str| {
+        common::master(
+            ids::TAGS,
+            &[common::tag(
+                &[],
+                &[common::simple_tag("TITLE", title, None)],
+            )],
+        )
+    };
+    let original = common::file(&[
+        common::info("Title"),
+        // a Tracks whose CRC-32 is rewritten along with it
+        common::master(
+            ids::TRACKS,
+            &[
+                common::element(ids::CRC32, &[0; 4]),
+                common::track(1, 1, "V_VP9", &[]),
+            ],
+        ),
+        tags("First"),
+        tags("Second"),
+        common::cluster(0, &[common::simple_block(1, 0, 0x80, &[], b"key")]),
+    ]);
+
+    let edits =
+        MetadataEdits::default().with_track(1, TrackEdit::default().with_name(Some("Video")));
+    let mut remuxed = Cursor::new(Vec::new());
+    matroska::remux(Cursor::new(&original), &mut remuxed, edits).unwrap();
+    let data = remuxed.into_inner();
+
+    let options = matroska::ParseOptions::default().with_verify_crc(true);
+    let m = Matroska::open_with(Cursor::new(&data), &options).unwrap();
+    assert_eq!(m.tracks[0].name.as_deref(), Some("Video"));
+    assert_eq!(m.tags.len(), 2);
+
+    // both Tags sections are listed by the SeekHead
+    let seek_head = SeekHead::read(Cursor::new(&data)).unwrap().unwrap();
+    let offsets = seek_head.offsets(ids::TAGS).unwrap();
+    assert_eq!(offsets.len(), 2);
+    for offset in offsets {
+        let offset = offset as usize;
+        assert_eq!(data[offset..offset + 4], ids::TAGS.to_be_bytes());
+    }
+}
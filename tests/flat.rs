@@ -0,0 +1,246 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+
+use matroska::{BinaryFormat, Matroska};
+
+mod common;
+
+const SECOND: u64 = 1_000_000_000;
+
+fn sample() -> Matroska {
+    let data = common::file(&[
+        common::master(
+            0x1549_A966,
+            &[
+                common::element(0x73A4, &[0xAB; 16]),
+                common::float(0x4489, 1500.0),
+                common::string(0x4D80, "mux"),
+                common::string(0x5741, "write"),
+                common::string(0x7BA9, "Flat"),
+            ],
+        ),
+        common::master(
+            0x1654_AE6B,
+            &[
+                common::track(
+                    1,
+                    1,
+                    "V_VP9",
+                    &[
+                        common::element(0x63A2, b"abcd"),
+                        common::master(
+                            0xE0,
+                            &[
+                                common::uint(0xB0, 1920),
+                                common::uint(0xBA, 1080),
+                                common::uint(0x9D, 1),
+                            ],
+                        ),
+                    ],
+                ),
+                common::subtitle_track(2, "fre", false, true, Some("Forced")),
+            ],
+        ),
+        common::master(
+            0x1043_A770,
+            &[common::edition(
+                9,
+                &[common::timed_chapter(
+                    3,
+                    SECOND / 2,
+                    SECOND,
+                    &[
+                        common::master(
+                            0x80,
+                            &[common::string(0x85, "Intro"), common::string(0x437C, "eng")],
+                        ),
+                        common::master(
+                            0x80,
+                            &[common::string(0x85, "Début"), common::string(0x437C, "fre")],
+                        ),
+                    ],
+                )],
+            )],
+        ),
+        common::master(
+            0x1254_C367,
+            &[
+                common::master(
+                    0x7373,
+                    &[common::master(
+                        0x67C8,
+                        &[
+                            common::string(0x45A3, "ARTIST"),
+                            common::string(0x4487, "Someone"),
+                        ],
+                    )],
+                ),
+                common::master(
+                    0x7373,
+                    &[
+                        common::master(
+                            0x63C0,
+                            &[common::uint(0x68CA, 70), common::uint(0x63C5, 1)],
+                        ),
+                        common::master(
+                            0x67C8,
+                            &[
+                                common::string(0x45A3, "BPS"),
+                                common::string(0x447A, "eng"),
+                                common::string(0x4487, "1000"),
+                            ],
+                        ),
+                    ],
+                ),
+            ],
+        ),
+        common::master(
+            0x1941_A469,
+            &[common::master(
+                0x61A7,
+                &[
+                    common::string(0x466E, "cover.jpg"),
+                    common::string(0x4660, "image/jpeg"),
+                    common::element(0x465C, b"hello"),
+                ],
+            )],
+        ),
+    ]);
+    Matroska::open(Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn flat_map() {
+    let map = sample().to_flat_map();
+    let get = |key: &str| map.get(key).map(String::as_str);
+
+    assert_eq!(get("info.title"), Some("Flat"));
+    assert_eq!(get("info.uid"), Some("16"));
+    assert_eq!(get("info.duration"), Some("1.500000000"));
+    assert_eq!(get("info.timestamp_scale"), Some("1000000"));
+    assert_eq!(get("track.1.codec_id"), Some("V_VP9"));
+    assert_eq!(get("track.1.type"), Some("video"));
+    assert_eq!(get("track.1.codec_private"), Some("4"));
+    assert_eq!(get("track.1.video.pixel_width"), Some("1920"));
+    assert_eq!(get("track.1.video.field_order"), Some("top_field_first"));
+    assert_eq!(get("track.2.language"), Some("fre"));
+    assert_eq!(get("track.2.forced"), Some("true"));
+    assert_eq!(get("track.2.name"), Some("Forced"));
+    assert_eq!(get("edition.9.ordered"), Some("false"));
+    assert_eq!(get("chapter.3.start"), Some("0.500000000"));
+    assert_eq!(get("chapter.3.end"), Some("1.000000000"));
+    assert_eq!(get("chapter.3.title.eng"), Some("Intro"));
+    assert_eq!(get("chapter.3.title.fre"), Some("Début"));
+    assert_eq!(get("tag.episode.ARTIST"), Some("Someone"));
+    assert_eq!(get("tag.collection.track.1.BPS.eng"), Some("1000"));
+    assert_eq!(get("attachment.0.name"), Some("cover.jpg"));
+    assert_eq!(get("attachment.0.data"), Some("5"));
+    // absent values are omitted rather than empty
+    assert_eq!(get("info.date_utc"), None);
+    assert_eq!(get("track.1.name"), None);
+}
+
+#[test]
+fn flat_map_base64() {
+    let map = sample().to_flat_map_with(BinaryFormat::Base64);
+    assert_eq!(map["track.1.codec_private"], "YWJjZA==");
+    assert_eq!(map["attachment.0.data"], "aGVsbG8=");
+    assert_eq!(map["info.uid"], "q6urq6urq6urq6urq6urqw==");
+}
+
+#[test]
+fn flat_map_repeats() {
+    let data = common::file(&[
+        common::info("Repeats"),
+        common::video_track(&[
+            common::uint(0xB0, 640),
+            common::uint(0xBA, 480),
+            common::uint(0x53B8, 1),
+        ]),
+        common::master(
+            0x1043_A770,
+            &[common::edition(
+                9,
+                &[
+                    common::timed_chapter(
+                        3,
+                        0,
+                        SECOND,
+                        &[
+                            common::master(
+                                0x80,
+                                &[
+                                    common::string(0x85, "Colour"),
+                                    common::string(0x437C, "eng"),
+                                    common::string(0x437E, "us"),
+                                ],
+                            ),
+                            common::master(
+                                0x80,
+                                &[
+                                    common::string(0x85, "Colour"),
+                                    common::string(0x437C, "eng"),
+                                    common::string(0x437E, "gb"),
+                                    common::string(0x437E, "au"),
+                                ],
+                            ),
+                        ],
+                    ),
+                    common::chapter(0, SECOND, "First"),
+                    common::chapter(0, 2 * SECOND, "Second"),
+                ],
+            )],
+        ),
+        common::master(
+            0x1254_C367,
+            &[common::master(
+                0x7373,
+                &[
+                    common::master(
+                        0x67C8,
+                        &[
+                            common::string(0x45A3, "ARTIST"),
+                            common::string(0x4487, "Someone"),
+                            common::master(
+                                0x67C8,
+                                &[
+                                    common::string(0x45A3, "SORT_WITH"),
+                                    common::string(0x4487, "One, Some"),
+                                ],
+                            ),
+                        ],
+                    ),
+                    common::master(
+                        0x67C8,
+                        &[
+                            common::string(0x45A3, "ARTIST"),
+                            common::string(0x4487, "Someone Else"),
+                        ],
+                    ),
+                ],
+            )],
+        ),
+    ]);
+    let map = Matroska::open(Cursor::new(data)).unwrap().to_flat_map();
+    let get = |key: &str| map.get(key).map(String::as_str);
+
+    assert_eq!(get("track.1.video.stereo"), Some("side_by_side_left_first"));
+    assert_eq!(get("chapter.3.title.eng"), Some("Colour"));
+    assert_eq!(get("chapter.3.title.eng.country.0"), Some("us"));
+    assert_eq!(get("chapter.3.title.eng.1"), Some("Colour"));
+    assert_eq!(get("chapter.3.title.eng.1.country.0"), Some("gb"));
+    assert_eq!(get("chapter.3.title.eng.1.country.1"), Some("au"));
+    assert_eq!(get("chapter.0.start"), Some("1.000000000"));
+    assert_eq!(get("chapter.0.start.1"), Some("2.000000000"));
+    assert_eq!(get("chapter.0.title.eng"), Some("First"));
+    assert_eq!(get("chapter.0.title.eng.1"), Some("Second"));
+    assert_eq!(get("tag.episode.ARTIST"), Some("Someone"));
+    assert_eq!(get("tag.episode.ARTIST.SORT_WITH"), Some("One, Some"));
+    assert_eq!(get("tag.episode.ARTIST.1"), Some("Someone Else"));
+}
@@ -6,10 +6,17 @@
 // <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use std::{fs::File, path::PathBuf, time::Duration};
+use std::{
+    fs::File,
+    io::Cursor,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
 
 use matroska::{Settings, TagValue, Tracktype};
 
+mod common;
+
 #[test]
 fn info() {
     let f = File::open(PathBuf::from("tests").join("samples").join("bbb.mkv")).unwrap();
@@ -60,3 +67,2112 @@ fn info() {
         _ => panic!("invalid tag value"),
     }
 }
+
+#[test]
+fn chapter_lookup() {
+    let data = common::file(&[
+        common::info("Chapters"),
+        common::master(
+            0x1043_A770,
+            &[
+                common::edition(
+                    10,
+                    &[
+                        common::chapter(1, 0, "Intro"),
+                        common::chapter(2, 1_000_000_000, "Main"),
+                    ],
+                ),
+                common::edition(20, &[common::chapter(3, 0, "Other")]),
+            ],
+        ),
+    ]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+
+    let (edition, chapter) = m.chapter_by_uid(3).unwrap();
+    assert_eq!(edition.uid, Some(20));
+    assert_eq!(chapter.display[0].string, "Other");
+    assert_eq!(m.edition_by_uid(10).unwrap().chapters.len(), 2);
+    assert!(m.chapter_by_uid(0).is_none());
+    assert!(m.edition_by_uid(0).is_none());
+    assert!(m.chapter_by_uid(4).is_none());
+}
+
+#[test]
+fn missing_info() {
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[common::master(
+            0xAE,
+            &[common::uint(0xD7, 1), common::string(0x86, "V_VP8")],
+        )],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data.clone())).unwrap();
+    assert!(m.info.is_placeholder());
+    assert_eq!(m.info.timestamp_scale, matroska::DEFAULT_TIMESTAMP_SCALE);
+    assert_eq!(m.info.timestamp(5), Duration::from_millis(5));
+    assert_eq!(m.tracks.len(), 1);
+    assert!(matroska::get::<_, matroska::Info>(Cursor::new(data))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn minimal_info() {
+    let data = common::file(&[common::master(
+        0x1549_A966,
+        &[common::uint(0x2A_D7B1, 1_000)],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data.clone())).unwrap();
+    assert!(!m.info.is_placeholder());
+    assert_eq!(m.info.timestamp_scale, 1_000);
+    assert_eq!(m.info.muxing_app, "");
+    assert_eq!(m.info.timestamp(5), Duration::from_micros(5));
+    let info = matroska::get::<_, matroska::Info>(Cursor::new(data))
+        .unwrap()
+        .unwrap();
+    assert!(!info.is_placeholder());
+}
+
+#[test]
+fn raw_duration() {
+    let data = common::file(&[common::master(
+        0x1549_A966,
+        &[
+            common::uint(0x2A_D7B1, 1_000),
+            common::float(0x4489, 1_500.25),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    assert_eq!(m.info.duration, Some(Duration::from_nanos(1_500_250)));
+    assert_eq!(m.info.duration_raw, Some(1_500.25));
+    assert_eq!(m.info.duration_seconds(), Some(0.001_500_25));
+    assert_eq!(m.info, m.info.clone());
+    assert_eq!(matroska::Info::default().duration_seconds(), None);
+}
+
+#[test]
+fn pathological_durations() {
+    use matroska::{Info, InfoProblem, ParseOptions, Parseable};
+
+    let parse = |duration: f64| {
+        let body = common::float(0x4489, duration);
+        Info::parse(&mut Cursor::new(&body), body.len() as u64).unwrap()
+    };
+    for duration in [-1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let info = parse(duration);
+        assert_eq!(info.duration, None);
+        assert_eq!(
+            info.duration_raw.map(f64::to_bits),
+            Some(duration.to_bits())
+        );
+        assert_eq!(info.problems, vec![InfoProblem::InvalidDuration]);
+    }
+
+    let info = parse(1e30);
+    assert_eq!(info.duration, Some(Duration::from_nanos(u64::MAX)));
+    assert_eq!(info.problems, vec![InfoProblem::DurationOverflow]);
+
+    let info = parse(0.0);
+    assert_eq!(info.duration, Some(Duration::ZERO));
+    assert!(info.problems.is_empty());
+
+    // only invalid Durations are errors in strict mode
+    let file = |duration: f64| {
+        Cursor::new(common::file(&[common::master(
+            0x1549_A966,
+            &[common::float(0x4489, duration)],
+        )]))
+    };
+    let strict = ParseOptions::default().with_strict(true);
+    match matroska::Matroska::open_with(file(-1.0), &strict) {
+        Err(matroska::MatroskaError::InvalidDuration { value }) => assert_eq!(value, -1.0),
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(matroska::Matroska::open_with(file(1e30), &strict).is_ok());
+    assert!(matroska::Matroska::open(file(-1.0)).is_ok());
+}
+
+#[test]
+fn parallel_matches_sequential() {
+    let path = PathBuf::from("tests").join("samples").join("bbb.mkv");
+    let sequential = matroska::open(&path).unwrap();
+    for threads in [1, 4] {
+        let options = matroska::ParseOptions::default().with_threads(threads);
+        let parallel = matroska::open_parallel(&path, options).unwrap();
+        assert_eq!(parallel, sequential);
+    }
+}
+
+#[test]
+fn parallel_matches_sequential_fixtures() {
+    let seek = |id: u32, position: u64| {
+        common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(id)),
+                common::uint(0x53AC, position),
+            ],
+        )
+    };
+    let seekhead = |entries: &[(u32, u64)]| {
+        let seeks: Vec<_> = entries.iter().map(|&(id, pos)| seek(id, pos)).collect();
+        common::master(0x114D_9B74, &seeks)
+    };
+    let tags = |title: &str| {
+        common::master(
+            0x1254_C367,
+            &[common::tag(
+                &[],
+                &[common::simple_tag("TITLE", title, None)],
+            )],
+        )
+    };
+    let info = common::info("Parallel");
+    let tracks = common::master(0x1654_AE6B, &[common::track(1, 1, "V_VP9", &[])]);
+
+    // every entry listed, with two Tags sections
+    let listed_len = seekhead(&[(0x1549_A966, 0); 4]).len() as u64;
+    let tracks_pos = listed_len + info.len() as u64;
+    let tags_pos = tracks_pos + tracks.len() as u64;
+    let more_tags_pos = tags_pos + tags("First").len() as u64;
+    let listed = common::file(&[
+        seekhead(&[
+            (0x1549_A966, listed_len),
+            (0x1654_AE6B, tracks_pos),
+            (0x1254_C367, tags_pos),
+            (0x1254_C367, more_tags_pos),
+        ]),
+        info.clone(),
+        tracks.clone(),
+        tags("First"),
+        tags("Second"),
+    ]);
+
+    // a SeekHead pointing past the end of the file
+    let out_of_bounds = common::file(&[
+        seekhead(&[(0x1549_A966, 1 << 40), (0x1654_AE6B, 1 << 40)]),
+        info.clone(),
+        tracks.clone(),
+    ]);
+
+    // and one pointing at the wrong section
+    let misplaced_len = seekhead(&[(0x1549_A966, 0); 2]).len() as u64;
+    let misplaced = common::file(&[
+        seekhead(&[
+            (0x1549_A966, misplaced_len + info.len() as u64),
+            (0x1654_AE6B, misplaced_len + info.len() as u64),
+        ]),
+        info,
+        tracks,
+    ]);
+
+    for (name, data) in [
+        ("listed", listed),
+        ("out-of-bounds", out_of_bounds),
+        ("misplaced", misplaced),
+    ] {
+        let path = std::env::temp_dir().join(format!(
+            "matroska-{}-parallel-{}.mkv",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, &data).unwrap();
+        let sequential = matroska::Matroska::open(Cursor::new(&data));
+        let parallel = matroska::open_parallel(&path, matroska::ParseOptions::default());
+        std::fs::remove_file(&path).unwrap();
+        let (sequential, parallel) = (sequential.unwrap(), parallel.unwrap());
+        assert_eq!(parallel, sequential, "{}", name);
+        assert_eq!(
+            sequential.info.title.as_deref(),
+            Some("Parallel"),
+            "{}",
+            name
+        );
+        assert_eq!(sequential.tracks.len(), 1, "{}", name);
+    }
+}
+
+#[test]
+fn metadata_diff() {
+    fn tags(entries: &[(&str, &str)]) -> Vec<u8> {
+        common::master(
+            0x1254_C367,
+            &entries
+                .iter()
+                .map(|(name, value)| {
+                    common::master(
+                        0x7373,
+                        &[common::master(
+                            0x67C8,
+                            &[common::string(0x45A3, name), common::string(0x4487, value)],
+                        )],
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    let a = common::file(&[
+        common::info("Before"),
+        tags(&[("ARTIST", "Someone"), ("DATE", "2012")]),
+        common::master(
+            0x1043_A770,
+            &[common::edition(1, &[common::chapter(5, 0, "Intro")])],
+        ),
+    ]);
+    let b = common::file(&[
+        common::info("After"),
+        // reordered, with one value changed and one tag added
+        tags(&[
+            ("GENRE", "Animation"),
+            ("DATE", "2013"),
+            ("ARTIST", "Someone"),
+        ]),
+        common::master(
+            0x1043_A770,
+            &[common::edition(1, &[common::chapter(5, 1_000, "Intro")])],
+        ),
+    ]);
+    let a = matroska::Matroska::open(Cursor::new(a)).unwrap();
+    let b = matroska::Matroska::open(Cursor::new(b)).unwrap();
+
+    assert!(matroska::diff(&a, &a).is_empty());
+
+    let changes = matroska::diff(&a, &b).changes;
+    assert_eq!(changes.len(), 4);
+    assert!(matches!(
+        changes[0],
+        matroska::Change::Info { field: "title", .. }
+    ));
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        matroska::Change::TagModified { before, after, .. }
+            if before.name == "DATE"
+                && after.value == Some(TagValue::String("2013".into()))
+    )));
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        matroska::Change::TagAdded { tag, .. } if tag.name == "GENRE"
+    )));
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        matroska::Change::ChapterRetimed { uid: 5, after: (start, None), .. }
+            if *start == Duration::from_nanos(1_000)
+    )));
+}
+
+#[test]
+fn metadata_diff_nested() {
+    let attached = |uid: u64, name: &str, data: &[u8]| {
+        common::master(
+            0x61A7,
+            &[
+                common::string(0x466E, name),
+                common::string(0x4660, "application/octet-stream"),
+                common::element(0x465C, data),
+                common::uint(0x46AE, uid),
+            ],
+        )
+    };
+    let file = |nested: &str, data: &[u8]| {
+        common::file(&[
+            common::info("Same"),
+            common::master(
+                0x1043_A770,
+                &[common::edition(
+                    1,
+                    &[common::master(
+                        0xB6,
+                        &[
+                            common::uint(0x73C4, 1),
+                            common::uint(0x91, 0),
+                            common::master(0x80, &[common::string(0x85, "Part")]),
+                            common::chapter(2, 0, nested),
+                        ],
+                    )],
+                )],
+            ),
+            // two attachments sharing a name
+            common::master(
+                0x1941_A469,
+                &[
+                    attached(1, "font.ttf", b"one"),
+                    attached(2, "font.ttf", data),
+                ],
+            ),
+        ])
+    };
+    let a = matroska::Matroska::open(Cursor::new(file("Nested", b"two"))).unwrap();
+    let b = matroska::Matroska::open(Cursor::new(file("Renamed", b"three"))).unwrap();
+
+    let changes = matroska::diff(&a, &b).changes;
+    assert_eq!(changes.len(), 2, "{:?}", changes);
+    // only the nested chapter is reported
+    assert!(matches!(
+        &changes[0],
+        matroska::Change::ChapterModified { before, after, .. }
+            if before.uid == 2 && after.display[0].string == "Renamed"
+    ));
+    assert!(matches!(
+        changes[1],
+        matroska::Change::AttachmentReplaced { size_delta: 2, .. }
+    ));
+
+    // deferred attachments are compared by their real size
+    let options = matroska::ParseOptions::default().with_defer_attachment_data(true);
+    let a = matroska::Matroska::open_with(Cursor::new(file("Nested", b"two")), &options).unwrap();
+    let empty =
+        matroska::Matroska::open(Cursor::new(common::file(&[common::info("Same")]))).unwrap();
+    let changes = matroska::diff(&a, &empty).changes;
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, matroska::Change::AttachmentRemoved { size: 3, .. })));
+}
+
+#[test]
+fn cues() {
+    let data = common::file(&[
+        common::master(0x1549_A966, &[common::uint(0x2A_D7B1, 100_000)]),
+        common::master(
+            0x1C53_BB6B,
+            &[
+                common::master(
+                    0xBB,
+                    &[
+                        common::uint(0xB3, 0),
+                        common::master(0xB7, &[common::uint(0xF7, 1), common::uint(0xF1, 100)]),
+                    ],
+                ),
+                common::master(
+                    0xBB,
+                    &[
+                        common::uint(0xB3, 20),
+                        common::master(
+                            0xB7,
+                            &[
+                                common::uint(0xF7, 2),
+                                common::uint(0xF1, 200),
+                                common::uint(0xF0, 12),
+                                common::uint(0xB2, 5),
+                                common::uint(0x5378, 3),
+                            ],
+                        ),
+                    ],
+                ),
+            ],
+        ),
+    ]);
+    let m = matroska::Matroska::open(Cursor::new(data.clone())).unwrap();
+    let cues = m.cues.unwrap();
+    assert_eq!(cues.points.len(), 2);
+    let point = &cues.points[1];
+    assert_eq!(point.time, 20);
+    assert_eq!(point.timestamp(&m.info), Duration::from_millis(2));
+    let positions = &point.positions[0];
+    assert_eq!(positions.track, 2);
+    assert_eq!(positions.cluster_position, 200);
+    assert_eq!(positions.relative_position, Some(12));
+    assert_eq!(
+        positions.scaled_duration(&m.info),
+        Some(Duration::from_micros(500))
+    );
+    assert_eq!(positions.block_number, Some(3));
+    assert_eq!(cues.points[0].positions[0].relative_position, None);
+
+    assert_eq!(
+        matroska::get::<_, matroska::Cues>(Cursor::new(data))
+            .unwrap()
+            .unwrap(),
+        cues
+    );
+
+    // files without Cues simply have none
+    let m = matroska::Matroska::open(Cursor::new(common::file(&[common::info("")]))).unwrap();
+    assert!(m.cues.is_none());
+}
+
+#[test]
+fn track_problems() {
+    use matroska::{MatroskaError, ParseOptions, TrackProblem};
+
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[
+            // missing TrackUID
+            common::master(
+                0xAE,
+                &[
+                    common::uint(0xD7, 1),
+                    common::uint(0x83, 2),
+                    common::string(0x86, "A_OPUS"),
+                ],
+            ),
+            // missing CodecID
+            common::master(
+                0xAE,
+                &[
+                    common::uint(0xD7, 2),
+                    common::uint(0x73C5, 2),
+                    common::uint(0x83, 1),
+                ],
+            ),
+        ],
+    )]);
+
+    let m = matroska::Matroska::open(Cursor::new(data.clone())).unwrap();
+    assert_eq!(m.tracks[0].problems, vec![TrackProblem::MissingUid]);
+    assert_eq!(m.tracks[1].problems, vec![TrackProblem::MissingCodecId]);
+
+    let strict = ParseOptions::default().with_strict(true);
+    match matroska::Matroska::open_with(Cursor::new(data), &strict) {
+        Err(MatroskaError::InvalidTrack { number, problem }) => {
+            assert_eq!(number, 2);
+            assert_eq!(problem, TrackProblem::MissingCodecId);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // only a missing CodecID is fatal
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[common::master(
+            0xAE,
+            &[common::uint(0x83, 2), common::string(0x86, "A_OPUS")],
+        )],
+    )]);
+    let m = matroska::Matroska::open_with(Cursor::new(data), &strict).unwrap();
+    assert_eq!(
+        m.tracks[0].problems,
+        vec![TrackProblem::MissingNumber, TrackProblem::MissingUid]
+    );
+
+    let f = File::open(PathBuf::from("tests").join("samples").join("bbb.mkv")).unwrap();
+    let m = matroska::Matroska::open_with(f, &strict).unwrap();
+    assert!(m.tracks.iter().all(|t| t.problems.is_empty()));
+}
+
+#[test]
+fn deferred_codec_private() {
+    let path = PathBuf::from("tests").join("samples").join("bbb.mkv");
+    let eager = matroska::open(&path).unwrap();
+    let options = matroska::ParseOptions::default().with_defer_codec_private(true);
+    let mut file = File::open(&path).unwrap();
+    let deferred = matroska::Matroska::open_with(&mut file, &options).unwrap();
+
+    assert!(eager.tracks.iter().any(|t| t.codec_private.is_some()));
+    assert_eq!(eager.tracks.len(), deferred.tracks.len());
+    for (eager, deferred) in eager.tracks.iter().zip(&deferred.tracks) {
+        assert_eq!(deferred.codec_private, None);
+        assert_eq!(deferred.codec_id, eager.codec_id);
+        assert_eq!(
+            deferred.read_codec_private(&mut file).unwrap(),
+            eager.codec_private
+        );
+        assert_eq!(
+            eager.read_codec_private(&mut file).unwrap(),
+            eager.codec_private
+        );
+    }
+}
+
+#[test]
+fn empty_files() {
+    use matroska::{MatroskaError, Probe};
+
+    let mut truncated = common::ebml_header("matroska");
+    truncated.extend(common::id(0x1853_8067));
+    truncated.extend(common::size(1000));
+
+    let empty = [
+        Vec::new(),
+        common::ebml_header("matroska"),
+        common::file(&[]),
+        truncated,
+    ];
+    for data in empty.iter() {
+        assert_eq!(matroska::probe(Cursor::new(data)).unwrap(), Probe::Empty);
+        assert!(matches!(
+            matroska::Matroska::open(Cursor::new(data)),
+            Err(MatroskaError::EmptySegment)
+        ));
+        assert!(matches!(
+            matroska::get::<_, matroska::Info>(Cursor::new(data)),
+            Ok(None)
+        ));
+    }
+
+    assert_eq!(
+        matroska::probe(Cursor::new(b"RIFF\x00\x00\x00\x00WAVE")).unwrap(),
+        Probe::NotMatroska
+    );
+    assert_eq!(
+        matroska::probe(Cursor::new(common::ebml_header("other"))).unwrap(),
+        Probe::NotMatroska
+    );
+    let f = File::open(PathBuf::from("tests").join("samples").join("bbb.mkv")).unwrap();
+    assert_eq!(matroska::probe(f).unwrap(), Probe::Matroska);
+}
+
+#[test]
+fn cache_hints() {
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[
+            common::track(
+                1,
+                1,
+                "V_MPEG4/ISO/AVC",
+                &[
+                    common::uint(0x6DE7, 1),
+                    common::uint(0x6DF8, 4),
+                    common::uint(0x55EE, 2),
+                ],
+            ),
+            common::track(2, 2, "A_AAC", &[]),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    assert_eq!(m.tracks[0].min_cache, Some(1));
+    assert_eq!(m.tracks[0].max_cache, Some(4));
+    assert_eq!(m.tracks[0].max_block_addition_id, Some(2));
+    assert_eq!(m.tracks[1].min_cache, None);
+    assert_eq!(m.tracks[1].max_cache, None);
+    assert_eq!(m.tracks[1].max_block_addition_id, None);
+
+    // the fixture (muxed by libavformat) has none of the three
+    let f = File::open(PathBuf::from("tests").join("samples").join("bbb.mkv")).unwrap();
+    let m = matroska::Matroska::open(f).unwrap();
+    for track in m.tracks.iter() {
+        assert_eq!(track.min_cache, None);
+        assert_eq!(track.max_cache, None);
+        assert_eq!(track.max_block_addition_id, None);
+    }
+}
+
+#[test]
+fn track_translate() {
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[common::track(
+            1,
+            1,
+            "V_MPEG2",
+            &[
+                common::master(
+                    0x6624,
+                    &[
+                        common::uint(0x66FC, 10),
+                        common::uint(0x66FC, 11),
+                        common::uint(0x66BF, 1),
+                        common::element(0x66A5, &[0x00, 0x01]),
+                    ],
+                ),
+                common::master(
+                    0x6624,
+                    &[common::uint(0x66BF, 0), common::element(0x66A5, &[0x02])],
+                ),
+            ],
+        )],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    let translate = &m.tracks[0].translate;
+    assert_eq!(translate.len(), 2);
+    assert_eq!(translate[0].edition_uids, vec![10, 11]);
+    assert_eq!(translate[0].codec, 1);
+    assert_eq!(translate[0].track_id, vec![0x00, 0x01]);
+    assert!(translate[1].edition_uids.is_empty());
+    assert_eq!(translate[1].codec, 0);
+    assert_eq!(translate[1].track_id, vec![0x02]);
+}
+
+#[test]
+fn content_encodings() {
+    use matroska::{CompressionAlgorithm, ContentEncodingType};
+
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[
+            common::track(
+                1,
+                2,
+                "A_AC3",
+                &[common::master(
+                    0x6D80,
+                    &[common::master(
+                        0x6240,
+                        &[common::master(
+                            0x5034,
+                            &[
+                                common::uint(0x4254, 3),
+                                common::element(0x4255, &[0x0B, 0x77]),
+                            ],
+                        )],
+                    )],
+                )],
+            ),
+            common::track(2, 2, "A_AC3", &[]),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    let encodings = &m.tracks[0].encodings;
+    assert_eq!(encodings.len(), 1);
+    assert_eq!(encodings[0].order, 0);
+    assert_eq!(encodings[0].scope, 1);
+    assert_eq!(encodings[0].encoding_type, ContentEncodingType::Compression);
+    let compression = encodings[0].compression.as_ref().unwrap();
+    assert_eq!(compression.algorithm, CompressionAlgorithm::HeaderStripping);
+    assert_eq!(compression.stripped_header(), Some(&[0x0B, 0x77][..]));
+    assert!(m.tracks[1].encodings.is_empty());
+}
+
+#[test]
+fn content_encryption() {
+    use matroska::{CipherMode, ContentEncodingType, EncryptionAlgorithm};
+
+    let key_id = [
+        0xEB, 0x67, 0x6A, 0xBB, 0xCB, 0x34, 0x5E, 0x96, 0xBB, 0xCF, 0x61, 0x66, 0x30, 0xF1, 0xA3,
+        0xDA,
+    ];
+    let data = common::file_of_type(
+        "webm",
+        &[common::master(
+            0x1654_AE6B,
+            &[common::track(
+                1,
+                1,
+                "V_VP9",
+                &[common::master(
+                    0x6D80,
+                    &[common::master(
+                        0x6240,
+                        &[
+                            common::uint(0x5033, 1),
+                            common::master(
+                                0x5035,
+                                &[
+                                    common::uint(0x47E1, 5),
+                                    common::element(0x47E2, &key_id),
+                                    common::master(0x47E7, &[common::uint(0x47E8, 1)]),
+                                ],
+                            ),
+                        ],
+                    )],
+                )],
+            )],
+        )],
+    );
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    let encoding = &m.tracks[0].encodings[0];
+    assert_eq!(encoding.encoding_type, ContentEncodingType::Encryption);
+    assert_eq!(encoding.compression, None);
+    let encryption = encoding.encryption.as_ref().unwrap();
+    assert_eq!(encryption.algorithm, EncryptionAlgorithm::AES);
+    assert_eq!(encryption.key_id, key_id.to_vec());
+    assert_eq!(encryption.cipher_mode, Some(CipherMode::CTR));
+}
+
+#[test]
+fn attachment_links() {
+    let attached = |uid, name: &str| {
+        common::master(
+            0x61A7,
+            &[
+                common::string(0x466E, name),
+                common::string(0x4660, "font/ttf"),
+                common::element(0x465C, b"font"),
+                common::uint(0x46AE, uid),
+            ],
+        )
+    };
+    let data = common::file(&[
+        common::master(
+            0x1654_AE6B,
+            &[
+                common::track(1, 0x11, "S_TEXT/ASS", &[common::uint(0x7446, 200)]),
+                common::track(2, 0x11, "S_TEXT/ASS", &[]),
+            ],
+        ),
+        common::master(
+            0x1941_A469,
+            &[attached(100, "regular.ttf"), attached(200, "bold.ttf")],
+        ),
+    ]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    assert_eq!(m.attachments[0].uid, 100);
+    assert_eq!(m.tracks[0].attachment_links, vec![200]);
+    assert!(m.tracks[1].attachment_links.is_empty());
+    let linked = m
+        .attachment_by_uid(m.tracks[0].attachment_links[0])
+        .unwrap();
+    assert_eq!(linked.name, "bold.ttf");
+    assert!(m.attachment_by_uid(0).is_none());
+    assert!(m.attachment_by_uid(300).is_none());
+}
+
+#[test]
+fn attachments_by_mime() {
+    let attached = |uid, name: &str, mime_type: &str| {
+        common::master(
+            0x61A7,
+            &[
+                common::string(0x466E, name),
+                common::string(0x4660, mime_type),
+                common::element(0x465C, b"data"),
+                common::uint(0x46AE, uid),
+            ],
+        )
+    };
+    let data = common::file(&[common::master(
+        0x1941_A469,
+        &[
+            attached(1, "regular.ttf", "application/x-truetype-font"),
+            attached(2, "cover.jpg", "image/jpeg"),
+            attached(3, "bold.otf", "Font/OTF"),
+            attached(4, "back.png", "IMAGE/PNG"),
+            attached(5, "symbols.ttf", "application/vnd.ms-opentype"),
+            attached(6, "notes.xml", "application/xml; charset=utf-8"),
+            attached(7, "fonts.txt", "text/plain"),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    let uids = |attachments: Vec<&matroska::Attachment>| {
+        attachments.iter().map(|a| a.uid).collect::<Vec<_>>()
+    };
+    assert_eq!(uids(m.attachments_by_mime("image/*").collect()), vec![2, 4]);
+    assert_eq!(uids(m.attachments_by_mime("image/png").collect()), vec![4]);
+    assert_eq!(
+        uids(m.attachments_by_mime("application/xml").collect()),
+        vec![6]
+    );
+    assert_eq!(uids(m.attachments_by_mime("*").collect()).len(), 7);
+    assert!(m.attachments_by_mime("image/").next().is_none());
+    assert_eq!(uids(m.font_attachments().collect()), vec![1, 3, 5]);
+}
+
+#[test]
+fn track_lookup() {
+    let language = |tag: &str| common::string(0x22_B59C, tag);
+    let language_ietf = |tag: &str| common::string(0x22_B59D, tag);
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[
+            common::track(1, 1, "V_MPEG4/ISO/AVC", &[language("und")]),
+            common::track(2, 2, "A_OPUS", &[language("eng")]),
+            common::track(3, 2, "A_OPUS", &[language("ger"), language_ietf("en-US")]),
+            common::track(4, 0x11, "S_TEXT/UTF8", &[language_ietf("EN")]),
+            common::track(5, 0x11, "S_TEXT/UTF8", &[]),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    assert_eq!(m.track_by_number(3).unwrap().uid, 3);
+    assert!(m.track_by_number(6).is_none());
+    assert_eq!(m.track_by_uid(4).unwrap().number, 4);
+    assert!(m.track_by_uid(0).is_none());
+
+    let numbers = |language| {
+        m.tracks_by_language(language)
+            .map(|t| t.number)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(numbers("eng"), vec![2, 3, 4]);
+    assert_eq!(numbers("en"), vec![2, 3, 4]);
+    assert_eq!(numbers("en-GB"), vec![2, 3, 4]);
+    assert_eq!(numbers("deu"), Vec::<u64>::new());
+    assert_eq!(numbers("und"), vec![1, 5]);
+}
+
+#[test]
+fn language_matching() {
+    use matroska::Language;
+
+    let ger = Language::ISO639("ger".to_owned());
+    assert_eq!(ger.primary_code(), "ger");
+    assert!(ger.matches("ger"));
+    assert!(ger.matches("DEU"));
+    assert!(ger.matches("de"));
+    assert!(ger.matches("de-CH"));
+    assert!(!ger.matches("dan"));
+    assert!(!ger.is_undetermined());
+
+    let pt_br = Language::IETF("pt-BR".to_owned());
+    assert_eq!(pt_br.primary_code(), "pt");
+    assert!(pt_br.matches("por"));
+    assert!(pt_br.matches("pt_PT"));
+    assert!(!pt_br.matches("spa"));
+
+    // codes without a mapping still match themselves
+    let tlh = Language::ISO639("tlh".to_owned());
+    assert!(tlh.matches("TLH"));
+    assert!(!tlh.matches("tl"));
+
+    assert!(Language::ISO639("und".to_owned()).is_undetermined());
+    assert!(Language::ISO639("zxx".to_owned()).is_undetermined());
+    assert!(Language::IETF("und-Latn".to_owned()).is_undetermined());
+}
+
+#[test]
+fn legacy_codec_fields() {
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[
+            common::track(
+                1,
+                1,
+                "V_MS/VFW/FOURCC",
+                &[
+                    common::string(0x3A_9697, "quality=high"),
+                    common::string(0x3B_4040, "http://example.com/info"),
+                    common::string(0x26_B240, "http://example.com/codec"),
+                    common::uint(0xAA, 0),
+                ],
+            ),
+            common::track(2, 2, "A_MPEG/L3", &[]),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    let legacy = &m.tracks[0];
+    assert_eq!(legacy.codec_settings.as_deref(), Some("quality=high"));
+    assert_eq!(
+        legacy.codec_info_url.as_deref(),
+        Some("http://example.com/info")
+    );
+    assert_eq!(
+        legacy.codec_download_url.as_deref(),
+        Some("http://example.com/codec")
+    );
+    assert_eq!(legacy.codec_decode_all, Some(false));
+    let modern = &m.tracks[1];
+    assert_eq!(modern.codec_settings, None);
+    assert_eq!(modern.codec_info_url, None);
+    assert_eq!(modern.codec_download_url, None);
+    assert_eq!(modern.codec_decode_all, None);
+}
+
+#[test]
+fn attachment_usage() {
+    let data = common::file(&[common::master(
+        0x1941_A469,
+        &[
+            common::master(
+                0x61A7,
+                &[
+                    common::string(0x466E, "preview.jpg"),
+                    common::string(0x4660, "image/jpeg"),
+                    common::element(0x465C, b"jpeg"),
+                    common::uint(0x46AE, 1),
+                    common::element(0x4675, &[0x01, 0x02]),
+                    common::uint(0x4661, 5_000_000_000),
+                    common::uint(0x4662, 10_000_000_000),
+                ],
+            ),
+            common::master(
+                0x61A7,
+                &[
+                    common::string(0x466E, "font.ttf"),
+                    common::string(0x4660, "font/ttf"),
+                    common::element(0x465C, b"font"),
+                    common::uint(0x46AE, 2),
+                ],
+            ),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    let scoped = &m.attachments[0];
+    assert_eq!(scoped.referral, Some(vec![0x01, 0x02]));
+    assert_eq!(scoped.used_start_time, Some(Duration::from_secs(5)));
+    assert_eq!(scoped.used_end_time, Some(Duration::from_secs(10)));
+    let ordinary = &m.attachments[1];
+    assert_eq!(ordinary.referral, None);
+    assert_eq!(ordinary.used_start_time, None);
+    assert_eq!(ordinary.used_end_time, None);
+}
+
+#[test]
+fn deferred_attachment_data() {
+    let attached = |name: &str, data: &[u8]| {
+        common::master(
+            0x61A7,
+            &[
+                common::string(0x466E, name),
+                common::string(0x4660, "application/octet-stream"),
+                common::element(0x465C, data),
+                common::uint(0x46AE, data.len() as u64),
+            ],
+        )
+    };
+    let data = common::file(&[
+        common::info("Attachments"),
+        common::master(
+            0x1941_A469,
+            &[
+                attached("small.bin", b"abc"),
+                attached("large.bin", &[7; 1000]),
+            ],
+        ),
+    ]);
+    let eager = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    let options = matroska::ParseOptions::default().with_defer_attachment_data(true);
+    let mut file = Cursor::new(&data);
+    let deferred = matroska::Matroska::open_with(&mut file, &options).unwrap();
+
+    assert_eq!(eager.attachments.len(), deferred.attachments.len());
+    for (eager, deferred) in eager.attachments.iter().zip(&deferred.attachments) {
+        assert!(deferred.data.is_empty());
+        assert_eq!(deferred.name, eager.name);
+        assert_eq!(deferred.data_offset, eager.data_offset);
+        assert_eq!(deferred.data_len, eager.data.len() as u64);
+        assert_eq!(deferred.read_data(&mut file).unwrap(), eager.data);
+        assert_eq!(eager.read_data(&mut file).unwrap(), eager.data);
+        let offset = eager.data_offset as usize;
+        assert_eq!(&data[offset..offset + eager.data.len()], &eager.data[..]);
+    }
+}
+
+#[test]
+fn constructed_attachment_data() {
+    use matroska::{Attachment, MetadataEdits};
+
+    // an attachment built rather than parsed, whose data_len is unset
+    let mut attachment = Attachment::default();
+    attachment.name = "cover.jpg".to_string();
+    attachment.mime_type = "image/jpeg".to_string();
+    attachment.data = b"jpeg data".to_vec();
+    attachment.uid = 1;
+
+    let mut empty = Cursor::new(Vec::new());
+    assert_eq!(attachment.read_data(&mut empty).unwrap(), b"jpeg data");
+    let mut written = Vec::new();
+    assert_eq!(attachment.write_data(&mut empty, &mut written).unwrap(), 9);
+    assert_eq!(written, b"jpeg data");
+
+    // and written out by remux as it is
+    let data = common::file(&[common::info("Attached")]);
+    let edits = MetadataEdits::default().with_attachments(vec![attachment]);
+    let mut remuxed = Cursor::new(Vec::new());
+    matroska::remux(Cursor::new(&data), &mut remuxed, edits).unwrap();
+    let m = matroska::Matroska::open(Cursor::new(remuxed.into_inner())).unwrap();
+    assert_eq!(m.attachments.len(), 1);
+    assert_eq!(m.attachments[0].data, b"jpeg data");
+}
+
+#[test]
+fn built_attachment() {
+    use matroska::Attachment;
+
+    let path = PathBuf::from("tests").join("samples").join("bbb.mkv");
+    let options = matroska::ParseOptions::default().with_defer_attachment_data(true);
+    let data = common::file(&[
+        common::info("Attached"),
+        common::master(
+            0x1941_A469,
+            &[common::master(
+                0x61A7,
+                &[
+                    common::string(0x466E, "old.bin"),
+                    common::string(0x4660, "application/octet-stream"),
+                    common::element(0x465C, b"old data"),
+                    common::uint(0x46AE, 7),
+                ],
+            )],
+        ),
+    ]);
+    let deferred = matroska::Matroska::open_with(Cursor::new(&data), &options).unwrap();
+
+    // replacing deferred data means it's no longer read from the file
+    let attachment = deferred.attachments[0]
+        .clone()
+        .with_name("cover.jpg")
+        .with_mime_type("image/jpeg")
+        .with_description("Cover")
+        .with_data(b"jpeg data".to_vec());
+    assert_eq!(attachment.name, "cover.jpg");
+    assert_eq!(attachment.description.as_deref(), Some("Cover"));
+    assert_eq!(attachment.uid, 7);
+    assert_eq!(attachment.data_len, 9);
+    let mut other = std::fs::File::open(&path).unwrap();
+    assert_eq!(attachment.read_data(&mut other).unwrap(), b"jpeg data");
+
+    let built = Attachment::default()
+        .with_name("cover.jpg")
+        .with_mime_type("image/jpeg")
+        .with_description("Cover")
+        .with_data(b"jpeg data".to_vec())
+        .with_uid(7);
+    assert_eq!(built, attachment);
+}
+
+#[test]
+fn skipped_sections() {
+    use matroska::ParseOptions;
+
+    // without a SeekHead, skipped sections are seeked past
+    let data = common::file(&[
+        common::info("Skipped"),
+        common::master(
+            0x1941_A469,
+            &[common::master(
+                0x61A7,
+                &[
+                    common::string(0x466E, "cover.jpg"),
+                    common::string(0x4660, "image/jpeg"),
+                    common::element(0x465C, &[0; 100]),
+                ],
+            )],
+        ),
+        common::master(0x1043_A770, &[common::edition(1, &[])]),
+    ]);
+    let options = ParseOptions::default().with_attachments(false);
+    let m = matroska::Matroska::open_with(Cursor::new(&data), &options).unwrap();
+    assert_eq!(m.info.title, Some("Skipped".into()));
+    assert!(m.attachments.is_empty());
+    assert_eq!(m.chapters.len(), 1);
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.attachments.len(), 1);
+
+    // and with one, they're never visited
+    let path = PathBuf::from("tests").join("samples").join("bbb.mkv");
+    let options = ParseOptions::default().with_tracks(false).with_tags(false);
+    let sequential = matroska::Matroska::open_with(File::open(&path).unwrap(), &options).unwrap();
+    assert!(sequential.tracks.is_empty());
+    assert!(sequential.tags.is_empty());
+    assert_eq!(sequential.info.title, Some("Big Buck Bunny".into()));
+    let parallel = matroska::open_parallel(&path, options).unwrap();
+    assert_eq!(format!("{parallel:?}"), format!("{sequential:?}"));
+}
+
+#[test]
+fn extracted_attachments() {
+    let attached = |name: &str, mime_type: &str, data: &[u8]| {
+        common::master(
+            0x61A7,
+            &[
+                common::string(0x466E, name),
+                common::string(0x4660, mime_type),
+                common::element(0x465C, data),
+            ],
+        )
+    };
+    let font: Vec<u8> = (0..20_000).map(|i| i as u8).collect();
+    let data = common::file(&[
+        common::info("Fonts"),
+        common::master(
+            0x1941_A469,
+            &[
+                attached("cover.jpg", "image/jpeg", b"jpeg"),
+                attached("sans.ttf", "font/ttf", &font),
+            ],
+        ),
+    ]);
+    let dir = std::env::temp_dir();
+    let mut names = Vec::new();
+    let count = matroska::extract_attachments(Cursor::new(&data), |attachment| {
+        names.push(attachment.name.clone());
+        assert!(attachment.data.is_empty());
+        if attachment.mime_type == "font/ttf" {
+            File::create(dir.join(format!("extracted-{}", attachment.name))).map(Some)
+        } else {
+            Ok(None)
+        }
+    })
+    .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(names, vec!["cover.jpg", "sans.ttf"]);
+    let path = dir.join("extracted-sans.ttf");
+    let extracted = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(extracted, font);
+}
+
+#[test]
+fn linked_filenames() {
+    let data = common::file(&[common::master(
+        0x1549_A966,
+        &[
+            common::uint(0x2A_D7B1, 1_000_000),
+            common::element(0x3C_B923, &[1; 16]),
+            common::string(0x7384, "capture-002.mkv"),
+            common::string(0x3C_83AB, "capture-001.mkv"),
+            common::string(0x3E_83BB, "capture-003.mkv"),
+        ],
+    )]);
+    let info = matroska::Matroska::open(Cursor::new(data)).unwrap().info;
+    assert_eq!(info.filename.as_deref(), Some("capture-002.mkv"));
+    assert_eq!(info.prev_filename.as_deref(), Some("capture-001.mkv"));
+    assert_eq!(info.next_filename.as_deref(), Some("capture-003.mkv"));
+    assert_eq!(info.prev_uid, Some(vec![1; 16]));
+    assert_eq!(info.next_uid, None);
+}
+
+#[test]
+fn ebml_header() {
+    let f = File::open(PathBuf::from("tests").join("samples").join("bbb.mkv")).unwrap();
+    let matroska = matroska::Matroska::open(f).unwrap();
+    let header = matroska.ebml_header.as_ref().unwrap();
+    assert_eq!(header.doc_type, "matroska");
+    assert_eq!(header.read_version, 1);
+    assert_eq!(header.max_id_length, 4);
+    assert_eq!(header.max_size_length, 8);
+    assert!(!matroska.is_webm());
+
+    let data = common::file_of_type("webm", &[common::info("clip")]);
+    let matroska = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    assert!(matroska.is_webm());
+    assert_eq!(
+        matroska
+            .to_flat_map()
+            .get("ebml.doc_type")
+            .map(String::as_str),
+        Some("webm")
+    );
+}
+
+#[test]
+fn unknown_size_segment() {
+    let mut data = common::ebml_header("webm");
+    data.extend(common::unknown_size(
+        0x1853_8067,
+        &[
+            common::info("Live"),
+            common::master(0x1654_AE6B, &[common::track(1, 1, "V_VP9", &[])]),
+            common::unknown_size(
+                0x1F43_B675,
+                &[
+                    common::uint(0xE7, 0),
+                    common::simple_block(1, 0, 0x80, &[], &[0; 16]),
+                ],
+            ),
+            common::unknown_size(
+                0x1F43_B675,
+                &[
+                    common::uint(0xE7, 1000),
+                    common::simple_block(1, 0, 0x80, &[], &[0; 16]),
+                ],
+            ),
+        ],
+    ));
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Live"));
+    assert_eq!(m.tracks.len(), 1);
+    assert_eq!(m.tracks[0].codec_id, "V_VP9");
+
+    // a recording killed partway through its final Cluster
+    data.truncate(data.len() - 10);
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Live"));
+    assert_eq!(m.tracks.len(), 1);
+}
+
+#[test]
+fn nested_unknown_sizes() {
+    let file = |voids: usize| {
+        let mut data = common::ebml_header("webm");
+        data.extend(common::unknown_size(
+            0x1853_8067,
+            &[[0xEC, 0xFF].repeat(voids), common::info("Nested")],
+        ));
+        data
+    };
+
+    // a few unknown-size Voids, each running to the Info
+    let m = matroska::Matroska::open(Cursor::new(file(3))).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Nested"));
+
+    // and millions of them, each nested within the last
+    let data = file(2_000_000);
+    let nested = |err: matroska::MatroskaError| {
+        matches!(
+            err.root(),
+            matroska::MatroskaError::NestingTooDeep { id: 0xEC }
+        )
+    };
+    assert!(nested(
+        matroska::get::<_, matroska::Info>(Cursor::new(&data)).unwrap_err()
+    ));
+    assert!(nested(
+        matroska::Matroska::open(Cursor::new(&data)).unwrap_err()
+    ));
+    let info = matroska::Info::default();
+    let mut file = Cursor::new(&data);
+    let mut clusters = matroska::clusters(&mut file, &info).unwrap();
+    assert!(clusters.any(|c| c.err().is_some_and(nested)));
+    assert!(nested(
+        matroska::check_mse_compat(Cursor::new(&data)).unwrap_err()
+    ));
+}
+
+#[test]
+fn misplaced_seekhead_entry() {
+    // a SeekHead whose Info entry points at the Tracks instead
+    let seek = |id: u32, position: u64| {
+        common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(id)),
+                common::uint(0x53AC, position),
+            ],
+        )
+    };
+    let seekhead_len =
+        common::master(0x114D_9B74, &[seek(0x1549_A966, 0), seek(0x1654_AE6B, 0)]).len() as u64;
+    let info = common::info("Misplaced");
+    let tracks_position = seekhead_len + info.len() as u64;
+    let seekhead = common::master(
+        0x114D_9B74,
+        &[
+            seek(0x1549_A966, tracks_position),
+            seek(0x1654_AE6B, tracks_position),
+        ],
+    );
+    let tracks = common::master(0x1654_AE6B, &[common::track(1, 1, "V_VP9", &[])]);
+    let tracks_len = tracks.len() as u64;
+    let data = common::file(&[seekhead, info, tracks]);
+
+    // the misplaced Info is found by scanning instead
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Misplaced"));
+    assert_eq!(m.tracks.len(), 1);
+
+    match matroska::get::<_, matroska::Info>(Cursor::new(&data)) {
+        Err(matroska::MatroskaError::UnexpectedElement {
+            expected,
+            found,
+            offset,
+        }) => {
+            assert_eq!(expected, 0x1549_A966);
+            assert_eq!(found, 0x1654_AE6B);
+            assert_eq!(offset, data.len() as u64 - tracks_len);
+        }
+        other => panic!("unexpected result {:?}", other.map(|i| i.is_some())),
+    }
+}
+
+#[test]
+fn sections_missing_from_seekhead() {
+    let seek = |id: u32, position: u64| {
+        common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(id)),
+                common::uint(0x53AC, position),
+            ],
+        )
+    };
+    let seekhead_len = common::master(0x114D_9B74, &[seek(0x1549_A966, 0)]).len() as u64;
+    let seekhead = common::master(0x114D_9B74, &[seek(0x1549_A966, seekhead_len)]);
+    // Tags appended after the clusters without updating the SeekHead
+    let tags = common::master(
+        0x1254_C367,
+        &[common::tag(
+            &[],
+            &[common::simple_tag("TITLE", "Appended", None)],
+        )],
+    );
+    let data = common::file(&[
+        seekhead,
+        common::info("Listed"),
+        common::cluster(0, &[common::simple_block(1, 0, 0x80, &[], &[0; 4])]),
+        tags,
+    ]);
+
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Listed"));
+    assert_eq!(m.tags.len(), 1);
+    assert_eq!(m.tags[0].simple[0].name, "TITLE");
+
+    let tags = matroska::get::<_, matroska::Tag>(Cursor::new(&data)).unwrap();
+    assert_eq!(tags.map(|t| t.len()), Some(1));
+}
+
+#[test]
+fn get_many() {
+    use matroska::{Attachment, Info, Tag, Track};
+
+    let seek = |id: u32, position: u64| {
+        common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(id)),
+                common::uint(0x53AC, position),
+            ],
+        )
+    };
+    let info = common::info("Many");
+    let seekhead_len = common::master(0x114D_9B74, &[seek(0x1549_A966, 0)]).len();
+    let seekhead = common::master(0x114D_9B74, &[seek(0x1549_A966, seekhead_len as u64)]);
+    let tracks = common::master(0x1654_AE6B, &[common::track(1, 1, "V_VP9", &[])]);
+    let tags = common::master(
+        0x1254_C367,
+        &[common::tag(
+            &[],
+            &[common::simple_tag("TITLE", "Many", None)],
+        )],
+    );
+
+    // with a SeekHead listing only some of the sections
+    let data = common::file(&[seekhead, info.clone(), tracks.clone(), tags.clone()]);
+    let (i, t, g, a) =
+        matroska::get_many::<_, (Info, Track, Tag, Attachment)>(Cursor::new(&data)).unwrap();
+    assert_eq!(i.unwrap().title.as_deref(), Some("Many"));
+    assert_eq!(t.map(|t| t.len()), Some(1));
+    assert_eq!(g.map(|g| g.len()), Some(1));
+    assert!(a.is_none());
+
+    // and without any SeekHead
+    let data = common::file(&[tracks, common::info("Scanned")]);
+    let (i, t, g) = matroska::get_many::<_, (Info, Track, Tag)>(Cursor::new(&data)).unwrap();
+    assert_eq!(i.unwrap().title.as_deref(), Some("Scanned"));
+    assert_eq!(t.map(|t| t.len()), Some(1));
+    assert!(g.is_none());
+}
+
+#[test]
+fn public_seek_head() {
+    let seek = |id: u32, position: u64| {
+        common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(id)),
+                common::uint(0x53AC, position),
+            ],
+        )
+    };
+    // a SeekHead chained to a second one after the Info
+    let seekhead_len =
+        common::master(0x114D_9B74, &[seek(0x1549_A966, 0), seek(0x114D_9B74, 0)]).len();
+    let info = common::info("Chained");
+    let chained_position = (seekhead_len + info.len()) as u64;
+    let seekhead = common::master(
+        0x114D_9B74,
+        &[
+            seek(0x1549_A966, seekhead_len as u64),
+            seek(0x114D_9B74, chained_position),
+        ],
+    );
+    let tracks = common::master(0x1654_AE6B, &[common::track(1, 1, "V_VP9", &[])]);
+    let chained_len = common::master(0x114D_9B74, &[seek(0x1654_AE6B, 0)]).len() as u64;
+    let chained = common::master(
+        0x114D_9B74,
+        &[seek(0x1654_AE6B, chained_position + chained_len)],
+    );
+    let data = common::file(&[seekhead, info, chained, tracks]);
+    let segment_start = (common::ebml_header("matroska").len() + 4 + 8) as u64;
+
+    let seek_head = matroska::SeekHead::read(Cursor::new(&data))
+        .unwrap()
+        .unwrap();
+    assert_eq!(seek_head.segment_start, segment_start);
+    assert_eq!(seek_head.entries.len(), 2);
+    assert!(seek_head.contains(0x1654_AE6B));
+    assert!(!seek_head.contains(0x1043_A770));
+    assert_eq!(
+        seek_head.offsets(0x1654_AE6B).unwrap(),
+        vec![segment_start + chained_position + chained_len]
+    );
+
+    // on its own, only the first SeekHead's entries are parsed
+    let first = matroska::get::<_, matroska::SeekHead>(Cursor::new(&data))
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.segment_start, 0);
+    assert!(first.contains(0x114D_9B74));
+    assert!(!first.contains(0x1654_AE6B));
+
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.tracks.len(), 1);
+    assert_eq!(m.seek_head, Some(seek_head));
+
+    let data = common::file(&[common::info("Unlisted")]);
+    assert_eq!(matroska::SeekHead::read(Cursor::new(&data)).unwrap(), None);
+}
+
+#[test]
+fn repeated_seekhead_entries() {
+    let seek = |id: u32, position: u64| {
+        common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(id)),
+                common::uint(0x53AC, position),
+            ],
+        )
+    };
+    let tags = |title: &str| {
+        common::master(
+            0x1254_C367,
+            &[common::tag(
+                &[],
+                &[common::simple_tag("TITLE", title, None)],
+            )],
+        )
+    };
+    let info = common::info("Split");
+    let before = tags("Before");
+    let cluster = common::cluster(0, &[common::simple_block(1, 0, 0x80, &[], &[0; 4])]);
+    let seekhead_len = common::master(
+        0x114D_9B74,
+        &[
+            seek(0x1549_A966, 0),
+            seek(0x1254_C367, 0),
+            seek(0x1254_C367, 0),
+        ],
+    )
+    .len();
+    let info_position = seekhead_len;
+    let before_position = info_position + info.len();
+    let after_position = before_position + before.len() + cluster.len();
+    let seekhead = common::master(
+        0x114D_9B74,
+        &[
+            seek(0x1549_A966, info_position as u64),
+            seek(0x1254_C367, before_position as u64),
+            seek(0x1254_C367, after_position as u64),
+        ],
+    );
+    let data = common::file(&[seekhead, info, before, cluster, tags("After")]);
+
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    let titles: Vec<_> = m.tags.iter().map(|t| t.simple[0].value.clone()).collect();
+    assert_eq!(
+        titles,
+        vec![
+            Some(matroska::TagValue::String("Before".to_string())),
+            Some(matroska::TagValue::String("After".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn repeated_sections() {
+    let tags = |title: &str| {
+        common::master(
+            0x1254_C367,
+            &[common::tag(
+                &[],
+                &[common::simple_tag("TITLE", title, None)],
+            )],
+        )
+    };
+    let cluster = common::cluster(0, &[common::simple_block(1, 0, 0x80, &[], &[0; 4])]);
+    let data = common::file(&[
+        common::info("First"),
+        tags("Before"),
+        cluster,
+        tags("After"),
+        common::info("Second"),
+    ]);
+    let titles = |m: &matroska::Matroska| -> Vec<_> {
+        m.tags.iter().map(|t| t.simple[0].value.clone()).collect()
+    };
+    let expected = vec![
+        Some(matroska::TagValue::String("Before".to_string())),
+        Some(matroska::TagValue::String("After".to_string())),
+    ];
+
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(titles(&m), expected);
+    assert_eq!(m.info.title.as_deref(), Some("First"));
+
+    let (m, errors) = matroska::Matroska::open_lossy(Cursor::new(&data));
+    assert_eq!(titles(&m), expected);
+    match errors.as_slice() {
+        [matroska::MatroskaError::DuplicateElement { id, offset }] => {
+            assert_eq!(*id, 0x1549_A966);
+            assert_eq!(*offset as usize, data.len() - common::info("Second").len());
+        }
+        other => panic!("unexpected errors {:?}", other),
+    }
+
+    // an incomplete Info gives way to a complete one
+    let incomplete = common::master(0x1549_A966, &[common::string(0x7BA9, "Incomplete")]);
+    let data = common::file(&[incomplete.clone(), common::info("Complete")]);
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Complete"));
+    // and nothing complete was discarded
+    let (m, errors) = matroska::Matroska::open_lossy(Cursor::new(&data));
+    assert_eq!(m.info.title.as_deref(), Some("Complete"));
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+
+    // but a second incomplete Info is
+    let data = common::file(&[incomplete.clone(), incomplete]);
+    let (m, errors) = matroska::Matroska::open_lossy(Cursor::new(&data));
+    assert_eq!(m.info.title.as_deref(), Some("Incomplete"));
+    assert!(matches!(
+        errors.as_slice(),
+        [matroska::MatroskaError::DuplicateElement {
+            id: 0x1549_A966,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn get_skips_unread() {
+    use std::io::{self, Read, Seek, SeekFrom};
+
+    // a reader which counts the bytes read from it
+    struct Counted<'a>(Cursor<&'a [u8]>, u64);
+    impl Read for Counted<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.0.read(buf)?;
+            self.1 += read as u64;
+            Ok(read)
+        }
+    }
+    impl Seek for Counted<'_> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+    let title = |data: &[u8]| {
+        let mut file = Counted(Cursor::new(data), 0);
+        let info = matroska::get::<_, matroska::Info>(&mut file).unwrap();
+        (info.and_then(|i| i.title), file.1)
+    };
+
+    // a large attachment before the Info, with no SeekHead
+    let attachments = common::master(
+        0x1941_A469,
+        &[common::master(
+            0x61A7,
+            &[
+                common::string(0x466E, "large.bin"),
+                common::element(0x465C, &vec![0; 1 << 20]),
+                common::uint(0x46AE, 1),
+            ],
+        )],
+    );
+    let data = common::file(&[attachments, common::info("Skipped")]);
+    let (found, read) = title(&data);
+    assert_eq!(found.as_deref(), Some("Skipped"));
+    assert!(read < 1024, "read {} bytes", read);
+
+    // and a SeekHead padded with a large unknown element
+    let seek = |position: u64| {
+        common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(0x1549_A966)),
+                common::uint(0x53AC, position),
+            ],
+        )
+    };
+    let padding = common::element(0x5FFF, &vec![0; 1 << 20]);
+    let seekhead_len = common::master(0x114D_9B74, &[padding.clone(), seek(0)]).len();
+    let seekhead = common::master(0x114D_9B74, &[padding, seek(seekhead_len as u64)]);
+    let data = common::file(&[seekhead, common::info("Sought")]);
+    let (found, read) = title(&data);
+    assert_eq!(found.as_deref(), Some("Sought"));
+    assert!(read < 1024, "read {} bytes", read);
+
+    let seek_head = matroska::SeekHead::read(Cursor::new(&data))
+        .unwrap()
+        .unwrap();
+    assert_eq!(seek_head.entries.len(), 1);
+    assert_eq!(seek_head.entries[0].position, seekhead_len as u64);
+}
+
+#[test]
+fn unusable_seekhead() {
+    let seek = |id: u32, position: u64| {
+        common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(id)),
+                common::uint(0x53AC, position),
+            ],
+        )
+    };
+    // placeholder positions which were never filled in
+    let seekhead = common::master(
+        0x114D_9B74,
+        &[seek(0x1549_A966, u64::MAX), seek(0x1654_AE6B, 1 << 40)],
+    );
+    let tracks = common::master(0x1654_AE6B, &[common::track(1, 1, "V_VP9", &[])]);
+    let data = common::file(&[seekhead, common::info("Unclean"), tracks]);
+
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Unclean"));
+    assert_eq!(m.tracks.len(), 1);
+
+    let info = matroska::get::<_, matroska::Info>(Cursor::new(&data)).unwrap();
+    assert_eq!(info.unwrap().title.as_deref(), Some("Unclean"));
+
+    let (m, errors) = matroska::Matroska::open_lossy(Cursor::new(&data));
+    assert_eq!(m.tracks.len(), 1);
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0].root(),
+        matroska::MatroskaError::InvalidSeekHead { .. }
+    ));
+}
+
+#[test]
+fn oversized_child_element() {
+    fn info_with_title_size(declared: u64) -> Vec<u8> {
+        let title = [
+            common::id(0x7BA9),
+            common::size(declared),
+            b"Oversized".to_vec(),
+        ]
+        .concat();
+        common::file(&[common::element(0x1549_A966, &title)])
+    }
+
+    for declared in [b"Oversized".len() as u64 + 1, 1 << 50].iter() {
+        let err = matroska::Matroska::open(Cursor::new(info_with_title_size(*declared)))
+            .map(|m| m.info)
+            .unwrap_err();
+        match err.root() {
+            matroska::MatroskaError::OversizedChildElement {
+                id,
+                size,
+                parent_remaining,
+            } => {
+                assert_eq!(*id, 0x7BA9);
+                assert_eq!(size, declared);
+                assert_eq!(*parent_remaining, 2 + 8 + 9);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn segment_size_mismatch() {
+    let info = common::info("Mismatched");
+    let tracks = common::master(0x1654_AE6B, &[common::track(1, 1, "V_VP9", &[])]);
+    let cluster = common::cluster(0, &[common::simple_block(1, 0, 0x80, &[], &[0; 64])]);
+    let body = [info.clone(), tracks.clone(), cluster].concat();
+
+    // a Segment declared one byte short of its Tracks' end
+    let mut short = common::ebml_header("matroska");
+    short.extend(common::id(0x1853_8067));
+    short.extend(common::size((info.len() + tracks.len() - 1) as u64));
+    short.extend(&body);
+    let m = matroska::Matroska::open(Cursor::new(short)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Mismatched"));
+
+    // a recording truncated partway through its Cluster
+    let full = common::file(std::slice::from_ref(&body));
+    let mut truncated = full.clone();
+    truncated.truncate(full.len() - 32);
+    let m = matroska::Matroska::open(Cursor::new(&truncated)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Mismatched"));
+    assert_eq!(m.tracks.len(), 1);
+    assert!(matroska::get::<_, matroska::Info>(Cursor::new(&truncated))
+        .unwrap()
+        .is_some());
+
+    // a recording truncated partway through the Tracks' header
+    let mut truncated = full.clone();
+    truncated.truncate(full.len() - body.len() + info.len() + 2);
+    let m = matroska::Matroska::open(Cursor::new(&truncated)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Mismatched"));
+    assert!(m.tracks.is_empty());
+}
+
+#[test]
+fn element_too_large() {
+    // an Info whose SegmentUID claims to be 8 GiB
+    let declared = 8 << 30;
+    let uid = [common::id(0x73A4), common::size(declared), vec![0; 16]].concat();
+    let mut data = common::ebml_header("matroska");
+    data.extend(common::id(0x1853_8067));
+    data.extend(common::size(declared + 64));
+    data.extend(common::id(0x1549_A966));
+    data.extend(common::size(declared + 32));
+    data.extend(uid);
+
+    let err = matroska::Matroska::open(Cursor::new(&data))
+        .map(|m| m.info)
+        .unwrap_err();
+    match err.root() {
+        matroska::MatroskaError::ElementTooLarge { id, size, limit } => {
+            assert_eq!(*id, 0x73A4);
+            assert_eq!(*size, declared);
+            assert_eq!(*limit, 64 << 20);
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+
+    // with the limit lifted, the short read is caught without allocating it all
+    let options = matroska::ParseOptions::default().with_max_element_size(u64::MAX);
+    let err = matroska::Matroska::open_with(Cursor::new(&data), &options)
+        .map(|m| m.info)
+        .unwrap_err();
+    match err.root() {
+        matroska::MatroskaError::Io(err) => {
+            assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof)
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+
+    // a limit below an attachment's size rejects it
+    let attachments = common::master(
+        0x1941_A469,
+        &[common::master(
+            0x61A7,
+            &[
+                common::string(0x466E, "cover.jpg"),
+                common::string(0x4660, "image/jpeg"),
+                common::element(0x465C, &[0; 1024]),
+            ],
+        )],
+    );
+    let data = common::file(&[attachments]);
+    let options = matroska::ParseOptions::default().with_max_element_size(1000);
+    assert!(matches!(
+        matroska::Matroska::open_with(Cursor::new(&data), &options)
+            .map(|m| m.info)
+            .unwrap_err()
+            .root(),
+        matroska::MatroskaError::ElementTooLarge { id: 0x465C, .. }
+    ));
+    let options = options.with_defer_attachment_data(true);
+    let m = matroska::Matroska::open_with(Cursor::new(&data), &options).unwrap();
+    assert_eq!(m.attachments[0].data_len, 1024);
+}
+
+#[test]
+fn large_unknown_elements() {
+    // custom elements a muxer has put inside a TrackEntry and a Tag
+    let unknown = common::element(0x5FFF, &vec![0; 1 << 20]);
+    let tracks = common::master(
+        0x1654_AE6B,
+        &[common::track(1, 1, "V_VP9", std::slice::from_ref(&unknown))],
+    );
+    let tags = common::master(
+        0x1254_C367,
+        &[common::tag(
+            &[],
+            &[unknown, common::simple_tag("TITLE", "Unknown", Some("eng"))],
+        )],
+    );
+    let data = common::file(&[common::info("Unknown"), tracks, tags]);
+
+    // are passed over without changing what's parsed
+    let skipped = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(skipped.tracks[0].codec_id, "V_VP9");
+    assert_eq!(skipped.tags[0].simple[0].name, "TITLE");
+    let options = matroska::ParseOptions::default().with_max_unknown_size(u64::MAX);
+    let kept = matroska::Matroska::open_with(Cursor::new(&data), &options).unwrap();
+    assert_eq!(skipped, kept);
+}
+
+#[test]
+fn extreme_dates() {
+    for date in [i64::MIN, -1, 0, i64::MAX].iter() {
+        let data = common::file(&[common::master(
+            0x1549_A966,
+            &[
+                common::uint(0x2A_D7B1, 1_000_000),
+                common::int(0x4461, *date),
+            ],
+        )]);
+        let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+        assert_eq!(m.info.date_utc.clone().map(i64::from), Some(*date));
+        // every date is within SystemTime's range
+        let _ = m.info.date_utc.as_ref().unwrap().to_system_time();
+        assert_eq!(
+            m.to_flat_map().get("info.date_utc"),
+            Some(&date.to_string())
+        );
+    }
+
+    let date = |nanos: i64| {
+        let data = common::file(&[common::master(0x1549_A966, &[common::int(0x4461, nanos)])]);
+        let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+        SystemTime::from(m.info.date_utc.unwrap())
+    };
+    let epoch = SystemTime::UNIX_EPOCH + Duration::from_secs(978_307_200);
+    assert_eq!(date(0), epoch);
+    assert_eq!(date(1_500), epoch + Duration::from_nanos(1_500));
+    assert_eq!(
+        date(-86_400_000_000_000),
+        epoch - Duration::from_secs(86_400)
+    );
+
+    // DateUTC is always 8 bytes
+    let data = common::file(&[common::master(
+        0x1549_A966,
+        &[common::element(0x4461, &[0xFF; 4])],
+    )]);
+    assert!(matches!(
+        matroska::Matroska::open(Cursor::new(data))
+            .map(|m| m.info)
+            .unwrap_err()
+            .root(),
+        matroska::MatroskaError::InvalidDate
+    ));
+}
+
+#[test]
+fn located_errors() {
+    // a Tracks whose TrackEntry has an invalid element ID partway through
+    let info = common::info("Located");
+    let entry = [common::uint(0xD7, 1), vec![0x00, 0x81, 0x00]].concat();
+    let tracks = common::master(0x1654_AE6B, &[common::element(0xAE, &entry)]);
+    let data = common::file(&[info.clone(), tracks]);
+    let tracks_start = data.len() - 2 * (4 + 8) - entry.len();
+
+    let err = matroska::Matroska::open(Cursor::new(&data))
+        .map(|m| m.info)
+        .unwrap_err();
+    match &err {
+        matroska::MatroskaError::Located {
+            offset,
+            element,
+            error,
+        } => {
+            assert!(matches!(**error, matroska::MatroskaError::InvalidID));
+            assert_eq!(*element, Some(0x1654_AE6B));
+            // just past the invalid ID's first byte
+            assert_eq!(*offset as usize, data.len() - 2);
+            assert!(*offset as usize > tracks_start);
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(err.offset(), Some(data.len() as u64 - 2));
+    assert!(err.to_string().contains("inside element 0x1654AE6B"));
+}
+
+#[test]
+fn error_variants() {
+    use std::error::Error;
+
+    fn open_info(children: &[Vec<u8>]) -> Result<matroska::Info, matroska::Error> {
+        let data = common::file(&[common::master(0x1549_A966, children)]);
+        matroska::Matroska::open(Cursor::new(data)).map(|m| m.info)
+    }
+
+    // a 9 byte TimestampScale
+    let err = open_info(&[common::element(0x2A_D7B1, &[0; 9])]).unwrap_err();
+    assert!(matches!(
+        err.root(),
+        matroska::MatroskaError::InvalidIntegerWidth { size: 9 }
+    ));
+    assert!(err.source().is_some());
+
+    // a 2 byte Duration
+    let err = open_info(&[common::element(0x4489, &[0; 2])]).unwrap_err();
+    assert!(matches!(
+        err.root(),
+        matroska::MatroskaError::InvalidFloatWidth { size: 2 }
+    ));
+
+    // a Title with an unknown size
+    let title = [common::id(0x7BA9), vec![0xFF]].concat();
+    let err = open_info(&[title]).unwrap_err();
+    assert!(matches!(
+        err.root(),
+        matroska::MatroskaError::UnknownSizeElement { id: 0x7BA9 }
+    ));
+
+    // an empty Duration is zero
+    let info = open_info(&[common::element(0x4489, &[])]).unwrap();
+    assert_eq!(info.duration, Some(Duration::ZERO));
+}
+
+#[test]
+fn crc32_elements() {
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in data {
+            crc ^= u32::from(*byte);
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+
+    fn info_with_crc(title: &str, corrupt: bool) -> Vec<u8> {
+        let body = [
+            common::uint(0x2A_D7B1, 1_000_000),
+            common::string(0x4D80, "test"),
+            common::string(0x5741, "test"),
+            common::string(0x7BA9, title),
+        ]
+        .concat();
+        let mut crc = crc32(&body);
+        if corrupt {
+            crc ^= 1;
+        }
+        common::master(
+            0x1549_A966,
+            &[common::element(0xBF, &crc.to_le_bytes()), body],
+        )
+    }
+
+    let verify = matroska::ParseOptions::default().with_verify_crc(true);
+
+    // a matching CRC-32 is skipped, with or without verification
+    let data = common::file(&[info_with_crc("Checked", false)]);
+    for options in [matroska::ParseOptions::default(), verify.clone()] {
+        let m = matroska::Matroska::open_with(Cursor::new(&data), &options).unwrap();
+        assert_eq!(m.info.title.as_deref(), Some("Checked"));
+    }
+
+    // a mismatched CRC-32 is only an error when verifying
+    let info = info_with_crc("Corrupt", true);
+    let data = common::file(std::slice::from_ref(&info));
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.info.title.as_deref(), Some("Corrupt"));
+    match matroska::Matroska::open_with(Cursor::new(&data), &verify) {
+        Err(matroska::MatroskaError::ChecksumMismatch { element_id, offset }) => {
+            assert_eq!(element_id, 0x1549_A966);
+            // the CRC-32 begins the Info's body, just past its header
+            assert_eq!(offset as usize, data.len() - (info.len() - (4 + 8)));
+        }
+        other => panic!("unexpected result {:?}", other.map(|m| m.info)),
+    }
+
+    // CRC-32 elements never reach generic element lists
+    let tag = common::tag(
+        &[],
+        &[
+            common::element(0xBF, &[0; 4]),
+            common::simple_tag("TITLE", "Tagged", None),
+        ],
+    );
+    let data = common::file(&[common::info("Tags"), common::master(0x1254_C367, &[tag])]);
+    let m = matroska::Matroska::open(Cursor::new(&data)).unwrap();
+    assert_eq!(m.tags[0].simple.len(), 1);
+}
+
+#[test]
+fn lossy_parsing() {
+    let tracks = common::master(0x1654_AE6B, &[common::track(1, 1, "V_VP9", &[])]);
+    // a Tags section with an invalid element ID partway through
+    let tags = common::master(
+        0x1254_C367,
+        &[
+            common::tag(&[], &[common::simple_tag("TITLE", "Lost", None)]),
+            vec![0x00, 0x81, 0x00],
+        ],
+    );
+    let data = common::file(&[common::info("Lossy"), tags, tracks]);
+
+    assert!(matroska::Matroska::open(Cursor::new(&data)).is_err());
+
+    let (m, errors) = matroska::Matroska::open_lossy(Cursor::new(&data));
+    assert_eq!(m.info.title.as_deref(), Some("Lossy"));
+    assert_eq!(m.tracks.len(), 1);
+    assert!(m.tags.is_empty());
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0].root(),
+        matroska::MatroskaError::InvalidID
+    ));
+    assert!(errors[0].offset().is_some());
+
+    // a truncated final section
+    let mut data = common::file(&[
+        common::info("Truncated"),
+        common::master(0x1254_C367, &[common::tag(&[], &[])]),
+    ]);
+    data.truncate(data.len() - 2);
+    let (m, errors) = matroska::Matroska::open_lossy(Cursor::new(&data));
+    assert_eq!(m.info.title.as_deref(), Some("Truncated"));
+    assert_eq!(errors.len(), 1);
+
+    // without a Segment there's nothing to parse
+    let (m, errors) = matroska::Matroska::open_lossy(Cursor::new(common::ebml_header("matroska")));
+    assert!(m.tracks.is_empty());
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn element_names() {
+    use matroska::ids;
+
+    assert_eq!(ids::name(ids::SEGMENT), Some("Segment"));
+    assert_eq!(ids::name(ids::TRACKENTRY), Some("TrackEntry"));
+    assert_eq!(ids::name(ids::TIMECODESCALE), Some("TimestampScale"));
+    assert_eq!(ids::name(0xAE), Some("TrackEntry"));
+    assert_eq!(ids::name(0), None);
+
+    // errors carrying IDs can be described by name
+    let entry = [common::uint(ids::TRACKNUMBER, 1), vec![0x00, 0x81, 0x00]].concat();
+    let tracks = common::master(ids::TRACKS, &[common::element(ids::TRACKENTRY, &entry)]);
+    let data = common::file(&[common::info("Named"), tracks]);
+    match matroska::Matroska::open(Cursor::new(&data)) {
+        Err(matroska::MatroskaError::Located {
+            element: Some(id), ..
+        }) => assert_eq!(ids::name(id), Some("Tracks")),
+        other => panic!("unexpected result {:?}", other.map(|m| m.info)),
+    }
+}
+
+#[test]
+fn display_names() {
+    use matroska::Language;
+
+    let names = [
+        (Tracktype::Video, "video"),
+        (Tracktype::Audio, "audio"),
+        (Tracktype::Complex, "complex"),
+        (Tracktype::Logo, "logo"),
+        (Tracktype::Subtitle, "subtitle"),
+        (Tracktype::Buttons, "buttons"),
+        (Tracktype::Control, "control"),
+        (Tracktype::Unknown, "unknown"),
+    ];
+    for (tracktype, name) in names {
+        assert_eq!(tracktype.as_str(), name);
+        assert_eq!(tracktype.to_string(), name);
+    }
+
+    let iso = Language::ISO639("eng".to_owned());
+    assert_eq!(iso.as_str(), "eng");
+    assert_eq!(iso.to_string(), "eng");
+    assert_eq!(format!("{iso:#}"), "eng (ISO 639-2)");
+
+    let ietf = Language::IETF("en-US".to_owned());
+    assert_eq!(ietf.as_str(), "en-US");
+    assert_eq!(ietf.to_string(), "en-US");
+    assert_eq!(format!("{ietf:#}"), "en-US (BCP 47)");
+}
+
+#[test]
+fn deferred_entries_skip_void() {
+    // Void padding inside entries is seeked past whether or not it fits the limits
+    let void = common::element(0xEC, &[0; 2000]);
+    let tracks = common::master(
+        0x1654_AE6B,
+        &[common::track(
+            1,
+            1,
+            "V_VP9",
+            &[common::element(0x63A2, b"private"), void.clone()],
+        )],
+    );
+    let attachments = common::master(
+        0x1941_A469,
+        &[common::master(
+            0x61A7,
+            &[
+                common::string(0x466E, "cover.jpg"),
+                void,
+                common::string(0x4660, "image/jpeg"),
+                common::element(0x465C, b"data"),
+            ],
+        )],
+    );
+    let data = common::file(&[common::info("Void"), tracks, attachments]);
+    let options = matroska::ParseOptions::default()
+        .with_max_element_size(1000)
+        .with_defer_codec_private(true)
+        .with_defer_attachment_data(true);
+    let mut file = Cursor::new(&data);
+    let m = matroska::Matroska::open_with(&mut file, &options).unwrap();
+    assert_eq!(m.tracks.len(), 1);
+    assert_eq!(m.tracks[0].codec_id, "V_VP9");
+    assert_eq!(
+        m.tracks[0].read_codec_private(&mut file).unwrap(),
+        Some(b"private".to_vec())
+    );
+    assert_eq!(m.attachments.len(), 1);
+    assert_eq!(m.attachments[0].mime_type, "image/jpeg");
+    assert_eq!(m.attachments[0].read_data(&mut file).unwrap(), b"data");
+}
@@ -0,0 +1,156 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+
+use matroska::{MseProblem, MseViolation};
+
+mod common;
+
+fn tracks(video_codec: &str) -> Vec<u8> {
+    common::master(
+        0x1654_AE6B,
+        &[
+            common::track(1, 1, video_codec, &[]),
+            common::track(2, 2, "A_OPUS", &[]),
+        ],
+    )
+}
+
+fn cluster(video_flags: u8) -> Vec<u8> {
+    common::cluster(
+        0,
+        &[
+            common::simple_block(2, 0, 0x80, &[], &[0; 4]),
+            common::simple_block(1, 0, video_flags, &[], &[0; 4]),
+            // later blocks needn't be keyframes
+            common::simple_block(1, 40, 0x00, &[], &[0; 4]),
+        ],
+    )
+}
+
+fn check(data: Vec<u8>) -> Option<MseViolation> {
+    matroska::check_mse_compat(Cursor::new(data))
+        .unwrap()
+        .violation
+}
+
+/// The file offset of a Segment's child, given its preceding siblings
+fn offset(preceding: &[&[u8]]) -> u64 {
+    // the EBML header, then the Segment's 4 byte ID and 8 byte size
+    (common::ebml_header("webm").len() + 12 + preceding.iter().map(|p| p.len()).sum::<usize>())
+        as u64
+}
+
+#[test]
+fn compatible() {
+    let data = common::file_of_type(
+        "webm",
+        &[
+            common::info("MSE"),
+            tracks("V_VP9"),
+            cluster(0x80),
+            cluster(0x80),
+        ],
+    );
+    let report = matroska::check_mse_compat(Cursor::new(data)).unwrap();
+    assert!(report.is_compatible());
+}
+
+#[test]
+fn doc_type() {
+    let data = common::file(&[common::info("MSE"), tracks("V_VP9"), cluster(0x80)]);
+    let violation = check(data).unwrap();
+    assert_eq!(violation.offset, 0);
+    assert_eq!(violation.problem, MseProblem::DocType("matroska".into()));
+}
+
+#[test]
+fn cluster_before_init() {
+    let info = common::info("MSE");
+    let first = cluster(0x80);
+    let data = common::file_of_type("webm", &[info.clone(), first.clone(), tracks("V_VP9")]);
+    let violation = check(data).unwrap();
+    assert_eq!(violation.offset, offset(&[&info]));
+    assert_eq!(violation.problem, MseProblem::ClusterBeforeInit);
+}
+
+#[test]
+fn unsupported_codec() {
+    let info = common::info("MSE");
+    let data = common::file_of_type("webm", &[info.clone(), tracks("V_MPEG4/ISO/AVC")]);
+    let violation = check(data).unwrap();
+    // the first TrackEntry, just inside the Tracks element's header
+    assert_eq!(violation.offset, offset(&[&info]) + 12);
+    assert_eq!(
+        violation.problem,
+        MseProblem::UnsupportedCodec {
+            track: 1,
+            codec_id: "V_MPEG4/ISO/AVC".into(),
+        }
+    );
+}
+
+#[test]
+fn cluster_without_keyframe() {
+    let info = common::info("MSE");
+    let tracks = tracks("V_VP8");
+    let first = cluster(0x80);
+    let data = common::file_of_type(
+        "webm",
+        &[info.clone(), tracks.clone(), first.clone(), cluster(0)],
+    );
+    let violation = check(data).unwrap();
+    assert_eq!(violation.offset, offset(&[&info, &tracks, &first]));
+    assert_eq!(
+        violation.problem,
+        MseProblem::ClusterWithoutKeyframe { track: 1 }
+    );
+}
+
+#[test]
+fn unknown_size_in_block_group() {
+    // BlockAdditions of unknown size, running to the end of its BlockGroup
+    let info = common::info("MSE");
+    let tracks = tracks("V_VP9");
+    let first = common::cluster(
+        0,
+        &[
+            common::simple_block(2, 0, 0x80, &[], &[0; 4]),
+            common::master(
+                0xA0,
+                &[
+                    common::element(0xA1, &common::block_body(1, 0, 0x00, &[], &[0; 4])),
+                    common::unknown_size(
+                        0x75A1,
+                        &[common::master(
+                            0xA6,
+                            &[common::uint(0xEE, 1), common::element(0xA5, &[0; 4])],
+                        )],
+                    ),
+                ],
+            ),
+        ],
+    );
+    let data = common::file_of_type(
+        "webm",
+        &[info.clone(), tracks.clone(), first.clone(), cluster(0x80)],
+    );
+    // a BlockGroup without a ReferenceBlock is a keyframe
+    assert_eq!(check(data.clone()), None);
+
+    let data = common::file_of_type(
+        "webm",
+        &[info.clone(), tracks.clone(), first.clone(), cluster(0)],
+    );
+    let violation = check(data).unwrap();
+    assert_eq!(violation.offset, offset(&[&info, &tracks, &first]));
+    assert_eq!(
+        violation.problem,
+        MseProblem::ClusterWithoutKeyframe { track: 1 }
+    );
+}
@@ -0,0 +1,130 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+use std::time::Duration;
+
+use matroska::{MatroskaError, TrackPackets};
+
+mod common;
+
+// "hello hello hello hello zlib" with fixed Huffman codes
+const FIXED: [u8; 20] = [
+    120, 156, 203, 72, 205, 201, 201, 87, 200, 192, 32, 171, 114, 50, 147, 0, 152, 120, 10, 130,
+];
+
+// "stored" without compression
+const STORED: [u8; 17] = [
+    120, 1, 1, 6, 0, 249, 255, 115, 116, 111, 114, 101, 100, 9, 60, 2, 146,
+];
+
+// two pangrams with dynamic Huffman codes
+const DYNAMIC: [u8; 85] = [
+    120, 218, 181, 203, 199, 1, 128, 32, 16, 5, 209, 86, 126, 5, 212, 226, 193, 6, 64, 73, 6, 86,
+    178, 80, 189, 219, 132, 231, 121, 179, 58, 141, 88, 253, 118, 66, 37, 234, 1, 134, 94, 28, 245,
+    126, 50, 168, 233, 132, 194, 249, 146, 115, 96, 39, 43, 176, 254, 134, 23, 201, 238, 30, 80,
+    140, 186, 47, 14, 198, 55, 205, 105, 234, 128, 203, 199, 74, 137, 95, 155, 197, 7, 178, 251,
+    63, 13,
+];
+
+fn tracks() -> Vec<u8> {
+    let zlib = common::master(
+        0x6D80,
+        &[common::master(
+            0x6240,
+            &[common::master(0x5034, &[common::uint(0x4254, 0)])],
+        )],
+    );
+    common::master(
+        0x1654_AE6B,
+        &[
+            // 20 ms of audio per frame
+            common::track(1, 2, "A_OPUS", &[common::uint(0x23_E383, 20_000_000), zlib]),
+            common::track(2, 1, "V_MPEG4/ISO/AVC", &[]),
+        ],
+    )
+}
+
+#[test]
+fn track_packets() {
+    let info = common::info("Packets");
+    let tracks = tracks();
+    let first = common::cluster(
+        0,
+        &[
+            common::simple_block(1, 0, 0x80, &[], &FIXED),
+            common::simple_block(2, 0, 0x80, &[], &[0; 100]),
+            // fixed-size lacing, two frames
+            common::simple_block(1, 20, 0x84, &[1], &[STORED, STORED].concat()),
+        ],
+    );
+    let second = common::cluster(
+        1000,
+        &[
+            common::simple_block(1, 100, 0x00, &[], &STORED),
+            common::simple_block(1, 50, 0x80, &[], &DYNAMIC),
+        ],
+    );
+    let second_position = (info.len() + tracks.len() + first.len()) as u64;
+    let cues = common::master(
+        0x1C53_BB6B,
+        &[
+            common::cue_point(0, 1, info.len() as u64 + tracks.len() as u64, None),
+            common::cue_point(1000, 1, second_position, None),
+        ],
+    );
+    let data = common::file(&[info, tracks, first, second, cues]);
+
+    let packets = TrackPackets::new(Cursor::new(&data), 1)
+        .unwrap()
+        .map(|packet| packet.map(|p| (p.pts, p.keyframe, p.data)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let ms = Duration::from_millis;
+    assert_eq!(
+        packets,
+        vec![
+            (ms(0), true, b"hello hello hello hello zlib".to_vec()),
+            (ms(20), true, b"stored".to_vec()),
+            (ms(40), true, b"stored".to_vec()),
+            (
+                ms(1050),
+                true,
+                [
+                    &b"The quick brown fox jumps over the lazy dog. "[..].repeat(3),
+                    &b"Pack my box with five dozen liquor jugs."[..],
+                ]
+                .concat()
+            ),
+            (ms(1100), false, b"stored".to_vec()),
+        ]
+    );
+
+    let mut packets = TrackPackets::new(Cursor::new(&data), 1).unwrap();
+    assert_eq!(packets.track().codec_id, "A_OPUS");
+    assert!(packets.seek(Duration::from_millis(1500)).unwrap());
+    assert_eq!(packets.next().unwrap().unwrap().pts, ms(1050));
+    assert_eq!(packets.count(), 1);
+
+    assert!(matches!(
+        TrackPackets::new(Cursor::new(&data), 3),
+        Err(MatroskaError::UnknownTrack { number: 3 })
+    ));
+}
+
+#[test]
+fn invalid_compressed_data() {
+    let data = common::file(&[
+        tracks(),
+        common::cluster(0, &[common::simple_block(1, 0, 0x80, &[], &FIXED[..10])]),
+    ]);
+    let mut packets = TrackPackets::new(Cursor::new(data), 1).unwrap();
+    assert!(matches!(
+        packets.next(),
+        Some(Err(MatroskaError::InvalidCompressedData { track: 1 }))
+    ));
+}
@@ -0,0 +1,217 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+mod common;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("matroska-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn open_with_options() {
+    let mut options = OpenOptions::new();
+    options.read(true);
+    let m = matroska::open_with_file_options(
+        PathBuf::from("tests").join("samples").join("bbb.mkv"),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(m.info.title, Some("Big Buck Bunny".into()));
+}
+
+/// A stream of the given pieces at their offsets, reading as zeros
+/// everywhere else, so large files need no backing data
+struct Sparse {
+    pieces: Vec<(u64, Vec<u8>)>,
+    len: u64,
+    position: u64,
+}
+
+impl Read for Sparse {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = (self.len.saturating_sub(self.position)).min(buf.len() as u64) as usize;
+        let buf = &mut buf[..len];
+        buf.iter_mut().for_each(|b| *b = 0);
+        for (offset, piece) in &self.pieces {
+            let end = offset + piece.len() as u64;
+            let start = self.position.max(*offset);
+            let stop = end.min(self.position + len as u64);
+            if start < stop {
+                buf[(start - self.position) as usize..(stop - self.position) as usize]
+                    .copy_from_slice(&piece[(start - offset) as usize..(stop - offset) as usize]);
+            }
+        }
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl Seek for Sparse {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+        };
+        self.position = position.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+        Ok(self.position)
+    }
+}
+
+#[test]
+fn large_file() {
+    // a SeekHead pointing at an Info element beyond the 4 GiB mark
+    const GAP: u64 = (1 << 32) + 4096;
+
+    let info = common::info("Large");
+    let void_header = [common::id(0xEC), common::size(GAP)].concat();
+    let seek_len = common::master(
+        0x114D_9B74,
+        &[common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(0x1549_A966)),
+                common::uint(0x53AC, 0),
+            ],
+        )],
+    )
+    .len() as u64;
+    let info_position = seek_len + void_header.len() as u64 + GAP;
+    let seekhead = common::master(
+        0x114D_9B74,
+        &[common::master(
+            0x4DBB,
+            &[
+                common::element(0x53AB, &common::id(0x1549_A966)),
+                common::uint(0x53AC, info_position),
+            ],
+        )],
+    );
+    let segment_size = info_position + info.len() as u64;
+
+    let head = [
+        common::ebml_header("matroska"),
+        common::id(0x1853_8067),
+        common::size(segment_size),
+        seekhead,
+        void_header,
+    ]
+    .concat();
+    let info_offset = head.len() as u64 + GAP;
+    let file = Sparse {
+        len: info_offset + info.len() as u64,
+        pieces: vec![(0, head), (info_offset, info)],
+        position: 0,
+    };
+
+    let m = matroska::Matroska::open(file).unwrap();
+    assert_eq!(m.info.title, Some("Large".into()));
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8_name() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::env::temp_dir().join(OsStr::from_bytes(b"matroska-\xFF\xFE.mkv"));
+    std::fs::write(&path, common::file(&[common::info("Bytes")])).unwrap();
+    let result = matroska::open(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(result.unwrap().info.title, Some("Bytes".into()));
+}
+
+#[cfg(windows)]
+#[test]
+fn unc_path() {
+    let path = temp_path("unc.mkv");
+    std::fs::write(&path, common::file(&[common::info("UNC")])).unwrap();
+    // canonicalize yields a verbatim \\?\ path on Windows
+    let unc = std::fs::canonicalize(&path).unwrap();
+    let result = matroska::open(&unc);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(result.unwrap().info.title, Some("UNC".into()));
+}
+
+#[cfg(windows)]
+#[test]
+fn open_while_writing() {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const FILE_SHARE_READ: u32 = 0x1;
+    const FILE_SHARE_WRITE: u32 = 0x2;
+    const FILE_SHARE_DELETE: u32 = 0x4;
+
+    let path = temp_path("live.mkv");
+    let mut writer = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE)
+        .open(&path)
+        .unwrap();
+    writer
+        .write_all(&common::file(&[common::info("Live")]))
+        .unwrap();
+    writer.flush().unwrap();
+
+    let mut options = OpenOptions::new();
+    options
+        .read(true)
+        .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE);
+    let result = matroska::open_with_file_options(&path, &options);
+    drop(writer);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(result.unwrap().info.title, Some("Live".into()));
+}
+
+#[test]
+fn linked_segments() {
+    fn segment(uid: u8, prev: Option<u8>, next: Option<u8>) -> Vec<u8> {
+        let mut info = vec![
+            common::uint(0x2A_D7B1, 1_000_000),
+            common::element(0x73A4, &[uid; 16]),
+        ];
+        info.extend(prev.map(|prev| common::element(0x3C_B923, &[prev; 16])));
+        info.extend(next.map(|next| common::element(0x3E_B923, &[next; 16])));
+        common::file(&[common::master(0x1549_A966, &info)])
+    }
+
+    let dir = temp_path("linked");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.mkv"), segment(1, None, Some(2))).unwrap();
+    std::fs::write(dir.join("b.mkv"), segment(2, Some(1), Some(3))).unwrap();
+    std::fs::write(dir.join("c.mkv"), segment(3, Some(2), None)).unwrap();
+    std::fs::write(dir.join("d.mkv"), segment(4, Some(3), Some(5))).unwrap();
+    std::fs::write(dir.join("notes.txt"), b"not a segment").unwrap();
+
+    let chain = matroska::open_linked(dir.join("b.mkv"));
+    let mut resolver = matroska::SegmentResolver::new();
+    assert_eq!(resolver.add_dir(&dir).unwrap(), 4);
+    let broken = resolver.resolve(dir.join("d.mkv"));
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let names = chain
+        .unwrap()
+        .iter()
+        .map(|(path, _)| path.file_name().unwrap().to_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["a.mkv", "b.mkv", "c.mkv"]);
+
+    // 3 (c.mkv) is resolved, but nothing has the next UID 5,
+    // and following c.mkv backward leads to the start of the chain
+    match broken {
+        Err(matroska::MatroskaError::UnresolvedSegments { uids }) => {
+            assert_eq!(uids, vec![vec![5; 16]])
+        }
+        other => panic!("unexpected result {:?}", other.map(|c| c.len())),
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::{Cursor, Read};
+
+use matroska::{ids, ElementReader, ElementType, MatroskaError};
+
+mod common;
+
+#[test]
+fn walk_elements() {
+    let tracks = common::master(ids::TRACKS, &[common::track(1, 1, "V_VP9", &[])]);
+    let attachment = common::master(
+        ids::ATTACHMENTS,
+        &[common::master(
+            ids::ATTACHEDFILE,
+            &[common::element(ids::FILEDATA, &[7; 1000])],
+        )],
+    );
+    let data = common::file(&[common::info("Walked"), tracks, attachment]);
+    let mut reader = ElementReader::new(Cursor::new(&data)).unwrap();
+
+    // the EBML header is skipped unread
+    let header = reader.next_element().unwrap().unwrap();
+    assert_eq!(header.id, ids::EBML);
+    assert_eq!(header.offset, 0);
+
+    let segment = reader.next_element().unwrap().unwrap();
+    assert_eq!(segment.id, ids::SEGMENT);
+    assert_eq!(segment.end(), Some(data.len() as u64));
+    reader.children().unwrap();
+    assert_eq!(reader.depth(), 1);
+
+    // an Info parsed whole
+    let info = reader.next_element().unwrap().unwrap();
+    assert_eq!(info.id, ids::INFO);
+    match reader.read_body().unwrap() {
+        ElementType::Master(children) => assert_eq!(children.len(), 4),
+        other => panic!("unexpected body {:?}", other),
+    }
+
+    // a TrackEntry's children one at a time
+    assert_eq!(reader.next_element().unwrap().unwrap().id, ids::TRACKS);
+    reader.children().unwrap();
+    assert_eq!(reader.next_element().unwrap().unwrap().id, ids::TRACKENTRY);
+    reader.children().unwrap();
+    assert_eq!(reader.next_element().unwrap().unwrap().id, ids::TRACKNUMBER);
+    assert!(matches!(reader.read_body().unwrap(), ElementType::UInt(1)));
+    assert_eq!(reader.next_element().unwrap().unwrap().id, ids::TRACKUID);
+    assert_eq!(reader.read_raw().unwrap().len(), 8);
+    // and the rest of the Tracks skipped
+    reader.skip().unwrap();
+    reader.skip().unwrap();
+    assert_eq!(reader.depth(), 1);
+
+    // attachment data streamed, without needing to read it all
+    assert_eq!(reader.next_element().unwrap().unwrap().id, ids::ATTACHMENTS);
+    reader.children().unwrap();
+    assert_eq!(
+        reader.next_element().unwrap().unwrap().id,
+        ids::ATTACHEDFILE
+    );
+    reader.children().unwrap();
+    let data_header = reader.next_element().unwrap().unwrap();
+    assert_eq!(data_header.size, Some(1000));
+    let mut start = [0; 10];
+    reader.raw_reader().unwrap().read_exact(&mut start).unwrap();
+    assert_eq!(start, [7; 10]);
+
+    // then each level ends in turn
+    assert!(reader.next_element().unwrap().is_none());
+    assert!(reader.next_element().unwrap().is_none());
+    assert!(reader.next_element().unwrap().is_none());
+    assert_eq!(reader.depth(), 0);
+    assert!(reader.next_element().unwrap().is_none());
+    assert_eq!(reader.position(), data.len() as u64);
+}
+
+#[test]
+fn reader_checks_sizes() {
+    // a Title claiming more than its Info holds
+    let title = [common::id(ids::TITLE), common::size(100), b"Short".to_vec()].concat();
+    let data = common::file(&[common::element(ids::INFO, &title)]);
+    let mut reader = ElementReader::new(Cursor::new(&data)).unwrap();
+    reader.next_element().unwrap();
+    reader.next_element().unwrap();
+    reader.children().unwrap();
+    reader.next_element().unwrap();
+    reader.children().unwrap();
+    assert!(matches!(
+        reader.next_element(),
+        Err(MatroskaError::OversizedChildElement { id: ids::TITLE, .. })
+    ));
+
+    // bodies beyond the limit can be skipped but not read
+    let data = common::file(&[common::element(ids::VOID, &[0; 100])]);
+    let mut reader = ElementReader::new(Cursor::new(&data))
+        .unwrap()
+        .with_max_element_size(10);
+    reader.next_element().unwrap();
+    reader.next_element().unwrap();
+    reader.children().unwrap();
+    reader.next_element().unwrap();
+    assert!(matches!(
+        reader.read_raw(),
+        Err(MatroskaError::ElementTooLarge { size: 100, .. })
+    ));
+    assert!(reader.next_element().unwrap().is_none());
+
+    // descending needs an element to descend into
+    let mut reader = ElementReader::new(Cursor::new(&data)).unwrap();
+    assert!(reader.children().is_err());
+}
+
+#[test]
+fn large_unknown_bodies() {
+    let info = common::master(
+        ids::INFO,
+        &[
+            common::string(ids::TITLE, "Unknown"),
+            common::element(0x5FFF, &[0; 100]),
+        ],
+    );
+    let data = common::file(&[info]);
+    let read_info = |max_unknown_size| {
+        let mut reader = ElementReader::new(Cursor::new(&data))
+            .unwrap()
+            .with_max_unknown_size(max_unknown_size);
+        reader.next_element().unwrap();
+        reader.next_element().unwrap();
+        reader.children().unwrap();
+        reader.next_element().unwrap();
+        match reader.read_body().unwrap() {
+            ElementType::Master(children) => children,
+            other => panic!("unexpected body {:?}", other),
+        }
+    };
+
+    // an unknown body past the limit is left where it is
+    let children = read_info(10);
+    assert!(matches!(children[0].val, ElementType::UTF8(ref title) if title == "Unknown"));
+    match children[1].val {
+        ElementType::Skipped { offset, size } => {
+            assert_eq!(offset, data.len() as u64 - 100);
+            assert_eq!(size, 100);
+        }
+        ref other => panic!("unexpected body {:?}", other),
+    }
+
+    // and one within it read as usual
+    let children = read_info(100);
+    assert!(matches!(children[1].val, ElementType::Binary(ref body) if body.len() == 100));
+}
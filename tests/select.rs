@@ -0,0 +1,112 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+use matroska::Matroska;
+
+mod common;
+
+fn open(tracks: &[Vec<u8>]) -> Matroska {
+    let data = common::file(&[common::master(0x1654_AE6B, tracks)]);
+    Matroska::open(Cursor::new(data)).unwrap()
+}
+
+fn candidate(m: &Matroska, lang: &str) -> Option<u64> {
+    matroska::burn_in_candidate(m, lang).map(|t| t.number)
+}
+
+#[test]
+fn burn_in_forced_flag() {
+    let m = open(&[
+        common::track(1, 2, "A_OPUS", &[common::string(0x22_B59C, "eng")]),
+        common::subtitle_track(2, "eng", true, false, None),
+        common::subtitle_track(3, "eng", false, true, None),
+        common::subtitle_track(4, "fre", false, true, None),
+    ]);
+    assert_eq!(candidate(&m, "eng"), Some(3));
+    assert_eq!(candidate(&m, "ENG"), Some(3));
+    assert_eq!(candidate(&m, "fre"), Some(4));
+    // full subtitles in the audio language are never burned in
+    assert_eq!(candidate(&m, "ger"), None);
+}
+
+#[test]
+fn burn_in_forced_name() {
+    let m = open(&[
+        common::subtitle_track(1, "eng", true, false, Some("English")),
+        common::subtitle_track(2, "eng", false, false, Some("English (Forced)")),
+    ]);
+    assert_eq!(candidate(&m, "eng"), Some(2));
+}
+
+#[test]
+fn burn_in_full_subtitles_only() {
+    let m = open(&[common::subtitle_track(1, "eng", true, false, None)]);
+    assert_eq!(candidate(&m, "eng"), None);
+}
+
+#[test]
+fn burn_in_default_flag_abuse() {
+    // forced subtitles marked default instead of forced
+    let m = open(&[
+        common::subtitle_track(1, "eng", false, false, None),
+        common::subtitle_track(2, "eng", true, false, None),
+    ]);
+    assert_eq!(candidate(&m, "eng"), None);
+
+    let counts = BTreeMap::from([(1, 1200), (2, 35)]);
+    assert_eq!(
+        matroska::burn_in_candidate_with_counts(&m, "eng", &counts).map(|t| t.number),
+        Some(2)
+    );
+
+    // similarly-sized tracks are both full subtitles
+    let counts = BTreeMap::from([(1, 1200), (2, 1100)]);
+    assert!(matroska::burn_in_candidate_with_counts(&m, "eng", &counts).is_none());
+}
+
+#[test]
+fn default_tracks() {
+    let flags = |default: bool, forced: bool, enabled: bool| {
+        vec![
+            common::uint(0x88, default as u64),
+            common::uint(0x55AA, forced as u64),
+            common::uint(0xB9, enabled as u64),
+        ]
+    };
+    let m = open(&[
+        common::track(1, 1, "V_VP9", &flags(false, false, true)),
+        common::track(2, 1, "V_VP9", &flags(false, false, true)),
+        common::track(3, 2, "A_OPUS", &flags(true, true, false)),
+        common::track(4, 2, "A_OPUS", &flags(true, false, true)),
+        common::track(5, 2, "A_OPUS", &flags(false, true, true)),
+        common::subtitle_track(6, "eng", false, false, None),
+        common::subtitle_track(7, "eng", true, false, None),
+        common::subtitle_track(8, "eng", false, true, None),
+        common::subtitle_track(9, "fre", true, true, None),
+    ]);
+    // no flags, so the first of its type
+    assert_eq!(m.default_video_track().map(|t| t.number), Some(1));
+    // forced over default, ignoring disabled tracks
+    assert_eq!(m.default_audio_track().map(|t| t.number), Some(5));
+    assert_eq!(m.default_subtitle_track().map(|t| t.number), Some(8));
+    assert_eq!(m.forced_subtitle_track("eng").map(|t| t.number), Some(8));
+    assert_eq!(m.forced_subtitle_track("en").map(|t| t.number), Some(8));
+    assert_eq!(m.forced_subtitle_track("fre").map(|t| t.number), Some(9));
+    assert_eq!(m.forced_subtitle_track("ger"), None);
+
+    // subtitles without flags are left off
+    let m = open(&[
+        common::track(1, 2, "A_OPUS", &flags(false, false, true)),
+        common::subtitle_track(2, "eng", false, false, None),
+    ]);
+    assert_eq!(m.default_audio_track().map(|t| t.number), Some(1));
+    assert_eq!(m.default_subtitle_track(), None);
+    assert_eq!(m.default_video_track(), None);
+}
@@ -0,0 +1,197 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+use std::time::Duration;
+
+use matroska::MatroskaError;
+
+mod common;
+
+fn tracks() -> Vec<u8> {
+    common::master(
+        0x1654_AE6B,
+        &[
+            // 20 ms of audio per frame
+            common::track(1, 2, "A_OPUS", &[common::uint(0x23_E383, 20_000_000)]),
+            common::track(2, 1, "V_MPEG4/ISO/AVC", &[]),
+        ],
+    )
+}
+
+#[test]
+fn lacing_statistics() {
+    let data = common::file(&[
+        common::info("Laced"),
+        tracks(),
+        common::cluster(
+            0,
+            &[
+                // no lacing
+                common::simple_block(1, 0, 0x80, &[], &[0; 8]),
+                // Xiph lacing, frames of 300, 10 and 5 bytes
+                common::simple_block(1, 20, 0x82, &[2, 255, 45, 10], &[0; 315]),
+                // EBML lacing, frames of 100, 90, 95 and 7 bytes
+                common::simple_block(1, 80, 0x86, &[3, 0xE4, 0xB5, 0xC4], &[0; 292]),
+                // fixed-size lacing, two 10 byte frames
+                common::simple_block(1, 160, 0x84, &[1], &[0; 20]),
+                common::master(
+                    0xA0,
+                    &[
+                        common::element(0xA1, &common::block_body(2, 0, 0, &[], &[0; 50])),
+                        common::uint(0x9B, 40),
+                    ],
+                ),
+            ],
+        ),
+    ]);
+
+    let statistics = matroska::lacing_statistics(Cursor::new(data)).unwrap();
+    let audio = &statistics[&1];
+    assert_eq!(
+        audio.frames_per_block.iter().collect::<Vec<_>>(),
+        vec![(&1, &1), (&2, &1), (&3, &1), (&4, &1)]
+    );
+    assert_eq!(audio.max_block_duration, Some(Duration::from_millis(80)));
+    let video = &statistics[&2];
+    assert_eq!(
+        video.frames_per_block.iter().collect::<Vec<_>>(),
+        vec![(&1, &1)]
+    );
+    assert_eq!(video.max_block_duration, None);
+}
+
+#[test]
+fn invalid_lacing() {
+    // Xiph lace sizes adding up to more than the payload
+    let data = common::file(&[
+        tracks(),
+        common::cluster(0, &[common::simple_block(1, 0, 0x82, &[1, 200], &[0; 100])]),
+    ]);
+    // located at their Cluster, as clusters() reports them
+    assert!(matches!(
+        matroska::lacing_statistics(Cursor::new(data))
+            .unwrap_err()
+            .root(),
+        MatroskaError::InvalidLacing
+    ));
+
+    // fixed-size lacing that doesn't divide the payload evenly
+    let data = common::file(&[
+        tracks(),
+        common::cluster(0, &[common::simple_block(1, 0, 0x84, &[2], &[0; 10])]),
+    ]);
+    assert!(matches!(
+        matroska::lacing_statistics(Cursor::new(data))
+            .unwrap_err()
+            .root(),
+        MatroskaError::InvalidLacing
+    ));
+}
+
+#[test]
+fn unknown_size_clusters() {
+    let mut data = common::ebml_header("matroska");
+    data.extend(common::unknown_size(
+        0x1853_8067,
+        &[
+            common::info("Live"),
+            tracks(),
+            common::unknown_size(
+                0x1F43_B675,
+                &[
+                    common::uint(0xE7, 0),
+                    common::simple_block(1, 0, 0x80, &[], &[0; 8]),
+                ],
+            ),
+            common::unknown_size(
+                0x1F43_B675,
+                &[
+                    common::uint(0xE7, 20),
+                    common::simple_block(1, 0, 0x82, &[1, 4], &[0; 8]),
+                ],
+            ),
+        ],
+    ));
+
+    let statistics = matroska::lacing_statistics(Cursor::new(data)).unwrap();
+    assert_eq!(
+        statistics[&1].frames_per_block.iter().collect::<Vec<_>>(),
+        vec![(&1, &1), (&2, &1)]
+    );
+}
+
+#[test]
+fn track_sizes() {
+    let clusters: Vec<Vec<u8>> = (0..4)
+        .map(|i| {
+            common::cluster(
+                i * 1000,
+                &[
+                    common::simple_block(1, 0, 0x80, &[], &[0; 100]),
+                    common::simple_block(1, 500, 0x80, &[], &[0; 150]),
+                    common::simple_block(2, 0, 0x80, &[], &[0; 1000]),
+                ],
+            )
+        })
+        .collect();
+    let mut children = vec![common::info("Sizes"), tracks()];
+    children.extend(clusters);
+    let data = common::file(&children);
+
+    let usage = matroska::track_sizes(Cursor::new(&data)).unwrap();
+    let audio = &usage[&1];
+    assert_eq!(audio.bytes, 1000);
+    assert_eq!(audio.packets, 8);
+    assert_eq!(audio.first, Some(Duration::ZERO));
+    assert_eq!(audio.last, Some(Duration::from_millis(3500)));
+    assert_eq!(audio.bits_per_second(), Some(8000.0 / 3.5));
+    let video = &usage[&2];
+    assert_eq!(video.bytes, 4000);
+    assert_eq!(video.packets, 4);
+    assert_eq!(video.bits_per_second(), Some(32000.0 / 3.0));
+
+    // only the first and third Clusters are read
+    let usage = matroska::track_sizes_sampled(Cursor::new(&data), 2).unwrap();
+    let audio = &usage[&1];
+    assert_eq!(audio.bytes, 1000);
+    assert_eq!(audio.packets, 8);
+    assert_eq!(audio.last, Some(Duration::from_millis(2500)));
+    assert_eq!(usage[&2].bytes, 4000);
+}
+
+#[test]
+fn unknown_size_in_block_group() {
+    // BlockAdditions of unknown size, running to the end of its BlockGroup,
+    // as Clusters are read by clusters()
+    let data = common::file(&[
+        tracks(),
+        common::cluster(
+            0,
+            &[common::master(
+                0xA0,
+                &[
+                    common::element(0xA1, &common::block_body(1, 0, 0x06, &[1, 4], &[0; 8])),
+                    common::unknown_size(
+                        0x75A1,
+                        &[common::master(
+                            0xA6,
+                            &[common::uint(0xEE, 1), common::element(0xA5, &[0; 4])],
+                        )],
+                    ),
+                ],
+            )],
+        ),
+        common::cluster(1000, &[common::simple_block(1, 0, 0x80, &[], &[0; 8])]),
+    ]);
+
+    let statistics = matroska::lacing_statistics(Cursor::new(data)).unwrap();
+    assert_eq!(
+        statistics[&1].frames_per_block.iter().collect::<Vec<_>>(),
+        vec![(&1, &1), (&2, &1)]
+    );
+}
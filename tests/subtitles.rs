@@ -0,0 +1,116 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+use std::time::Duration;
+
+use matroska::MatroskaError;
+
+mod common;
+
+/// A BlockGroup with a Block and BlockDuration
+fn block_group(track: u8, timestamp: i16, duration: u64, data: &[u8]) -> Vec<u8> {
+    common::master(
+        0xA0,
+        &[
+            common::element(0xA1, &common::block_body(track, timestamp, 0, &[], data)),
+            common::uint(0x9B, duration),
+        ],
+    )
+}
+
+#[test]
+fn srt_subtitles() {
+    let data = common::file(&[
+        common::info("Subtitles"),
+        common::master(
+            0x1654_AE6B,
+            &[
+                common::track(1, 2, "A_OPUS", &[]),
+                common::subtitle_track(2, "eng", true, false, None),
+            ],
+        ),
+        common::cluster(
+            0,
+            &[
+                common::simple_block(1, 0, 0x80, &[], &[0; 8]),
+                block_group(2, 1500, 1000, b"Hello"),
+            ],
+        ),
+        common::cluster(
+            3000,
+            &[
+                block_group(2, 500, 2250, b"World\r\nagain"),
+                // no duration, so shown until the end of the previous one
+                common::simple_block(2, -1000, 0x80, &[], b"Between"),
+            ],
+        ),
+    ]);
+
+    let entries = matroska::extract_subtitles(Cursor::new(&data), 2).unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].start, Duration::from_millis(1500));
+    assert_eq!(entries[0].duration, Some(Duration::from_secs(1)));
+    assert_eq!(entries[0].text, "Hello");
+    assert_eq!(entries[1].start, Duration::from_secs(2));
+    assert_eq!(entries[1].duration, None);
+    assert_eq!(entries[2].end(), Some(Duration::from_millis(5750)));
+
+    assert_eq!(
+        matroska::to_srt(&entries),
+        "1\n00:00:01,500 --> 00:00:02,500\nHello\n\n\
+         2\n00:00:02,000 --> 00:00:03,500\nBetween\n\n\
+         3\n00:00:03,500 --> 00:00:05,750\nWorld\nagain\n\n"
+    );
+
+    assert!(matches!(
+        matroska::extract_subtitles(Cursor::new(&data), 3),
+        Err(MatroskaError::UnknownTrack { number: 3 })
+    ));
+}
+
+#[test]
+fn ass_subtitles() {
+    let header = "[Script Info]\nScriptType: v4.00+\n\n[Events]\n\
+                  Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+    let stripping = common::master(
+        0x6D80,
+        &[common::master(
+            0x6240,
+            &[common::master(
+                0x5034,
+                &[common::uint(0x4254, 3), common::element(0x4255, b"0,")],
+            )],
+        )],
+    );
+    let data = common::file(&[
+        common::info("Subtitles"),
+        common::master(
+            0x1654_AE6B,
+            &[common::track(
+                1,
+                0x11,
+                "S_TEXT/ASS",
+                &[common::element(0x63A2, header.as_bytes()), stripping],
+            )],
+        ),
+        common::cluster(
+            0,
+            &[block_group(1, 250, 1000, b"0,Default,,0,0,0,,Hi, there")],
+        ),
+    ]);
+
+    let mut file = Cursor::new(data);
+    let matroska = matroska::Matroska::open(&mut file).unwrap();
+    let entries = matroska::extract_subtitles(&mut file, 1).unwrap();
+    assert_eq!(entries[0].text, "0,0,Default,,0,0,0,,Hi, there");
+    let codec_private = matroska.tracks[0].codec_private.as_deref().unwrap();
+    assert_eq!(
+        matroska::to_ass(codec_private, &entries),
+        format!("{header}\nDialogue: 0,0:00:00.25,0:00:01.25,Default,,0,0,0,,Hi, there\n")
+    );
+}
@@ -0,0 +1,388 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+
+use matroska::Matroska;
+
+mod common;
+
+fn open(tags: &[Vec<u8>]) -> Matroska {
+    let data = common::file(&[common::master(0x1254_C367, tags)]);
+    Matroska::open(Cursor::new(data)).unwrap()
+}
+
+fn names<'a>(tags: impl Iterator<Item = &'a matroska::SimpleTag>) -> Vec<&'a str> {
+    tags.map(|t| t.name.as_str()).collect()
+}
+
+#[test]
+fn tags_for_targets() {
+    let m = open(&[
+        common::tag(&[], &[common::simple_tag("TITLE", "Movie", None)]),
+        common::tag(
+            &[common::uint(0x68CA, 30), common::uint(0x63C5, 1)],
+            &[common::simple_tag("BPS", "1000", None)],
+        ),
+        // a second tag at the same level for the same track
+        common::tag(
+            &[common::uint(0x68CA, 30), common::uint(0x63C5, 1)],
+            &[common::simple_tag("ENCODER", "x264", None)],
+        ),
+        // the track within a chapter
+        common::tag(
+            &[common::uint(0x63C5, 2), common::uint(0x63C4, 7)],
+            &[common::simple_tag("COMMENT", "Director's cut", None)],
+        ),
+        common::tag(
+            &[common::uint(0x63C4, 7)],
+            &[common::simple_tag("CHAPTER_TITLE", "Intro", None)],
+        ),
+        // a UID of 0 means all tracks
+        common::tag(
+            &[common::uint(0x63C5, 0)],
+            &[common::simple_tag("ARTIST", "Someone", None)],
+        ),
+    ]);
+    assert_eq!(
+        names(m.tags_for_track(1)),
+        vec!["TITLE", "BPS", "ENCODER", "ARTIST"]
+    );
+    assert_eq!(
+        names(m.tags_for_track(2)),
+        vec!["TITLE", "COMMENT", "ARTIST"]
+    );
+    assert_eq!(
+        names(m.tags_for_chapter(7)),
+        vec!["TITLE", "COMMENT", "CHAPTER_TITLE"]
+    );
+    assert_eq!(names(m.tags_for_chapter(8)), vec!["TITLE"]);
+}
+
+#[test]
+fn simple_tag_lookup() {
+    let ietf = common::master(
+        0x67C8,
+        &[
+            common::string(0x45A3, "TITLE"),
+            common::string(0x4487, "Le film"),
+            common::string(0x447B, "fr-CA"),
+        ],
+    );
+    let default = common::master(
+        0x67C8,
+        &[
+            common::string(0x45A3, "TITLE"),
+            common::string(0x4487, "Der Film"),
+            common::string(0x447A, "ger"),
+            common::uint(0x4484, 1),
+        ],
+    );
+    let m = open(&[common::tag(
+        &[],
+        &[
+            common::simple_tag("title", "The Movie", Some("eng")),
+            ietf,
+            default,
+            common::simple_tag("ARTIST", "Someone", None),
+        ],
+    )]);
+    let value = |tag: Option<&matroska::SimpleTag>| match tag.and_then(|t| t.value.as_ref()) {
+        Some(matroska::TagValue::String(s)) => s.clone(),
+        _ => panic!("missing tag"),
+    };
+    assert_eq!(value(m.simple_tag("TITLE", Some("eng"))), "The Movie");
+    assert_eq!(value(m.simple_tag("Title", Some("fr"))), "Le film");
+    // falls back to the default, then to the first
+    assert_eq!(value(m.simple_tag("TITLE", Some("spa"))), "Der Film");
+    assert_eq!(value(m.simple_tag("TITLE", None)), "Der Film");
+    assert_eq!(value(m.simple_tag("ARTIST", Some("eng"))), "Someone");
+    assert!(m.simple_tag("COMPOSER", None).is_none());
+
+    assert_eq!(m.simple_tags("TITLE", None).count(), 3);
+    assert_eq!(m.simple_tags("TITLE", Some("fr")).count(), 1);
+    assert_eq!(m.simple_tags("TITLE", Some("spa")).count(), 0);
+}
+
+#[test]
+fn track_statistics() {
+    use std::time::Duration;
+
+    let info = common::master(
+        0x1549_A966,
+        &[
+            common::uint(0x2A_D7B1, 1_000_000),
+            common::string(0x4D80, "libebml"),
+            common::string(0x5741, "mkvmerge v80.0"),
+        ],
+    );
+    let statistics = |uid, app: &str| {
+        common::tag(
+            &[common::uint(0x68CA, 50), common::uint(0x63C5, uid)],
+            &[
+                common::simple_tag("BPS", "128000", Some("eng")),
+                common::simple_tag("DURATION", "01:02:03.500000000", Some("eng")),
+                common::simple_tag("NUMBER_OF_FRAMES", "not a number", Some("eng")),
+                common::simple_tag("_STATISTICS_WRITING_APP", app, Some("eng")),
+                common::simple_tag(
+                    "_STATISTICS_TAGS",
+                    "BPS DURATION NUMBER_OF_FRAMES",
+                    Some("eng"),
+                ),
+            ],
+        )
+    };
+    let remuxed = common::tag(
+        &[common::uint(0x63C5, 3)],
+        &[
+            common::simple_tag("BPS-eng", "96000", None),
+            common::simple_tag("DURATION-eng", "00:00:01.5", None),
+        ],
+    );
+    let data = common::file(&[
+        info,
+        common::master(
+            0x1254_C367,
+            &[
+                common::tag(&[], &[common::simple_tag("BPS", "1", None)]),
+                statistics(1, "mkvmerge v80.0"),
+                statistics(2, "mkvmerge v10.0"),
+                remuxed,
+            ],
+        ),
+    ]);
+    let m = Matroska::open(Cursor::new(data)).unwrap();
+
+    let first = m.track_statistics(1).unwrap();
+    assert_eq!(first.bps, Some(128_000));
+    assert_eq!(first.duration, Some(Duration::new(3723, 500_000_000)));
+    assert_eq!(first.number_of_frames, None);
+    assert_eq!(first.number_of_bytes, None);
+    assert_eq!(first.tags, vec!["BPS", "DURATION", "NUMBER_OF_FRAMES"]);
+    assert!(first.is_current(&m.info));
+    assert!(!m.track_statistics(2).unwrap().is_current(&m.info));
+
+    let third = m.track_statistics(3).unwrap();
+    assert_eq!(third.bps, Some(96_000));
+    assert_eq!(third.duration, Some(Duration::from_millis(1500)));
+    assert_eq!(third.writing_app, None);
+    assert!(third.is_current(&m.info));
+
+    assert_eq!(m.track_statistics(4), None);
+}
+
+#[test]
+fn void_elements() {
+    // reserved space left behind by an in-place edit,
+    // well beyond the largest element body we'll read
+    let void = common::element(0xEC, &vec![0; 4 << 20]);
+    let data = common::file(&[common::master(
+        0x1254_C367,
+        &[
+            void.clone(),
+            common::tag(&[], &[void, common::simple_tag("TITLE", "Voided", None)]),
+        ],
+    )]);
+    let options = matroska::ParseOptions::default().with_max_element_size(1 << 20);
+    let m = Matroska::open_with(Cursor::new(data), &options).unwrap();
+    assert_eq!(m.tags.len(), 1);
+    assert_eq!(names(m.tags[0].simple.iter()), vec!["TITLE"]);
+}
+
+#[test]
+fn tag_equality() {
+    use std::collections::HashSet;
+
+    let track_tag = || {
+        common::tag(
+            &[common::uint(0x68CA, 30), common::uint(0x63C5, 1)],
+            &[common::simple_tag("BPS", "1000", None)],
+        )
+    };
+    let m = open(&[
+        track_tag(),
+        track_tag(),
+        common::tag(&[], &[common::simple_tag("BPS", "1000", None)]),
+    ]);
+    assert_eq!(m.tags[0], m.tags[1]);
+    assert_ne!(m.tags[0], m.tags[2]);
+    assert_eq!(m.tags.iter().collect::<HashSet<_>>().len(), 2);
+    assert_eq!(m, m.clone());
+}
+
+#[test]
+fn nested_simple_tags() {
+    let m = open(&[common::tag(
+        &[],
+        &[common::master(
+            0x67C8,
+            &[
+                common::string(0x45A3, "ARTIST"),
+                common::string(0x4487, "Someone"),
+                common::simple_tag("URL", "https://example.com", None),
+            ],
+        )],
+    )]);
+    let artist = &m.tags[0].simple[0];
+    assert_eq!(artist.name, "ARTIST");
+    assert_eq!(names(artist.simple.iter()), vec!["URL"]);
+}
+
+#[test]
+fn tags_xml_nesting() {
+    use matroska::{MatroskaError, SimpleTag, Tag, TagValue};
+
+    let nested = |depth: usize| {
+        let mut simple = SimpleTag::default()
+            .with_name("LEAF")
+            .with_value(TagValue::String("value".to_owned()));
+        for _ in 1..depth {
+            simple = SimpleTag::default().with_name("PART").with_simple(simple);
+        }
+        vec![Tag::default().with_simple(simple)]
+    };
+
+    // nested SimpleTags as deep as a file's Tags may hold
+    let tags = nested(20);
+    assert_eq!(Tag::from_xml(&Tag::to_xml(&tags)).unwrap(), tags);
+
+    // but no deeper, however many openers there are
+    for xml in [
+        Tag::to_xml(&nested(40)),
+        format!("<Tags>{}", "<Tag>".repeat(500_000)),
+    ] {
+        assert!(matches!(
+            Tag::from_xml(&xml),
+            Err(MatroskaError::InvalidXml { .. })
+        ));
+    }
+}
+
+#[test]
+fn tags_xml() {
+    use matroska::{Language, MatroskaError, SimpleTag, Tag, TagValue, Target, TargetTypeValue};
+
+    let string = |s: &str| TagValue::String(s.to_owned());
+    let tags = vec![
+        Tag::default()
+            .with_targets(
+                Target::default()
+                    .with_target_type_value(TargetTypeValue::Episode)
+                    .with_target_type("MOVIE")
+                    .with_track_uid(1)
+                    .with_track_uid(u64::MAX)
+                    .with_edition_uid(2)
+                    .with_chapter_uid(3)
+                    .with_attachment_uid(4),
+            )
+            .with_simple(
+                SimpleTag::default()
+                    .with_name("TITLE")
+                    .with_value(string("<Fish & \"Chips\"> 'n' Ünïcödé 日本"))
+                    .with_language(Language::ISO639("ger".to_owned()))
+                    .with_default(true),
+            )
+            .with_simple(
+                SimpleTag::default()
+                    .with_name("ARTIST")
+                    .with_value(string("  spaced\r\nlines  "))
+                    .with_language(Language::IETF("en-US".to_owned()))
+                    .with_simple(
+                        SimpleTag::default()
+                            .with_name("URL")
+                            .with_value(string("https://example.com/?a=1&b=2"))
+                            .with_simple(SimpleTag::default().with_name("EMPTY")),
+                    ),
+            ),
+        Tag::default()
+            .with_targets(Target::default())
+            .with_simple(
+                SimpleTag::default()
+                    .with_name("COVER")
+                    .with_value(TagValue::Binary((0..=255).collect())),
+            )
+            .with_simple(
+                SimpleTag::default()
+                    .with_name("SHORT")
+                    .with_value(TagValue::Binary(vec![1])),
+            )
+            .with_simple(
+                SimpleTag::default()
+                    .with_name("NOTHING")
+                    .with_value(TagValue::Binary(Vec::new())),
+            ),
+        Tag::default(),
+    ];
+    let xml = Tag::to_xml(&tags);
+    assert!(xml.contains("<TargetTypeValue>50</TargetTypeValue>"));
+    assert!(xml.contains("<String>&lt;Fish &amp; \"Chips\"&gt; 'n' Ünïcödé 日本</String>"));
+    assert!(xml.contains("<Binary format=\"base64\">AQ==</Binary>"));
+    let parsed = Tag::from_xml(&xml).unwrap();
+    assert_eq!(parsed, tags);
+    assert_eq!(Tag::to_xml(&parsed), xml);
+
+    // as mkvtoolnix and people write it
+    let xml = "\u{FEFF}<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r\n\
+        <!DOCTYPE Tags SYSTEM \"matroskatags.dtd\">\r\n\
+        <!-- a comment -->\r\n\
+        <Tags>\r\n\
+          <Tag>\r\n\
+            <Targets><TargetTypeValue> 30 </TargetTypeValue></Targets>\r\n\
+            <Simple>\r\n\
+              <Name>TITLE</Name>\r\n\
+              <String>Caf&#233; &#x263A; <![CDATA[<raw>]]></String>\r\n\
+              <TagLanguageIETF>fr</TagLanguageIETF>\r\n\
+              <TagLanguage>fre</TagLanguage>\r\n\
+            </Simple>\r\n\
+            <Simple><Name>HEX</Name><Binary format='hex'>DE ad</Binary></Simple>\r\n\
+            <Simple><Name>ASCII</Name><Binary format=\"ascii\">hi</Binary></Simple>\r\n\
+            <Simple><Name>B64</Name><Binary>aGk=\r\n</Binary><DefaultLanguage>0</DefaultLanguage></Simple>\r\n\
+          </Tag>\r\n\
+          <Tag/>\r\n\
+        </Tags>\r\n";
+    let parsed = Tag::from_xml(xml).unwrap();
+    assert_eq!(parsed.len(), 2);
+    let targets = parsed[0].targets.as_ref().unwrap();
+    assert_eq!(targets.target_type_value, Some(TargetTypeValue::Chapter));
+    let simple = &parsed[0].simple;
+    assert_eq!(simple[0].value, Some(string("Café ☺ <raw>")));
+    assert_eq!(simple[0].language, Some(Language::IETF("fr".to_owned())));
+    assert!(simple[0].default);
+    assert_eq!(simple[1].value, Some(TagValue::Binary(vec![0xDE, 0xAD])));
+    assert_eq!(simple[2].value, Some(TagValue::Binary(b"hi".to_vec())));
+    assert_eq!(simple[3].value, Some(TagValue::Binary(b"hi".to_vec())));
+    assert!(!simple[3].default);
+    assert_eq!(parsed[1], Tag::default());
+
+    for (xml, line) in [
+        ("<Tags>\n<Tag>\n<Simple><Name>A</Name></Tag>\n</Tags>", 3),
+        ("<Tags>\n<Tag><Simple>\n<Unknown/></Simple></Tag></Tags>", 3),
+        (
+            "<Tags><Tag><Targets>\n<TrackUID>x</TrackUID></Targets></Tag></Tags>",
+            2,
+        ),
+        (
+            "<Tags><Tag><Simple><Binary>a</Binary></Simple></Tag></Tags>",
+            1,
+        ),
+        (
+            "<Tags><Tag><Simple><Name>&bogus;</Name></Simple></Tag></Tags>",
+            1,
+        ),
+        ("<Tags>\n<Tag>text<Simple/></Tag></Tags>", 2),
+        ("<Chapters/>", 1),
+        ("<Tags/>\n<Tags/>", 2),
+        ("<Tags>", 1),
+    ]
+    .iter()
+    {
+        match Tag::from_xml(xml) {
+            Err(MatroskaError::InvalidXml { line: l }) => assert_eq!(l, *line, "{}", xml),
+            other => panic!("unexpected result {:?} for {}", other, xml),
+        }
+    }
+}
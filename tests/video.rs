@@ -0,0 +1,365 @@
+// Copyright 2017-2022 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::io::Cursor;
+
+use matroska::{Settings, Video};
+
+mod common;
+
+fn video(children: &[Vec<u8>]) -> Video {
+    let data = common::file(&[common::video_track(children)]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    match m.tracks.into_iter().next().unwrap().settings {
+        Settings::Video(video) => video,
+        _ => panic!("unexpected track settings"),
+    }
+}
+
+fn stereo(mode: u64) -> Video {
+    video(&[
+        common::uint(0xB0, 3840),
+        common::uint(0xBA, 2160),
+        common::uint(0x53B8, mode),
+    ])
+}
+
+#[test]
+fn per_eye_dimensions() {
+    // side by side, both eye orders
+    assert_eq!(stereo(1).per_eye_dimensions(), Some((1920, 2160)));
+    assert_eq!(stereo(11).per_eye_dimensions(), Some((1920, 2160)));
+    // top - bottom, both eye orders
+    assert_eq!(stereo(2).per_eye_dimensions(), Some((3840, 1080)));
+    assert_eq!(stereo(3).per_eye_dimensions(), Some((3840, 1080)));
+    // mono and full-resolution interlaced frames
+    assert_eq!(stereo(0).per_eye_dimensions(), Some((3840, 2160)));
+    assert_eq!(stereo(13).per_eye_dimensions(), Some((3840, 2160)));
+    assert_eq!(stereo(14).per_eye_dimensions(), Some((3840, 2160)));
+    // no stereo mode at all
+    let mono = video(&[common::uint(0xB0, 3840), common::uint(0xBA, 2160)]);
+    assert_eq!(mono.per_eye_dimensions(), None);
+}
+
+#[test]
+fn stereo_mode_round_trip() {
+    use matroska::{EyeOrder, StereoColors, StereoMode};
+
+    for raw in 0..=14 {
+        let mode = StereoMode::from_raw(raw).unwrap();
+        assert_eq!(mode.to_raw(), raw);
+        assert_eq!(stereo(raw).stereo, Some(mode));
+    }
+    assert_eq!(StereoMode::from_raw(15), None);
+    assert_eq!(stereo(15).stereo, None);
+
+    // the asymmetric entries that are easy to get backwards
+    assert_eq!(
+        StereoMode::from_raw(2),
+        Some(StereoMode::TopBottom(EyeOrder::RightFirst))
+    );
+    assert_eq!(
+        StereoMode::from_raw(3),
+        Some(StereoMode::TopBottom(EyeOrder::LeftFirst))
+    );
+    assert_eq!(
+        StereoMode::from_raw(11),
+        Some(StereoMode::SideBySide(EyeOrder::RightFirst))
+    );
+    assert_eq!(
+        StereoMode::from_raw(12),
+        Some(StereoMode::Anaglyph(StereoColors::GreenMagenta))
+    );
+}
+
+#[test]
+fn colour() {
+    use matroska::{
+        ChromaSiting, ColourRange, MatrixCoefficients, Primaries, TransferCharacteristics,
+    };
+
+    let v = video(&[
+        common::uint(0xB0, 3840),
+        common::uint(0xBA, 2160),
+        common::master(
+            0x55B0,
+            &[
+                common::uint(0x55B1, 9),
+                common::uint(0x55B2, 10),
+                common::uint(0x55B3, 1),
+                common::uint(0x55B4, 1),
+                common::uint(0x55B7, 1),
+                common::uint(0x55B8, 2),
+                common::uint(0x55B9, 1),
+                common::uint(0x55BA, 16),
+                common::uint(0x55BB, 99),
+            ],
+        ),
+    ]);
+    let colour = v.colour.unwrap();
+    assert_eq!(
+        colour.matrix_coefficients,
+        Some(MatrixCoefficients::BT2020NCL)
+    );
+    assert_eq!(colour.bits_per_channel, Some(10));
+    assert_eq!(colour.chroma_subsampling_horz, Some(1));
+    assert_eq!(colour.chroma_subsampling_vert, Some(1));
+    assert_eq!(colour.cb_subsampling_horz, None);
+    assert_eq!(colour.chroma_siting_horz, Some(ChromaSiting::Collocated));
+    assert_eq!(colour.chroma_siting_vert, Some(ChromaSiting::Half));
+    assert_eq!(colour.range, Some(ColourRange::Broadcast));
+    assert_eq!(
+        colour.transfer_characteristics,
+        Some(TransferCharacteristics::SMPTE2084)
+    );
+    assert_eq!(colour.primaries, Some(Primaries::Unknown(99)));
+
+    let v = video(&[common::uint(0xB0, 320), common::uint(0xBA, 180)]);
+    assert_eq!(v.colour, None);
+    assert_eq!(v.pixel_width, 320);
+}
+
+#[test]
+fn mastering_metadata() {
+    let v = video(&[common::master(
+        0x55B0,
+        &[
+            common::uint(0x55BC, 1000),
+            common::uint(0x55BD, 400),
+            common::master(
+                0x55D0,
+                &[
+                    common::float(0x55D1, 0.708),
+                    common::float(0x55D2, 0.292),
+                    common::float(0x55D7, 0.3127),
+                    common::float(0x55D8, 0.329),
+                    common::float(0x55D9, 1000.0),
+                    common::float(0x55DA, 0.0001),
+                ],
+            ),
+        ],
+    )]);
+    let colour = v.colour.unwrap();
+    assert_eq!(colour.max_cll, Some(1000));
+    assert_eq!(colour.max_fall, Some(400));
+    let mastering = colour.mastering_metadata.unwrap();
+    assert_eq!(mastering.primary_r_chromaticity_x, Some(0.708));
+    assert_eq!(mastering.primary_r_chromaticity_y, Some(0.292));
+    assert_eq!(mastering.primary_g_chromaticity_x, None);
+    assert_eq!(mastering.white_point_chromaticity_x, Some(0.3127));
+    assert_eq!(mastering.white_point_chromaticity_y, Some(0.329));
+    assert_eq!(mastering.luminance_max, Some(1000.0));
+    assert_eq!(mastering.luminance_min, Some(0.0001));
+
+    // luminance alone still comes through
+    let v = video(&[common::master(
+        0x55B0,
+        &[common::master(0x55D0, &[common::float(0x55D9, 4000.0)])],
+    )]);
+    let mastering = v.colour.unwrap().mastering_metadata.unwrap();
+    assert_eq!(mastering.luminance_max, Some(4000.0));
+    assert_eq!(mastering.luminance_min, None);
+}
+
+#[test]
+fn cropped_dimensions() {
+    // 1080p letterboxed to 2.40:1 and trimmed at the sides
+    let cropped = video(&[
+        common::uint(0xB0, 1920),
+        common::uint(0xBA, 1080),
+        common::uint(0x54BB, 138),
+        common::uint(0x54AA, 142),
+        common::uint(0x54CC, 4),
+        common::uint(0x54DD, 6),
+    ]);
+    assert_eq!(cropped.crop_top, Some(138));
+    assert_eq!(cropped.crop_bottom, Some(142));
+    assert_eq!(cropped.crop_left, Some(4));
+    assert_eq!(cropped.crop_right, Some(6));
+    assert_eq!(cropped.cropped_dimensions(), (1910, 800));
+
+    let uncropped = video(&[common::uint(0xB0, 1920), common::uint(0xBA, 1080)]);
+    assert_eq!(uncropped.crop_top, None);
+    assert_eq!(uncropped.cropped_dimensions(), (1920, 1080));
+
+    // the crop applies to the whole frame before it's split between eyes
+    let side_by_side = video(&[
+        common::uint(0xB0, 3840),
+        common::uint(0xBA, 1080),
+        common::uint(0x54BB, 140),
+        common::uint(0x54AA, 140),
+        common::uint(0x53B8, 1),
+    ]);
+    assert_eq!(side_by_side.per_eye_dimensions(), Some((1920, 800)));
+}
+
+#[test]
+fn aspect_ratio_type() {
+    use matroska::AspectRatioType;
+
+    let aspect = |raw| video(&[common::uint(0x54B3, raw)]).aspect_ratio_type;
+    assert_eq!(aspect(0), Some(AspectRatioType::FreeResizing));
+    assert_eq!(aspect(1), Some(AspectRatioType::KeepAspectRatio));
+    assert_eq!(aspect(2), Some(AspectRatioType::Fixed));
+    assert_eq!(aspect(3), Some(AspectRatioType::Unknown(3)));
+    assert_eq!(video(&[]).aspect_ratio_type, None);
+}
+
+#[test]
+fn field_order() {
+    use matroska::FieldOrder;
+
+    // 1080i, bottom field first
+    let interlaced = video(&[
+        common::uint(0xB0, 1920),
+        common::uint(0xBA, 1080),
+        common::uint(0x9A, 1),
+        common::uint(0x9D, 6),
+    ]);
+    assert_eq!(interlaced.interlaced, Some(true));
+    assert_eq!(interlaced.field_order, Some(FieldOrder::BottomFieldFirst));
+
+    let order = |raw| video(&[common::uint(0x9D, raw)]).field_order;
+    assert_eq!(order(0), Some(FieldOrder::Progressive));
+    assert_eq!(order(1), Some(FieldOrder::TopFieldFirst));
+    assert_eq!(order(2), Some(FieldOrder::Undetermined));
+    assert_eq!(order(9), Some(FieldOrder::TopFieldFirstSwapped));
+    assert_eq!(order(14), Some(FieldOrder::BottomFieldFirstSwapped));
+    assert_eq!(order(3), Some(FieldOrder::Unknown(3)));
+
+    // progressive files usually omit it entirely
+    let progressive = video(&[common::uint(0xB0, 1920), common::uint(0xBA, 1080)]);
+    assert_eq!(progressive.field_order, None);
+}
+
+#[test]
+fn display_unit() {
+    use matroska::DisplayUnit;
+
+    // mkvmerge's display aspect ratio form
+    let ratio = video(&[
+        common::uint(0xB0, 1440),
+        common::uint(0xBA, 1080),
+        common::uint(0x54B0, 16),
+        common::uint(0x54BA, 9),
+        common::uint(0x54B2, 3),
+    ]);
+    assert_eq!(ratio.display_width, Some(16));
+    assert_eq!(ratio.display_height, Some(9));
+    assert_eq!(ratio.display_unit, Some(DisplayUnit::DisplayAspectRatio));
+
+    let unit = |raw| video(&[common::uint(0x54B2, raw)]).display_unit;
+    assert_eq!(unit(0), Some(DisplayUnit::Pixels));
+    assert_eq!(unit(1), Some(DisplayUnit::Centimeters));
+    assert_eq!(unit(2), Some(DisplayUnit::Inches));
+    assert_eq!(unit(4), Some(DisplayUnit::Unknown(4)));
+    assert_eq!(video(&[]).display_unit, None);
+}
+
+#[test]
+fn colour_space() {
+    let uyvy = video(&[common::element(0x2E_B524, b"UYVY")]);
+    assert_eq!(uyvy.colour_space, Some(*b"UYVY"));
+    assert_eq!(uyvy.colour_space_fourcc(), Some("UYVY"));
+
+    let unprintable = video(&[common::element(0x2E_B524, &[0, 1, 2, 3])]);
+    assert_eq!(unprintable.colour_space, Some([0, 1, 2, 3]));
+    assert_eq!(unprintable.colour_space_fourcc(), None);
+
+    // FourCCs are always 4 bytes
+    let invalid = video(&[common::element(0x2E_B524, b"YV12X")]);
+    assert_eq!(invalid.colour_space, None);
+    assert_eq!(video(&[]).colour_space, None);
+}
+
+#[test]
+fn track_operation() {
+    use matroska::{PlaneType, TrackOperation};
+
+    let plane = |uid, plane_type| {
+        common::master(
+            0xE4,
+            &[common::uint(0xE5, uid), common::uint(0xE6, plane_type)],
+        )
+    };
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[
+            common::track(1, 1, "V_MPEG4/ISO/AVC", &[]),
+            common::track(2, 1, "V_MPEG4/ISO/AVC", &[]),
+            common::track(
+                3,
+                1,
+                "V_MPEG4/ISO/AVC",
+                &[common::master(
+                    0xE2,
+                    &[common::master(0xE3, &[plane(1, 0), plane(2, 1)])],
+                )],
+            ),
+            common::track(
+                4,
+                1,
+                "V_MPEG4/ISO/AVC",
+                &[common::master(
+                    0xE2,
+                    &[common::master(
+                        0xE9,
+                        &[common::uint(0xED, 1), common::uint(0xED, 2)],
+                    )],
+                )],
+            ),
+        ],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    assert_eq!(m.tracks[0].operation, None);
+    assert_eq!(
+        m.tracks[2].operation,
+        Some(TrackOperation::CombinePlanes(vec![
+            (1, PlaneType::LeftEye),
+            (2, PlaneType::RightEye),
+        ]))
+    );
+    assert_eq!(
+        m.tracks[3].operation,
+        Some(TrackOperation::JoinBlocks(vec![1, 2]))
+    );
+    let flat = m.to_flat_map();
+    assert_eq!(flat["track.3.plane.2"], "right_eye");
+    assert_eq!(flat["track.4.join.1"], "2");
+}
+
+#[test]
+fn block_addition_mapping() {
+    let config = [0x01, 0x00, 0x10, 0x35, 0x10, 0x00, 0x00, 0x00];
+    let data = common::file(&[common::master(
+        0x1654_AE6B,
+        &[common::track(
+            1,
+            1,
+            "V_MPEGH/ISO/HEVC",
+            &[common::master(
+                0x41E4,
+                &[
+                    common::uint(0x41F0, 1),
+                    common::string(0x41A4, "Dolby Vision configuration"),
+                    common::uint(0x41E7, u64::from(u32::from_be_bytes(*b"dvvC"))),
+                    common::element(0x41ED, &config),
+                ],
+            )],
+        )],
+    )]);
+    let m = matroska::Matroska::open(Cursor::new(data)).unwrap();
+    let mappings = &m.tracks[0].block_addition_mappings;
+    assert_eq!(mappings.len(), 1);
+    assert_eq!(mappings[0].value, Some(1));
+    assert_eq!(
+        mappings[0].name.as_deref(),
+        Some("Dolby Vision configuration")
+    );
+    assert_eq!(mappings[0].id_type_fourcc().as_deref(), Some("dvvC"));
+    assert_eq!(mappings[0].extra_data, Some(config.to_vec()));
+}